@@ -130,7 +130,7 @@ fn event_type(event: &DoraEvent) -> ffi::DoraEventType {
             Event::Stop => ffi::DoraEventType::Stop,
             Event::Input { .. } => ffi::DoraEventType::Input,
             Event::InputClosed { .. } => ffi::DoraEventType::InputClosed,
-            Event::Error(_) => ffi::DoraEventType::Error,
+            Event::Error(_) | Event::DecodeError { .. } => ffi::DoraEventType::Error,
             _ => ffi::DoraEventType::Unknown,
         },
         None => ffi::DoraEventType::AllInputsClosed,
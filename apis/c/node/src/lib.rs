@@ -94,7 +94,7 @@ pub unsafe extern "C" fn read_dora_event_type(event: *const ()) -> EventType {
         Event::Stop => EventType::Stop,
         Event::Input { .. } => EventType::Input,
         Event::InputClosed { .. } => EventType::InputClosed,
-        Event::Error(_) => EventType::Error,
+        Event::Error(_) | Event::DecodeError { .. } => EventType::Error,
         _ => EventType::Unknown,
     }
 }
@@ -11,7 +11,19 @@ from enum import Enum
 
 from .dora import *
 from .dora import (
+    AckTimeoutError,
+    DisconnectedError,
+    DoraError,
+    DuplicateNodeIdError,
+    Event,
+    EventTee,
+    EventType,
+    ExternalEventStream,
+    LifecycleEvents,
+    Metadata,
     Node,
+    RecordBatchOutput,
+    Recording,
     Ros2Context,
     Ros2Durability,
     Ros2Liveliness,
@@ -21,10 +33,26 @@ from .dora import (
     Ros2QosPolicies,
     Ros2Subscription,
     Ros2Topic,
+    SchemaError,
+    SendError,
+    Transaction,
+    VersionMismatchError,
     __author__,
     __version__,
+    descriptor_to_yaml,
+    dict_to_metadata,
+    external_stream_from_async,
+    get_log_level,
+    list_dataflows,
+    list_operators,
+    metadata_to_dict,
+    operator_info,
+    register_schema,
+    set_log_level,
     start_runtime,
+    validate_dataflow,
 )
+from .supervisor import run_with_restart
 
 
 class DoraStatus(Enum):
@@ -1,85 +1,1954 @@
+import decimal
 import dora
+import os
 import pyarrow
 import typing
 
+class AckTimeoutError(dora.DoraError):
+    """Raised if a reliably sent output was not acknowledged within its timeout."""
+
+class DisconnectedError(dora.DoraError):
+    """Raised if the connection to the dora daemon was lost."""
+
+class DoraError(RuntimeError):
+    """Base class of the exceptions raised by dora."""
+
+class DuplicateNodeIdError(dora.DoraError):
+    """Raised by `Node()` if another process is already connected with the same node ID."""
+
 @typing.final
 class Enum:
     """Generic enumeration.
 
-Derive from this class to define new enumerations."""
-    __members__: mappingproxy = ...
+Derive from this class to define new enumerations."""
+    __members__: mappingproxy = ...
+
+@typing.final
+class Event:
+    """Dora Event
+
+Returned instead of a plain `dict` by nodes that were created with
+`event_class=True`. Events can still be accessed like a dictionary, e.g.
+`event["type"]`, `event["id"]`, `event["value"]`, or `event["metadata"]`.
+They are registered as `collections.abc.Mapping` and can be converted into
+a plain `dict` through `copy()`.
+
+The value and the metadata are decoded on first access and cached for the
+lifetime of the event, so repeated accesses are cheap. Reading the metadata
+does not decode the value."""
+    type: dora.EventType
+
+    def as_proto(self, msg_class: type) -> google.protobuf.message.Message:
+        """Parses an input that was sent through `Node.send_proto` as a message of
+the given protobuf class:
+
+```python
+from robot_pb2 import Command
+
+command = event.as_proto(Command)
+```
+
+Raises a `dora.SchemaError` if the sender attached a different message
+type. Inputs that were sent as plain bytes are parsed without this check."""
+
+    def attachment(self, name: str) -> bytes:
+        """Returns the binary attachment with the given name, as set through the
+`attachments` argument of `send_output`.
+
+Returns `None` if the event has no attachment of that name."""
+
+    def baggage(self) -> dict:
+        """Returns the OpenTelemetry baggage propagated with an input event.
+
+The baggage is also available in W3C format as `event["metadata"]["open_telemetry_baggage"]`,
+which can be passed to the OpenTelemetry propagator:
+
+```python
+from opentelemetry import propagate
+
+ctx = propagate.extract({"baggage": event["metadata"]["open_telemetry_baggage"]})
+```"""
+
+    def byte_len(self) -> int:
+        """Returns the size of the payload of an input event in bytes, without
+converting the value into a Python object.
+
+The size is the length of the received arrow buffers, so it's cheap to
+check, e.g. to skip large messages before decoding them:
+
+```python
+if event.byte_len() > 10_000_000:
+    continue
+```
+
+Slices created through `slice` report the size of the buffers that they
+share with the original event. Returns `0` for inputs without payload and
+for non-input events."""
+
+    def content_type(self) -> str:
+        """Returns the MIME type of the encoded data of an input event, e.g.
+`"image/jpeg"`, as set through the `content_type` argument of `send_output`.
+
+Returns `None` if the sender did not specify a content type."""
+
+    def copy(self) -> dict:
+        """Returns the dictionary representation of this event as a plain `dict`."""
+
+    def dropped_count(self) -> int:
+        """Returns the number of events of the same input that were dropped before this one
+because the input queue was full, see the `queue_size` option of inputs.
+
+Returns `0` for non-input events."""
+
+    def frame_id(self) -> str:
+        """Returns the coordinate frame of an input event, as set through the
+`frame_id` argument of `send_output`.
+
+Returns `None` if the sender did not specify a frame."""
+
+    def get(self, key: str, default: typing.Any=None) -> typing.Any:
+        """Returns the value for `key` if the event contains it, else `default`."""
+
+    def id_and_seq(self) -> tuple[str, int]:
+        """Returns a stable identifier of an input event as `(input_id, sequence_number)`.
+
+The sequence number is assigned per output by the sending node. Pass the
+identifier as `caused_by` when sending an output to record its lineage:
+
+```python
+node.send_output("command", data, caused_by=[event.id_and_seq()])
+```
+
+Returns `None` for non-input events and for inputs without sequence number,
+e.g. timer inputs."""
+
+    def items(self) -> list:...
+
+    def keys(self) -> list:...
+
+    def slice(self, start: int, length: int) -> dora.Event:
+        """Returns a new input event whose value is the sub-range `[start, start + length)`
+of this event's value.
+
+The slice is zero-copy: it shares the underlying arrow buffer with this event,
+which is kept alive for as long as the slice exists. The `id` and `metadata` of
+the event are preserved.
+
+```python
+detections = event.slice(4, 2)
+```"""
+
+    def timestamp(self) -> float:
+        """Returns the timestamp of an input event or the time of a watermark event,
+in seconds since the UNIX epoch (like `time.time()`).
+
+Input timestamps are assigned by the sending node. They can be compared to
+the `time` of `WATERMARK` events, e.g. to assign inputs to time windows."""
+
+    def timestamp_ns(self) -> int:
+        """Returns the timestamp of an input event or the time of a watermark event,
+as an integer number of nanoseconds since the UNIX epoch.
+
+Unlike `timestamp()`, the value is not rounded to a float. If the sender
+set the `timestamp_ns` metadata key, e.g. to the acquisition time of a
+sensor reading, that value is returned exactly:
+
+```python
+node.send_output("imu", data, {"timestamp_ns": time.time_ns()})
+...
+acquired = event.timestamp_ns()
+```
+
+Otherwise, the timestamp that was assigned when the input was sent is
+returned."""
+
+    def to_flight_data(self) -> tuple[pyarrow.flight.FlightDescriptor, pyarrow.RecordBatch, bytes]:
+        """Converts an input event into an Arrow Flight frame.
+
+Returns a `(descriptor, batch, app_metadata)` tuple that can be written to a
+`pyarrow.flight` stream. The descriptor path is the input id, and the
+`app_metadata` contains the dora metadata of the event:
+
+```python
+descriptor, batch, app_metadata = event.to_flight_data()
+writer, _ = client.do_put(descriptor, batch.schema)
+writer.write_with_metadata(batch, app_metadata)
+```
+
+Struct arrays are converted to record batches with one column per field,
+all other values to a record batch with a single `value` column. Use
+`Node.send_flight_data` to send a received frame as dora output."""
+
+    def to_ndarray(self) -> numpy.ndarray:
+        """Converts the value of an input that was sent as a numpy array with two or
+more dimensions back to an array of the same shape.
+
+Such arrays are sent as `arrow.fixed_shape_tensor`. Arrays in Fortran order
+and other permutations of row-major order, e.g. transposed arrays, keep
+their strides. The returned array is a read-only view into the received
+value if possible:
+
+```python
+image = event.to_ndarray()
+print(image.shape, image.strides)
+```"""
+
+    def to_ros2(self, msg_type: type) -> typing.Any:
+        """Converts an input into an instance of the given ROS 2 message class of
+`rclpy`, e.g. to publish it through an `rclpy` publisher in the same
+process.
+
+Struct values, e.g. sent through `Node.send_output_from_ros2`, must have a
+single element, whose fields are assigned to the fields of the message.
+Other values are assigned to the `data` field of the message. If the
+message has a `header` that is not part of the value, its `stamp` is set
+to `timestamp_ns()` and its `frame_id` to the `frame_id` metadata:
+
+```python
+from geometry_msgs.msg import PoseStamped
+
+publisher.publish(event.to_ros2(PoseStamped))
+```
+
+Requires `rosidl_runtime_py`, which is installed with ROS 2."""
+
+    def to_sparse(self) -> scipy.sparse.csr_matrix | scipy.sparse.coo_matrix:
+        """Converts the value of an input that was sent as a `scipy.sparse` matrix
+back to a matrix, without densifying it.
+
+CSR and COO matrices keep their format, matrices of other formats are
+received in CSR format. Requires `scipy` to be installed:
+
+```python
+matrix = event.to_sparse()
+```"""
+
+    def to_structured(self) -> numpy.ndarray:
+        """Converts the value of an input that was sent as a numpy structured array
+back to a structured array.
+
+The dtype has the same field names and types as the sent array, but is
+packed, since the padding of aligned dtypes is not transferred:
+
+```python
+records = event.to_structured()
+print(records["pos"])
+```"""
+
+    def to_table(self) -> pyarrow.Table:
+        """Converts the value of an input that was sent as a `pyarrow.Table` or as a
+list of `pyarrow.RecordBatch` back to a table.
+
+The batches of the table are the batches that were sent:
+
+```python
+table = event.to_table()
+print(table.num_rows, table.schema)
+```"""
+
+    def to_torch(self, device: str=None) -> torch.Tensor:
+        """Converts the value of an input into a `torch` tensor without copying it.
+
+The tensor shares the memory of the received value through DLPack and
+keeps it alive, so it should be treated as read-only. Numpy arrays with
+two or more dimensions keep their shape and strides, see `to_ndarray`.
+Values that were sent from a GPU buffer, see `Node.send_output`, get
+their shape back and are copied to the CUDA device again. Pass a `device`
+to move the tensor to another device, e.g. `"cpu"` or `"cuda:1"`:
+
+```python
+image = event.to_torch(device="cuda")
+prediction = model(image.unsqueeze(0))
+```
+
+Only primitive arrays without nulls can be converted. Requires `torch` and
+`pyarrow` 15 or later."""
+
+    def units(self) -> str:
+        """Returns the physical units of an input event, e.g. `"m"` or `"rad"`, as set
+through the `units` argument of `send_output`.
+
+Returns `None` if the sender did not specify units."""
+
+    def validate_against(self, name: str=None) -> None:
+        """Checks the value of an input against a schema of the registry, see
+`dora.register_schema`.
+
+Without a `name`, the value is checked against the schema that the sender
+passed to `send_output`. Raises a `dora.SchemaError` if the value does not
+match the schema or if the schema is not registered:
+
+```python
+event.validate_against("Detection")
+detections = event["value"]
+```"""
+
+    def values(self) -> list:...
+
+    def __contains__(self, key: typing.Any) -> bool:
+        """Return key in self."""
+
+    def __delitem__(self, key: typing.Any) -> None:
+        """Delete self[key]."""
+
+    def __eq__(self, value: typing.Any) -> bool:
+        """Return self==value."""
+
+    def __getitem__(self, key: typing.Any) -> typing.Any:
+        """Return self[key]."""
+
+    def __iter__(self) -> typing.Any:
+        """Implement iter(self)."""
+
+    def __len__(self) -> int:
+        """Return len(self)."""
+
+    def __ne__(self, value: typing.Any) -> bool:
+        """Return self!=value."""
+
+    def __repr__(self) -> str:
+        """Return repr(self)."""
+
+    def __setitem__(self, key: typing.Any, value: typing.Any) -> None:
+        """Set self[key] to value."""
+
+@typing.final
+class EventTee:
+    """A fork of the event stream of a node.
+
+Created through `Node.tee`. Receives a copy of every event that is returned
+by `Node.next()`, which allows processing the same inputs independently,
+e.g. in a separate logging thread:
+
+```python
+tee = node.tee()
+threading.Thread(target=lambda: [log(event) for event in tee], daemon=True).start()
+
+for event in node:
+    process(event)
+```"""
+
+    def recv(self, timeout: float=None) -> dict:
+        """Waits for the next copied event.
+
+Returns `None` if no event arrived within `timeout` seconds. The copies
+include the `STOP` event, after which no further events arrive."""
+
+    def __iter__(self) -> typing.Any:
+        """Implement iter(self)."""
+
+    def __next__(self) -> typing.Any:
+        """Implement next(self)."""
+
+@typing.final
+class EventType:
+    """Type of a dora event, see `Event.type`.
+
+`str(event_type)` is the string that is returned by `event["type"]`."""
+
+    def __eq__(self, value: typing.Any) -> bool:
+        """Return self==value."""
+
+    def __int__(self) -> None:
+        """int(self)"""
+
+    def __ne__(self, value: typing.Any) -> bool:
+        """Return self!=value."""
+
+    def __repr__(self) -> str:
+        """Return repr(self)."""
+
+    def __str__(self) -> str:
+        """Return str(self)."""
+    DECODE_ERROR: EventType = ...
+    ERROR: EventType = ...
+    INPUT: EventType = ...
+    INPUT_CLOSED: EventType = ...
+    STOP: EventType = ...
+    UNKNOWN: EventType = ...
+    WARNING: EventType = ...
+    WATERMARK: EventType = ...
+
+@typing.final
+class ExternalEventStream:
+    """A stream of external events that can be merged into the dora event stream
+using `Node.merge_external_events`.
+
+Created through `dora.external_stream_from_async`."""
+
+@typing.final
+class LifecycleEvents:
+    """Iterator over the lifecycle events of the dataflow.
+
+Created through `Node.lifecycle_events`. Each event is a dict of the form
+`{"type": "NODE_JOINED" | "NODE_LEFT", "node": str}`, or
+`{"type": "PROGRESS", "node": str, "task": str, "progress": float, "message": str | None}`
+for progress reports. Nodes that were created with `emit_summary_on_close=True`
+report their statistics as `{"type": "SUMMARY", "node": str, "received": int,
+"received_bytes": int, "dropped": int, "sent": int, "sent_bytes": int,
+"mean_latency": float | None}` when they close, with the latency in seconds.
+
+```python
+roster = set()
+for event in node.lifecycle_events():
+    if event["type"] == "NODE_JOINED":
+        roster.add(event["node"])
+    elif event["type"] == "NODE_LEFT":
+        roster.discard(event["node"])
+```"""
+
+    def recv(self, timeout: float=None) -> dict:
+        """Waits for the next lifecycle event.
+
+Returns `None` if no event arrived within `timeout` seconds or if the
+event stream of the node was closed."""
+
+    def __iter__(self) -> typing.Any:
+        """Implement iter(self)."""
+
+    def __next__(self) -> typing.Any:
+        """Implement next(self)."""
+
+@typing.final
+class Metadata:
+    """Metadata of a dora message.
+
+Use `dora.metadata_to_dict` and `dora.dict_to_metadata` to convert it from
+and to a plain dict."""
+
+    def parameters(self) -> dict:
+        """Returns the parameters of the message, in the same format as `event["metadata"]`."""
+
+    def timestamp(self) -> str:
+        """Returns the hybrid logical clock timestamp of the message."""
+
+    def __eq__(self, value: typing.Any) -> bool:
+        """Return self==value."""
+
+    def __repr__(self) -> str:
+        """Return repr(self)."""
+
+@typing.final
+class Node:
+    """The custom node API lets you integrate `dora` into your application.
+It allows you to retrieve input and send output in any fashion you want.
+
+Use with:
+
+```python
+from dora import Node
+
+node = Node()
+```
+
+To bound the memory used by inputs that were received but not processed yet,
+pass `max_buffered_bytes`. When the total payload size of the buffered inputs
+exceeds it, the oldest events of the input that buffers the most bytes are
+dropped, like for the `queue_size` limit of inputs:
+
+```python
+node = Node(max_buffered_bytes=256 * 1024 * 1024)
+```
+
+To detect clock bugs of upstream nodes, pass `assert_monotonic_timestamps=True`.
+A `RuntimeWarning` is then emitted whenever the timestamp of an input event is
+older than the timestamp of the previous event of the same input. Pass
+`assert_monotonic_timestamps="raise"` to raise a `RuntimeError` instead. The
+event is still processed and available as the `event` attribute of the error.
+
+By default, arbitrary keys are accepted in the `metadata` of sent outputs. Pass
+`strict_metadata=True` to reject keys that are not known to dora, e.g. to catch
+typos like `"frameid"`. User-defined metadata must then be nested under the
+`parameters` key:
+
+```python
+node = Node(strict_metadata=True)
+node.send_output("image", data, {"units": "m", "parameters": {"exposure": 20}})
+```
+
+Metadata values without a native representation, e.g. floats or nested dicts,
+are converted to strings by default. Pass `metadata_codec="json"`, `"msgpack"`,
+or `"cbor"` to serialize them with the given format instead. The codec is
+stored in the metadata, so receivers decode the values automatically:
+
+```python
+node = Node(metadata_codec="msgpack")
+node.send_output("pose", data, {"covariance": [[0.1, 0.0], [0.0, 0.1]]})
+```
+
+For event-time processing, e.g. time-windowed aggregations across inputs, pass
+`watermarks=True`. The node then emits `WATERMARK` events, whose `time` signals
+that no more inputs with an earlier or equal `event.timestamp()` will arrive.
+The watermark is the minimum over all open inputs of the latest received input
+timestamp. There is no watermark until every input received an event, and
+closed inputs no longer hold back the watermark:
+
+```python
+node = Node(watermarks=True, event_class=True)
+for event in node:
+    if event["type"] == "INPUT":
+        windows.add(event.timestamp(), event["value"])
+    elif event["type"] == "WATERMARK":
+        windows.close_until(event["time"])
+```
+
+A node is bound to the process that created it. Using it in a child process
+created through `fork`, e.g. by `multiprocessing`, raises a `RuntimeError`
+since sharing the connection to the daemon with the parent corrupts it. To
+use the node in the child instead, call `reinit_after_fork` there first.
+
+If another process is already connected with the same node ID, e.g. because
+the node was started twice, `Node()` raises a `dora.DuplicateNodeIdError`.
+If the node was built against a dora version that is not compatible with the
+running daemon, `Node()` raises a `dora.VersionMismatchError`, which reports
+both versions.
+
+Errors of dora are raised as subclasses of `dora.DoraError`, which derives
+from `RuntimeError`. Failed sends raise a `dora.SendError`, or a
+`dora.DisconnectedError` if the connection to the daemon was lost:
+
+```python
+try:
+    node.send_output("status", pa.array([1]))
+except dora.DisconnectedError:
+    reconnect()
+```
+
+To record all received inputs into an Arrow IPC file, pass the path of the file
+as `record`. The file can be compressed through the built-in compression of the
+Arrow IPC format by passing `compression="zstd"` or `compression="lz4"`, which
+substantially reduces the size of recordings of sensor data. Recordings are
+replayed through `Node.from_recording`, which detects the compression
+automatically:
+
+```python
+node = Node(record="events.arrow", compression="zstd")
+...
+for event in Node.from_recording("events.arrow"):
+    ...
+```
+
+Long recordings can be split into several files through `rotate_recording`
+and `set_recording_rotation`. Inputs that cannot be written to the recording,
+e.g. because the disk is full, are still returned by `next()`, with a
+`RuntimeWarning`.
+
+To test the node logic without a running dataflow, pass `dry_run=True`. Outputs
+are then converted and validated as usual, but recorded instead of being sent,
+and can be inspected through `dry_run_outputs`. All output IDs are accepted and
+the node receives no events:
+
+```python
+node = Node("detector", dry_run=True)
+node.send_output("boxes", pa.array([1.0, 2.0]))
+[output] = node.dry_run_outputs()
+assert output["id"] == "boxes" and output["value"].type == pa.float64()
+```
+
+To observe which outputs are lost, pass the ID of a declared output as
+`dead_letter`. Outputs that cannot be delivered are then sent on this output
+instead of being dropped, with the metadata parameters `dead_letter_reason`
+and `dead_letter_output`. The reason is `"undeclared_output"` for outputs that
+are not listed in the dataflow descriptor, `"no_subscribers"` if no node
+receives the output, and `"not_acknowledged"` if a reliably sent output timed
+out. Only these failures of the sender are dead-lettered: inputs that a
+receiver drops because of its `queue_size` or `max_buffered_bytes` limit, or
+because a newer message of a conflated output replaced them, are reported to
+the receiver through the `dropped_count` parameter instead:
+
+```python
+node = Node(dead_letter="dlq")
+node.send_output("debug_image", image)  # sent on `dlq` if nobody listens
+```
+
+Inputs sent with `reliable=True` are acknowledged to the sender as soon as they
+are returned by `next()`. To acknowledge them only after they were processed,
+pass `manual_acks=True` and call `ack` or `ack_all`, see `ack` for details.
+
+Large inputs are received without copying, so their values are views into
+memory that is shared with the sender and released when the event and all
+arrays derived from it are dropped. To avoid reasoning about the lifetime of
+these views, e.g. when handing values to native code that keeps raw pointers,
+pass `always_copy=True`. Every input is then copied into memory that is owned
+by the event and the shared memory is returned to the sender immediately.
+This costs an additional copy of each payload, which is noticeable for
+inputs of several megabytes at high rates, such as camera images.
+
+To connect to a specific dataflow, independent of the environment of the
+process, pass its ID as `dataflow` together with the `node_id` of a dynamic
+node of that dataflow. `daemon_address` selects another daemon than the local
+one. A process can connect to several dataflows this way, e.g. to bridge them
+through `Node.connect` and `forward`:
+
+```python
+source = Node.connect(dataflow_a, "bridge")
+sink = Node.connect(dataflow_b, "bridge")
+for event in source:
+    if event["type"] == "INPUT":
+        sink.forward(event)
+```
+
+Pass `emit_summary_on_close=True` to report the statistics of the node when
+it is closed, i.e. when the `Node` object is dropped. The summary is reported
+before the connection to the daemon is closed and delivered as `SUMMARY` event
+to the nodes that subscribed through `lifecycle_events`, e.g. a collector:
+
+```python
+for event in node.lifecycle_events():
+    if event["type"] == "SUMMARY":
+        print(f"{event['node']}: {event['received']} inputs, {event['dropped']} dropped")
+```
+
+Inputs whose value cannot be decoded, e.g. because the payload is corrupted
+or because a decoder registered through `set_decoder` raised an exception,
+are handled according to `on_decode_error`, so that a faulty sender does not
+stop the whole event stream:
+
+- `"event"` (default): `next()` returns a `{"type": "DECODE_ERROR", "id": ...,
+  "error": ...}` event instead of the input and continues with the next event.
+- `"ignore"`: the input is skipped.
+- `"raise"`: `next()` raises the error. The undecoded input is available as
+  the `event` attribute of the exception.
+
+```python
+for event in Node(on_decode_error="event"):
+    if event["type"] == "DECODE_ERROR":
+        print(f"dropped corrupt message on `{event['id']}`: {event['error']}")
+```
+
+By default, `next()` alternates between the inputs that have queued events.
+Pass `input_schedule="weighted_fair"` together with `weights` per input to
+return the queued events of each input at a rate proportional to its weight
+instead, so that a flood of events on one input cannot starve a low-rate but
+important input. Inputs without weight have weight `1.0`. Events sent with
+`priority="high"` are still returned first:
+
+```python
+node = Node(input_schedule="weighted_fair", weights={"command": 4.0, "image": 1.0})
+```
+
+Events are returned as plain `dict`s. Pass `event_class=True` to receive
+`dora.Event` objects instead, which can be accessed like a `dict` but also
+provide methods such as `event.timestamp()` or `event.to_ndarray()`:
+
+```python
+for event in Node(event_class=True):
+    if event["type"] == "INPUT":
+        print(event.id_and_seq(), event.timestamp())
+```"""
+
+    def __init__(self, node_id: str=None, max_buffered_bytes: int=None, assert_monotonic_timestamps: bool | str=None, strict_metadata: bool=None, watermarks: bool=None, record: str=None, compression: str=None, dry_run: bool=None, dead_letter: str=None, manual_acks: bool=None, always_copy: bool=None, metadata_codec: str=None, dataflow: str=None, daemon_address: str=None, emit_summary_on_close: bool=None, on_decode_error: str=None, input_schedule: str=None, weights: dict[str, float]=None, event_class: bool=None) -> None:
+        """The custom node API lets you integrate `dora` into your application.
+It allows you to retrieve input and send output in any fashion you want.
+
+Use with:
+
+```python
+from dora import Node
+
+node = Node()
+```
+
+To bound the memory used by inputs that were received but not processed yet,
+pass `max_buffered_bytes`. When the total payload size of the buffered inputs
+exceeds it, the oldest events of the input that buffers the most bytes are
+dropped, like for the `queue_size` limit of inputs:
+
+```python
+node = Node(max_buffered_bytes=256 * 1024 * 1024)
+```
+
+To detect clock bugs of upstream nodes, pass `assert_monotonic_timestamps=True`.
+A `RuntimeWarning` is then emitted whenever the timestamp of an input event is
+older than the timestamp of the previous event of the same input. Pass
+`assert_monotonic_timestamps="raise"` to raise a `RuntimeError` instead. The
+event is still processed and available as the `event` attribute of the error.
+
+By default, arbitrary keys are accepted in the `metadata` of sent outputs. Pass
+`strict_metadata=True` to reject keys that are not known to dora, e.g. to catch
+typos like `"frameid"`. User-defined metadata must then be nested under the
+`parameters` key:
+
+```python
+node = Node(strict_metadata=True)
+node.send_output("image", data, {"units": "m", "parameters": {"exposure": 20}})
+```
+
+Metadata values without a native representation, e.g. floats or nested dicts,
+are converted to strings by default. Pass `metadata_codec="json"`, `"msgpack"`,
+or `"cbor"` to serialize them with the given format instead. The codec is
+stored in the metadata, so receivers decode the values automatically:
+
+```python
+node = Node(metadata_codec="msgpack")
+node.send_output("pose", data, {"covariance": [[0.1, 0.0], [0.0, 0.1]]})
+```
+
+For event-time processing, e.g. time-windowed aggregations across inputs, pass
+`watermarks=True`. The node then emits `WATERMARK` events, whose `time` signals
+that no more inputs with an earlier or equal `event.timestamp()` will arrive.
+The watermark is the minimum over all open inputs of the latest received input
+timestamp. There is no watermark until every input received an event, and
+closed inputs no longer hold back the watermark:
+
+```python
+node = Node(watermarks=True, event_class=True)
+for event in node:
+    if event["type"] == "INPUT":
+        windows.add(event.timestamp(), event["value"])
+    elif event["type"] == "WATERMARK":
+        windows.close_until(event["time"])
+```
+
+A node is bound to the process that created it. Using it in a child process
+created through `fork`, e.g. by `multiprocessing`, raises a `RuntimeError`
+since sharing the connection to the daemon with the parent corrupts it. To
+use the node in the child instead, call `reinit_after_fork` there first.
+
+If another process is already connected with the same node ID, e.g. because
+the node was started twice, `Node()` raises a `dora.DuplicateNodeIdError`.
+If the node was built against a dora version that is not compatible with the
+running daemon, `Node()` raises a `dora.VersionMismatchError`, which reports
+both versions.
+
+Errors of dora are raised as subclasses of `dora.DoraError`, which derives
+from `RuntimeError`. Failed sends raise a `dora.SendError`, or a
+`dora.DisconnectedError` if the connection to the daemon was lost:
+
+```python
+try:
+    node.send_output("status", pa.array([1]))
+except dora.DisconnectedError:
+    reconnect()
+```
+
+To record all received inputs into an Arrow IPC file, pass the path of the file
+as `record`. The file can be compressed through the built-in compression of the
+Arrow IPC format by passing `compression="zstd"` or `compression="lz4"`, which
+substantially reduces the size of recordings of sensor data. Recordings are
+replayed through `Node.from_recording`, which detects the compression
+automatically:
+
+```python
+node = Node(record="events.arrow", compression="zstd")
+...
+for event in Node.from_recording("events.arrow"):
+    ...
+```
+
+Long recordings can be split into several files through `rotate_recording`
+and `set_recording_rotation`. Inputs that cannot be written to the recording,
+e.g. because the disk is full, are still returned by `next()`, with a
+`RuntimeWarning`.
+
+To test the node logic without a running dataflow, pass `dry_run=True`. Outputs
+are then converted and validated as usual, but recorded instead of being sent,
+and can be inspected through `dry_run_outputs`. All output IDs are accepted and
+the node receives no events:
+
+```python
+node = Node("detector", dry_run=True)
+node.send_output("boxes", pa.array([1.0, 2.0]))
+[output] = node.dry_run_outputs()
+assert output["id"] == "boxes" and output["value"].type == pa.float64()
+```
+
+To observe which outputs are lost, pass the ID of a declared output as
+`dead_letter`. Outputs that cannot be delivered are then sent on this output
+instead of being dropped, with the metadata parameters `dead_letter_reason`
+and `dead_letter_output`. The reason is `"undeclared_output"` for outputs that
+are not listed in the dataflow descriptor, `"no_subscribers"` if no node
+receives the output, and `"not_acknowledged"` if a reliably sent output timed
+out. Only these failures of the sender are dead-lettered: inputs that a
+receiver drops because of its `queue_size` or `max_buffered_bytes` limit, or
+because a newer message of a conflated output replaced them, are reported to
+the receiver through the `dropped_count` parameter instead:
+
+```python
+node = Node(dead_letter="dlq")
+node.send_output("debug_image", image)  # sent on `dlq` if nobody listens
+```
+
+Inputs sent with `reliable=True` are acknowledged to the sender as soon as they
+are returned by `next()`. To acknowledge them only after they were processed,
+pass `manual_acks=True` and call `ack` or `ack_all`, see `ack` for details.
+
+Large inputs are received without copying, so their values are views into
+memory that is shared with the sender and released when the event and all
+arrays derived from it are dropped. To avoid reasoning about the lifetime of
+these views, e.g. when handing values to native code that keeps raw pointers,
+pass `always_copy=True`. Every input is then copied into memory that is owned
+by the event and the shared memory is returned to the sender immediately.
+This costs an additional copy of each payload, which is noticeable for
+inputs of several megabytes at high rates, such as camera images.
+
+To connect to a specific dataflow, independent of the environment of the
+process, pass its ID as `dataflow` together with the `node_id` of a dynamic
+node of that dataflow. `daemon_address` selects another daemon than the local
+one. A process can connect to several dataflows this way, e.g. to bridge them
+through `Node.connect` and `forward`:
+
+```python
+source = Node.connect(dataflow_a, "bridge")
+sink = Node.connect(dataflow_b, "bridge")
+for event in source:
+    if event["type"] == "INPUT":
+        sink.forward(event)
+```
+
+Pass `emit_summary_on_close=True` to report the statistics of the node when
+it is closed, i.e. when the `Node` object is dropped. The summary is reported
+before the connection to the daemon is closed and delivered as `SUMMARY` event
+to the nodes that subscribed through `lifecycle_events`, e.g. a collector:
+
+```python
+for event in node.lifecycle_events():
+    if event["type"] == "SUMMARY":
+        print(f"{event['node']}: {event['received']} inputs, {event['dropped']} dropped")
+```
+
+Inputs whose value cannot be decoded, e.g. because the payload is corrupted
+or because a decoder registered through `set_decoder` raised an exception,
+are handled according to `on_decode_error`, so that a faulty sender does not
+stop the whole event stream:
+
+- `"event"` (default): `next()` returns a `{"type": "DECODE_ERROR", "id": ...,
+  "error": ...}` event instead of the input and continues with the next event.
+- `"ignore"`: the input is skipped.
+- `"raise"`: `next()` raises the error. The undecoded input is available as
+  the `event` attribute of the exception.
+
+```python
+for event in Node(on_decode_error="event"):
+    if event["type"] == "DECODE_ERROR":
+        print(f"dropped corrupt message on `{event['id']}`: {event['error']}")
+```
+
+By default, `next()` alternates between the inputs that have queued events.
+Pass `input_schedule="weighted_fair"` together with `weights` per input to
+return the queued events of each input at a rate proportional to its weight
+instead, so that a flood of events on one input cannot starve a low-rate but
+important input. Inputs without weight have weight `1.0`. Events sent with
+`priority="high"` are still returned first:
+
+```python
+node = Node(input_schedule="weighted_fair", weights={"command": 4.0, "image": 1.0})
+```
+
+Events are returned as plain `dict`s. Pass `event_class=True` to receive
+`dora.Event` objects instead, which can be accessed like a `dict` but also
+provide methods such as `event.timestamp()` or `event.to_ndarray()`:
+
+```python
+for event in Node(event_class=True):
+    if event["type"] == "INPUT":
+        print(event.id_and_seq(), event.timestamp())
+```"""
+
+    def ack(self, event: dict) -> None:
+        """Acknowledges a reliably sent input event after it was processed.
+
+Only needed if the node was created with `manual_acks=True`, otherwise
+inputs are acknowledged as soon as they are returned by `next()`. The
+sender of a reliable output blocks until all receivers acknowledged it, so
+acknowledging after processing limits how far the sender can run ahead:
+
+```python
+node = Node(manual_acks=True)
+for event in node:
+    if event["type"] == "INPUT":
+        process(event["value"])
+        node.ack(event)
+```
+
+Inputs that are not acknowledged within the `reliable_timeout` of the
+sender are sent again every second, so the same input can be received
+again before it is acknowledged. Acknowledging an input also acknowledges
+these copies. Events that were not sent reliably are ignored."""
+
+    def ack_all(self) -> None:
+        """Acknowledges all reliably sent inputs that were received, but not
+acknowledged yet, see `ack`.
+
+The acknowledgements are sent to the daemon in a single batch, which is
+cheaper than calling `ack` for each input, e.g. after processing a batch
+of inputs at once."""
+
+    def ack_stop(self) -> None:
+        """Acknowledges that the node finished its shutdown after receiving a `STOP`
+event, e.g. after flushing its buffered data.
+
+If the dataflow declares a `shutdown_order`, the daemon sends the `STOP`
+event to the next node of the order only after this acknowledgement, or
+after the node exited. This allows stopping sinks before sources, so that
+no data is lost. The grace duration of the stop is split evenly between the
+ordered nodes and the remaining nodes, so the acknowledgement must arrive
+within the share of the node:
+
+```python
+for event in node:
+    if event["type"] == "STOP":
+        recorder.flush()
+        node.ack_stop()
+        break
+```
+
+Without a `shutdown_order`, the acknowledgement has no effect."""
+
+    def add_timer(self, timer_id: str, period: float=None, frequency: float=None, drift_correct: bool=None) -> None:
+        """Adds a timer that emits an `INPUT` event with the given ID and an empty
+value periodically, like the `dora/timer` inputs of the dataflow, but
+without declaring an input.
+
+The interval is given either as `period` in seconds or as `frequency` in
+Hz. The ticks are emitted by `next()`, so they are only delivered while
+the node waits for events.
+
+By default, each tick is scheduled one period after the previous one was
+delivered, so delays add up and the timer slowly falls behind. With
+`drift_correct=True`, the ticks are scheduled at multiples of the period
+after the start of the timer instead. Ticks that are missed because the
+node did not call `next()` in time are skipped, not delivered in a burst:
+
+```python
+node.add_timer("tick", frequency=100, drift_correct=True)
+for event in node:
+    if event["type"] == "INPUT" and event["id"] == "tick":
+        control_step()
+```
+
+The timing quality can be checked through `timer_stats`. To drive the
+timers by the time of a simulator instead of the wall clock, see
+`use_sim_time`."""
+
+    def bandwidth_stats(self) -> dict:
+        """Returns the bandwidth used by each input and output of this node, in bytes
+per second.
+
+The result has the form `{"inputs": {id: rate}, "outputs": {id: rate}}`. The
+rates are averaged over the last 5 seconds and only count the payload of
+the messages. This helps to find the edges that saturate a network link:
+
+```python
+stats = node.bandwidth_stats()
+for output_id, rate in stats["outputs"].items():
+    print(f"{output_id}: {rate / 1e6:.1f} MB/s")
+```
+
+Inputs and outputs without any messages so far are not included."""
+
+    def buffer_info(self) -> dict:
+        """Returns the sizes of the internal buffers of the node, to check that none
+of them grows unexpectedly:
+
+- `input_queues`: number of received inputs that were not returned by
+  `next()` yet, per input,
+- `buffered_bytes`: total payload size of these inputs,
+- `unacked_inputs`: reliably sent inputs that were not acknowledged yet,
+  see `ack`,
+- `scheduled_outputs`: outputs that were sent with `at` and are not due yet,
+- `pending_drop_tokens`: shared memory outputs that are still in use by
+  receivers,
+- `shared_memory_cache`: shared memory regions kept for reuse by later
+  outputs.
+
+```python
+info = node.buffer_info()
+assert info["unacked_inputs"] < 100
+```"""
+
+    def buffered_bytes(self) -> int:
+        """Returns the total payload size in bytes of the inputs that were received,
+but not yet returned by `next()`.
+
+Inputs are not buffered anymore once external events were merged through
+`merge_external_events`, so `0` is returned in that case."""
+
+    def capture_output(self, enabled: bool) -> None:
+        """Redirects the stdout and stderr of the node process to dora log messages.
+
+While enabled, every line that is written to stdout or stderr, e.g. through
+`print`, is sent to the dora coordinator as a log message tagged with the ID
+of this node, instead of being written to the terminal or the log file of
+the node. Lines of stdout are logged with level `INFO`, lines of stderr with
+level `WARN`. The messages are shown when attaching to the dataflow, e.g.
+through `dora start --attach`.
+
+```python
+node.capture_output(True)
+print("sent as log message")
+node.capture_output(False)
+```
+
+The redirection applies to the whole process, including native libraries
+and threads. Captured lines are buffered in memory, so large bursts of
+output never block the writer. They are forwarded while the node waits for
+events in `next()` and when it sends outputs. This is only supported on
+Unix."""
+
+    def clear_buffers(self) -> dict:
+        """Empties the internal buffers of the node, e.g. between tests or to recover
+from a backlog, and returns the number of discarded items per buffer.
+
+Queued inputs are dropped, unacknowledged inputs are forgotten without
+acknowledging them, so that their senders send them again, and scheduled
+outputs are discarded. Other events that are still queued, e.g.
+`INPUT_CLOSED` or `STOP`, are kept. Shared memory outputs that are still in
+use by receivers cannot be cleared."""
+
+    def clock_skew(self, node_id: str) -> tuple[float, float]:
+        """Returns the estimated offset of the wall clock of another node of the
+dataflow relative to the clock of this node, and the uncertainty of the
+estimate, both in seconds.
+
+The offset is positive if the clock of the other node is ahead. Subtract it
+from the timestamps of the other node to compare them with local timestamps:
+
+```python
+offset, uncertainty = node.clock_skew("lidar")
+latency = time.time() - (event["metadata"]["timestamp_ns"] / 1e9 - offset)
+```
+
+Nodes on the same machine have an offset of zero. For other machines, the
+offset is estimated by the dora-coordinator, which exchanges timestamps with
+every daemon along with its heartbeats, as in NTP. The uncertainty is half
+the round-trip delay of these exchanges. An error is raised if the clocks
+were not synchronized yet, e.g. in the first seconds after the start."""
+
+    @staticmethod
+    def connect(dataflow: str, node_id: str, daemon_address: str=None) -> dora.Node:
+        """Connects to the dynamic node `node_id` of the given dataflow.
+
+This is a shorthand for `Node(node_id, dataflow=dataflow, daemon_address=daemon_address)`,
+which ignores the `DORA_NODE_CONFIG` of the process. Call it once per dataflow
+to be a member of several dataflows at once, e.g. to bridge them:
+
+```python
+source = Node.connect("2d4ce4e5-...", "bridge")
+sink = Node.connect("9b1a27f0-...", "bridge", daemon_address="10.0.0.2:53291")
+```"""
+
+    def dataflow_descriptor(self) -> dict:
+        """Returns the full dataflow descriptor that this node is part of.
+
+This method returns the parsed dataflow YAML file."""
+
+    def dataflow_id(self) -> str:
+        """Returns the dataflow id."""
+
+    def dataflow_start_time(self) -> float:
+        """Returns the time at which the dataflow was started, in seconds since the
+Unix epoch.
+
+The start time is assigned by the dora-coordinator, so it is the same for
+all nodes, regardless of when they joined. It has millisecond precision.
+Returns `None` if the start time is unknown, e.g. in dry-run mode."""
+
+    def dataflow_uptime(self) -> float:
+        """Returns how long the dataflow has been running, in seconds.
+
+See `dataflow_start_time`. The uptime is measured with the clock of this
+machine, so it is affected by clock differences to the machine of the
+dora-coordinator:
+
+```python
+node.send_output("uptime", pa.array([node.dataflow_uptime()]))
+```"""
+
+    def declare_dynamic_output(self, output_id: str, schema: pyarrow.Schema | str=None) -> dora.RecordBatchOutput:
+        """Declares an output that is not listed in the dataflow YAML.
+
+The daemon routes the new output to all inputs that are mapped to it in the
+dataflow YAML. Inputs can only be mapped to outputs that are not listed in
+`outputs` if the node sets `dynamic_outputs: true`. If a `schema` is given, a
+`RecordBatchOutput` for the new output is returned, otherwise `None`.
+
+```python
+for device in discover_devices():
+    node.declare_dynamic_output(device.name)
+```"""
+
+    def disconnect_input(self, input_id: str) -> None:
+        """Unsubscribes the given input from its upstream output.
+
+The daemon stops sending new messages to the input and an `INPUT_CLOSED`
+event is reported for it. Messages that were already sent to the node are
+still returned by `next()` before the `INPUT_CLOSED` event. The other
+inputs are not affected.
+
+```python
+node.disconnect_input("old_camera")
+```"""
+
+    def dry_run_outputs(self) -> list[dict]:
+        """Returns the outputs that were recorded since the last call, for nodes created
+with `dry_run=True`.
+
+Each output is a dict with the output `id`, the sent `value` as
+`pyarrow.Array`, and its `metadata` as `dora.Metadata`, in send order."""
+
+    def forward(self, event: dict, output_id: str=None) -> None:
+        """Sends the value and metadata of an input event on an output of this node.
+
+The output defaults to the ID of the input. Combined with `Node.connect`, this
+forwards messages between dataflows:
+
+```python
+for event in source:
+    if event["type"] == "INPUT" and event["id"] == "pose":
+        sink.forward(event, "remote_pose")
+```
+
+The value is sent as received, without invoking a decoder. The metadata
+parameters are kept, except for the ones that describe the delivery of the
+input, e.g. `dropped_count`."""
+
+    @staticmethod
+    def from_recording(path: str, speed: float=None, event_class: bool=None) -> dora.Recording:
+        """Replays the inputs of a recording that was created through `Node(record=...)`.
+
+Returns an iterator over the recorded input events, in the order in which
+they were received. Compressed recordings are decompressed transparently.
+
+```python
+for event in Node.from_recording("events.arrow"):
+    process(event["id"], event["value"])
+```
+
+By default, the events are yielded with their original timing, based on
+the recorded timestamps. The `speed` factor scales the playback, e.g.
+`speed=2.0` replays twice as fast and `speed=0.5` in slow motion. Pass
+`speed=0` to yield the events as fast as possible, e.g. for regression
+tests.
+
+Like `next()`, the events are yielded as `dict`s, unless `event_class` is
+set."""
+
+    def get_state(self, key: str) -> typing.Any:
+        """Returns the value stored for `key` in the persistent state of this node,
+or `None` if no value was stored yet.
+
+The state is stored under the working directory, keyed by the dataflow id
+and the node id, so it survives restarts of the node:
+
+```python
+offset = node.get_state("calibration_offset")
+if offset is None:
+    offset = calibrate()
+    node.set_state("calibration_offset", offset)
+```
+
+The state is not locked across processes. If multiple instances of the
+same node run at the same time, the last `set_state` call wins."""
+
+    def input_schema(self, input_id: str) -> pyarrow.Schema:
+        """Returns the arrow schema of the most recent message on the given input.
+
+Inputs that are sent as struct arrays, e.g. through `record_batch_output`,
+have one schema field per struct field. Other inputs have a single nullable
+field named `value`. This allows configuring Arrow compute kernels ahead of
+time instead of inspecting every event:
+
+```python
+schema = node.input_schema("detections")
+if schema is not None and "score" in schema.names:
+    ...
+```
+
+Returns `None` if no message was received on the input through `next()` yet."""
+
+    def lifecycle_events(self) -> dora.LifecycleEvents:
+        """Subscribes to notifications about nodes joining or leaving the dataflow.
+
+The returned iterator yields `{"type": "NODE_JOINED", "node": id}` and
+`{"type": "NODE_LEFT", "node": id}` dicts, separately from the inputs that
+are returned by `next()`. It starts with a `NODE_JOINED` event for every node
+that joined already, including this node. This allows building a live roster
+of the dataflow without polling:
+
+```python
+lifecycle = node.lifecycle_events()
+while (event := lifecycle.recv(timeout=0.1)) is not None:
+    print(event["type"], event["node"])
+```
+
+Progress reports of other nodes, see `send_progress`, are yielded as
+`{"type": "PROGRESS", "node": id, "task": str, "progress": float, "message": str | None}`
+dicts.
+
+Iterating over the returned object blocks until the next event arrives, so
+it's usually done in a separate thread."""
+
+    def merge_external_events(self, subscription: dora.Ros2Subscription | dora.ExternalEventStream, name: str=None, on_error: str=None, idle_timeout: float=None) -> None:
+        """Merge an external event stream with dora main loop.
+Supported sources are ROS2 subscriptions and streams created through
+`dora.external_stream_from_async`.
+
+Events of the external stream are tagged with the given `name` as `source`.
+
+If the external stream errors, the behavior depends on `on_error`:
+
+- `"event"` (default): emit an `{"type": "ERROR", "source": name, "error": ...}`
+  event and keep receiving from the external stream.
+- `"ignore"`: silently skip the erroneous message.
+
+If `idle_timeout` (in seconds) is set, a `{"type": "WARNING", "source": name}` event
+is emitted each time the external stream produces no value for this duration.
+
+```python
+node.merge_external_events(subscription, name="turtle_pose", idle_timeout=5.0)
+```"""
+
+    def merge_info(self) -> list[dict]:
+        """Describes the external streams that were merged through
+`merge_external_events`, in the order in which they were merged.
+
+Each stream is described by a dict containing its `name`, i.e. the `source`
+of its events, its `type` (`ros2` or `external`), whether it is still
+`active`, the number of `events` received from it so far, and its
+`idle_timeout` in seconds. A stream is not active anymore once it ended,
+e.g. because the async generator of an external stream returned:
+
+```python
+for source in node.merge_info():
+    if not source["active"]:
+        print(f"{source['name']} ended after {source['events']} events")
+```"""
+
+    def metrics_text(self) -> str:
+        """Returns the counters of this node in the OpenMetrics text format, which
+can be scraped by Prometheus.
+
+The text contains the total number of messages and payload bytes that were
+received on each input and sent on each output, the number of messages
+that were dropped because an input queue was full, and the sum of the
+latencies of the received messages. Samples are labeled with the
+`dataflow`, the `node`, and the `input` or `output` ID:
+
+```text
+dora_node_input_messages_total{dataflow="...",node="plot",input="image"} 1200
+dora_node_input_latency_seconds_sum{dataflow="...",node="plot",input="image"} 3.6
+dora_node_input_latency_seconds_count{dataflow="...",node="plot",input="image"} 1200
+```
+
+Serve it on an HTTP endpoint to monitor the node, e.g. through
+`prometheus_client` or `http.server`:
+
+```python
+class MetricsHandler(http.server.BaseHTTPRequestHandler):
+    def do_GET(self):
+        body = node.metrics_text().encode()
+        self.send_response(200)
+        self.send_header("Content-Type", "application/openmetrics-text; version=1.0.0")
+        self.end_headers()
+        self.wfile.write(body)
+```
+
+Inputs and outputs without any messages so far are not included."""
+
+    def namespace(self) -> str | None:
+        """Returns the namespace of this node, e.g. `perception` for the node
+`perception/camera`, or `None` if the node ID has no namespace.
+
+Node IDs are organized into namespaces through `/`, which keeps the IDs
+of the subsystems of large dataflows apart. Inputs refer to the outputs
+of namespaced nodes as `<namespace>/<node>/<output>`:
+
+```yaml
+- id: planning/planner
+  inputs:
+    image: perception/camera/image
+```"""
+
+    def next(self, timeout: float=None) -> dict:
+        """`.next()` gives you the next input that the node has received.
+It blocks until the next event becomes available.
+You can use timeout in seconds to return if no input is available.
+It will return `None` when all senders has been dropped.
+
+```python
+event = node.next()
+```
+
+You can also iterate over the event stream with a loop
+
+```python
+for event in node:
+match event["type"]:
+case "INPUT":
+match event["id"]:
+case "image":
+```"""
+
+    def next_tick(self, key: str=None, inputs: list[str]=None, timeout: float=None) -> tuple[int, dict[str, dict]]:
+        """Waits until each input has an event of the same tick and returns the
+tick number with the events, as a dict from input ID to event.
+
+This is a barrier for lockstep pipelines, in which a tick must only be
+processed once all of its inputs arrived. The tick number is read from the
+integer metadata value `key`, which the senders set per message. Events of
+future ticks are buffered until their tick is complete:
+
+```python
+while (result := node.next_tick(key="tick")) is not None:
+    tick, events = result
+    step(tick, events["lidar"]["value"], events["camera"]["value"])
+```
+
+By default, all inputs of the node take part in a tick, pass `inputs` to
+synchronize only some of them. Events of other inputs and other events
+than `STOP` are dropped, use `next()` to receive them instead. Ticks are
+returned in increasing order: once a newer tick is complete, older ticks
+that still miss events are discarded with a `RuntimeWarning`, and so are
+late events of ticks that were already returned.
+
+Returns `None` on `STOP`, at the end of the event stream, or if no tick
+completed within `timeout` seconds. Raises an error if an input of the
+tick is closed or if an input event has no `key` metadata."""
+
+    def node_id(self) -> str:
+        """Returns the ID of this node, e.g. `perception/camera`."""
+
+    def on_all_inputs_closed(self, callback: typing.Callable[[], None]) -> None:
+        """Registers a callback that is invoked once when all inputs of the node are
+closed, i.e. when every upstream node finished or was disconnected.
+
+The callback takes no arguments and is called when the last `INPUT_CLOSED`
+event is received, before that event is returned. This applies to every
+method that receives events, i.e. `next()`, `next_tick()`, and the readers of
+`record_batch_reader`, which also propagate exceptions raised by the
+callback. The received event is then available as the `event` attribute of
+the exception, so it is not lost. The callback is called immediately if all
+inputs are already closed. This simplifies the end-of-stream cleanup of nodes
+with multiple inputs:
+
+```python
+node.on_all_inputs_closed(lambda: writer.close())
+for event in node:
+    ...
+```
+
+Inputs added through `subscribe` are not known in advance, so for nodes that
+subscribed to a pattern, the callback is called at the end of the event
+stream instead, when `next()` returns `None`."""
+
+    def param(self, name: str, default: typing.Any=None) -> typing.Any:
+        """Returns the value of the dataflow-level parameter `name`.
+
+Parameters are declared in the top-level `params` section of the dataflow
+YAML and shared by all nodes:
+
+```yaml
+params:
+  map_path: maps/office.yaml
+  max_speed: 1.5
+```
+
+An environment variable named like the parameter in upper case with the
+`DORA_PARAM_` prefix, e.g. `DORA_PARAM_MAP_PATH`, overrides the declared
+value, for example through the `env` entries of a single node. If a
+`default` is given, the value is converted to the type of the default, so
+`"2"` is returned as `2.0` for a `float` default. The `default` is returned
+if the parameter is not set, otherwise a `KeyError` is raised:
+
+```python
+map_path = node.param("map_path", default="map.yaml")
+max_speed = node.param("max_speed", default=1.0)
+```"""
+
+    def pipeline_depths(self) -> dict[tuple[str, str], int]:
+        """Returns the number of queued events for every edge of the dataflow.
+
+The result maps `(source, target)` pairs, e.g. `("camera/image", "plot/image")`,
+to the number of events that the target node received on the input, but
+did not process yet. This helps to find the bottleneck of a dataflow:
+
+```python
+depths = node.pipeline_depths()
+(source, target), depth = max(depths.items(), key=lambda edge: edge[1])
+print(f"bottleneck: {target} with {depth} queued events from {source}")
+```
+
+The depths are reported periodically by the receiving nodes, so they can be
+slightly outdated. Only edges to nodes on the same machine are included."""
+
+    def ready_inputs(self) -> set[str]:
+        """Returns the IDs of the inputs that have at least one received event that
+was not returned by `next()` yet.
+
+The events are not consumed, so a node can use this to decide how to
+handle its backlog, e.g. to skip expensive work while a newer input is
+already waiting:
+
+```python
+if "image" in node.ready_inputs():
+    continue  # a newer image is queued already
+```
+
+Inputs are not buffered anymore once external events were merged through
+`merge_external_events`, so an empty set is returned in that case."""
+
+    def record_batch_output(self, output_id: str, schema: pyarrow.Schema | str) -> dora.RecordBatchOutput:
+        """Declares a `RecordBatch`-shaped output with a fixed arrow schema.
+
+The returned sender validates every batch against the schema before sending it
+as a struct array with one field per schema column.
+
+```python
+schema = pa.schema([("label", pa.string()), ("score", pa.float32())])
+sender = node.record_batch_output("detections", schema)
+sender.send({"label": pa.array(["cat"]), "score": pa.array([0.9], pa.float32())})
+```
+
+Instead of a schema, the name of a registered schema can be given, see
+`dora.register_schema`. The batches are then sent with `schema` metadata."""
+
+    def record_batch_reader(self, input_id: str, schema: pyarrow.Schema=None) -> pyarrow.RecordBatchReader:
+        """Returns a `pyarrow.RecordBatchReader` over the events of the given input.
+
+Every input event becomes one record batch, so the input must be sent as a
+struct array, e.g. through `record_batch_output`. This allows feeding dora
+inputs directly into Arrow-native libraries:
+
+```python
+table = node.record_batch_reader("detections")
+duckdb.sql("SELECT label, avg(score) FROM table GROUP BY label")
+```
+
+If no `schema` is given, the reader waits for the first event of the input
+and uses its schema. The reader ends when the input is closed or when the
+dataflow is stopped. Events of other inputs that are received while reading
+are kept and returned by `next` afterwards, in the order they were received,
+including the event that closed the input or stopped the dataflow."""
+
+    def reinit_after_fork(self) -> None:
+        """Re-establishes the connection to the daemon in a child process created
+through `fork`.
+
+The connection of the parent process is left untouched, it is neither used
+nor closed by the child. The child connects as the same node and takes over
+the subscription of the parent, so the parent must not use its node anymore
+afterwards:
+
+```python
+node = Node()
+if os.fork() == 0:
+    node.reinit_after_fork()
+    for event in node:
+        ...
+```
+
+Senders created through `record_batch_output` and `declare_dynamic_output`
+are bound to the old connection and need to be recreated. This method is
+only supported for nodes that were started by the daemon, not for dynamic
+nodes.
+
+The resources of the parent node are leaked in the child instead of being
+cleaned up, since they are shared with the parent: Inputs are only
+recorded by the parent, if `record` was passed. If the output was captured
+through `capture_output`, the stdout and stderr of the child stay
+redirected to the pipes of the parent, so its output is forwarded by the
+parent. Call `capture_output(True)` in the child to forward it directly."""
+
+    def remove_output(self, output_id: str) -> None:
+        """Removes an output that was declared through `declare_dynamic_output`.
+
+Inputs mapped to the output are closed. A removed output cannot be declared
+again."""
+
+    def remove_timer(self, timer_id: str) -> bool:
+        """Removes a timer that was added through `add_timer`.
+
+Returns `False` if no timer with the given ID exists."""
+
+    def send_flight_data(self, descriptor: pyarrow.flight.FlightDescriptor, batch: pyarrow.RecordBatch, app_metadata: bytes=None, metadata: dict=None, caused_by: list[dict | tuple[str, int]]=None) -> None:
+        """Sends an Arrow Flight frame as dora output.
+
+This is the counterpart of `Event.to_flight_data`, which allows bridge nodes
+to relay data between dora and Arrow Flight services. The output id is
+taken from the path of the `descriptor`. If `app_metadata` was created by
+`Event.to_flight_data`, the metadata parameters of the original message are
+sent along:
+
+```python
+reader = client.do_get(ticket)
+for chunk in reader:
+    node.send_flight_data(descriptor, chunk.data, chunk.app_metadata)
+```
+
+A record batch with a single `value` column is sent as the column array,
+all other record batches as struct arrays. Additional `metadata` and
+`caused_by` are handled like in `send_output` and take precedence over the
+parameters of `app_metadata`."""
+
+    def send_output(self, output_id: str, data: pyarrow.Array | pyarrow.Table | list[pyarrow.RecordBatch] | bytes | list[decimal.Decimal] | scipy.sparse.spmatrix | numpy.ndarray | cupy.ndarray, metadata: dict=None, caused_by: list[dict | tuple[str, int]]=None, at: float=None, frame_id: str=None, units: str=None, priority: str=None, attachments: dict[str, bytes]=None, reliable: bool=None, reliable_timeout: float=None, schema: str=None, content_type: str=None) -> None:
+        """`send_output` send data from the node.
+
+```python
+Args:
+output_id: str,
+data: pyarrow.Array,
+metadata: Option[Dict],
+```
+
+ex:
+
+```python
+node.send_output("string", b"string", {"open_telemetry_context": "7632e76"})
+```
 
-@typing.final
-class Node:
-    """The custom node API lets you integrate `dora` into your application.
-It allows you to retrieve input and send output in any fashion you want.
+Empty data is valid and is delivered as a zero-length array, which is useful
+for pure trigger signals:
 
-Use with:
+```python
+node.send_output("trigger", b"")
+```
+
+Use `caused_by` to reference the input events that produced this output.
+The references are stored in the metadata and allow tooling to
+reconstruct the provenance of a message across the dataflow:
 
 ```python
-from dora import Node
+node.send_output("command", data, caused_by=[event])
+```
 
-node = Node()
+OpenTelemetry baggage is not propagated implicitly, since an output is not
+necessarily caused by the last received input. To propagate the baggage of
+an input, or to set new entries, pass them as `open_telemetry_baggage`
+metadata:
+
+```python
+node.send_output("pose", data, {"open_telemetry_baggage": event.baggage()})
+node.send_output("pose", data, {"open_telemetry_baggage": {"robot.id": "r2"}})
+```
+
+To describe the data of robotics messages, pass the coordinate frame as
+`frame_id` and the physical units as `units`. Receivers can read them through
+`event.frame_id()` and `event.units()`. Units should be one of the standard
+names, e.g. `"m"`, `"rad"`, `"m/s"`, or `"degC"`; other units are sent as well,
+but emit a `RuntimeWarning`:
+
+```python
+node.send_output("position", data, frame_id="base_link", units="m")
+```
+
+Encoded data, e.g. a JPEG image or a serialized protobuf message, can be
+annotated with its MIME type as `content_type`, so that generic consumers
+like viewers know how to decode the bytes. Receivers read it through
+`event.content_type()`. Any syntactically valid MIME type is accepted:
+
+```python
+node.send_output("image", cv2.imencode(".jpg", frame)[1].tobytes(), content_type="image/jpeg")
+```
+
+Control messages can be sent with `priority="high"` to deliver them before
+queued messages of normal priority, e.g. so that a stop command is not stuck
+behind large images. Bulk data can be sent with `priority="low"`. By default,
+messages are delivered in order:
+
+```python
+node.send_output("command", pa.array(["stop"]), priority="high")
+```
+
+Exact decimal values can be sent as a list of `decimal.Decimal`, which is
+converted to an arrow `Decimal128` array with the smallest precision and scale
+that represent all values exactly. To choose the precision and scale, pass a
+`pyarrow` decimal array instead. Receivers get `decimal.Decimal` values back
+through `event["value"].to_pylist()`:
+
+```python
+node.send_output("price", [Decimal("12.3456")])
+node.send_output("price", pa.array([Decimal("12.3456")], pa.decimal128(18, 6)))
+```
+
+`scipy.sparse` matrices are sent without densifying them. CSR and COO
+matrices keep their format, other formats are converted to CSR. Receivers
+reconstruct the matrix through `event.to_sparse()`:
+
+```python
+node.send_output("occupancy", scipy.sparse.csr_matrix(grid))
+```
+
+numpy structured arrays, e.g. arrays of C structs, are sent as struct arrays
+with one field per dtype field. Nested dtypes become nested structs and
+subarray fields fixed-size lists. Receivers reconstruct the structured array
+through `event.to_structured()`, with a packed dtype of the same fields:
+
+```python
+dtype = np.dtype([("id", "u4"), ("pos", "f4", (3,))], align=True)
+node.send_output("objects", np.zeros(8, dtype))
+```
+
+`pyarrow.Table`s and lists of `pyarrow.RecordBatch` are sent as a `uint8`
+array in the Arrow IPC streaming format, which keeps the batches separate.
+All batches must have the same schema. Receivers reconstruct the table
+through `event.to_table()`:
+
+```python
+node.send_output("detections", pa.Table.from_pylist(rows))
+```
+
+numpy arrays with two or more dimensions are sent as a single element of the
+canonical `arrow.fixed_shape_tensor` extension type. The `ARROW:extension:name`
+and `ARROW:extension:metadata` metadata keys hold the extension type with the
+shape of the array, so other Arrow tensor consumers can read it as well.
+Receivers reconstruct the array through `event.to_ndarray()`. Fortran-ordered
+and transposed arrays keep their strides, other strided arrays are copied:
+
+```python
+node.send_output("depth", np.zeros((480, 640), np.float32))
+```
+
+GPU buffers that implement `__cuda_array_interface__`, e.g. `cupy` arrays or
+CUDA `torch` tensors, are copied to host memory and sent as flat array. The
+metadata keys `cuda_shape`, `cuda_typestr`, and `cuda_device` describe the
+original buffer, so that receivers can restore it on the GPU:
+
+```python
+node.send_output("depth", cupy.zeros((480, 640), "f4"))
+# receiver
+depth = cupy.asarray(event["value"].to_numpy()).reshape(event["metadata"]["cuda_shape"])
+```
+
+Small binary blobs, e.g. calibration data or debug artifacts, can be sent
+along with the data as named `attachments`. Unlike the fields of a struct
+array, attachments are opaque and not part of the typed data. Receivers can
+read them through `event.attachment(name)`. The total size of the
+attachments of a message is limited to 64 KiB:
+
+```python
+node.send_output("image", data, attachments={"calib": calibration_bytes})
+```
+
+To reproduce the timing of recorded messages, pass the time at which the output
+should be sent as `at`, in seconds since the UNIX epoch (like `time.time()`).
+Scheduled outputs are buffered by the node and sent once their time is reached,
+while the node waits for events in `next()` or sends other outputs. If `at` is
+in the past, the output is sent immediately:
+
+```python
+node.send_output("replay", data, at=start + recorded_offset)
+```
+
+By default, outputs are sent on a best-effort basis. For critical commands,
+pass `reliable=True` to block until all receiving nodes acknowledged the
+output, which happens when their `next()` returns it. Unacknowledged outputs
+are sent again every second, until `reliable_timeout` seconds (default `5`)
+passed. On timeout, a `dora.AckTimeoutError` is raised:
+
+```python
+node.send_output("emergency_stop", pa.array([True]), reliable=True)
+```
+
+Retries can deliver an output more than once, possibly after newer outputs,
+so receivers must handle duplicates idempotently. All copies carry the same
+`reliable` metadata value, which can be used to detect them. Only receivers on
+the same machine acknowledge outputs: receivers on other machines get reliable
+outputs on a best-effort basis, without retries. If all receivers of an output
+run on other machines, `send_output` returns immediately and the daemon logs
+a warning.
+
+Outputs of a named message type can be checked against a schema that was
+registered through `dora.register_schema` or in the `schemas` section of the
+dataflow descriptor. If `schema` is given, the data must match it, otherwise
+a `dora.SchemaError` is raised. The name is sent as `schema` metadata, so
+that receivers validate the input against the same schema:
+
+```python
+node.send_output("detections", pa.array(rows), schema="Detection")
 ```"""
 
-    def __init__(self, node_id: str=None) -> None:
-        """The custom node API lets you integrate `dora` into your application.
-It allows you to retrieve input and send output in any fashion you want.
+    def send_output_delta(self, output_id: str, data: pyarrow.Array | bytes, metadata: dict=None, full_every: int=None) -> None:
+        """Sends only the regions of a large value that changed since the previous
+call for the same output, e.g. for a costmap of which only a few cells
+change per update.
 
-Use with:
+The value must be `bytes` or an arrow array of a primitive type without
+nulls. The first value, values whose type or length changed, and values
+that changed too much are sent in full. The receiving node reconstructs
+the full value from the previous value of the input, so receivers see the
+same events as for `send_output`:
 
 ```python
-from dora import Node
+node.send_output_delta("costmap", pa.array(grid.ravel()), full_every=100)
+```
 
-node = Node()
+Deltas can only be applied if the receiver got the previous message of
+the output. If a message was dropped, e.g. because the input queue was
+full, the receiver gets `DECODE_ERROR` events until the next full value,
+see `on_decode_error`. Pass `full_every` to send every `full_every`-th
+value in full, so that receivers recover from dropped messages."""
+
+    def send_output_from_ros2(self, output_id: str, message: typing.Any, metadata: dict=None) -> None:
+        """Sends a ROS 2 message of `rclpy` as output, e.g. a message received
+through an `rclpy` subscription in the same process.
+
+The message is sent as a struct array with a single element, in the same
+format as for the publishers of the ROS 2 bridge, so that receivers can
+convert it back through `Event.to_ros2`. The `frame_id` and `stamp` of the
+message header are sent as `frame_id` and `timestamp_ns` metadata, unless
+these keys are given in `metadata`:
+
+```python
+def on_pose(msg):
+    node.send_output_from_ros2("pose", msg)
+
+rclpy_node.create_subscription(PoseStamped, "/pose", on_pose, 10)
+```
+
+Requires `rosidl_runtime_py`, which is installed with ROS 2."""
+
+    def send_progress(self, task: str, progress: float, message: str=None) -> None:
+        """Reports the progress of a long-running task, e.g. a batch job, as a
+fraction between `0.0` and `1.0`.
+
+The report is not sent as output, but delivered as `PROGRESS` event to the
+nodes that subscribed through `lifecycle_events`, e.g. a monitoring node
+that shows a progress bar per node and task:
+
+```python
+for i, chunk in enumerate(chunks):
+    encode(chunk)
+    node.send_progress("job1", (i + 1) / len(chunks), message="encoding")
 ```"""
 
-    def dataflow_descriptor(self) -> dict:
-        """Returns the full dataflow descriptor that this node is part of.
+    def send_proto(self, output_id: str, message: google.protobuf.message.Message, metadata: dict=None) -> None:
+        """Sends a protobuf message as output, serialized through its
+`SerializeToString` method.
 
-This method returns the parsed dataflow YAML file."""
+The message is sent as `uint8` array with the `application/x-protobuf`
+content type. The full name of the message type is added to the metadata
+as `proto_type`, so that receivers can parse it through `Event.as_proto`:
 
-    def dataflow_id(self) -> str:
-        """Returns the dataflow id."""
+```python
+from robot_pb2 import Command
 
-    def merge_external_events(self, subscription: dora.Ros2Subscription) -> None:
-        """Merge an external event stream with dora main loop.
-This currently only work with ROS2."""
+node.send_proto("command", Command(speed=0.5))
+```"""
 
-    def next(self, timeout: float=None) -> dict:
-        """`.next()` gives you the next input that the node has received.
-It blocks until the next event becomes available.
-You can use timeout in seconds to return if no input is available.
-It will return `None` when all senders has been dropped.
+    def set_affinity(self, cores: list[int]) -> None:
+        """Pins the node to the given CPU cores.
+
+Applies to the thread that calls this method, which should be the thread
+that processes the events, and to the background threads that dora uses
+to communicate with the daemon. This keeps latency-critical nodes off the
+cores that are used by heavy processing, e.g. perception:
 
 ```python
-event = node.next()
+node.set_affinity([2, 3])
 ```
 
-You can also iterate over the event stream with a loop
+Only supported on Linux, a `RuntimeError` is raised on other platforms."""
+
+    def set_conflation(self, output_id: str, enabled: bool=True) -> None:
+        """Enables or disables conflation for the given output.
+
+If a receiver falls behind, messages of a conflated output replace the
+messages of the output that are still buffered for it, so that it only gets
+the most recent value instead of a growing backlog. Unlike a rate limit, this
+doesn't delay or drop messages for receivers that keep up:
+
+```python
+node.set_conflation("pose", True)
+```
+
+The replaced messages are counted in the `dropped_count` metadata of the next
+delivered message of the input."""
+
+    def set_decoder(self, input_id: str, decoder: typing.Callable[[pyarrow.Array], typing.Any]) -> None:
+        """Registers a decoder for the values of the given input.
+
+The decoder is called with the `pyarrow.Array` of every event of the input
+that is returned by `next()`. Its result replaces the value of the event, so
+consumers read the decoded object from `event["value"]`:
 
 ```python
+node.set_decoder("pose", lambda value: Pose.FromString(value.to_numpy().tobytes()))
+
 for event in node:
-match event["type"]:
-case "INPUT":
-match event["id"]:
-case "image":
+    if event["type"] == "INPUT" and event["id"] == "pose":
+        print(event["value"].position)
+```
+
+The decoder runs on the thread that calls `next()`, with the GIL held.
+Exceptions raised by the decoder are handled according to the
+`on_decode_error` policy of the node, see `Node`. Events that are
+received through a `tee` are not decoded. Pass `None` to remove the decoder."""
+
+    def set_state(self, key: str, value: typing.Any) -> None:
+        """Stores `value` for `key` in the persistent state of this node.
+
+The value must be a JSON-serializable scalar (`bool`, `int`, `float` or
+`str`) or a `pyarrow.Scalar`. Passing `None` removes `key` from the state."""
+
+    def sim_time(self) -> float:
+        """Returns the current time of the node in seconds since the UNIX epoch.
+
+With `use_sim_time`, this is the latest time that was published on the clock
+input, or `None` if no time was published yet. Other nodes get the wall clock
+time, so the same code runs in simulations and on the real system."""
+
+    def subscribe(self, pattern: str) -> None:
+        """Subscribes to all outputs of the dataflow that match the given glob pattern.
+
+The pattern supports `*` and `?` wildcards. The matching outputs are
+returned by `next()` like other inputs, with the output ID as event `id`.
+Patterns that contain a `/` are matched against `<node>/<output>`, which
+is used as `id` then. Outputs that are declared later are included too.
+
+```python
+node.subscribe("camera_*")
+for event in node:
+    if event["type"] == "INPUT" and event["id"].startswith("camera_"):
+        ...
+```
+
+To receive all outputs of the nodes of a namespace, e.g. of
+`perception/camera` and `perception/lidar`, subscribe to the namespace
+prefix, see `namespace`:
+
+```python
+node.subscribe("perception/*")
+```
+
+The outputs of this node and outputs of nodes on other machines are not
+matched. Subscribing fails once all inputs of the node are closed."""
+
+    def tee(self) -> dora.EventTee:
+        """Forks the event stream of this node.
+
+The returned iterator receives a copy of every event that is returned by
+`next()`, which allows running two independent consumers over the same
+inputs within one node, e.g. for logging and processing:
+
+```python
+tee = node.tee()
+threading.Thread(target=lambda: [log(event) for event in tee], daemon=True).start()
+
+for event in node:
+    process(event)
+```
+
+The copies share the data buffer with the original events, so large payloads
+are not duplicated. However, the buffer of an input is only freed once all
+copies are released, and copies that are not consumed from the tee are kept
+in memory indefinitely."""
+
+    def timer_stats(self) -> dict:
+        """Returns timing statistics of the timers that were added through
+`add_timer`, per timer ID.
+
+Each timer is described by a dict containing its `period`, the number of
+delivered `ticks` and of `missed` ticks, and the `mean_jitter` and
+`max_jitter` of the ticks, i.e. the delay between the scheduled time of a
+tick and its delivery by `next()`. All durations are in seconds:
+
+```python
+stats = node.timer_stats()["tick"]
+print(f"jitter: {stats['mean_jitter'] * 1e6:.0f}us, missed: {stats['missed']}")
 ```"""
 
-    def send_output(self, output_id: str, data: pyarrow.Array, metadata: dict=None) -> None:
-        """`send_output` send data from the node.
+    def transaction(self) -> dora.Transaction:
+        """Starts a transaction for sending several related outputs together.
+
+Outputs sent through the returned transaction are buffered and only sent
+when the `with` block exits normally. They are discarded if the block
+raises an exception, so downstream nodes never see a partial set of
+outputs of a tick:
 
 ```python
-Args:
-output_id: str,
-data: pyarrow.Array,
-metadata: Option[Dict],
+with node.transaction() as tx:
+    tx.send_output("pose", pose)
+    tx.send_output("covariance", covariance)
+```"""
+
+    def use_sim_time(self, input_id: str) -> None:
+        """Drives the timers of the node by the simulation time that is published on
+the given input, instead of the wall clock.
+
+By default, nodes run on wall time: the ticks of `add_timer` follow the
+monotonic clock of the machine and `sim_time()` returns the current time. For
+deterministic simulations and replays, the simulator publishes its time on an
+output instead, as a single `int64` or `uint64` in nanoseconds or a `float64`
+in seconds since the UNIX epoch. Nodes opt into this time by calling
+`use_sim_time` with the ID of the input that receives it, before adding any
+timer:
+
+```python
+node.use_sim_time("clock")
+node.add_timer("control", frequency=100)
+for event in node:
+    if event["type"] == "INPUT" and event["id"] == "control":
+        step(node.sim_time())
 ```
 
-ex:
+Ticks then only become due when the simulation time advances, independent of
+how fast the simulator runs. If the time advances by several periods at once,
+every tick is delivered, in order, after the event of the clock input. Ticks
+carry their simulation time as `timestamp_ns` metadata, and outputs sent
+through `send_output` carry the current simulation time as `timestamp_ns`
+unless it is set explicitly. Clock values that are invalid or go backwards are
+ignored with a `RuntimeWarning`, the event of the clock input is still returned
+by `next()`."""
+
+    def window(self, input_id: str, n: int) -> list[dict]:
+        """Returns the last `n` events of the given input as a list, oldest first.
+
+The events are kept in a ring buffer of the node, which is filled by
+`next()`. This replaces a hand-written `deque` for sliding-window filters:
 
 ```python
-node.send_output("string", b"string", {"open_telemetry_context": "7632e76"})
+node.window("imu", n=10)  # start buffering before the event loop
+for event in node:
+    if event["type"] == "INPUT" and event["id"] == "imu":
+        samples = node.window("imu", n=10)
+        mean = sum(e["value"][0].as_py() for e in samples) / len(samples)
+```
+
+Events are only buffered after the first call for an input, so the list
+has fewer than `n` elements until enough events were received. Calling
+`window` again with a larger `n` grows the buffer, a smaller `n` returns
+only the most recent events of the buffer.
+
+Every buffered event keeps its value alive. For large inputs that are
+received through shared memory, e.g. camera images, this also keeps the
+shared memory of the sender in use, which may stall the sender once all of
+its buffers are held. Keep `n` small for such inputs or create the node
+with `always_copy=True` so that buffered events own a copy of their data."""
+
+    def __iter__(self) -> typing.Any:
+        """Implement iter(self)."""
+
+    def __next__(self) -> typing.Any:
+        """Implement next(self)."""
+
+@typing.final
+class RecordBatchOutput:
+    """Sender for an output with a fixed arrow schema.
+
+Created through `Node.record_batch_output`."""
+
+    def schema(self) -> pyarrow.Schema:
+        """The schema of this output."""
+
+    def send(self, batch: pyarrow.RecordBatch | list[pyarrow.Array] | dict, metadata: dict=None) -> None:
+        """Sends a batch matching the schema of this output.
+
+The batch can be given as a `pyarrow.RecordBatch`, as a list of arrays in
+schema order, or as a dict mapping column names to arrays. A
+`dora.SchemaError` is raised if the columns don't match the names and types
+of the schema, or if they differ in length."""
+
+@typing.final
+class Recording:
+    """Iterator over the inputs of a recording.
+
+Created through `Node.from_recording`. Yields the recorded input events in
+the order in which they were received, including their original metadata.
+
+```python
+for event in Node.from_recording("events.arrow"):
+    process(event["id"], event["value"])
 ```"""
 
     def __iter__(self) -> typing.Any:
@@ -307,5 +2176,209 @@ warnings:
 - dora Ros2 bridge functionality is considered **unstable**. It may be changed
 at any point without it being considered a breaking change."""
 
-def start_runtime() -> None:
-    """Start a runtime for Operators"""
\ No newline at end of file
+class SchemaError(dora.DoraError):
+    """Raised if data does not match the schema of an output."""
+
+class SendError(dora.DoraError):
+    """Raised if an output could not be sent."""
+
+@typing.final
+class Transaction:
+    """A set of outputs that are sent together on success, or not at all.
+
+Created through `Node.transaction`. Outputs sent through the transaction are
+buffered by the node and only sent when the `with` block exits normally. If
+the block raises an exception, e.g. because the node crashes mid-tick, the
+buffered outputs are discarded, so downstream nodes never see a partial set
+of related outputs:
+
+```python
+with node.transaction() as tx:
+    tx.send_output("pose", pose)
+    tx.send_output("covariance", covariance)
+```
+
+The data and metadata are validated when they are buffered, so invalid
+outputs raise an error before anything is sent."""
+
+    def send_output(self, output_id: str, data: pyarrow.Array | bytes, metadata: dict=None) -> None:
+        """Buffers an output until the transaction is committed.
+
+The `data` and `metadata` are interpreted like for `Node.send_output`."""
+
+    def __enter__(self) -> typing.Any:...
+
+    def __exit__(self, exc_type: typing.Any, _exc_value: typing.Any, _traceback: typing.Any) -> typing.Any:
+        """Sends the buffered outputs if the `with` block exited normally, and
+discards them otherwise.
+
+Exceptions of the `with` block are not suppressed."""
+
+    def __len__(self) -> int:
+        """Returns the number of buffered outputs."""
+
+class VersionMismatchError(dora.DoraError):
+    """Raised by `Node()` if the node was built against a dora version that is not compatible with the daemon."""
+
+def descriptor_to_yaml(descriptor: dict) -> str:
+    """Converts a dataflow descriptor dict into YAML.
+
+The dict must be in the format of the dataflow YAML file, as returned by
+`Node.dataflow_descriptor`. It is checked against the dataflow schema before
+it is converted. See `dora.builder.DataflowBuilder` to construct descriptors
+programmatically."""
+
+def dict_to_metadata(metadata: dict) -> dora.Metadata:
+    """Creates a `dora.Metadata` from a dict in the format of `dora.metadata_to_dict`.
+
+All keys are optional. A missing `timestamp` is set to the current time and a
+missing `type_info` describes empty data.
+
+```python
+metadata = dora.dict_to_metadata({"parameters": {"camera": {"String": "front"}}})
+assert dora.dict_to_metadata(dora.metadata_to_dict(metadata)) == metadata
+```"""
+
+def external_stream_from_async(coro_factory: typing.Callable[[], typing.AsyncIterable[typing.Any]]) -> dora.ExternalEventStream:
+    """Creates an external event stream from an asynchronous Python source.
+
+`coro_factory` is called without arguments and must return an async iterable,
+e.g. an async generator. The iterable is driven on a dedicated `asyncio` event
+loop in a background thread and each yielded item becomes an external event.
+
+```python
+async def messages():
+    async with websockets.connect("ws://localhost:8765") as ws:
+        async for message in ws:
+            yield message
+
+node.merge_external_events(dora.external_stream_from_async(messages), name="ws")
+```
+
+When the node is closed, the pending tasks of the event loop are cancelled and
+the loop is shut down."""
+
+def get_log_level(target: str) -> str:
+    """Returns the log level that was set for the target through `set_log_level`,
+or `None` if no level was set."""
+
+def list_dataflows(coordinator_addr: str=None) -> list[dict]:
+    """Lists the dataflows that are currently running, as reported by the
+dora-coordinator.
+
+This is a query of the control plane that does not require a node, e.g. for
+tools that monitor several dataflows. Each dataflow is described by a dict
+containing its `id`, its `name` or `None`, its `node_count`, and its
+`start_time` in seconds since the Unix epoch:
+
+```python
+for dataflow in dora.list_dataflows("10.0.0.2"):
+    print(dataflow["id"], dataflow["name"], dataflow["node_count"])
+```
+
+The `coordinator_addr` is an IP address with an optional port, the default
+control port of the coordinator is used if no port is given. Defaults to the
+coordinator on the local machine."""
+
+def list_operators() -> list[dict]:
+    """List the operators loaded by the runtime started with `start_runtime`.
+
+Each operator is described by a dict containing its `id`, its `inputs` and
+`outputs`, its `status`, and statistics about its last tick.
+
+```python
+for operator in dora.list_operators():
+print(operator["id"], operator["status"], operator["last_input"])
+```"""
+
+def metadata_to_dict(metadata: dora.Metadata | dict) -> dict:
+    """Converts the metadata of an input event, or a `dora.Metadata`, into a plain dict.
+
+The dict contains the `timestamp` as string, the arrow `type_info` of the data,
+and the `parameters`, with each value tagged by its type:
+
+```python
+{
+    "timestamp": "7340479876186425344/a6b5c2d4e4f64c0b",
+    "type_info": {...},
+    "parameters": {"open_telemetry_context": {"String": "..."}, "dora/sequence_number": {"Integer": 4}},
+}
+```
+
+The conversion is lossless: `dora.dict_to_metadata` restores the original metadata."""
+
+def operator_info(operator_id: str) -> dict:
+    """Inspect a single operator loaded by the runtime.
+
+Returns `None` if no operator with the given id is loaded."""
+
+def register_schema(name: str, schema: pyarrow.Schema) -> None:
+    """Registers a schema of a named message type, e.g. `Detection`.
+
+Outputs that are sent with `schema="Detection"` are validated against the
+schema, and so are the inputs that carry its name. Schemas can also be defined
+in the `schemas` section of the dataflow descriptor, which are registered when
+the node is created. Registering a different schema under an existing name
+raises an error:
+
+```python
+dora.register_schema("Detection", pa.schema([("label", pa.string()), ("score", pa.float32())]))
+node.send_output("detections", detections, schema="Detection")
+```"""
+
+def set_log_level(target: str, level: str=None) -> None:
+    """Sets the log level of dora's internal logging for the given target.
+
+The target is a Rust module path prefix, e.g. `dora_node_api` for the node
+API including its daemon connection. The level is one of `"trace"`,
+`"debug"`, `"info"`, `"warn"`, `"error"` and `"off"`, or `None` to remove
+the level of the target again. The messages are printed to stderr:
+
+```python
+dora.set_log_level("dora_node_api", "debug")
+node = Node()
+```
+
+The level only enables additional messages, messages that are enabled
+through `RUST_LOG` or the default `warn` level are still printed. Levels
+that are set before the node is created are applied once it is."""
+
+def start_runtime() -> bool:
+    """Start a runtime for Operators
+
+Returns `True` if all operators finished sending their outputs on shutdown, or
+`False` if the drain timeout (`DORA_RUNTIME_DRAIN_TIMEOUT`, in seconds) expired
+and remaining outputs were dropped."""
+
+def validate_dataflow(descriptor: str | os.PathLike | dict, allow_cycles: bool=None) -> list[dict]:
+    """Checks a dataflow descriptor for structural errors, without starting it.
+
+The descriptor is either the path of a dataflow YAML file or a dict in the
+same format, e.g. as created through `dora.builder.DataflowBuilder`. The
+following problems are detected:
+
+- invalid node definitions (`"invalid_node"`), e.g. nodes without `path`,
+- multiple nodes with the same ID (`"duplicate_node_id"`),
+- inputs mapped to nodes that don't exist (`"unknown_source"`),
+- inputs mapped to outputs that are not declared (`"undeclared_output"`),
+- cycles between nodes (`"cycle"`), unless `allow_cycles=True` is passed,
+- unknown or repeated nodes in the `shutdown_order`
+  (`"invalid_shutdown_order"`).
+
+Returns the list of all found errors, which is empty for valid dataflows.
+Each error is a dict of the form `{"type": str, "node": str | None,
+"message": str}`. A descriptor that cannot be parsed results in a single
+`"invalid_descriptor"` error:
+
+```python
+errors = dora.validate_dataflow("dataflow.yml")
+for error in errors:
+    print(f"{error['type']}: {error['message']}")
+sys.exit(1 if errors else 0)
+```
+
+Dora can run dataflows with cycles, e.g. for feedback loops, so pass
+`allow_cycles=True` for such dataflows. Outputs that are declared at runtime
+through `Node.declare_dynamic_output` are reported as `"undeclared_output"`.
+In contrast to `dora check`, the existence of the node sources is not
+checked."""
\ No newline at end of file
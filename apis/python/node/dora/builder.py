@@ -0,0 +1,195 @@
+"""Programmatic construction of dataflow descriptors.
+
+Example Use:
+```python
+from dora.builder import DataflowBuilder
+
+dataflow = DataflowBuilder()
+camera = dataflow.add_node("camera", path="camera.py")
+camera.add_input("tick", "dora/timer/millis/50")
+camera.add_output("image")
+
+plot = dataflow.add_node("plot", path="plot.py", env={"WIDTH": 640})
+plot.add_input("image", camera.output("image"), queue_size=1)
+
+dataflow.to_yaml("dataflow.yml")
+```
+"""
+
+from typing import Dict, List, Optional, Union
+
+from .dora import descriptor_to_yaml
+
+EnvValue = Union[bool, int, float, str]
+
+
+class DataflowBuilderError(ValueError):
+    """Raised when the dataflow under construction is invalid."""
+
+
+class DataflowNode:
+    """A node of a dataflow under construction.
+
+    Created through `DataflowBuilder.add_node`.
+    """
+
+    def __init__(
+        self,
+        node_id: str,
+        path: Optional[str] = None,
+        args: Optional[str] = None,
+        env: Optional[Dict[str, EnvValue]] = None,
+        build: Optional[str] = None,
+    ):
+        self.id = node_id
+        self.path = path
+        self.args = args
+        self.env = dict(env) if env else {}
+        self.build = build
+        self.inputs: Dict[str, dict] = {}
+        self.outputs: List[str] = []
+
+    def add_input(
+        self, input_id: str, source: str, queue_size: Optional[int] = None
+    ) -> "DataflowNode":
+        """Adds an input that receives data from `source`.
+
+        The source is either an output of another node, as `"<node>/<output>"`
+        (see `DataflowNode.output`), or a timer, e.g. `"dora/timer/millis/100"`.
+        """
+        if input_id in self.inputs:
+            raise DataflowBuilderError(
+                f"node `{self.id}` already has an input `{input_id}`"
+            )
+        self.inputs[input_id] = {"source": source, "queue_size": queue_size}
+        return self
+
+    def add_output(self, output_id: str) -> "DataflowNode":
+        """Declares an output of this node."""
+        if output_id in self.outputs:
+            raise DataflowBuilderError(
+                f"node `{self.id}` already has an output `{output_id}`"
+            )
+        self.outputs.append(output_id)
+        return self
+
+    def output(self, output_id: str) -> str:
+        """Returns the source string of the given output, for use in `add_input`."""
+        return f"{self.id}/{output_id}"
+
+    def to_dict(self) -> dict:
+        """Returns the descriptor entry of this node."""
+        node: dict = {"id": self.id}
+        if self.path is not None:
+            node["path"] = self.path
+        if self.args is not None:
+            node["args"] = self.args
+        if self.build is not None:
+            node["build"] = self.build
+        if self.env:
+            node["env"] = self.env
+        if self.inputs:
+            node["inputs"] = {
+                input_id: (
+                    input["source"]
+                    if input["queue_size"] is None
+                    else {"source": input["source"], "queue_size": input["queue_size"]}
+                )
+                for input_id, input in self.inputs.items()
+            }
+        if self.outputs:
+            node["outputs"] = list(self.outputs)
+        return node
+
+
+class DataflowBuilder:
+    """Builds a dataflow descriptor, as an alternative to writing the YAML file by hand.
+
+    All edges are validated before the descriptor is emitted: every input must
+    reference a declared output of a declared node, or a dora timer.
+    """
+
+    def __init__(self):
+        self.nodes: Dict[str, DataflowNode] = {}
+
+    def add_node(
+        self,
+        node_id: str,
+        path: Optional[str] = None,
+        args: Optional[str] = None,
+        env: Optional[Dict[str, EnvValue]] = None,
+        build: Optional[str] = None,
+    ) -> DataflowNode:
+        """Adds a node to the dataflow.
+
+        The `path` is the executable or script of the node. It can be omitted for
+        dynamic nodes, which are started manually through `Node(node_id)`.
+        """
+        if node_id in self.nodes:
+            raise DataflowBuilderError(f"duplicate node id `{node_id}`")
+        node = DataflowNode(node_id, path=path, args=args, env=env, build=build)
+        self.nodes[node_id] = node
+        return node
+
+    def validate(self):
+        """Checks that all inputs reference declared outputs or timers.
+
+        Raises a `DataflowBuilderError` otherwise.
+        """
+        for node in self.nodes.values():
+            for input_id, input in node.inputs.items():
+                source = input["source"]
+                if source.startswith("dora/"):
+                    if not source.startswith("dora/timer/"):
+                        raise DataflowBuilderError(
+                            f"input `{node.id}/{input_id}` references unknown dora source `{source}`"
+                        )
+                    continue
+                first, sep, rest = source.partition("/")
+                if not sep or not rest:
+                    raise DataflowBuilderError(
+                        f"source `{source}` of input `{node.id}/{input_id}` must have the form `<node>/<output>`"
+                    )
+                source_node = self._source_node(source)
+                if source_node is None:
+                    raise DataflowBuilderError(
+                        f"input `{node.id}/{input_id}` references unknown node `{first}`"
+                    )
+                output_id = source[len(source_node) + 1 :]
+                if output_id not in self.nodes[source_node].outputs:
+                    raise DataflowBuilderError(
+                        f"input `{node.id}/{input_id}` references undeclared output `{source}`"
+                    )
+
+    def _source_node(self, source: str) -> Optional[str]:
+        """Returns the ID of the node that produces `source`, or `None` if there is none.
+
+        Node IDs can contain `/` themselves, e.g. `perception/camera`, so the
+        longest node ID that is a prefix of the source is used, like for dataflow
+        YAML files.
+        """
+        candidates = [
+            node_id for node_id in self.nodes if source.startswith(node_id + "/")
+        ]
+        return max(candidates, key=len, default=None)
+
+    def to_dict(self) -> dict:
+        """Validates the dataflow and returns it as a dict in the format of the dataflow YAML file.
+
+        This is the same format as returned by `Node.dataflow_descriptor`.
+        """
+        self.validate()
+        return {"nodes": [node.to_dict() for node in self.nodes.values()]}
+
+    def to_yaml(self, path: Optional[str] = None) -> str:
+        """Validates the dataflow and returns it as YAML.
+
+        The descriptor is additionally checked against the dataflow schema. If
+        `path` is given, the YAML is also written to that file, which can then be
+        started through `dora start <path>`.
+        """
+        yaml = descriptor_to_yaml(self.to_dict())
+        if path is not None:
+            with open(path, "w") as f:
+                f.write(yaml)
+        return yaml
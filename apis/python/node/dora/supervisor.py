@@ -0,0 +1,80 @@
+"""Supervision of the event loop of a node, with restarts after errors.
+
+Example Use:
+```python
+import dora
+from dora import Node
+
+
+def handle(node, event, state):
+    if event["type"] == "INPUT":
+        state["count"] = state.get("count", 0) + 1
+        node.send_output("count", pa.array([state["count"]]))
+
+
+dora.run_with_restart(Node, handle, max_restarts=5, state={})
+```
+"""
+
+import gc
+import logging
+import time
+from typing import Any, Callable, Optional
+
+logger = logging.getLogger("dora.supervisor")
+
+
+def run_with_restart(
+    make_node: Callable[[], Any],
+    handle: Callable[..., Any],
+    max_restarts: int = 5,
+    backoff: float = 1.0,
+    max_backoff: float = 30.0,
+    state: Optional[dict] = None,
+) -> int:
+    """Runs the event loop of a node and restarts it with a fresh node if it fails.
+
+    `make_node` creates the node, e.g. `Node` or `lambda: Node("my-node")`.
+    `handle` is called as `handle(node, event)` for every event of the node, or
+    as `handle(node, event, state)` if a `state` dict is given. The same dict is
+    passed again after a restart, so that the state survives it.
+
+    If creating the node or handling an event raises an `Exception`, the reason
+    is logged through the `dora.supervisor` logger, the node is closed, and a new
+    node is created after a delay. The delay starts at `backoff` seconds and
+    doubles with every restart, up to `max_backoff`. After `max_restarts`
+    restarts, the error is raised instead.
+
+    Returns the number of restarts once the event stream of the node ended, e.g.
+    because the dataflow was stopped.
+    """
+    restarts = 0
+    while True:
+        node = None
+        try:
+            node = make_node()
+            for event in node:
+                if state is None:
+                    handle(node, event)
+                else:
+                    handle(node, event, state)
+            return restarts
+        except Exception:
+            if restarts >= max_restarts:
+                logger.error("giving up after %d restarts", restarts, exc_info=True)
+                raise
+            restarts += 1
+            delay = min(backoff * 2 ** (restarts - 1), max_backoff)
+            logger.warning(
+                "node failed, restarting in %.1fs (restart %d of %d)",
+                delay,
+                restarts,
+                max_restarts,
+                exc_info=True,
+            )
+        # The daemon accepts the new node only after the connection of the
+        # failed node was closed, which happens when the node is dropped. The
+        # traceback of the error is released at the end of the `except` block.
+        node = None
+        gc.collect()
+        time.sleep(delay)
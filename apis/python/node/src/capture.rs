@@ -0,0 +1,177 @@
+//! Capturing of the stdout and stderr of a node as dora log messages.
+
+use std::time::{Duration, Instant};
+
+use dora_node_api::LogLevel;
+use pyo3::prelude::*;
+
+/// Maximum delay before captured lines are forwarded while the node waits for events.
+pub const CAPTURE_FORWARD_INTERVAL: Duration = Duration::from_millis(100);
+/// How long to wait for buffered output when a capture is stopped.
+const DRAIN_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// A line that was written to stdout or stderr while capturing.
+pub struct CapturedLine {
+    pub level: LogLevel,
+    /// The name of the stream, i.e. `stdout` or `stderr`.
+    pub target: &'static str,
+    pub line: String,
+}
+
+/// Redirects the stdout and stderr file descriptors of the process into pipes.
+///
+/// The pipes are read by background threads, which forward every line into an
+/// unbounded channel. So writes to stdout and stderr never block, even if the
+/// captured lines are not consumed for a while.
+///
+/// The original file descriptors are restored on drop.
+pub struct OutputCapture {
+    /// The redirected file descriptors and duplicates of their original targets.
+    saved: Vec<(i32, i32)>,
+    lines: flume::Receiver<CapturedLine>,
+}
+
+impl OutputCapture {
+    pub fn start(py: Python<'_>) -> eyre::Result<Self> {
+        if !cfg!(unix) {
+            eyre::bail!("capturing the output is only supported on Unix");
+        }
+        flush_python_streams(py)?;
+        let os = py.import_bound("os")?;
+        let (sender, lines) = flume::unbounded();
+        // restores the already redirected file descriptors on error
+        let mut capture = Self {
+            saved: Vec::new(),
+            lines,
+        };
+        for (fd, level, target) in [(1, LogLevel::Info, "stdout"), (2, LogLevel::Warn, "stderr")] {
+            let (read, write): (i32, i32) = os.call_method0("pipe")?.extract()?;
+            // the reader owns the read end from now on, also if it fails to start
+            let saved = spawn_reader(read, level, target, sender.clone())
+                .and_then(|()| Ok(redirect(&os, fd, write)?));
+            // the write end stays open as `fd` if the redirection succeeded
+            let closed = os.call_method1("close", (write,));
+            capture.saved.push((fd, saved?));
+            closed?;
+        }
+        Ok(capture)
+    }
+
+    /// Returns the lines that were captured since the last call, without blocking.
+    pub fn try_lines(&self) -> impl Iterator<Item = CapturedLine> + '_ {
+        self.lines.try_iter()
+    }
+
+    /// Restores stdout and stderr and returns the remaining captured lines.
+    pub fn stop(mut self, py: Python<'_>) -> PyResult<Vec<CapturedLine>> {
+        self.restore(py)?;
+        // The readers finish once all write ends of the pipes are closed. This
+        // might never happen if a child process inherited them, so don't wait
+        // forever.
+        let lines = py.allow_threads(|| {
+            let deadline = Instant::now() + DRAIN_TIMEOUT;
+            let mut lines = Vec::new();
+            while let Ok(line) = self.lines.recv_deadline(deadline) {
+                lines.push(line);
+            }
+            lines
+        });
+        Ok(lines)
+    }
+
+    fn restore(&mut self, py: Python<'_>) -> PyResult<()> {
+        if self.saved.is_empty() {
+            return Ok(());
+        }
+        flush_python_streams(py)?;
+        let os = py.import_bound("os")?;
+        for (fd, saved) in self.saved.drain(..) {
+            os.call_method1("dup2", (saved, fd))?;
+            os.call_method1("close", (saved,))?;
+        }
+        Ok(())
+    }
+}
+
+impl Drop for OutputCapture {
+    fn drop(&mut self) {
+        if let Err(err) = Python::with_gil(|py| self.restore(py)) {
+            eprintln!("failed to restore stdout and stderr: {err}");
+        }
+    }
+}
+
+/// Redirects `fd` to `target`, returns a duplicate of the original file descriptor.
+fn redirect(os: &Bound<'_, PyModule>, fd: i32, target: i32) -> PyResult<i32> {
+    let saved: i32 = os.call_method1("dup", (fd,))?.extract()?;
+    if let Err(err) = os.call_method1("dup2", (target, fd)) {
+        os.call_method1("close", (saved,))?;
+        return Err(err);
+    }
+    Ok(saved)
+}
+
+/// Flushes the buffers of `sys.stdout` and `sys.stderr`, so that their content is
+/// written to the current targets before redirecting them.
+fn flush_python_streams(py: Python<'_>) -> PyResult<()> {
+    let sys = py.import_bound("sys")?;
+    for name in ["stdout", "stderr"] {
+        let stream = sys.getattr(name)?;
+        if !stream.is_none() {
+            stream.call_method0("flush")?;
+        }
+    }
+    Ok(())
+}
+
+/// Forwards the lines that are written to the pipe `fd` to `sender`.
+///
+/// Takes ownership of `fd`, which is closed if the reader cannot be started.
+#[cfg(unix)]
+fn spawn_reader(
+    fd: i32,
+    level: LogLevel,
+    target: &'static str,
+    sender: flume::Sender<CapturedLine>,
+) -> eyre::Result<()> {
+    use std::io::{BufRead, BufReader};
+    use std::os::fd::FromRawFd;
+
+    // SAFETY: the file descriptor was just created by `os.pipe` and is owned by
+    // the reader from now on
+    let file = unsafe { std::fs::File::from_raw_fd(fd) };
+    std::thread::Builder::new()
+        .name(format!("dora-capture-{target}"))
+        .spawn(move || {
+            let mut reader = BufReader::new(file);
+            let mut buffer = Vec::new();
+            loop {
+                buffer.clear();
+                match reader.read_until(b'\n', &mut buffer) {
+                    Ok(0) | Err(_) => break,
+                    Ok(_) => {}
+                }
+                let line = String::from_utf8_lossy(&buffer)
+                    .trim_end_matches(['\n', '\r'])
+                    .to_owned();
+                // keep reading even if the capture was stopped, otherwise writers
+                // that still hold the pipe would block once it's full
+                let _ = sender.send(CapturedLine {
+                    level,
+                    target,
+                    line,
+                });
+            }
+        })?;
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn spawn_reader(
+    _fd: i32,
+    _level: LogLevel,
+    _target: &'static str,
+    _sender: flume::Sender<CapturedLine>,
+) -> eyre::Result<()> {
+    eyre::bail!("capturing the output is only supported on Unix")
+}
@@ -0,0 +1,137 @@
+//! Python exceptions raised by the node API.
+//!
+//! All exceptions derive from `DoraError`, which derives from `RuntimeError` so
+//! that existing `except RuntimeError` handlers keep working. Errors of the
+//! arguments, e.g. an unsupported `data` type, are not part of the hierarchy.
+
+use std::io::ErrorKind as IoErrorKind;
+
+use dora_node_api::{
+    AckTimeoutError as AckTimeout, DuplicateNodeIdError as NodeIdInUse,
+    VersionMismatchError as Incompatible,
+};
+use pyo3::{create_exception, exceptions::PyRuntimeError, prelude::*};
+
+create_exception!(
+    dora,
+    DoraError,
+    PyRuntimeError,
+    "Base class of the exceptions raised by dora."
+);
+create_exception!(
+    dora,
+    SendError,
+    DoraError,
+    "Raised if an output could not be sent."
+);
+create_exception!(
+    dora,
+    AckTimeoutError,
+    DoraError,
+    "Raised if a reliably sent output was not acknowledged within its timeout."
+);
+create_exception!(
+    dora,
+    DisconnectedError,
+    DoraError,
+    "Raised if the connection to the dora daemon was lost."
+);
+create_exception!(
+    dora,
+    SchemaError,
+    DoraError,
+    "Raised if data does not match the schema of an output."
+);
+create_exception!(
+    dora,
+    DuplicateNodeIdError,
+    DoraError,
+    "Raised by `Node()` if another process is already connected with the same node ID."
+);
+create_exception!(
+    dora,
+    VersionMismatchError,
+    DoraError,
+    "Raised by `Node()` if the node was built against a dora version that is not compatible with the daemon."
+);
+
+/// The operation that failed, which determines the exception if the cause of
+/// the error has no more specific one.
+#[derive(Debug, Clone, Copy)]
+pub enum Operation {
+    Init,
+    Send,
+    Receive,
+}
+
+/// Converts the error into the matching exception of the hierarchy.
+///
+/// The returned report only contains the exception, so pyo3 raises it as is.
+pub fn dora_error(err: eyre::Report, operation: Operation) -> eyre::Report {
+    // e.g. exceptions raised by callbacks, which are passed through unchanged
+    if err.downcast_ref::<PyErr>().is_some() {
+        return err;
+    }
+    if let Some(duplicate) = err.chain().find_map(|c| c.downcast_ref::<NodeIdInUse>()) {
+        return DuplicateNodeIdError::new_err(duplicate.to_string()).into();
+    }
+    if let Some(mismatch) = err.chain().find_map(|c| c.downcast_ref::<Incompatible>()) {
+        return VersionMismatchError::new_err(mismatch.to_string()).into();
+    }
+    let message = format!("{err:?}");
+    let exception = if err.chain().any(|c| c.is::<AckTimeout>()) {
+        AckTimeoutError::new_err(message)
+    } else if is_disconnected(&err) {
+        DisconnectedError::new_err(message)
+    } else {
+        match operation {
+            Operation::Init | Operation::Receive => DoraError::new_err(message),
+            Operation::Send => SendError::new_err(message),
+        }
+    };
+    exception.into()
+}
+
+/// Creates a [`SchemaError`] with the given message.
+pub fn schema_error(message: String) -> eyre::Report {
+    SchemaError::new_err(message).into()
+}
+
+/// Whether the error was caused by a closed connection to the daemon.
+fn is_disconnected(err: &eyre::Report) -> bool {
+    err.chain()
+        .filter_map(|cause| cause.downcast_ref::<std::io::Error>())
+        .any(|err| {
+            matches!(
+                err.kind(),
+                IoErrorKind::BrokenPipe
+                    | IoErrorKind::ConnectionReset
+                    | IoErrorKind::ConnectionAborted
+                    | IoErrorKind::ConnectionRefused
+                    | IoErrorKind::NotConnected
+                    | IoErrorKind::UnexpectedEof
+            )
+        })
+}
+
+/// Registers the exceptions in the `dora` module.
+pub fn register(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    let py = m.py();
+    m.add("DoraError", py.get_type_bound::<DoraError>())?;
+    m.add("SendError", py.get_type_bound::<SendError>())?;
+    m.add("AckTimeoutError", py.get_type_bound::<AckTimeoutError>())?;
+    m.add(
+        "DisconnectedError",
+        py.get_type_bound::<DisconnectedError>(),
+    )?;
+    m.add("SchemaError", py.get_type_bound::<SchemaError>())?;
+    m.add(
+        "DuplicateNodeIdError",
+        py.get_type_bound::<DuplicateNodeIdError>(),
+    )?;
+    m.add(
+        "VersionMismatchError",
+        py.get_type_bound::<VersionMismatchError>(),
+    )?;
+    Ok(())
+}
@@ -0,0 +1,185 @@
+//! External event sources that are driven by Python code.
+
+use std::sync::{Arc, Mutex};
+
+use eyre::eyre;
+use futures::{Stream, StreamExt};
+use pyo3::{
+    exceptions::PyStopAsyncIteration,
+    prelude::*,
+    types::{IntoPyDict, PyTuple},
+};
+
+/// A stream of external events that can be merged into the dora event stream
+/// using `Node.merge_external_events`.
+///
+/// Created through `dora.external_stream_from_async`.
+#[pyclass]
+pub struct ExternalEventStream {
+    source: Option<AsyncSource>,
+}
+
+impl ExternalEventStream {
+    /// Takes out the underlying stream. Each stream can only be merged once.
+    pub fn take_stream(
+        &mut self,
+    ) -> eyre::Result<impl Stream<Item = eyre::Result<PyObject>> + Unpin + Send> {
+        let AsyncSource { receiver, guard } = self
+            .source
+            .take()
+            .ok_or_else(|| eyre!("external stream was already merged"))?;
+        Ok(receiver.into_stream().map(move |item| {
+            // keep the event loop running as long as the stream is alive
+            let _ = &guard;
+            item.map_err(|err| eyre!(err))
+        }))
+    }
+}
+
+/// Creates an external event stream from an asynchronous Python source.
+///
+/// `coro_factory` is called without arguments and must return an async iterable,
+/// e.g. an async generator. The iterable is driven on a dedicated `asyncio` event
+/// loop in a background thread and each yielded item becomes an external event.
+///
+/// ```python
+/// async def messages():
+///     async with websockets.connect("ws://localhost:8765") as ws:
+///         async for message in ws:
+///             yield message
+///
+/// node.merge_external_events(dora.external_stream_from_async(messages), name="ws")
+/// ```
+///
+/// When the node is closed, the pending tasks of the event loop are cancelled and
+/// the loop is shut down.
+///
+/// :type coro_factory: typing.Callable[[], typing.AsyncIterable[typing.Any]]
+/// :rtype: dora.ExternalEventStream
+#[pyfunction]
+pub fn external_stream_from_async(coro_factory: PyObject) -> ExternalEventStream {
+    let (sender, receiver) = flume::bounded(1);
+    let shared = Arc::new(Mutex::new(LoopState::default()));
+    let guard = LoopGuard {
+        shared: shared.clone(),
+    };
+    std::thread::spawn(move || {
+        Python::with_gil(|py| {
+            if let Err(err) = drive_async_source(py, coro_factory, &sender, &shared) {
+                let _ = py.allow_threads(|| sender.send(Err(format!("{err}"))));
+            }
+        })
+    });
+    ExternalEventStream {
+        source: Some(AsyncSource { receiver, guard }),
+    }
+}
+
+struct AsyncSource {
+    receiver: flume::Receiver<Result<PyObject, String>>,
+    guard: LoopGuard,
+}
+
+#[derive(Default)]
+struct LoopState {
+    stopped: bool,
+    event_loop: Option<PyObject>,
+    current_task: Option<PyObject>,
+}
+
+/// Cancels the running task of the async source when dropped.
+struct LoopGuard {
+    shared: Arc<Mutex<LoopState>>,
+}
+
+impl Drop for LoopGuard {
+    fn drop(&mut self) {
+        let (event_loop, task) = match self.shared.lock() {
+            Ok(mut state) => {
+                state.stopped = true;
+                (state.event_loop.take(), state.current_task.take())
+            }
+            Err(_) => return,
+        };
+        if let (Some(event_loop), Some(task)) = (event_loop, task) {
+            Python::with_gil(|py| {
+                let cancel = task.getattr(py, "cancel")?;
+                event_loop.call_method1(py, "call_soon_threadsafe", (cancel,))
+            })
+            .ok();
+        }
+    }
+}
+
+fn drive_async_source(
+    py: Python<'_>,
+    coro_factory: PyObject,
+    sender: &flume::Sender<Result<PyObject, String>>,
+    shared: &Mutex<LoopState>,
+) -> PyResult<()> {
+    let asyncio = py.import_bound("asyncio")?;
+    let event_loop = asyncio.call_method0("new_event_loop")?;
+    asyncio.call_method1("set_event_loop", (&event_loop,))?;
+    if let Ok(mut state) = shared.lock() {
+        state.event_loop = Some(event_loop.clone().unbind());
+    }
+
+    let result = (|| -> PyResult<()> {
+        let source = coro_factory.call0(py)?.into_bound(py);
+        let iter = source.call_method0("__aiter__")?;
+        loop {
+            let task = asyncio.call_method1("ensure_future", (iter.call_method0("__anext__")?,))?;
+            let stopped = match shared.lock() {
+                Ok(mut state) => {
+                    state.current_task = Some(task.clone().unbind());
+                    state.stopped
+                }
+                Err(_) => true,
+            };
+            if stopped {
+                task.call_method0("cancel")?;
+                break;
+            }
+
+            match event_loop.call_method1("run_until_complete", (&task,)) {
+                Ok(item) => {
+                    let item = item.unbind();
+                    if py.allow_threads(|| sender.send(Ok(item))).is_err() {
+                        break;
+                    }
+                }
+                Err(err) if err.is_instance_of::<PyStopAsyncIteration>(py) => break,
+                Err(_) if shared.lock().map(|s| s.stopped).unwrap_or(true) => break,
+                Err(err) => return Err(err),
+            }
+        }
+        Ok(())
+    })();
+
+    shutdown_loop(py, &asyncio, &event_loop)?;
+    result
+}
+
+/// Cancels all remaining tasks of the loop and closes it, like `asyncio.run` does.
+fn shutdown_loop(
+    py: Python<'_>,
+    asyncio: &Bound<'_, PyModule>,
+    event_loop: &Bound<'_, PyAny>,
+) -> PyResult<()> {
+    let tasks = asyncio
+        .call_method1("all_tasks", (event_loop,))?
+        .iter()?
+        .collect::<PyResult<Vec<_>>>()?;
+    for task in &tasks {
+        task.call_method0("cancel")?;
+    }
+    let gather = asyncio.getattr("gather")?.call(
+        PyTuple::new_bound(py, tasks),
+        Some(&[("return_exceptions", true)].into_py_dict_bound(py)),
+    )?;
+    event_loop.call_method1("run_until_complete", (gather,))?;
+    let shutdown_asyncgens = event_loop.call_method0("shutdown_asyncgens")?;
+    event_loop.call_method1("run_until_complete", (shutdown_asyncgens,))?;
+    event_loop.call_method0("close")?;
+    Ok(())
+}
@@ -1,20 +1,80 @@
 #![allow(clippy::borrow_deref_ref)] // clippy warns about code generated by #[pymethods]
 
+use std::collections::{BTreeMap, BTreeSet, HashMap, VecDeque};
+use std::mem::ManuallyDrop;
+use std::net::{IpAddr, SocketAddr};
+use std::path::PathBuf;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use std::sync::Arc;
-use std::time::Duration;
+use std::task::Poll;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
-use arrow::pyarrow::{FromPyArrow, ToPyArrow};
-use dora_node_api::dora_core::config::NodeId;
+use arrow::array::{make_array, Array, ArrayData, ArrayRef, AsArray, StructArray, UInt8Array};
+use arrow::datatypes::{DataType, Field, Schema, SchemaRef};
+use arrow::pyarrow::{FromPyArrow, PyArrowType, ToPyArrow};
+use arrow::record_batch::RecordBatch;
+use dora_node_api::arrow_utils::{combine_chunks, deep_copy};
+use dora_node_api::dora_core::config::{DataId, NodeId, OperatorId};
+use dora_node_api::dora_core::descriptor::{Descriptor, DescriptorExt, ParamValue};
+use dora_node_api::dora_core::metadata::ArrowTypeInfoExt;
+use dora_node_api::dora_core::topics::{
+    DORA_COORDINATOR_PORT_CONTROL_DEFAULT, DORA_DAEMON_LOCAL_LISTEN_PORT_DEFAULT, LOCALHOST,
+};
 use dora_node_api::merged::{MergeExternalSend, MergedEvent};
-use dora_node_api::{DataflowId, DoraNode, EventStream};
-use dora_operator_api_python::{pydict_to_metadata, DelayedCleanup, NodeCleanupHandle, PyEvent};
+use dora_node_api::metadata::{
+    attachment_key, caused_by_parameter, is_valid_content_type, ArrowTypeInfo, Priority, CAUSED_BY,
+    CONFLATED, CONTENT_TYPE, DROPPED_COUNT, FRAME_ID, KNOWN_UNITS, PRIORITY, PROTO_TYPE, RELIABLE,
+    SCHEMA_NAME, SEQUENCE_NUMBER, TIMESTAMP_NS, UNITS,
+};
+use dora_node_api::{
+    openmetrics_text, summarize, uhlc, DataflowId, DoraNode, DryRunTransport, Event, EventStream,
+    InputSchedule, LifecycleEvent, MessageCounters, Metadata, MetadataParameters, Parameter,
+};
+use dora_operator_api_python::delta::{
+    DeltaDecoder, DeltaEncoder, DeltaFrame, DELTA_BASE, DELTA_VERSION,
+};
+use dora_operator_api_python::metadata_codec::MetadataCodec;
+use dora_operator_api_python::recording::{parse_compression, EventRecorder, RotationPolicy};
+use dora_operator_api_python::{
+    cuda, decimal, protobuf, ros2, schema_registry, sparse, structured, table, tensor,
+};
+use dora_operator_api_python::{
+    flight, pydict_to_metadata_strict, pydict_to_metadata_with_codec, CleanupHandle,
+    DelayedCleanup, EventType, ExternalEvent, NodeCleanupHandle, PyEvent, PyMetadata,
+};
 use dora_ros2_bridge_python::Ros2Subscription;
-use eyre::Context;
-use futures::{Stream, StreamExt};
+use eyre::{Context, ContextCompat};
+use futures::future::{select, Either};
+use futures::{FutureExt, Stream, StreamExt};
+use futures_timer::Delay;
+use pyo3::exceptions::{PyKeyError, PyRuntimeError, PyRuntimeWarning, PyTypeError, PyValueError};
 use pyo3::prelude::*;
-use pyo3::types::{PyBytes, PyDict};
+use pyo3::types::{PyBool, PyBytes, PyDict, PyFloat, PyInt, PyList, PyString};
 use pyo3_special_method_derive::{Dict, Dir, Repr, Str};
 
+use crate::capture::{OutputCapture, CAPTURE_FORWARD_INTERVAL};
+use crate::errors::{dora_error, schema_error, Operation};
+use crate::external::{external_stream_from_async, ExternalEventStream};
+use crate::lifecycle::LifecycleEvents;
+use crate::lockstep::{CompleteTick, CompleteTickEvents, Ticks};
+use crate::recording::Recording;
+use crate::tee::EventTee;
+use crate::timer::Timers;
+use crate::transaction::Transaction;
+use crate::window::Windows;
+
+mod capture;
+mod errors;
+mod external;
+mod lifecycle;
+mod lockstep;
+mod recording;
+mod tee;
+mod timer;
+mod transaction;
+mod window;
+
 /// The custom node API lets you integrate `dora` into your application.
 /// It allows you to retrieve input and send output in any fashion you want.
 ///
@@ -26,44 +86,329 @@ use pyo3_special_method_derive::{Dict, Dir, Repr, Str};
 /// node = Node()
 /// ```
 ///
+/// To bound the memory used by inputs that were received but not processed yet,
+/// pass `max_buffered_bytes`. When the total payload size of the buffered inputs
+/// exceeds it, the oldest events of the input that buffers the most bytes are
+/// dropped, like for the `queue_size` limit of inputs:
+///
+/// ```python
+/// node = Node(max_buffered_bytes=256 * 1024 * 1024)
+/// ```
+///
+/// To detect clock bugs of upstream nodes, pass `assert_monotonic_timestamps=True`.
+/// A `RuntimeWarning` is then emitted whenever the timestamp of an input event is
+/// older than the timestamp of the previous event of the same input. Pass
+/// `assert_monotonic_timestamps="raise"` to raise a `RuntimeError` instead. The
+/// event is still processed and available as the `event` attribute of the error.
+///
+/// By default, arbitrary keys are accepted in the `metadata` of sent outputs. Pass
+/// `strict_metadata=True` to reject keys that are not known to dora, e.g. to catch
+/// typos like `"frameid"`. User-defined metadata must then be nested under the
+/// `parameters` key:
+///
+/// ```python
+/// node = Node(strict_metadata=True)
+/// node.send_output("image", data, {"units": "m", "parameters": {"exposure": 20}})
+/// ```
+///
+/// Metadata values without a native representation, e.g. floats or nested dicts,
+/// are converted to strings by default. Pass `metadata_codec="json"`, `"msgpack"`,
+/// or `"cbor"` to serialize them with the given format instead. The codec is
+/// stored in the metadata, so receivers decode the values automatically:
+///
+/// ```python
+/// node = Node(metadata_codec="msgpack")
+/// node.send_output("pose", data, {"covariance": [[0.1, 0.0], [0.0, 0.1]]})
+/// ```
+///
+/// For event-time processing, e.g. time-windowed aggregations across inputs, pass
+/// `watermarks=True`. The node then emits `WATERMARK` events, whose `time` signals
+/// that no more inputs with an earlier or equal `event.timestamp()` will arrive.
+/// The watermark is the minimum over all open inputs of the latest received input
+/// timestamp. There is no watermark until every input received an event, and
+/// closed inputs no longer hold back the watermark:
+///
+/// ```python
+/// node = Node(watermarks=True, event_class=True)
+/// for event in node:
+///     if event["type"] == "INPUT":
+///         windows.add(event.timestamp(), event["value"])
+///     elif event["type"] == "WATERMARK":
+///         windows.close_until(event["time"])
+/// ```
+///
+/// A node is bound to the process that created it. Using it in a child process
+/// created through `fork`, e.g. by `multiprocessing`, raises a `RuntimeError`
+/// since sharing the connection to the daemon with the parent corrupts it. To
+/// use the node in the child instead, call `reinit_after_fork` there first.
+///
+/// If another process is already connected with the same node ID, e.g. because
+/// the node was started twice, `Node()` raises a `dora.DuplicateNodeIdError`.
+/// If the node was built against a dora version that is not compatible with the
+/// running daemon, `Node()` raises a `dora.VersionMismatchError`, which reports
+/// both versions.
+///
+/// Errors of dora are raised as subclasses of `dora.DoraError`, which derives
+/// from `RuntimeError`. Failed sends raise a `dora.SendError`, or a
+/// `dora.DisconnectedError` if the connection to the daemon was lost:
+///
+/// ```python
+/// try:
+///     node.send_output("status", pa.array([1]))
+/// except dora.DisconnectedError:
+///     reconnect()
+/// ```
+///
+/// To record all received inputs into an Arrow IPC file, pass the path of the file
+/// as `record`. The file can be compressed through the built-in compression of the
+/// Arrow IPC format by passing `compression="zstd"` or `compression="lz4"`, which
+/// substantially reduces the size of recordings of sensor data. Recordings are
+/// replayed through `Node.from_recording`, which detects the compression
+/// automatically:
+///
+/// ```python
+/// node = Node(record="events.arrow", compression="zstd")
+/// ...
+/// for event in Node.from_recording("events.arrow"):
+///     ...
+/// ```
+///
+/// Long recordings can be split into several files through `rotate_recording`
+/// and `set_recording_rotation`. Inputs that cannot be written to the recording,
+/// e.g. because the disk is full, are still returned by `next()`, with a
+/// `RuntimeWarning`.
+///
+/// To test the node logic without a running dataflow, pass `dry_run=True`. Outputs
+/// are then converted and validated as usual, but recorded instead of being sent,
+/// and can be inspected through `dry_run_outputs`. All output IDs are accepted and
+/// the node receives no events:
+///
+/// ```python
+/// node = Node("detector", dry_run=True)
+/// node.send_output("boxes", pa.array([1.0, 2.0]))
+/// [output] = node.dry_run_outputs()
+/// assert output["id"] == "boxes" and output["value"].type == pa.float64()
+/// ```
+///
+/// To observe which outputs are lost, pass the ID of a declared output as
+/// `dead_letter`. Outputs that cannot be delivered are then sent on this output
+/// instead of being dropped, with the metadata parameters `dead_letter_reason`
+/// and `dead_letter_output`. The reason is `"undeclared_output"` for outputs that
+/// are not listed in the dataflow descriptor, `"no_subscribers"` if no node
+/// receives the output, and `"not_acknowledged"` if a reliably sent output timed
+/// out. Only these failures of the sender are dead-lettered: inputs that a
+/// receiver drops because of its `queue_size` or `max_buffered_bytes` limit, or
+/// because a newer message of a conflated output replaced them, are reported to
+/// the receiver through the `dropped_count` parameter instead:
+///
+/// ```python
+/// node = Node(dead_letter="dlq")
+/// node.send_output("debug_image", image)  # sent on `dlq` if nobody listens
+/// ```
+///
+/// Inputs sent with `reliable=True` are acknowledged to the sender as soon as they
+/// are returned by `next()`. To acknowledge them only after they were processed,
+/// pass `manual_acks=True` and call `ack` or `ack_all`, see `ack` for details.
+///
+/// Large inputs are received without copying, so their values are views into
+/// memory that is shared with the sender and released when the event and all
+/// arrays derived from it are dropped. To avoid reasoning about the lifetime of
+/// these views, e.g. when handing values to native code that keeps raw pointers,
+/// pass `always_copy=True`. Every input is then copied into memory that is owned
+/// by the event and the shared memory is returned to the sender immediately.
+/// This costs an additional copy of each payload, which is noticeable for
+/// inputs of several megabytes at high rates, such as camera images.
+///
+/// To connect to a specific dataflow, independent of the environment of the
+/// process, pass its ID as `dataflow` together with the `node_id` of a dynamic
+/// node of that dataflow. `daemon_address` selects another daemon than the local
+/// one. A process can connect to several dataflows this way, e.g. to bridge them
+/// through `Node.connect` and `forward`:
+///
+/// ```python
+/// source = Node.connect(dataflow_a, "bridge")
+/// sink = Node.connect(dataflow_b, "bridge")
+/// for event in source:
+///     if event["type"] == "INPUT":
+///         sink.forward(event)
+/// ```
+///
+/// Pass `emit_summary_on_close=True` to report the statistics of the node when
+/// it is closed, i.e. when the `Node` object is dropped. The summary is reported
+/// before the connection to the daemon is closed and delivered as `SUMMARY` event
+/// to the nodes that subscribed through `lifecycle_events`, e.g. a collector:
+///
+/// ```python
+/// for event in node.lifecycle_events():
+///     if event["type"] == "SUMMARY":
+///         print(f"{event['node']}: {event['received']} inputs, {event['dropped']} dropped")
+/// ```
+///
+/// Inputs whose value cannot be decoded, e.g. because the payload is corrupted
+/// or because a decoder registered through `set_decoder` raised an exception,
+/// are handled according to `on_decode_error`, so that a faulty sender does not
+/// stop the whole event stream:
+///
+/// - `"event"` (default): `next()` returns a `{"type": "DECODE_ERROR", "id": ...,
+///   "error": ...}` event instead of the input and continues with the next event.
+/// - `"ignore"`: the input is skipped.
+/// - `"raise"`: `next()` raises the error. The undecoded input is available as
+///   the `event` attribute of the exception.
+///
+/// ```python
+/// for event in Node(on_decode_error="event"):
+///     if event["type"] == "DECODE_ERROR":
+///         print(f"dropped corrupt message on `{event['id']}`: {event['error']}")
+/// ```
+///
+/// By default, `next()` alternates between the inputs that have queued events.
+/// Pass `input_schedule="weighted_fair"` together with `weights` per input to
+/// return the queued events of each input at a rate proportional to its weight
+/// instead, so that a flood of events on one input cannot starve a low-rate but
+/// important input. Inputs without weight have weight `1.0`. Events sent with
+/// `priority="high"` are still returned first:
+///
+/// ```python
+/// node = Node(input_schedule="weighted_fair", weights={"command": 4.0, "image": 1.0})
+/// ```
+///
+/// Events are returned as plain `dict`s. Pass `event_class=True` to receive
+/// `dora.Event` objects instead, which can be accessed like a `dict` but also
+/// provide methods such as `event.timestamp()` or `event.to_ndarray()`:
+///
+/// ```python
+/// for event in Node(event_class=True):
+///     if event["type"] == "INPUT":
+///         print(event.id_and_seq(), event.timestamp())
+/// ```
+///
 /// :type node_id: str, optional
+/// :type max_buffered_bytes: int, optional
+/// :type assert_monotonic_timestamps: bool | str, optional
+/// :type strict_metadata: bool, optional
+/// :type watermarks: bool, optional
+/// :type record: str, optional
+/// :type compression: str, optional
+/// :type dry_run: bool, optional
+/// :type dead_letter: str, optional
+/// :type manual_acks: bool, optional
+/// :type always_copy: bool, optional
+/// :type metadata_codec: str, optional
+/// :type dataflow: str, optional
+/// :type daemon_address: str, optional
+/// :type emit_summary_on_close: bool, optional
+/// :type on_decode_error: str, optional
+/// :type input_schedule: str, optional
+/// :type weights: dict[str, float], optional
+/// :type event_class: bool, optional
 #[pyclass]
 #[derive(Dir, Dict, Str, Repr)]
 pub struct Node {
-    events: Events,
-    node: DelayedCleanup<DoraNode>,
+    /// Not dropped in forked child processes, see the `Drop` impl.
+    events: ManuallyDrop<Events>,
+    node: ManuallyDrop<DelayedCleanup<DoraNode>>,
 
     dataflow_id: DataflowId,
     node_id: NodeId,
+    lifecycle_events: flume::Receiver<LifecycleEvent>,
+    lifecycle_subscribed: bool,
+    /// Last timestamp per input, if `assert_monotonic_timestamps` is enabled.
+    timestamp_check: Option<TimestampCheck>,
+    /// Whether unknown metadata keys are rejected on send.
+    strict_metadata: bool,
+    /// Codec of metadata values without a native representation, see `metadata_codec`.
+    metadata_codec: Option<MetadataCodec>,
+    max_buffered_bytes: Option<usize>,
+    watermarks: bool,
+    manual_acks: bool,
+    /// Whether the payload of received inputs is copied, see `always_copy`.
+    always_copy: bool,
+    /// Daemon of a node that was connected through `dataflow` or `daemon_address`.
+    daemon_address: Option<SocketAddr>,
+    /// Whether a summary is reported when the node is dropped, see `emit_summary_on_close`.
+    emit_summary_on_close: bool,
+    /// ID of the process that owns the connection to the daemon.
+    pid: u32,
+    /// Redirection of stdout and stderr, if enabled through `capture_output`.
+    output_capture: Option<OutputCapture>,
+    /// Recording of the received inputs, if enabled through `record`.
+    recorder: Option<EventRecorder>,
+    /// Decoders registered through `set_decoder`.
+    decoders: HashMap<DataId, PyObject>,
+    /// Handling of inputs that cannot be decoded, see `on_decode_error`.
+    on_decode_error: DecodeErrorPolicy,
+    /// Order of the queued events of different inputs, see `input_schedule`.
+    input_schedule: InputSchedule,
+    /// Previously sent values of the outputs of `send_output_delta`.
+    delta_outputs: HashMap<DataId, DeltaEncoder>,
+    /// Last values of delta-encoded inputs.
+    delta_inputs: DeltaDecoder,
+    /// Callback registered through `on_all_inputs_closed`, taken when invoked.
+    on_all_inputs_closed: Option<PyObject>,
+    /// Data type of the most recent message per input, see `input_schema`.
+    input_types: HashMap<DataId, DataType>,
+    /// Records the sent outputs if the node was created with `dry_run=True`.
+    dry_run: Option<DryRunTransport>,
+    /// Timers added through `add_timer`.
+    timers: Timers,
+    /// Recent events of the inputs that were requested through `window`.
+    windows: Windows,
+    /// Inputs of incomplete ticks, see `next_tick`.
+    ticks: Ticks,
+    /// Events of other inputs that were received by a `record_batch_reader`,
+    /// returned by `next` before any new event.
+    pending: VecDeque<Py<PyEvent>>,
+    /// Whether events are returned as `dora.Event` instead of a `dict`.
+    event_class: bool,
 }
 
 #[pymethods]
 impl Node {
     #[new]
-    #[pyo3(signature = (node_id=None))]
-    pub fn new(node_id: Option<String>) -> eyre::Result<Self> {
-        let (node, events) = if let Some(node_id) = node_id {
-            DoraNode::init_flexible(NodeId::from(node_id))
-                .context("Could not setup node from node id. Make sure to have a running dataflow with this dynamic node")?
-        } else {
-            DoraNode::init_from_env().context("Could not initiate node from environment variable. For dynamic node, please add a node id in the initialization function.")?
-        };
-
-        let dataflow_id = *node.dataflow_id();
-        let node_id = node.id().clone();
-        let node = DelayedCleanup::new(node);
-        let events = DelayedCleanup::new(events);
-        let cleanup_handle = NodeCleanupHandle {
-            _handles: Arc::new((node.handle(), events.handle())),
-        };
-        Ok(Node {
-            events: Events {
-                inner: EventsInner::Dora(events),
-                cleanup_handle,
-            },
-            dataflow_id,
+    #[pyo3(signature = (node_id=None, max_buffered_bytes=None, assert_monotonic_timestamps=None, strict_metadata=None, watermarks=None, record=None, compression=None, dry_run=None, dead_letter=None, manual_acks=None, always_copy=None, metadata_codec=None, dataflow=None, daemon_address=None, emit_summary_on_close=None, on_decode_error=None, input_schedule=None, weights=None, event_class=None))]
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        node_id: Option<String>,
+        max_buffered_bytes: Option<usize>,
+        assert_monotonic_timestamps: Option<Bound<'_, PyAny>>,
+        strict_metadata: Option<bool>,
+        watermarks: Option<bool>,
+        record: Option<PathBuf>,
+        compression: Option<String>,
+        dry_run: Option<bool>,
+        dead_letter: Option<String>,
+        manual_acks: Option<bool>,
+        always_copy: Option<bool>,
+        metadata_codec: Option<String>,
+        dataflow: Option<String>,
+        daemon_address: Option<String>,
+        emit_summary_on_close: Option<bool>,
+        on_decode_error: Option<String>,
+        input_schedule: Option<String>,
+        weights: Option<BTreeMap<String, f64>>,
+        event_class: Option<bool>,
+    ) -> eyre::Result<Self> {
+        Node::from_options(NodeOptions {
             node_id,
-            node,
+            max_buffered_bytes,
+            assert_monotonic_timestamps,
+            strict_metadata,
+            watermarks,
+            record,
+            compression,
+            dry_run,
+            dead_letter,
+            manual_acks,
+            always_copy,
+            metadata_codec,
+            dataflow,
+            daemon_address,
+            emit_summary_on_close,
+            on_decode_error,
+            input_schedule,
+            weights,
+            event_class,
+            fork_of: None,
         })
     }
 
@@ -90,16 +435,10 @@ impl Node {
     /// :rtype: dict
     #[pyo3(signature = (timeout=None))]
     #[allow(clippy::should_implement_trait)]
-    pub fn next(&mut self, py: Python, timeout: Option<f32>) -> PyResult<Option<Py<PyDict>>> {
-        let event = py.allow_threads(|| self.events.recv(timeout.map(Duration::from_secs_f32)));
-        if let Some(event) = event {
-            let dict = event
-                .to_py_dict(py)
-                .context("Could not convert event into a dict")?;
-            Ok(Some(dict))
-        } else {
-            Ok(None)
-        }
+    pub fn next(&mut self, py: Python, timeout: Option<f32>) -> PyResult<Option<PyObject>> {
+        self.next_event(py, timeout)?
+            .map(|event| self.to_python(py, event))
+            .transpose()
     }
 
     /// You can iterate over the event stream with a loop
@@ -115,7 +454,7 @@ impl Node {
     /// Default behaviour is to timeout after 2 seconds.
     ///
     /// :rtype: dict
-    pub fn __next__(&mut self, py: Python) -> PyResult<Option<Py<PyDict>>> {
+    pub fn __next__(&mut self, py: Python) -> PyResult<Option<PyObject>> {
         self.next(py, None)
     }
 
@@ -149,157 +488,3724 @@ impl Node {
     /// node.send_output("string", b"string", {"open_telemetry_context": "7632e76"})
     /// ```
     ///
+    /// Empty data is valid and is delivered as a zero-length array, which is useful
+    /// for pure trigger signals:
+    ///
+    /// ```python
+    /// node.send_output("trigger", b"")
+    /// ```
+    ///
+    /// Use `caused_by` to reference the input events that produced this output.
+    /// The references are stored in the metadata and allow tooling to
+    /// reconstruct the provenance of a message across the dataflow:
+    ///
+    /// ```python
+    /// node.send_output("command", data, caused_by=[event])
+    /// ```
+    ///
+    /// OpenTelemetry baggage is not propagated implicitly, since an output is not
+    /// necessarily caused by the last received input. To propagate the baggage of
+    /// an input, or to set new entries, pass them as `open_telemetry_baggage`
+    /// metadata:
+    ///
+    /// ```python
+    /// node.send_output("pose", data, {"open_telemetry_baggage": event.baggage()})
+    /// node.send_output("pose", data, {"open_telemetry_baggage": {"robot.id": "r2"}})
+    /// ```
+    ///
+    /// To describe the data of robotics messages, pass the coordinate frame as
+    /// `frame_id` and the physical units as `units`. Receivers can read them through
+    /// `event.frame_id()` and `event.units()`. Units should be one of the standard
+    /// names, e.g. `"m"`, `"rad"`, `"m/s"`, or `"degC"`; other units are sent as well,
+    /// but emit a `RuntimeWarning`:
+    ///
+    /// ```python
+    /// node.send_output("position", data, frame_id="base_link", units="m")
+    /// ```
+    ///
+    /// Encoded data, e.g. a JPEG image or a serialized protobuf message, can be
+    /// annotated with its MIME type as `content_type`, so that generic consumers
+    /// like viewers know how to decode the bytes. Receivers read it through
+    /// `event.content_type()`. Any syntactically valid MIME type is accepted:
+    ///
+    /// ```python
+    /// node.send_output("image", cv2.imencode(".jpg", frame)[1].tobytes(), content_type="image/jpeg")
+    /// ```
+    ///
+    /// Control messages can be sent with `priority="high"` to deliver them before
+    /// queued messages of normal priority, e.g. so that a stop command is not stuck
+    /// behind large images. Bulk data can be sent with `priority="low"`. By default,
+    /// messages are delivered in order:
+    ///
+    /// ```python
+    /// node.send_output("command", pa.array(["stop"]), priority="high")
+    /// ```
+    ///
+    /// Exact decimal values can be sent as a list of `decimal.Decimal`, which is
+    /// converted to an arrow `Decimal128` array with the smallest precision and scale
+    /// that represent all values exactly. To choose the precision and scale, pass a
+    /// `pyarrow` decimal array instead. Receivers get `decimal.Decimal` values back
+    /// through `event["value"].to_pylist()`:
+    ///
+    /// ```python
+    /// node.send_output("price", [Decimal("12.3456")])
+    /// node.send_output("price", pa.array([Decimal("12.3456")], pa.decimal128(18, 6)))
+    /// ```
+    ///
+    /// `scipy.sparse` matrices are sent without densifying them. CSR and COO
+    /// matrices keep their format, other formats are converted to CSR. Receivers
+    /// reconstruct the matrix through `event.to_sparse()`:
+    ///
+    /// ```python
+    /// node.send_output("occupancy", scipy.sparse.csr_matrix(grid))
+    /// ```
+    ///
+    /// numpy structured arrays, e.g. arrays of C structs, are sent as struct arrays
+    /// with one field per dtype field. Nested dtypes become nested structs and
+    /// subarray fields fixed-size lists. Receivers reconstruct the structured array
+    /// through `event.to_structured()`, with a packed dtype of the same fields:
+    ///
+    /// ```python
+    /// dtype = np.dtype([("id", "u4"), ("pos", "f4", (3,))], align=True)
+    /// node.send_output("objects", np.zeros(8, dtype))
+    /// ```
+    ///
+    /// `pyarrow.Table`s and lists of `pyarrow.RecordBatch` are sent as a `uint8`
+    /// array in the Arrow IPC streaming format, which keeps the batches separate.
+    /// All batches must have the same schema. Receivers reconstruct the table
+    /// through `event.to_table()`:
+    ///
+    /// ```python
+    /// node.send_output("detections", pa.Table.from_pylist(rows))
+    /// ```
+    ///
+    /// numpy arrays with two or more dimensions are sent as a single element of the
+    /// canonical `arrow.fixed_shape_tensor` extension type. The `ARROW:extension:name`
+    /// and `ARROW:extension:metadata` metadata keys hold the extension type with the
+    /// shape of the array, so other Arrow tensor consumers can read it as well.
+    /// Receivers reconstruct the array through `event.to_ndarray()`. Fortran-ordered
+    /// and transposed arrays keep their strides, other strided arrays are copied:
+    ///
+    /// ```python
+    /// node.send_output("depth", np.zeros((480, 640), np.float32))
+    /// ```
+    ///
+    /// GPU buffers that implement `__cuda_array_interface__`, e.g. `cupy` arrays or
+    /// CUDA `torch` tensors, are copied to host memory and sent as flat array. The
+    /// metadata keys `cuda_shape`, `cuda_typestr`, and `cuda_device` describe the
+    /// original buffer, so that receivers can restore it on the GPU:
+    ///
+    /// ```python
+    /// node.send_output("depth", cupy.zeros((480, 640), "f4"))
+    /// # receiver
+    /// depth = cupy.asarray(event["value"].to_numpy()).reshape(event["metadata"]["cuda_shape"])
+    /// ```
+    ///
+    /// Small binary blobs, e.g. calibration data or debug artifacts, can be sent
+    /// along with the data as named `attachments`. Unlike the fields of a struct
+    /// array, attachments are opaque and not part of the typed data. Receivers can
+    /// read them through `event.attachment(name)`. The total size of the
+    /// attachments of a message is limited to 64 KiB:
+    ///
+    /// ```python
+    /// node.send_output("image", data, attachments={"calib": calibration_bytes})
+    /// ```
+    ///
+    /// To reproduce the timing of recorded messages, pass the time at which the output
+    /// should be sent as `at`, in seconds since the UNIX epoch (like `time.time()`).
+    /// Scheduled outputs are buffered by the node and sent once their time is reached,
+    /// while the node waits for events in `next()` or sends other outputs. If `at` is
+    /// in the past, the output is sent immediately:
+    ///
+    /// ```python
+    /// node.send_output("replay", data, at=start + recorded_offset)
+    /// ```
+    ///
+    /// By default, outputs are sent on a best-effort basis. For critical commands,
+    /// pass `reliable=True` to block until all receiving nodes acknowledged the
+    /// output, which happens when their `next()` returns it. Unacknowledged outputs
+    /// are sent again every second, until `reliable_timeout` seconds (default `5`)
+    /// passed. On timeout, a `dora.AckTimeoutError` is raised:
+    ///
+    /// ```python
+    /// node.send_output("emergency_stop", pa.array([True]), reliable=True)
+    /// ```
+    ///
+    /// Retries can deliver an output more than once, possibly after newer outputs,
+    /// so receivers must handle duplicates idempotently. All copies carry the same
+    /// `reliable` metadata value, which can be used to detect them. Only receivers on
+    /// the same machine acknowledge outputs: receivers on other machines get reliable
+    /// outputs on a best-effort basis, without retries. If all receivers of an output
+    /// run on other machines, `send_output` returns immediately and the daemon logs
+    /// a warning.
+    ///
+    /// Outputs of a named message type can be checked against a schema that was
+    /// registered through `dora.register_schema` or in the `schemas` section of the
+    /// dataflow descriptor. If `schema` is given, the data must match it, otherwise
+    /// a `dora.SchemaError` is raised. The name is sent as `schema` metadata, so
+    /// that receivers validate the input against the same schema:
+    ///
+    /// ```python
+    /// node.send_output("detections", pa.array(rows), schema="Detection")
+    /// ```
+    ///
+    /// :type output_id: str
+    /// :type data: pyarrow.Array | pyarrow.Table | list[pyarrow.RecordBatch] | bytes | list[decimal.Decimal] | scipy.sparse.spmatrix | numpy.ndarray | cupy.ndarray
+    /// :type metadata: dict, optional
+    /// :type caused_by: list[dict | tuple[str, int]], optional
+    /// :type at: float, optional
+    /// :type frame_id: str, optional
+    /// :type units: str, optional
+    /// :type priority: str, optional
+    /// :type attachments: dict[str, bytes], optional
+    /// :type reliable: bool, optional
+    /// :type reliable_timeout: float, optional
+    /// :type schema: str, optional
+    /// :type content_type: str, optional
+    /// :rtype: None
+    #[pyo3(signature = (output_id, data, metadata=None, caused_by=None, at=None, frame_id=None, units=None, priority=None, attachments=None, reliable=false, reliable_timeout=None, schema=None, content_type=None))]
+    #[allow(clippy::too_many_arguments)]
+    pub fn send_output<'py>(
+        &mut self,
+        output_id: String,
+        data: PyObject,
+        metadata: Option<Bound<'py, PyDict>>,
+        caused_by: Option<Vec<Bound<'py, PyAny>>>,
+        at: Option<f64>,
+        frame_id: Option<String>,
+        units: Option<String>,
+        priority: Option<String>,
+        attachments: Option<HashMap<String, Bound<'py, PyBytes>>>,
+        reliable: bool,
+        reliable_timeout: Option<f64>,
+        schema: Option<String>,
+        content_type: Option<String>,
+        py: Python<'py>,
+    ) -> eyre::Result<()> {
+        let caused_by = caused_by
+            .map(|references| references.iter().map(lineage_reference).collect())
+            .transpose()?;
+        let options = SendOptions {
+            caused_by,
+            at,
+            frame_id,
+            units,
+            priority,
+            attachments,
+            reliable,
+            reliable_timeout,
+            schema,
+            content_type,
+        };
+        self.send_output_with(output_id, data, metadata, options, py)
+    }
+
+    /// Sends only the regions of a large value that changed since the previous
+    /// call for the same output, e.g. for a costmap of which only a few cells
+    /// change per update.
+    ///
+    /// The value must be `bytes` or an arrow array of a primitive type without
+    /// nulls. The first value, values whose type or length changed, and values
+    /// that changed too much are sent in full. The receiving node reconstructs
+    /// the full value from the previous value of the input, so receivers see the
+    /// same events as for `send_output`:
+    ///
+    /// ```python
+    /// node.send_output_delta("costmap", pa.array(grid.ravel()), full_every=100)
+    /// ```
+    ///
+    /// Deltas can only be applied if the receiver got the previous message of
+    /// the output. If a message was dropped, e.g. because the input queue was
+    /// full, the receiver gets `DECODE_ERROR` events until the next full value,
+    /// see `on_decode_error`. Pass `full_every` to send every `full_every`-th
+    /// value in full, so that receivers recover from dropped messages.
+    ///
     /// :type output_id: str
-    /// :type data: pyarrow.Array
+    /// :type data: pyarrow.Array | bytes
     /// :type metadata: dict, optional
+    /// :type full_every: int, optional
     /// :rtype: None
-    #[pyo3(signature = (output_id, data, metadata=None))]
-    pub fn send_output(
+    #[pyo3(signature = (output_id, data, metadata=None, full_every=None))]
+    pub fn send_output_delta(
         &mut self,
         output_id: String,
         data: PyObject,
         metadata: Option<Bound<'_, PyDict>>,
+        full_every: Option<usize>,
         py: Python,
     ) -> eyre::Result<()> {
-        let parameters = pydict_to_metadata(metadata)?;
-
-        if let Ok(py_bytes) = data.downcast_bound::<PyBytes>(py) {
-            let data = py_bytes.as_bytes();
-            self.node
-                .get_mut()
-                .send_output_bytes(output_id.into(), parameters, data.len(), data)
-                .wrap_err("failed to send output")?;
-        } else if let Ok(arrow_array) = arrow::array::ArrayData::from_pyarrow_bound(data.bind(py)) {
-            self.node.get_mut().send_output(
-                output_id.into(),
-                parameters,
-                arrow::array::make_array(arrow_array),
-            )?;
-        } else {
-            eyre::bail!("invalid `data` type, must by `PyBytes` or arrow array")
+        if full_every == Some(0) {
+            eyre::bail!("`full_every` must be at least 1");
+        }
+        let array = data_to_array(data.bind(py))?;
+        let delta_id = DataId::from(output_id.clone());
+        let frame = self
+            .delta_outputs
+            .entry(delta_id.clone())
+            .or_default()
+            .encode(&array.to_data(), full_every)
+            .with_context(|| format!("failed to delta-encode output `{output_id}`"))?;
+        let metadata = match metadata {
+            Some(metadata) => metadata.copy()?,
+            None => PyDict::new_bound(py),
+        };
+        let data = match frame {
+            DeltaFrame::Full { version } => {
+                metadata.set_item(DELTA_VERSION, version)?;
+                data
+            }
+            DeltaFrame::Delta {
+                version,
+                base,
+                delta,
+            } => {
+                metadata.set_item(DELTA_VERSION, version)?;
+                metadata.set_item(DELTA_BASE, base)?;
+                PyBytes::new_bound(py, &delta).into_any().unbind()
+            }
+        };
+        let result =
+            self.send_output_with(output_id, data, Some(metadata), SendOptions::default(), py);
+        if result.is_err() {
+            // receivers can't apply deltas to a value that they never got
+            if let Some(encoder) = self.delta_outputs.get_mut(&delta_id) {
+                encoder.reset();
+            }
         }
+        result
+    }
 
-        Ok(())
+    /// Sends a ROS 2 message of `rclpy` as output, e.g. a message received
+    /// through an `rclpy` subscription in the same process.
+    ///
+    /// The message is sent as a struct array with a single element, in the same
+    /// format as for the publishers of the ROS 2 bridge, so that receivers can
+    /// convert it back through `Event.to_ros2`. The `frame_id` and `stamp` of the
+    /// message header are sent as `frame_id` and `timestamp_ns` metadata, unless
+    /// these keys are given in `metadata`:
+    ///
+    /// ```python
+    /// def on_pose(msg):
+    ///     node.send_output_from_ros2("pose", msg)
+    ///
+    /// rclpy_node.create_subscription(PoseStamped, "/pose", on_pose, 10)
+    /// ```
+    ///
+    /// Requires `rosidl_runtime_py`, which is installed with ROS 2.
+    ///
+    /// :type output_id: str
+    /// :type message: typing.Any
+    /// :type metadata: dict, optional
+    /// :rtype: None
+    #[pyo3(signature = (output_id, message, metadata=None))]
+    pub fn send_output_from_ros2(
+        &mut self,
+        output_id: String,
+        message: Bound<'_, PyAny>,
+        metadata: Option<Bound<'_, PyDict>>,
+        py: Python,
+    ) -> eyre::Result<()> {
+        let output = ros2::from_ros2_message(&message)?;
+        let metadata = match metadata {
+            Some(metadata) => metadata.copy()?,
+            None => PyDict::new_bound(py),
+        };
+        if let Some(nanos) = output.timestamp_ns {
+            if !metadata.contains(TIMESTAMP_NS)? {
+                metadata.set_item(TIMESTAMP_NS, nanos)?;
+            }
+        }
+        let has_frame_id = metadata.contains(FRAME_ID)?;
+        let frame_id = output.frame_id.filter(|_| !has_frame_id);
+        let options = SendOptions {
+            frame_id,
+            ..Default::default()
+        };
+        self.send_output_with(output_id, output.data, Some(metadata), options, py)
     }
 
-    /// Returns the full dataflow descriptor that this node is part of.
+    /// Sends a protobuf message as output, serialized through its
+    /// `SerializeToString` method.
     ///
-    /// This method returns the parsed dataflow YAML file.
+    /// The message is sent as `uint8` array with the `application/x-protobuf`
+    /// content type. The full name of the message type is added to the metadata
+    /// as `proto_type`, so that receivers can parse it through `Event.as_proto`:
     ///
-    /// :rtype: dict
-    pub fn dataflow_descriptor(&mut self, py: Python) -> eyre::Result<PyObject> {
-        Ok(
-            pythonize::pythonize(py, &self.node.get_mut().dataflow_descriptor())
-                .map(|x| x.unbind())?,
+    /// ```python
+    /// from robot_pb2 import Command
+    ///
+    /// node.send_proto("command", Command(speed=0.5))
+    /// ```
+    ///
+    /// :type output_id: str
+    /// :type message: google.protobuf.message.Message
+    /// :type metadata: dict, optional
+    /// :rtype: None
+    #[pyo3(signature = (output_id, message, metadata=None))]
+    pub fn send_proto(
+        &mut self,
+        output_id: String,
+        message: Bound<'_, PyAny>,
+        metadata: Option<Bound<'_, PyDict>>,
+        py: Python,
+    ) -> eyre::Result<()> {
+        let (bytes, type_name) = protobuf::proto_to_bytes(&message)?;
+        let metadata = match metadata {
+            Some(metadata) => metadata.copy()?,
+            None => PyDict::new_bound(py),
+        };
+        metadata.set_item(PROTO_TYPE, type_name)?;
+        let options = SendOptions {
+            content_type: Some(protobuf::PROTOBUF_CONTENT_TYPE.to_owned()),
+            ..Default::default()
+        };
+        self.send_output_with(
+            output_id,
+            bytes.into_any().unbind(),
+            Some(metadata),
+            options,
+            py,
         )
     }
 
-    /// Returns the dataflow id.
+    /// Starts a transaction for sending several related outputs together.
     ///
-    /// :rtype: str
-    pub fn dataflow_id(&self) -> String {
-        self.dataflow_id.to_string()
+    /// Outputs sent through the returned transaction are buffered and only sent
+    /// when the `with` block exits normally. They are discarded if the block
+    /// raises an exception, so downstream nodes never see a partial set of
+    /// outputs of a tick:
+    ///
+    /// ```python
+    /// with node.transaction() as tx:
+    ///     tx.send_output("pose", pose)
+    ///     tx.send_output("covariance", covariance)
+    /// ```
+    ///
+    /// :rtype: dora.Transaction
+    pub fn transaction(&mut self) -> eyre::Result<Transaction> {
+        self.check_process()?;
+        Ok(Transaction::new(
+            self.node.handle(),
+            self.strict_metadata,
+            self.metadata_codec,
+            self.pid,
+        ))
     }
 
-    /// Merge an external event stream with dora main loop.
-    /// This currently only work with ROS2.
+    /// Sends an Arrow Flight frame as dora output.
+    ///
+    /// This is the counterpart of `Event.to_flight_data`, which allows bridge nodes
+    /// to relay data between dora and Arrow Flight services. The output id is
+    /// taken from the path of the `descriptor`. If `app_metadata` was created by
+    /// `Event.to_flight_data`, the metadata parameters of the original message are
+    /// sent along:
+    ///
+    /// ```python
+    /// reader = client.do_get(ticket)
+    /// for chunk in reader:
+    ///     node.send_flight_data(descriptor, chunk.data, chunk.app_metadata)
+    /// ```
     ///
-    /// :type subscription: dora.Ros2Subscription
+    /// A record batch with a single `value` column is sent as the column array,
+    /// all other record batches as struct arrays. Additional `metadata` and
+    /// `caused_by` are handled like in `send_output` and take precedence over the
+    /// parameters of `app_metadata`.
+    ///
+    /// :type descriptor: pyarrow.flight.FlightDescriptor
+    /// :type batch: pyarrow.RecordBatch
+    /// :type app_metadata: bytes, optional
+    /// :type metadata: dict, optional
+    /// :type caused_by: list[dict | tuple[str, int]], optional
     /// :rtype: None
-    pub fn merge_external_events(
+    #[pyo3(signature = (descriptor, batch, app_metadata=None, metadata=None, caused_by=None))]
+    pub fn send_flight_data<'py>(
         &mut self,
-        subscription: &mut Ros2Subscription,
+        descriptor: Bound<'py, PyAny>,
+        batch: PyArrowType<RecordBatch>,
+        app_metadata: Option<Bound<'py, PyAny>>,
+        metadata: Option<Bound<'py, PyDict>>,
+        caused_by: Option<Vec<Bound<'py, PyAny>>>,
+        py: Python<'py>,
     ) -> eyre::Result<()> {
-        let subscription = subscription.into_stream()?;
-        let stream = futures::stream::poll_fn(move |cx| {
-            let s = subscription.as_stream().map(|item| {
-                match item.context("failed to read ROS2 message") {
-                    Ok((value, _info)) => Python::with_gil(|py| {
-                        value
-                            .to_pyarrow(py)
-                            .context("failed to convert value to pyarrow")
-                            .unwrap_or_else(|err| PyErr::from(err).to_object(py))
-                    }),
-                    Err(err) => Python::with_gil(|py| PyErr::from(err).to_object(py)),
-                }
-            });
-            futures::pin_mut!(s);
-            s.poll_next_unpin(cx)
-        });
-
-        // take out the event stream and temporarily replace it with a dummy
-        let events = std::mem::replace(
-            &mut self.events.inner,
-            EventsInner::Merged(Box::new(futures::stream::empty())),
-        );
-        // update self.events with the merged stream
-        self.events.inner = EventsInner::Merged(events.merge_external_send(Box::pin(stream)));
+        let output_id = flight::descriptor_to_id(&descriptor)?;
+        let parameters = match app_metadata.filter(|m| !m.is_none()) {
+            Some(app_metadata) => {
+                // `app_metadata` is a `pyarrow.Buffer` when read from a Flight stream
+                let bytes: Vec<u8> = app_metadata
+                    .call_method0("to_pybytes")
+                    .unwrap_or(app_metadata)
+                    .extract()
+                    .context("`app_metadata` must be `bytes` or a `pyarrow.Buffer`")?;
+                flight::app_metadata_to_parameters(&bytes)?
+            }
+            None => MetadataParameters::default(),
+        };
 
-        Ok(())
+        let caused_by = caused_by
+            .map(|references| references.iter().map(lineage_reference).collect())
+            .transpose()?;
+        let options = SendOptions {
+            caused_by,
+            ..Default::default()
+        };
+        let data = flight::flight_batch_to_array(batch.0);
+        self.send_parameters_with(
+            output_id,
+            OutputData::Arrow(data),
+            parameters,
+            metadata,
+            options,
+            py,
+        )
     }
-}
-
-struct Events {
-    inner: EventsInner,
-    cleanup_handle: NodeCleanupHandle,
-}
 
-impl Events {
-    fn recv(&mut self, timeout: Option<Duration>) -> Option<PyEvent> {
-        let event = match &mut self.inner {
-            EventsInner::Dora(events) => match timeout {
-                Some(timeout) => events
-                    .get_mut()
+    /// Declares a `RecordBatch`-shaped output with a fixed arrow schema.
+    ///
+    /// The returned sender validates every batch against the schema before sending it
+    /// as a struct array with one field per schema column.
+    ///
+    /// ```python
+    /// schema = pa.schema([("label", pa.string()), ("score", pa.float32())])
+    /// sender = node.record_batch_output("detections", schema)
+    /// sender.send({"label": pa.array(["cat"]), "score": pa.array([0.9], pa.float32())})
+    /// ```
+    ///
+    /// Instead of a schema, the name of a registered schema can be given, see
+    /// `dora.register_schema`. The batches are then sent with `schema` metadata.
+    ///
+    /// :type output_id: str
+    /// :type schema: pyarrow.Schema | str
+    /// :rtype: dora.RecordBatchOutput
+    pub fn record_batch_output(
+        &mut self,
+        output_id: String,
+        schema: Bound<'_, PyAny>,
+    ) -> eyre::Result<RecordBatchOutput> {
+        self.check_process()?;
+        let output_id = DataId::from(output_id);
+        if !self
+            .node
+            .get_mut()
+            .node_config()
+            .outputs
+            .contains(&output_id)
+        {
+            eyre::bail!("output `{output_id}` is not declared in the dataflow");
+        }
+        let (schema, schema_name) = match schema.extract::<String>() {
+            Ok(name) => {
+                let schema = schema_registry::get(&name)
+                    .ok_or_else(|| schema_error(format!("no schema registered as `{name}`")))?;
+                (schema, Some(name))
+            }
+            Err(_) => (Arc::new(Schema::from_pyarrow_bound(&schema)?), None),
+        };
+        Ok(RecordBatchOutput {
+            output_id,
+            schema,
+            schema_name,
+            node: self.node.handle(),
+            strict_metadata: self.strict_metadata,
+            metadata_codec: self.metadata_codec,
+            pid: self.pid,
+        })
+    }
+
+    /// Returns a `pyarrow.RecordBatchReader` over the events of the given input.
+    ///
+    /// Every input event becomes one record batch, so the input must be sent as a
+    /// struct array, e.g. through `record_batch_output`. This allows feeding dora
+    /// inputs directly into Arrow-native libraries:
+    ///
+    /// ```python
+    /// table = node.record_batch_reader("detections")
+    /// duckdb.sql("SELECT label, avg(score) FROM table GROUP BY label")
+    /// ```
+    ///
+    /// If no `schema` is given, the reader waits for the first event of the input
+    /// and uses its schema. The reader ends when the input is closed or when the
+    /// dataflow is stopped. Events of other inputs that are received while reading
+    /// are kept and returned by `next` afterwards, in the order they were received,
+    /// including the event that closed the input or stopped the dataflow.
+    ///
+    /// :type input_id: str
+    /// :type schema: pyarrow.Schema, optional
+    /// :rtype: pyarrow.RecordBatchReader
+    #[pyo3(signature = (input_id, schema=None))]
+    pub fn record_batch_reader(
+        slf: Bound<'_, Self>,
+        input_id: String,
+        schema: Option<PyArrowType<Schema>>,
+    ) -> eyre::Result<PyObject> {
+        slf.borrow().check_process()?;
+        let py = slf.py();
+        let mut batches = RecordBatchInput {
+            node: slf.unbind(),
+            input_id: DataId::from(input_id),
+            first: None,
+        };
+        let schema = match schema {
+            Some(schema) => Arc::new(schema.0),
+            None => {
+                let first = batches.next_batch(py)?.ok_or_else(|| {
+                    eyre::eyre!(
+                        "input `{}` was closed before receiving a batch",
+                        batches.input_id
+                    )
+                })?;
+                let schema = first.schema();
+                batches.first = Some(first);
+                schema
+            }
+        };
+        let reader = py
+            .import_bound("pyarrow")?
+            .getattr("RecordBatchReader")?
+            .call_method1("from_batches", (schema.to_pyarrow(py)?, batches))?;
+        Ok(reader.unbind())
+    }
+
+    /// Returns the arrow schema of the most recent message on the given input.
+    ///
+    /// Inputs that are sent as struct arrays, e.g. through `record_batch_output`,
+    /// have one schema field per struct field. Other inputs have a single nullable
+    /// field named `value`. This allows configuring Arrow compute kernels ahead of
+    /// time instead of inspecting every event:
+    ///
+    /// ```python
+    /// schema = node.input_schema("detections")
+    /// if schema is not None and "score" in schema.names:
+    ///     ...
+    /// ```
+    ///
+    /// Returns `None` if no message was received on the input through `next()` yet.
+    ///
+    /// :type input_id: str
+    /// :rtype: pyarrow.Schema
+    pub fn input_schema(&self, input_id: String) -> Option<PyArrowType<Schema>> {
+        let data_type = self.input_types.get(&DataId::from(input_id))?;
+        Some(PyArrowType(input_schema(data_type)))
+    }
+
+    /// Returns the last `n` events of the given input as a list, oldest first.
+    ///
+    /// The events are kept in a ring buffer of the node, which is filled by
+    /// `next()`. This replaces a hand-written `deque` for sliding-window filters:
+    ///
+    /// ```python
+    /// node.window("imu", n=10)  # start buffering before the event loop
+    /// for event in node:
+    ///     if event["type"] == "INPUT" and event["id"] == "imu":
+    ///         samples = node.window("imu", n=10)
+    ///         mean = sum(e["value"][0].as_py() for e in samples) / len(samples)
+    /// ```
+    ///
+    /// Events are only buffered after the first call for an input, so the list
+    /// has fewer than `n` elements until enough events were received. Calling
+    /// `window` again with a larger `n` grows the buffer, a smaller `n` returns
+    /// only the most recent events of the buffer.
+    ///
+    /// Every buffered event keeps its value alive. For large inputs that are
+    /// received through shared memory, e.g. camera images, this also keeps the
+    /// shared memory of the sender in use, which may stall the sender once all of
+    /// its buffers are held. Keep `n` small for such inputs or create the node
+    /// with `always_copy=True` so that buffered events own a copy of their data.
+    ///
+    /// :type input_id: str
+    /// :type n: int
+    /// :rtype: list[dict]
+    pub fn window(
+        &mut self,
+        py: Python,
+        input_id: String,
+        n: usize,
+    ) -> eyre::Result<Vec<PyObject>> {
+        if n == 0 {
+            eyre::bail!("window size `n` must be positive");
+        }
+        let events = self.windows.get(py, DataId::from(input_id), n);
+        Ok(events
+            .into_iter()
+            .map(|event| self.to_python(py, event))
+            .collect::<PyResult<_>>()?)
+    }
+
+    /// Waits until each input has an event of the same tick and returns the
+    /// tick number with the events, as a dict from input ID to event.
+    ///
+    /// This is a barrier for lockstep pipelines, in which a tick must only be
+    /// processed once all of its inputs arrived. The tick number is read from the
+    /// integer metadata value `key`, which the senders set per message. Events of
+    /// future ticks are buffered until their tick is complete:
+    ///
+    /// ```python
+    /// while (result := node.next_tick(key="tick")) is not None:
+    ///     tick, events = result
+    ///     step(tick, events["lidar"]["value"], events["camera"]["value"])
+    /// ```
+    ///
+    /// By default, all inputs of the node take part in a tick, pass `inputs` to
+    /// synchronize only some of them. Events of other inputs and other events
+    /// than `STOP` are dropped, use `next()` to receive them instead. Ticks are
+    /// returned in increasing order: once a newer tick is complete, older ticks
+    /// that still miss events are discarded with a `RuntimeWarning`, and so are
+    /// late events of ticks that were already returned.
+    ///
+    /// Returns `None` on `STOP`, at the end of the event stream, or if no tick
+    /// completed within `timeout` seconds. Raises an error if an input of the
+    /// tick is closed or if an input event has no `key` metadata.
+    ///
+    /// :type key: str, optional
+    /// :type inputs: list[str], optional
+    /// :type timeout: float, optional
+    /// :rtype: tuple[int, dict[str, dict]]
+    #[pyo3(signature = (key="tick", inputs=None, timeout=None))]
+    pub fn next_tick(
+        &mut self,
+        py: Python,
+        key: &str,
+        inputs: Option<Vec<String>>,
+        timeout: Option<f32>,
+    ) -> eyre::Result<Option<CompleteTickEvents>> {
+        self.check_process()?;
+        let inputs: BTreeSet<DataId> = match inputs {
+            Some(inputs) => inputs.into_iter().map(DataId::from).collect(),
+            None => self
+                .node
+                .get_mut()
+                .node_config()
+                .inputs
+                .keys()
+                .cloned()
+                .collect(),
+        };
+        if inputs.is_empty() {
+            eyre::bail!("`next_tick` requires at least one input");
+        }
+        let deadline = timeout.map(|timeout| Instant::now() + Duration::from_secs_f32(timeout));
+        loop {
+            if let Some(CompleteTick {
+                tick,
+                events,
+                skipped,
+            }) = self.ticks.take_complete(&inputs)
+            {
+                if !skipped.is_empty() {
+                    PyErr::warn_bound(
+                        py,
+                        &py.get_type_bound::<PyRuntimeWarning>(),
+                        &format!("discarding incomplete ticks {skipped:?} before tick {tick}"),
+                        1,
+                    )?;
+                }
+                let events = events
+                    .into_iter()
+                    .map(|(id, event)| Ok((id.to_string(), self.to_python(py, event)?)))
+                    .collect::<PyResult<_>>()?;
+                return Ok(Some((tick, events)));
+            }
+
+            let timeout = deadline.map(|deadline| {
+                deadline
+                    .saturating_duration_since(Instant::now())
+                    .as_secs_f32()
+            });
+            let Some(event) = self.next_event(py, timeout)? else {
+                return Ok(None);
+            };
+            let (id, tick) = match &event.borrow(py).event {
+                MergedEvent::Dora(Event::Input { id, metadata, .. }) if inputs.contains(id) => {
+                    let tick = lockstep::tick(metadata, key)
+                        .wrap_err_with(|| format!("invalid tick of input `{id}`"))?;
+                    (id.clone(), tick)
+                }
+                MergedEvent::Dora(Event::InputClosed { id }) if inputs.contains(id) => {
+                    eyre::bail!("input `{id}` was closed, so no further tick can complete")
+                }
+                MergedEvent::Dora(Event::Stop) => return Ok(None),
+                _ => continue,
+            };
+            if let Some(warning) = self.ticks.insert(id, tick, event) {
+                PyErr::warn_bound(py, &py.get_type_bound::<PyRuntimeWarning>(), &warning, 1)?;
+            }
+        }
+    }
+
+    /// Declares an output that is not listed in the dataflow YAML.
+    ///
+    /// The daemon routes the new output to all inputs that are mapped to it in the
+    /// dataflow YAML. Inputs can only be mapped to outputs that are not listed in
+    /// `outputs` if the node sets `dynamic_outputs: true`. If a `schema` is given, a
+    /// `RecordBatchOutput` for the new output is returned, otherwise `None`.
+    ///
+    /// ```python
+    /// for device in discover_devices():
+    ///     node.declare_dynamic_output(device.name)
+    /// ```
+    ///
+    /// :type output_id: str
+    /// :type schema: pyarrow.Schema | str, optional
+    /// :rtype: dora.RecordBatchOutput
+    #[pyo3(signature = (output_id, schema=None))]
+    pub fn declare_dynamic_output(
+        &mut self,
+        output_id: String,
+        schema: Option<Bound<'_, PyAny>>,
+    ) -> eyre::Result<Option<RecordBatchOutput>> {
+        self.check_process()?;
+        self.node
+            .get_mut()
+            .declare_dynamic_output(DataId::from(output_id.clone()))?;
+        schema
+            .map(|schema| self.record_batch_output(output_id, schema))
+            .transpose()
+    }
+
+    /// Removes an output that was declared through `declare_dynamic_output`.
+    ///
+    /// Inputs mapped to the output are closed. A removed output cannot be declared
+    /// again.
+    ///
+    /// :type output_id: str
+    /// :rtype: None
+    pub fn remove_output(&mut self, output_id: String) -> eyre::Result<()> {
+        self.check_process()?;
+        self.node.get_mut().remove_output(DataId::from(output_id))
+    }
+
+    /// Unsubscribes the given input from its upstream output.
+    ///
+    /// The daemon stops sending new messages to the input and an `INPUT_CLOSED`
+    /// event is reported for it. Messages that were already sent to the node are
+    /// still returned by `next()` before the `INPUT_CLOSED` event. The other
+    /// inputs are not affected.
+    ///
+    /// ```python
+    /// node.disconnect_input("old_camera")
+    /// ```
+    ///
+    /// :type input_id: str
+    /// :rtype: None
+    pub fn disconnect_input(&mut self, input_id: String) -> eyre::Result<()> {
+        self.check_process()?;
+        self.node.get_mut().disconnect_input(DataId::from(input_id))
+    }
+
+    /// Subscribes to all outputs of the dataflow that match the given glob pattern.
+    ///
+    /// The pattern supports `*` and `?` wildcards. The matching outputs are
+    /// returned by `next()` like other inputs, with the output ID as event `id`.
+    /// Patterns that contain a `/` are matched against `<node>/<output>`, which
+    /// is used as `id` then. Outputs that are declared later are included too.
+    ///
+    /// ```python
+    /// node.subscribe("camera_*")
+    /// for event in node:
+    ///     if event["type"] == "INPUT" and event["id"].startswith("camera_"):
+    ///         ...
+    /// ```
+    ///
+    /// To receive all outputs of the nodes of a namespace, e.g. of
+    /// `perception/camera` and `perception/lidar`, subscribe to the namespace
+    /// prefix, see `namespace`:
+    ///
+    /// ```python
+    /// node.subscribe("perception/*")
+    /// ```
+    ///
+    /// The outputs of this node and outputs of nodes on other machines are not
+    /// matched. Subscribing fails once all inputs of the node are closed.
+    ///
+    /// :type pattern: str
+    /// :rtype: None
+    pub fn subscribe(&mut self, pattern: &str) -> eyre::Result<()> {
+        self.check_process()?;
+        self.node.get_mut().subscribe(pattern)?;
+        self.events.subscribed_patterns = true;
+        Ok(())
+    }
+
+    /// Returns the outputs that were recorded since the last call, for nodes created
+    /// with `dry_run=True`.
+    ///
+    /// Each output is a dict with the output `id`, the sent `value` as
+    /// `pyarrow.Array`, and its `metadata` as `dora.Metadata`, in send order.
+    ///
+    /// :rtype: list[dict]
+    pub fn dry_run_outputs(&self, py: Python) -> eyre::Result<Py<PyList>> {
+        let transport = self
+            .dry_run
+            .as_ref()
+            .context("`dry_run_outputs` requires a node created with `dry_run=True`")?;
+        let outputs = PyList::empty_bound(py);
+        for output in transport.take_sent_outputs() {
+            let dict = PyDict::new_bound(py);
+            dict.set_item("id", output.output_id.to_string())?;
+            dict.set_item("value", output.data.0.to_data().to_pyarrow(py)?)?;
+            dict.set_item("metadata", Py::new(py, PyMetadata(output.metadata))?)?;
+            outputs.append(dict)?;
+        }
+        Ok(outputs.unbind())
+    }
+
+    /// Returns the total payload size in bytes of the inputs that were received,
+    /// but not yet returned by `next()`.
+    ///
+    /// Inputs are not buffered anymore once external events were merged through
+    /// `merge_external_events`, so `0` is returned in that case.
+    ///
+    /// :rtype: int
+    pub fn buffered_bytes(&mut self) -> usize {
+        self.events.buffered_bytes()
+    }
+
+    /// Returns the sizes of the internal buffers of the node, to check that none
+    /// of them grows unexpectedly:
+    ///
+    /// - `input_queues`: number of received inputs that were not returned by
+    ///   `next()` yet, per input,
+    /// - `buffered_bytes`: total payload size of these inputs,
+    /// - `unacked_inputs`: reliably sent inputs that were not acknowledged yet,
+    ///   see `ack`,
+    /// - `scheduled_outputs`: outputs that were sent with `at` and are not due yet,
+    /// - `pending_drop_tokens`: shared memory outputs that are still in use by
+    ///   receivers,
+    /// - `shared_memory_cache`: shared memory regions kept for reuse by later
+    ///   outputs.
+    ///
+    /// ```python
+    /// info = node.buffer_info()
+    /// assert info["unacked_inputs"] < 100
+    /// ```
+    ///
+    /// :rtype: dict
+    pub fn buffer_info(&mut self, py: Python) -> eyre::Result<Py<PyDict>> {
+        self.check_process()?;
+        let queues: HashMap<String, usize> = self
+            .events
+            .queue_depths()
+            .into_iter()
+            .map(|(id, depth)| (id.to_string(), depth))
+            .collect();
+        let node = self.node.get_mut();
+        let info = PyDict::new_bound(py);
+        info.set_item("input_queues", queues)?;
+        info.set_item("buffered_bytes", self.events.buffered_bytes())?;
+        info.set_item("unacked_inputs", self.events.unacked_count())?;
+        info.set_item("scheduled_outputs", node.scheduled_output_count())?;
+        info.set_item("pending_drop_tokens", node.pending_drop_token_count())?;
+        info.set_item("shared_memory_cache", node.shared_memory_cache_len())?;
+        Ok(info.unbind())
+    }
+
+    /// Returns the IDs of the inputs that have at least one received event that
+    /// was not returned by `next()` yet.
+    ///
+    /// The events are not consumed, so a node can use this to decide how to
+    /// handle its backlog, e.g. to skip expensive work while a newer input is
+    /// already waiting:
+    ///
+    /// ```python
+    /// if "image" in node.ready_inputs():
+    ///     continue  # a newer image is queued already
+    /// ```
+    ///
+    /// Inputs are not buffered anymore once external events were merged through
+    /// `merge_external_events`, so an empty set is returned in that case.
+    ///
+    /// :rtype: set[str]
+    pub fn ready_inputs(&mut self) -> eyre::Result<BTreeSet<String>> {
+        self.check_process()?;
+        Ok(self
+            .events
+            .queue_depths()
+            .into_iter()
+            .filter(|(_, depth)| *depth > 0)
+            .map(|(id, _)| id.to_string())
+            .collect())
+    }
+
+    /// Empties the internal buffers of the node, e.g. between tests or to recover
+    /// from a backlog, and returns the number of discarded items per buffer.
+    ///
+    /// Queued inputs are dropped, unacknowledged inputs are forgotten without
+    /// acknowledging them, so that their senders send them again, and scheduled
+    /// outputs are discarded. Other events that are still queued, e.g.
+    /// `INPUT_CLOSED` or `STOP`, are kept. Shared memory outputs that are still in
+    /// use by receivers cannot be cleared.
+    ///
+    /// :rtype: dict
+    pub fn clear_buffers(&mut self) -> eyre::Result<HashMap<&'static str, usize>> {
+        self.check_process()?;
+        let mut node = self.node.get_mut();
+        let mut cleared = HashMap::new();
+        cleared.insert("input_queues", self.events.clear_queues());
+        cleared.insert("unacked_inputs", self.events.clear_unacked());
+        cleared.insert("scheduled_outputs", node.clear_scheduled_outputs());
+        cleared.insert("shared_memory_cache", node.shared_memory_cache_len());
+        node.clear_shared_memory_cache();
+        Ok(cleared)
+    }
+
+    /// Acknowledges a reliably sent input event after it was processed.
+    ///
+    /// Only needed if the node was created with `manual_acks=True`, otherwise
+    /// inputs are acknowledged as soon as they are returned by `next()`. The
+    /// sender of a reliable output blocks until all receivers acknowledged it, so
+    /// acknowledging after processing limits how far the sender can run ahead:
+    ///
+    /// ```python
+    /// node = Node(manual_acks=True)
+    /// for event in node:
+    ///     if event["type"] == "INPUT":
+    ///         process(event["value"])
+    ///         node.ack(event)
+    /// ```
+    ///
+    /// Inputs that are not acknowledged within the `reliable_timeout` of the
+    /// sender are sent again every second, so the same input can be received
+    /// again before it is acknowledged. Acknowledging an input also acknowledges
+    /// these copies. Events that were not sent reliably are ignored.
+    ///
+    /// :type event: dict
+    /// :rtype: None
+    pub fn ack(&mut self, event: Bound<'_, PyAny>) -> eyre::Result<()> {
+        self.check_process()?;
+        let event = PyEvent::from_object(&event)
+            .ok_or_else(|| eyre::eyre!("`event` must be an event returned by `next()`"))?;
+        let event = event.borrow();
+        match &event.event {
+            MergedEvent::Dora(Event::Input { id, metadata, .. }) => self
+                .events
+                .ack(id, metadata)
+                .map_err(|err| dora_error(err, Operation::Send)),
+            _ => eyre::bail!("only input events can be acknowledged"),
+        }
+    }
+
+    /// Sends the value and metadata of an input event on an output of this node.
+    ///
+    /// The output defaults to the ID of the input. Combined with `Node.connect`, this
+    /// forwards messages between dataflows:
+    ///
+    /// ```python
+    /// for event in source:
+    ///     if event["type"] == "INPUT" and event["id"] == "pose":
+    ///         sink.forward(event, "remote_pose")
+    /// ```
+    ///
+    /// The value is sent as received, without invoking a decoder. The metadata
+    /// parameters are kept, except for the ones that describe the delivery of the
+    /// input, e.g. `dropped_count`.
+    ///
+    /// :type event: dict
+    /// :type output_id: str, optional
+    /// :rtype: None
+    #[pyo3(signature = (event, output_id=None))]
+    pub fn forward(
+        &mut self,
+        event: Bound<'_, PyAny>,
+        output_id: Option<String>,
+        py: Python,
+    ) -> eyre::Result<()> {
+        let event = PyEvent::from_object(&event)
+            .ok_or_else(|| eyre::eyre!("`event` must be an event returned by `next()`"))?;
+        let event = event.borrow();
+        let MergedEvent::Dora(Event::Input { id, metadata, data }) = &event.event else {
+            eyre::bail!("only input events can be forwarded");
+        };
+        let output_id = output_id.unwrap_or_else(|| id.to_string());
+        let mut parameters = metadata.parameters.clone();
+        for key in [DROPPED_COUNT, RELIABLE, CONFLATED, SEQUENCE_NUMBER] {
+            parameters.remove(key);
+        }
+        self.send_parameters_with(
+            output_id,
+            OutputData::Arrow(data.0.clone()),
+            parameters,
+            None,
+            SendOptions::default(),
+            py,
+        )
+    }
+
+    /// Acknowledges all reliably sent inputs that were received, but not
+    /// acknowledged yet, see `ack`.
+    ///
+    /// The acknowledgements are sent to the daemon in a single batch, which is
+    /// cheaper than calling `ack` for each input, e.g. after processing a batch
+    /// of inputs at once.
+    ///
+    /// :rtype: None
+    pub fn ack_all(&mut self) -> eyre::Result<()> {
+        self.check_process()?;
+        self.events
+            .ack_all()
+            .map_err(|err| dora_error(err, Operation::Send))
+    }
+
+    /// Adds a timer that emits an `INPUT` event with the given ID and an empty
+    /// value periodically, like the `dora/timer` inputs of the dataflow, but
+    /// without declaring an input.
+    ///
+    /// The interval is given either as `period` in seconds or as `frequency` in
+    /// Hz. The ticks are emitted by `next()`, so they are only delivered while
+    /// the node waits for events.
+    ///
+    /// By default, each tick is scheduled one period after the previous one was
+    /// delivered, so delays add up and the timer slowly falls behind. With
+    /// `drift_correct=True`, the ticks are scheduled at multiples of the period
+    /// after the start of the timer instead. Ticks that are missed because the
+    /// node did not call `next()` in time are skipped, not delivered in a burst:
+    ///
+    /// ```python
+    /// node.add_timer("tick", frequency=100, drift_correct=True)
+    /// for event in node:
+    ///     if event["type"] == "INPUT" and event["id"] == "tick":
+    ///         control_step()
+    /// ```
+    ///
+    /// The timing quality can be checked through `timer_stats`. To drive the
+    /// timers by the time of a simulator instead of the wall clock, see
+    /// `use_sim_time`.
+    ///
+    /// :type timer_id: str
+    /// :type period: float, optional
+    /// :type frequency: float, optional
+    /// :type drift_correct: bool, optional
+    /// :rtype: None
+    #[pyo3(signature = (timer_id, period=None, frequency=None, drift_correct=false))]
+    pub fn add_timer(
+        &mut self,
+        timer_id: String,
+        period: Option<f64>,
+        frequency: Option<f64>,
+        drift_correct: bool,
+    ) -> eyre::Result<()> {
+        self.check_process()?;
+        let period = match (period, frequency) {
+            (Some(period), None) => period,
+            (None, Some(frequency)) => 1.0 / frequency,
+            _ => eyre::bail!("exactly one of `period` and `frequency` must be given"),
+        };
+        let period = Duration::try_from_secs_f64(period).context("invalid timer period")?;
+        let timer_id = DataId::from(timer_id);
+        if self
+            .node
+            .get_mut()
+            .node_config()
+            .inputs
+            .contains_key(&timer_id)
+        {
+            eyre::bail!("timer `{timer_id}` has the same ID as an input of the node");
+        }
+        self.timers.add(timer_id, period, drift_correct)
+    }
+
+    /// Removes a timer that was added through `add_timer`.
+    ///
+    /// Returns `False` if no timer with the given ID exists.
+    ///
+    /// :type timer_id: str
+    /// :rtype: bool
+    pub fn remove_timer(&mut self, timer_id: String) -> bool {
+        self.timers.remove(&DataId::from(timer_id))
+    }
+
+    /// Returns timing statistics of the timers that were added through
+    /// `add_timer`, per timer ID.
+    ///
+    /// Each timer is described by a dict containing its `period`, the number of
+    /// delivered `ticks` and of `missed` ticks, and the `mean_jitter` and
+    /// `max_jitter` of the ticks, i.e. the delay between the scheduled time of a
+    /// tick and its delivery by `next()`. All durations are in seconds:
+    ///
+    /// ```python
+    /// stats = node.timer_stats()["tick"]
+    /// print(f"jitter: {stats['mean_jitter'] * 1e6:.0f}us, missed: {stats['missed']}")
+    /// ```
+    ///
+    /// :rtype: dict
+    pub fn timer_stats(&self, py: Python) -> PyResult<HashMap<String, Py<PyDict>>> {
+        self.timers
+            .stats()
+            .map(|(id, period, stats)| {
+                let info = PyDict::new_bound(py);
+                info.set_item("period", period.as_secs_f64())?;
+                info.set_item("ticks", stats.ticks)?;
+                info.set_item("missed", stats.missed)?;
+                info.set_item("mean_jitter", stats.mean_jitter().as_secs_f64())?;
+                info.set_item("max_jitter", stats.max_jitter.as_secs_f64())?;
+                Ok((id.to_string(), info.unbind()))
+            })
+            .collect()
+    }
+
+    /// Drives the timers of the node by the simulation time that is published on
+    /// the given input, instead of the wall clock.
+    ///
+    /// By default, nodes run on wall time: the ticks of `add_timer` follow the
+    /// monotonic clock of the machine and `sim_time()` returns the current time. For
+    /// deterministic simulations and replays, the simulator publishes its time on an
+    /// output instead, as a single `int64` or `uint64` in nanoseconds or a `float64`
+    /// in seconds since the UNIX epoch. Nodes opt into this time by calling
+    /// `use_sim_time` with the ID of the input that receives it, before adding any
+    /// timer:
+    ///
+    /// ```python
+    /// node.use_sim_time("clock")
+    /// node.add_timer("control", frequency=100)
+    /// for event in node:
+    ///     if event["type"] == "INPUT" and event["id"] == "control":
+    ///         step(node.sim_time())
+    /// ```
+    ///
+    /// Ticks then only become due when the simulation time advances, independent of
+    /// how fast the simulator runs. If the time advances by several periods at once,
+    /// every tick is delivered, in order, after the event of the clock input. Ticks
+    /// carry their simulation time as `timestamp_ns` metadata, and outputs sent
+    /// through `send_output` carry the current simulation time as `timestamp_ns`
+    /// unless it is set explicitly. Clock values that are invalid or go backwards are
+    /// ignored with a `RuntimeWarning`, the event of the clock input is still returned
+    /// by `next()`.
+    ///
+    /// :type input_id: str
+    /// :rtype: None
+    pub fn use_sim_time(&mut self, input_id: String) -> eyre::Result<()> {
+        self.timers.use_sim_time(DataId::from(input_id))
+    }
+
+    /// Returns the current time of the node in seconds since the UNIX epoch.
+    ///
+    /// With `use_sim_time`, this is the latest time that was published on the clock
+    /// input, or `None` if no time was published yet. Other nodes get the wall clock
+    /// time, so the same code runs in simulations and on the real system.
+    ///
+    /// :rtype: float
+    pub fn sim_time(&self) -> Option<f64> {
+        if self.timers.is_sim_time() {
+            return self.timers.sim_time().map(|time| time.as_secs_f64());
+        }
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default();
+        Some(now.as_secs_f64())
+    }
+
+    /// Returns the bandwidth used by each input and output of this node, in bytes
+    /// per second.
+    ///
+    /// The result has the form `{"inputs": {id: rate}, "outputs": {id: rate}}`. The
+    /// rates are averaged over the last 5 seconds and only count the payload of
+    /// the messages. This helps to find the edges that saturate a network link:
+    ///
+    /// ```python
+    /// stats = node.bandwidth_stats()
+    /// for output_id, rate in stats["outputs"].items():
+    ///     print(f"{output_id}: {rate / 1e6:.1f} MB/s")
+    /// ```
+    ///
+    /// Inputs and outputs without any messages so far are not included.
+    ///
+    /// :rtype: dict
+    pub fn bandwidth_stats(&mut self) -> eyre::Result<HashMap<&'static str, HashMap<String, f64>>> {
+        self.check_process()?;
+        let to_strings = |rates: BTreeMap<DataId, f64>| -> HashMap<String, f64> {
+            rates
+                .into_iter()
+                .map(|(id, rate)| (id.to_string(), rate))
+                .collect()
+        };
+        Ok(HashMap::from([
+            ("inputs", to_strings(self.events.input_bandwidth())),
+            (
+                "outputs",
+                to_strings(self.node.get_mut().output_bandwidth()),
+            ),
+        ]))
+    }
+
+    /// Returns the counters of this node in the OpenMetrics text format, which
+    /// can be scraped by Prometheus.
+    ///
+    /// The text contains the total number of messages and payload bytes that were
+    /// received on each input and sent on each output, the number of messages
+    /// that were dropped because an input queue was full, and the sum of the
+    /// latencies of the received messages. Samples are labeled with the
+    /// `dataflow`, the `node`, and the `input` or `output` ID:
+    ///
+    /// ```text
+    /// dora_node_input_messages_total{dataflow="...",node="plot",input="image"} 1200
+    /// dora_node_input_latency_seconds_sum{dataflow="...",node="plot",input="image"} 3.6
+    /// dora_node_input_latency_seconds_count{dataflow="...",node="plot",input="image"} 1200
+    /// ```
+    ///
+    /// Serve it on an HTTP endpoint to monitor the node, e.g. through
+    /// `prometheus_client` or `http.server`:
+    ///
+    /// ```python
+    /// class MetricsHandler(http.server.BaseHTTPRequestHandler):
+    ///     def do_GET(self):
+    ///         body = node.metrics_text().encode()
+    ///         self.send_response(200)
+    ///         self.send_header("Content-Type", "application/openmetrics-text; version=1.0.0")
+    ///         self.end_headers()
+    ///         self.wfile.write(body)
+    /// ```
+    ///
+    /// Inputs and outputs without any messages so far are not included.
+    ///
+    /// :rtype: str
+    pub fn metrics_text(&mut self) -> eyre::Result<String> {
+        self.check_process()?;
+        let inputs = self.events.input_counters();
+        let node = self.node.get_mut();
+        Ok(openmetrics_text(
+            node.dataflow_id(),
+            node.id(),
+            &inputs,
+            node.output_counters(),
+        ))
+    }
+
+    /// Returns the number of queued events for every edge of the dataflow.
+    ///
+    /// The result maps `(source, target)` pairs, e.g. `("camera/image", "plot/image")`,
+    /// to the number of events that the target node received on the input, but
+    /// did not process yet. This helps to find the bottleneck of a dataflow:
+    ///
+    /// ```python
+    /// depths = node.pipeline_depths()
+    /// (source, target), depth = max(depths.items(), key=lambda edge: edge[1])
+    /// print(f"bottleneck: {target} with {depth} queued events from {source}")
+    /// ```
+    ///
+    /// The depths are reported periodically by the receiving nodes, so they can be
+    /// slightly outdated. Only edges to nodes on the same machine are included.
+    ///
+    /// :rtype: dict[tuple[str, str], int]
+    pub fn pipeline_depths(&mut self) -> eyre::Result<HashMap<(String, String), usize>> {
+        self.check_process()?;
+        let depths = self.node.get_mut().pipeline_depths()?;
+        Ok(depths
+            .into_iter()
+            .map(|edge| {
+                let (source_node, output) = edge.source;
+                let (target_node, input) = edge.target;
+                (
+                    (
+                        format!("{source_node}/{output}"),
+                        format!("{target_node}/{input}"),
+                    ),
+                    edge.depth,
+                )
+            })
+            .collect())
+    }
+
+    /// Returns the estimated offset of the wall clock of another node of the
+    /// dataflow relative to the clock of this node, and the uncertainty of the
+    /// estimate, both in seconds.
+    ///
+    /// The offset is positive if the clock of the other node is ahead. Subtract it
+    /// from the timestamps of the other node to compare them with local timestamps:
+    ///
+    /// ```python
+    /// offset, uncertainty = node.clock_skew("lidar")
+    /// latency = time.time() - (event["metadata"]["timestamp_ns"] / 1e9 - offset)
+    /// ```
+    ///
+    /// Nodes on the same machine have an offset of zero. For other machines, the
+    /// offset is estimated by the dora-coordinator, which exchanges timestamps with
+    /// every daemon along with its heartbeats, as in NTP. The uncertainty is half
+    /// the round-trip delay of these exchanges. An error is raised if the clocks
+    /// were not synchronized yet, e.g. in the first seconds after the start.
+    ///
+    /// :type node_id: str
+    /// :rtype: tuple[float, float]
+    pub fn clock_skew(&mut self, node_id: String) -> eyre::Result<(f64, f64)> {
+        self.check_process()?;
+        let skew = self.node.get_mut().clock_skew(NodeId::from(node_id))?;
+        Ok((
+            skew.offset_ns as f64 / 1e9,
+            skew.uncertainty_ns as f64 / 1e9,
+        ))
+    }
+
+    /// Subscribes to notifications about nodes joining or leaving the dataflow.
+    ///
+    /// The returned iterator yields `{"type": "NODE_JOINED", "node": id}` and
+    /// `{"type": "NODE_LEFT", "node": id}` dicts, separately from the inputs that
+    /// are returned by `next()`. It starts with a `NODE_JOINED` event for every node
+    /// that joined already, including this node. This allows building a live roster
+    /// of the dataflow without polling:
+    ///
+    /// ```python
+    /// lifecycle = node.lifecycle_events()
+    /// while (event := lifecycle.recv(timeout=0.1)) is not None:
+    ///     print(event["type"], event["node"])
+    /// ```
+    ///
+    /// Progress reports of other nodes, see `send_progress`, are yielded as
+    /// `{"type": "PROGRESS", "node": id, "task": str, "progress": float, "message": str | None}`
+    /// dicts.
+    ///
+    /// Iterating over the returned object blocks until the next event arrives, so
+    /// it's usually done in a separate thread.
+    ///
+    /// :rtype: dora.LifecycleEvents
+    pub fn lifecycle_events(&mut self) -> eyre::Result<LifecycleEvents> {
+        self.check_process()?;
+        if !self.lifecycle_subscribed {
+            self.node.get_mut().subscribe_lifecycle_events()?;
+            self.lifecycle_subscribed = true;
+        }
+        Ok(LifecycleEvents::new(self.lifecycle_events.clone()))
+    }
+
+    /// Reports the progress of a long-running task, e.g. a batch job, as a
+    /// fraction between `0.0` and `1.0`.
+    ///
+    /// The report is not sent as output, but delivered as `PROGRESS` event to the
+    /// nodes that subscribed through `lifecycle_events`, e.g. a monitoring node
+    /// that shows a progress bar per node and task:
+    ///
+    /// ```python
+    /// for i, chunk in enumerate(chunks):
+    ///     encode(chunk)
+    ///     node.send_progress("job1", (i + 1) / len(chunks), message="encoding")
+    /// ```
+    ///
+    /// :type task: str
+    /// :type progress: float
+    /// :type message: str, optional
+    /// :rtype: None
+    #[pyo3(signature = (task, progress, message=None))]
+    pub fn send_progress(
+        &mut self,
+        task: String,
+        progress: f32,
+        message: Option<String>,
+    ) -> eyre::Result<()> {
+        self.check_process()?;
+        self.node
+            .get_mut()
+            .send_progress(task, progress, message)
+            .map_err(|err| dora_error(err, Operation::Send))
+    }
+
+    /// Acknowledges that the node finished its shutdown after receiving a `STOP`
+    /// event, e.g. after flushing its buffered data.
+    ///
+    /// If the dataflow declares a `shutdown_order`, the daemon sends the `STOP`
+    /// event to the next node of the order only after this acknowledgement, or
+    /// after the node exited. This allows stopping sinks before sources, so that
+    /// no data is lost. The grace duration of the stop is split evenly between the
+    /// ordered nodes and the remaining nodes, so the acknowledgement must arrive
+    /// within the share of the node:
+    ///
+    /// ```python
+    /// for event in node:
+    ///     if event["type"] == "STOP":
+    ///         recorder.flush()
+    ///         node.ack_stop()
+    ///         break
+    /// ```
+    ///
+    /// Without a `shutdown_order`, the acknowledgement has no effect.
+    ///
+    /// :rtype: None
+    pub fn ack_stop(&mut self) -> eyre::Result<()> {
+        self.check_process()?;
+        self.node
+            .get_mut()
+            .ack_stop()
+            .map_err(|err| dora_error(err, Operation::Send))
+    }
+
+    /// Forks the event stream of this node.
+    ///
+    /// The returned iterator receives a copy of every event that is returned by
+    /// `next()`, which allows running two independent consumers over the same
+    /// inputs within one node, e.g. for logging and processing:
+    ///
+    /// ```python
+    /// tee = node.tee()
+    /// threading.Thread(target=lambda: [log(event) for event in tee], daemon=True).start()
+    ///
+    /// for event in node:
+    ///     process(event)
+    /// ```
+    ///
+    /// The copies share the data buffer with the original events, so large payloads
+    /// are not duplicated. However, the buffer of an input is only freed once all
+    /// copies are released, and copies that are not consumed from the tee are kept
+    /// in memory indefinitely.
+    ///
+    /// :rtype: dora.EventTee
+    pub fn tee(&mut self) -> EventTee {
+        let receiver = self.events.tee();
+        EventTee::new(
+            receiver,
+            self.events.cleanup_handle.clone(),
+            self.event_class,
+        )
+    }
+
+    /// Registers a decoder for the values of the given input.
+    ///
+    /// The decoder is called with the `pyarrow.Array` of every event of the input
+    /// that is returned by `next()`. Its result replaces the value of the event, so
+    /// consumers read the decoded object from `event["value"]`:
+    ///
+    /// ```python
+    /// node.set_decoder("pose", lambda value: Pose.FromString(value.to_numpy().tobytes()))
+    ///
+    /// for event in node:
+    ///     if event["type"] == "INPUT" and event["id"] == "pose":
+    ///         print(event["value"].position)
+    /// ```
+    ///
+    /// The decoder runs on the thread that calls `next()`, with the GIL held.
+    /// Exceptions raised by the decoder are handled according to the
+    /// `on_decode_error` policy of the node, see `Node`. Events that are
+    /// received through a `tee` are not decoded. Pass `None` to remove the decoder.
+    ///
+    /// :type input_id: str
+    /// :type decoder: typing.Callable[[pyarrow.Array], typing.Any], optional
+    /// :rtype: None
+    #[pyo3(signature = (input_id, decoder))]
+    pub fn set_decoder(
+        &mut self,
+        input_id: String,
+        decoder: Option<Bound<'_, PyAny>>,
+    ) -> eyre::Result<()> {
+        let input_id = DataId::from(input_id);
+        match decoder {
+            Some(decoder) => {
+                if !decoder.is_callable() {
+                    eyre::bail!("decoder for input `{input_id}` must be callable");
+                }
+                self.decoders.insert(input_id, decoder.unbind());
+            }
+            None => {
+                self.decoders.remove(&input_id);
+            }
+        }
+        Ok(())
+    }
+
+    /// Pins the node to the given CPU cores.
+    ///
+    /// Applies to the thread that calls this method, which should be the thread
+    /// that processes the events, and to the background threads that dora uses
+    /// to communicate with the daemon. This keeps latency-critical nodes off the
+    /// cores that are used by heavy processing, e.g. perception:
+    ///
+    /// ```python
+    /// node.set_affinity([2, 3])
+    /// ```
+    ///
+    /// Only supported on Linux, a `RuntimeError` is raised on other platforms.
+    ///
+    /// :type cores: list[int]
+    /// :rtype: None
+    pub fn set_affinity(&mut self, cores: Vec<usize>) -> eyre::Result<()> {
+        self.check_process()?;
+        self.node.get_mut().set_affinity(&cores)?;
+        self.events.set_affinity(&cores)
+    }
+
+    /// Enables or disables conflation for the given output.
+    ///
+    /// If a receiver falls behind, messages of a conflated output replace the
+    /// messages of the output that are still buffered for it, so that it only gets
+    /// the most recent value instead of a growing backlog. Unlike a rate limit, this
+    /// doesn't delay or drop messages for receivers that keep up:
+    ///
+    /// ```python
+    /// node.set_conflation("pose", True)
+    /// ```
+    ///
+    /// The replaced messages are counted in the `dropped_count` metadata of the next
+    /// delivered message of the input.
+    ///
+    /// :type output_id: str
+    /// :type enabled: bool, optional
+    /// :rtype: None
+    #[pyo3(signature = (output_id, enabled=true))]
+    pub fn set_conflation(&mut self, output_id: String, enabled: bool) -> eyre::Result<()> {
+        self.check_process()?;
+        self.node
+            .get_mut()
+            .set_conflation(DataId::from(output_id), enabled)
+    }
+
+    /// Finishes the current recording file and continues the recording in a new
+    /// file, see `record`. Returns the path of the new file.
+    ///
+    /// Without a `path`, the new file is named after the `record` path with a
+    /// sequential number, e.g. `events_001.arrow` for `events.arrow`. Each file
+    /// is a complete recording that can be replayed on its own. Inputs are
+    /// recorded by `next()`, so no input is lost or split between files:
+    ///
+    /// ```python
+    /// node = Node(record="events.arrow")
+    /// ...
+    /// node.rotate_recording("events_002.arrow")
+    /// ```
+    ///
+    /// :type path: str, optional
+    /// :rtype: str
+    #[pyo3(signature = (path=None))]
+    pub fn rotate_recording(&mut self, path: Option<PathBuf>) -> eyre::Result<String> {
+        self.check_process()?;
+        let recorder = self
+            .recorder
+            .as_mut()
+            .context("node has no recording, create it with `record`")?;
+        let path = recorder.rotate(path.as_deref())?;
+        Ok(path.display().to_string())
+    }
+
+    /// Rotates the recording file automatically once it reaches `max_bytes` or
+    /// after `max_seconds`, see `rotate_recording`.
+    ///
+    /// The limits are checked after each recorded input, so files can exceed
+    /// `max_bytes` by the size of one input. Pass no limits to disable the
+    /// automatic rotation again:
+    ///
+    /// ```python
+    /// node = Node(record="events.arrow", compression="zstd")
+    /// node.set_recording_rotation(max_bytes=1 << 30, max_seconds=3600)
+    /// ```
+    ///
+    /// :type max_bytes: int, optional
+    /// :type max_seconds: float, optional
+    /// :rtype: None
+    #[pyo3(signature = (max_bytes=None, max_seconds=None))]
+    pub fn set_recording_rotation(
+        &mut self,
+        max_bytes: Option<u64>,
+        max_seconds: Option<f64>,
+    ) -> eyre::Result<()> {
+        self.check_process()?;
+        let max_duration = max_seconds
+            .map(Duration::try_from_secs_f64)
+            .transpose()
+            .context("invalid `max_seconds`")?;
+        let recorder = self
+            .recorder
+            .as_mut()
+            .context("node has no recording, create it with `record`")?;
+        recorder.set_rotation(RotationPolicy {
+            max_bytes,
+            max_duration,
+        });
+        Ok(())
+    }
+
+    /// Registers a callback that is invoked once when all inputs of the node are
+    /// closed, i.e. when every upstream node finished or was disconnected.
+    ///
+    /// The callback takes no arguments and is called when the last `INPUT_CLOSED`
+    /// event is received, before that event is returned. This applies to every
+    /// method that receives events, i.e. `next()`, `next_tick()`, and the readers of
+    /// `record_batch_reader`, which also propagate exceptions raised by the
+    /// callback. The received event is then available as the `event` attribute of
+    /// the exception, so it is not lost. The callback is called immediately if all
+    /// inputs are already closed. This simplifies the end-of-stream cleanup of nodes
+    /// with multiple inputs:
+    ///
+    /// ```python
+    /// node.on_all_inputs_closed(lambda: writer.close())
+    /// for event in node:
+    ///     ...
+    /// ```
+    ///
+    /// Inputs added through `subscribe` are not known in advance, so for nodes that
+    /// subscribed to a pattern, the callback is called at the end of the event
+    /// stream instead, when `next()` returns `None`.
+    ///
+    /// :type callback: typing.Callable[[], None]
+    /// :rtype: None
+    pub fn on_all_inputs_closed(&mut self, callback: Bound<'_, PyAny>) -> eyre::Result<()> {
+        if !callback.is_callable() {
+            eyre::bail!("`callback` must be callable");
+        }
+        if self.events.all_inputs_closed {
+            callback.call0()?;
+        } else {
+            self.on_all_inputs_closed = Some(callback.unbind());
+        }
+        Ok(())
+    }
+
+    /// Re-establishes the connection to the daemon in a child process created
+    /// through `fork`.
+    ///
+    /// The connection of the parent process is left untouched, it is neither used
+    /// nor closed by the child. The child connects as the same node and takes over
+    /// the subscription of the parent, so the parent must not use its node anymore
+    /// afterwards:
+    ///
+    /// ```python
+    /// node = Node()
+    /// if os.fork() == 0:
+    ///     node.reinit_after_fork()
+    ///     for event in node:
+    ///         ...
+    /// ```
+    ///
+    /// Senders created through `record_batch_output` and `declare_dynamic_output`
+    /// are bound to the old connection and need to be recreated. This method is
+    /// only supported for nodes that were started by the daemon, not for dynamic
+    /// nodes.
+    ///
+    /// The resources of the parent node are leaked in the child instead of being
+    /// cleaned up, since they are shared with the parent: Inputs are only
+    /// recorded by the parent, if `record` was passed. If the output was captured
+    /// through `capture_output`, the stdout and stderr of the child stay
+    /// redirected to the pipes of the parent, so its output is forwarded by the
+    /// parent. Call `capture_output(True)` in the child to forward it directly.
+    ///
+    /// :rtype: None
+    pub fn reinit_after_fork(&mut self) -> eyre::Result<()> {
+        if self.pid == std::process::id() {
+            eyre::bail!("`reinit_after_fork` must only be called in a forked child process");
+        }
+        let dead_letter = self
+            .node
+            .get_mut()
+            .dead_letter_output()
+            .map(|output_id| output_id.to_string());
+        let daemon_address = self.daemon_address.map(|address| address.to_string());
+        let parent = self.node.get_mut();
+        let mut child = Node::from_options(NodeOptions {
+            max_buffered_bytes: self.max_buffered_bytes,
+            strict_metadata: Some(self.strict_metadata),
+            watermarks: Some(self.watermarks),
+            dead_letter,
+            manual_acks: Some(self.manual_acks),
+            always_copy: Some(self.always_copy),
+            daemon_address,
+            emit_summary_on_close: Some(self.emit_summary_on_close),
+            event_class: Some(self.event_class),
+            fork_of: Some(&parent),
+            ..Default::default()
+        })
+        .context("failed to reconnect to the daemon after fork")?;
+        drop(parent);
+        child
+            .events
+            .set_input_schedule(self.input_schedule.clone())?;
+        child.input_schedule = self.input_schedule.clone();
+        child.delta_outputs = std::mem::take(&mut self.delta_outputs);
+        child.delta_inputs = std::mem::take(&mut self.delta_inputs);
+        child.timestamp_check = self.timestamp_check.take();
+        child.decoders = std::mem::take(&mut self.decoders);
+        child.on_decode_error = self.on_decode_error;
+        child.metadata_codec = self.metadata_codec;
+        child.on_all_inputs_closed = self.on_all_inputs_closed.take();
+        child.input_types = std::mem::take(&mut self.input_types);
+        // the parent node is not cleaned up on drop since it belongs to another process
+        drop(std::mem::replace(self, child));
+        Ok(())
+    }
+
+    /// Redirects the stdout and stderr of the node process to dora log messages.
+    ///
+    /// While enabled, every line that is written to stdout or stderr, e.g. through
+    /// `print`, is sent to the dora coordinator as a log message tagged with the ID
+    /// of this node, instead of being written to the terminal or the log file of
+    /// the node. Lines of stdout are logged with level `INFO`, lines of stderr with
+    /// level `WARN`. The messages are shown when attaching to the dataflow, e.g.
+    /// through `dora start --attach`.
+    ///
+    /// ```python
+    /// node.capture_output(True)
+    /// print("sent as log message")
+    /// node.capture_output(False)
+    /// ```
+    ///
+    /// The redirection applies to the whole process, including native libraries
+    /// and threads. Captured lines are buffered in memory, so large bursts of
+    /// output never block the writer. They are forwarded while the node waits for
+    /// events in `next()` and when it sends outputs. This is only supported on
+    /// Unix.
+    ///
+    /// :type enabled: bool
+    /// :rtype: None
+    pub fn capture_output(&mut self, enabled: bool, py: Python) -> eyre::Result<()> {
+        self.check_process()?;
+        if enabled {
+            if self.output_capture.is_none() {
+                self.output_capture = Some(OutputCapture::start(py)?);
+            }
+        } else if let Some(capture) = self.output_capture.take() {
+            for line in capture.stop(py)? {
+                self.node
+                    .get_mut()
+                    .log(line.level, Some(line.target.to_owned()), line.line)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Connects to the dynamic node `node_id` of the given dataflow.
+    ///
+    /// This is a shorthand for `Node(node_id, dataflow=dataflow, daemon_address=daemon_address)`,
+    /// which ignores the `DORA_NODE_CONFIG` of the process. Call it once per dataflow
+    /// to be a member of several dataflows at once, e.g. to bridge them:
+    ///
+    /// ```python
+    /// source = Node.connect("2d4ce4e5-...", "bridge")
+    /// sink = Node.connect("9b1a27f0-...", "bridge", daemon_address="10.0.0.2:53291")
+    /// ```
+    ///
+    /// :type dataflow: str
+    /// :type node_id: str
+    /// :type daemon_address: str, optional
+    /// :rtype: dora.Node
+    #[staticmethod]
+    #[pyo3(signature = (dataflow, node_id, daemon_address=None))]
+    pub fn connect(
+        dataflow: &str,
+        node_id: String,
+        daemon_address: Option<&str>,
+    ) -> eyre::Result<Self> {
+        Node::from_options(NodeOptions {
+            node_id: Some(node_id),
+            dataflow: Some(dataflow.to_owned()),
+            daemon_address: daemon_address.map(str::to_owned),
+            ..Default::default()
+        })
+    }
+
+    /// Replays the inputs of a recording that was created through `Node(record=...)`.
+    ///
+    /// Returns an iterator over the recorded input events, in the order in which
+    /// they were received. Compressed recordings are decompressed transparently.
+    ///
+    /// ```python
+    /// for event in Node.from_recording("events.arrow"):
+    ///     process(event["id"], event["value"])
+    /// ```
+    ///
+    /// By default, the events are yielded with their original timing, based on
+    /// the recorded timestamps. The `speed` factor scales the playback, e.g.
+    /// `speed=2.0` replays twice as fast and `speed=0.5` in slow motion. Pass
+    /// `speed=0` to yield the events as fast as possible, e.g. for regression
+    /// tests.
+    ///
+    /// Like `next()`, the events are yielded as `dict`s, unless `event_class` is
+    /// set.
+    ///
+    /// :type path: str
+    /// :type speed: float, optional
+    /// :type event_class: bool, optional
+    /// :rtype: dora.Recording
+    #[staticmethod]
+    #[pyo3(signature = (path, speed=1.0, event_class=false))]
+    pub fn from_recording(path: &str, speed: f64, event_class: bool) -> eyre::Result<Recording> {
+        Recording::open(path, speed, event_class)
+    }
+
+    /// Returns the full dataflow descriptor that this node is part of.
+    ///
+    /// This method returns the parsed dataflow YAML file.
+    ///
+    /// :rtype: dict
+    pub fn dataflow_descriptor(&mut self, py: Python) -> eyre::Result<PyObject> {
+        Ok(
+            pythonize::pythonize(py, &self.node.get_mut().dataflow_descriptor())
+                .map(|x| x.unbind())?,
+        )
+    }
+
+    /// Returns the value of the dataflow-level parameter `name`.
+    ///
+    /// Parameters are declared in the top-level `params` section of the dataflow
+    /// YAML and shared by all nodes:
+    ///
+    /// ```yaml
+    /// params:
+    ///   map_path: maps/office.yaml
+    ///   max_speed: 1.5
+    /// ```
+    ///
+    /// An environment variable named like the parameter in upper case with the
+    /// `DORA_PARAM_` prefix, e.g. `DORA_PARAM_MAP_PATH`, overrides the declared
+    /// value, for example through the `env` entries of a single node. If a
+    /// `default` is given, the value is converted to the type of the default, so
+    /// `"2"` is returned as `2.0` for a `float` default. The `default` is returned
+    /// if the parameter is not set, otherwise a `KeyError` is raised:
+    ///
+    /// ```python
+    /// map_path = node.param("map_path", default="map.yaml")
+    /// max_speed = node.param("max_speed", default=1.0)
+    /// ```
+    ///
+    /// :type name: str
+    /// :type default: typing.Any, optional
+    /// :rtype: typing.Any
+    #[pyo3(signature = (name, default=None))]
+    pub fn param(
+        &mut self,
+        name: &str,
+        default: Option<Bound<'_, PyAny>>,
+        py: Python,
+    ) -> PyResult<PyObject> {
+        let value = self.node.get_mut().param(name);
+        match (value, default) {
+            (Some(value), default) => param_to_python(py, name, value, default.as_ref()),
+            (None, Some(default)) => Ok(default.unbind()),
+            (None, None) => Err(PyKeyError::new_err(format!(
+                "dataflow parameter `{name}` is not set"
+            ))),
+        }
+    }
+
+    /// Returns the dataflow id.
+    ///
+    /// :rtype: str
+    pub fn dataflow_id(&self) -> String {
+        self.dataflow_id.to_string()
+    }
+
+    /// Returns the ID of this node, e.g. `perception/camera`.
+    ///
+    /// :rtype: str
+    pub fn node_id(&self) -> String {
+        self.node_id.to_string()
+    }
+
+    /// Returns the namespace of this node, e.g. `perception` for the node
+    /// `perception/camera`, or `None` if the node ID has no namespace.
+    ///
+    /// Node IDs are organized into namespaces through `/`, which keeps the IDs
+    /// of the subsystems of large dataflows apart. Inputs refer to the outputs
+    /// of namespaced nodes as `<namespace>/<node>/<output>`:
+    ///
+    /// ```yaml
+    /// - id: planning/planner
+    ///   inputs:
+    ///     image: perception/camera/image
+    /// ```
+    ///
+    /// :rtype: str | None
+    pub fn namespace(&self) -> Option<&str> {
+        self.node_id.namespace()
+    }
+
+    /// Returns the time at which the dataflow was started, in seconds since the
+    /// Unix epoch.
+    ///
+    /// The start time is assigned by the dora-coordinator, so it is the same for
+    /// all nodes, regardless of when they joined. It has millisecond precision.
+    /// Returns `None` if the start time is unknown, e.g. in dry-run mode.
+    ///
+    /// :rtype: float
+    pub fn dataflow_start_time(&mut self) -> Option<f64> {
+        let start = self.node.get_mut().dataflow_start_time()?;
+        let since_epoch = start.duration_since(UNIX_EPOCH).ok()?;
+        Some(since_epoch.as_secs_f64())
+    }
+
+    /// Returns how long the dataflow has been running, in seconds.
+    ///
+    /// See `dataflow_start_time`. The uptime is measured with the clock of this
+    /// machine, so it is affected by clock differences to the machine of the
+    /// dora-coordinator:
+    ///
+    /// ```python
+    /// node.send_output("uptime", pa.array([node.dataflow_uptime()]))
+    /// ```
+    ///
+    /// :rtype: float
+    pub fn dataflow_uptime(&mut self) -> Option<f64> {
+        let uptime = self.node.get_mut().dataflow_uptime()?;
+        Some(uptime.as_secs_f64())
+    }
+
+    /// Returns the value stored for `key` in the persistent state of this node,
+    /// or `None` if no value was stored yet.
+    ///
+    /// The state is stored under the working directory, keyed by the dataflow id
+    /// and the node id, so it survives restarts of the node:
+    ///
+    /// ```python
+    /// offset = node.get_state("calibration_offset")
+    /// if offset is None:
+    ///     offset = calibrate()
+    ///     node.set_state("calibration_offset", offset)
+    /// ```
+    ///
+    /// The state is not locked across processes. If multiple instances of the
+    /// same node run at the same time, the last `set_state` call wins.
+    ///
+    /// :type key: str
+    /// :rtype: typing.Any
+    pub fn get_state(&mut self, key: &str, py: Python) -> eyre::Result<PyObject> {
+        self.check_process()?;
+        match self.node.get_mut().get_state(key)? {
+            Some(value) => Ok(pythonize::pythonize(py, &value).map(|x| x.unbind())?),
+            None => Ok(py.None()),
+        }
+    }
+
+    /// Stores `value` for `key` in the persistent state of this node.
+    ///
+    /// The value must be a JSON-serializable scalar (`bool`, `int`, `float` or
+    /// `str`) or a `pyarrow.Scalar`. Passing `None` removes `key` from the state.
+    ///
+    /// :type key: str
+    /// :type value: typing.Any
+    /// :rtype: None
+    pub fn set_state(&mut self, key: &str, value: Bound<'_, PyAny>) -> eyre::Result<()> {
+        self.check_process()?;
+        let value = if value.is_none() {
+            None
+        } else if value.hasattr("as_py")? {
+            // pyarrow scalar
+            Some(pythonize::depythonize(&value.call_method0("as_py")?)?)
+        } else {
+            Some(pythonize::depythonize(&value)?)
+        };
+        self.node.get_mut().set_state(key, value)
+    }
+
+    /// Merge an external event stream with dora main loop.
+    /// Supported sources are ROS2 subscriptions and streams created through
+    /// `dora.external_stream_from_async`.
+    ///
+    /// Events of the external stream are tagged with the given `name` as `source`.
+    ///
+    /// If the external stream errors, the behavior depends on `on_error`:
+    ///
+    /// - `"event"` (default): emit an `{"type": "ERROR", "source": name, "error": ...}`
+    ///   event and keep receiving from the external stream.
+    /// - `"ignore"`: silently skip the erroneous message.
+    ///
+    /// If `idle_timeout` (in seconds) is set, a `{"type": "WARNING", "source": name}` event
+    /// is emitted each time the external stream produces no value for this duration.
+    ///
+    /// ```python
+    /// node.merge_external_events(subscription, name="turtle_pose", idle_timeout=5.0)
+    /// ```
+    ///
+    /// :type subscription: dora.Ros2Subscription | dora.ExternalEventStream
+    /// :type name: str, optional
+    /// :type on_error: str, optional
+    /// :type idle_timeout: float, optional
+    /// :rtype: None
+    #[pyo3(signature = (subscription, name=None, on_error=None, idle_timeout=None))]
+    pub fn merge_external_events(
+        &mut self,
+        subscription: Bound<'_, PyAny>,
+        name: Option<String>,
+        on_error: Option<&str>,
+        idle_timeout: Option<f32>,
+    ) -> eyre::Result<()> {
+        self.check_process()?;
+        type Items = Pin<Box<dyn Stream<Item = eyre::Result<PyObject>> + Send>>;
+        let (items, default_name): (Items, _) = if let Ok(mut ros2) =
+            subscription.extract::<PyRefMut<Ros2Subscription>>()
+        {
+            let stream = ros2.into_stream()?.map(|item| {
+                let (value, _info) = item.context("failed to read ROS2 message")?;
+                Python::with_gil(|py| value.to_pyarrow(py))
+                    .context("failed to convert value to pyarrow")
+            });
+            (Box::pin(stream), "ros2")
+        } else if let Ok(mut external) = subscription.extract::<PyRefMut<ExternalEventStream>>() {
+            (Box::pin(external.take_stream()?), "external")
+        } else {
+            eyre::bail!("invalid `subscription` type, must be a ROS2 or external stream")
+        };
+        let source = name.unwrap_or_else(|| default_name.to_owned());
+        let on_error = match on_error.unwrap_or("event") {
+            "event" => ErrorPolicy::Event,
+            "ignore" => ErrorPolicy::Ignore,
+            other => {
+                eyre::bail!("invalid `on_error` policy `{other}`, expected `event` or `ignore`")
+            }
+        };
+        let stream_source = source.clone();
+        let stream = items.filter_map(move |item| {
+            let event = match item {
+                Ok(value) => ExternalEvent::Value {
+                    source: stream_source.clone(),
+                    value,
+                },
+                Err(err) => ExternalEvent::Error {
+                    source: stream_source.clone(),
+                    error: format!("{err:?}"),
+                },
+            };
+            let event = match (event, on_error) {
+                (ExternalEvent::Error { .. }, ErrorPolicy::Ignore) => None,
+                (event, _) => Some(event),
+            };
+            futures::future::ready(event)
+        });
+        let state = Arc::new(MergedSourceState::default());
+        let stream = track_source(Box::pin(stream), state.clone());
+        let idle_timeout = idle_timeout.map(Duration::from_secs_f32);
+        self.events.merged_sources.push(MergedSource {
+            name: source.clone(),
+            kind: default_name,
+            idle_timeout,
+            state,
+        });
+        let stream: Box<dyn Stream<Item = ExternalEvent> + Unpin + Send> = match idle_timeout {
+            Some(timeout) => Box::new(with_idle_timeout(stream, source, timeout)),
+            None => Box::new(stream),
+        };
+
+        // take out the event stream and temporarily replace it with a dummy
+        let events = std::mem::replace(
+            &mut self.events.inner,
+            EventsInner::Merged(Box::new(futures::stream::empty())),
+        );
+        // update self.events with the merged stream
+        self.events.inner = EventsInner::Merged(events.merge_external_send(stream));
+
+        Ok(())
+    }
+
+    /// Describes the external streams that were merged through
+    /// `merge_external_events`, in the order in which they were merged.
+    ///
+    /// Each stream is described by a dict containing its `name`, i.e. the `source`
+    /// of its events, its `type` (`ros2` or `external`), whether it is still
+    /// `active`, the number of `events` received from it so far, and its
+    /// `idle_timeout` in seconds. A stream is not active anymore once it ended,
+    /// e.g. because the async generator of an external stream returned:
+    ///
+    /// ```python
+    /// for source in node.merge_info():
+    ///     if not source["active"]:
+    ///         print(f"{source['name']} ended after {source['events']} events")
+    /// ```
+    ///
+    /// :rtype: list[dict]
+    pub fn merge_info(&self, py: Python) -> PyResult<Vec<Py<PyDict>>> {
+        self.events
+            .merged_sources
+            .iter()
+            .map(|source| {
+                let info = PyDict::new_bound(py);
+                info.set_item("name", &source.name)?;
+                info.set_item("type", source.kind)?;
+                info.set_item("active", !source.state.finished.load(Ordering::Relaxed))?;
+                info.set_item("events", source.state.events.load(Ordering::Relaxed))?;
+                info.set_item(
+                    "idle_timeout",
+                    source.idle_timeout.map(|timeout| timeout.as_secs_f64()),
+                )?;
+                Ok(info.unbind())
+            })
+            .collect()
+    }
+}
+
+/// An external stream that was merged through `Node.merge_external_events`.
+struct MergedSource {
+    name: String,
+    kind: &'static str,
+    idle_timeout: Option<Duration>,
+    state: Arc<MergedSourceState>,
+}
+
+/// Updated by the merged stream while it is polled.
+#[derive(Default)]
+struct MergedSourceState {
+    finished: AtomicBool,
+    events: AtomicU64,
+}
+
+/// Counts the items of the given stream and records when it ends.
+fn track_source(
+    mut stream: impl Stream<Item = ExternalEvent> + Unpin + Send,
+    state: Arc<MergedSourceState>,
+) -> impl Stream<Item = ExternalEvent> + Unpin + Send {
+    futures::stream::poll_fn(move |cx| {
+        let item = stream.poll_next_unpin(cx);
+        match &item {
+            Poll::Ready(Some(_)) => {
+                state.events.fetch_add(1, Ordering::Relaxed);
+            }
+            Poll::Ready(None) => state.finished.store(true, Ordering::Relaxed),
+            Poll::Pending => {}
+        }
+        item
+    })
+}
+
+#[derive(Debug, Clone, Copy)]
+enum ErrorPolicy {
+    Event,
+    Ignore,
+}
+
+/// Handling of input events whose value cannot be decoded, see `Node(on_decode_error=...)`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum DecodeErrorPolicy {
+    /// Replace the input event by a `DECODE_ERROR` event.
+    Event,
+    /// Skip the input event.
+    Ignore,
+    /// Raise an exception from `next()`.
+    Raise,
+}
+
+/// Emits an [`ExternalEvent::IdleTimeout`] whenever the given stream yields no item
+/// for the given duration.
+fn with_idle_timeout(
+    mut stream: impl Stream<Item = ExternalEvent> + Unpin + Send,
+    source: String,
+    timeout: Duration,
+) -> impl Stream<Item = ExternalEvent> + Unpin + Send {
+    let mut deadline = Delay::new(timeout);
+    futures::stream::poll_fn(move |cx| match stream.poll_next_unpin(cx) {
+        Poll::Ready(item) => {
+            deadline.reset(timeout);
+            Poll::Ready(item)
+        }
+        Poll::Pending => match deadline.poll_unpin(cx) {
+            Poll::Ready(()) => {
+                deadline.reset(timeout);
+                Poll::Ready(Some(ExternalEvent::IdleTimeout {
+                    source: source.clone(),
+                    timeout,
+                }))
+            }
+            Poll::Pending => Poll::Pending,
+        },
+    })
+}
+
+struct Events {
+    inner: EventsInner,
+    cleanup_handle: NodeCleanupHandle,
+    /// Inputs of the node config for which no `InputClosed` event was received yet.
+    open_inputs: BTreeSet<DataId>,
+    /// Whether inputs were added through `subscribe`, which are not known in
+    /// advance, so that only the end of the event stream closes all inputs.
+    subscribed_patterns: bool,
+    /// Set when the last open input was closed.
+    all_inputs_closed: bool,
+    /// External streams merged through `merge_external_events`.
+    merged_sources: Vec<MergedSource>,
+}
+
+impl Events {
+    fn track_closed_inputs(&mut self, event: Option<&MergedEvent<ExternalEvent>>) {
+        match event {
+            Some(MergedEvent::Dora(Event::InputClosed { id })) => {
+                if self.open_inputs.remove(id)
+                    && self.open_inputs.is_empty()
+                    && !self.subscribed_patterns
+                {
+                    self.all_inputs_closed = true;
+                }
+            }
+            // the daemon closes the event stream once all inputs are closed
+            None => self.all_inputs_closed = true,
+            Some(_) => {}
+        }
+    }
+
+    fn tee(&mut self) -> flume::Receiver<Event> {
+        // also reachable after the event stream was merged with external events
+        self.cleanup_handle._handles.1.get_mut().tee()
+    }
+
+    fn input_bandwidth(&mut self) -> BTreeMap<DataId, f64> {
+        // also reachable after the event stream was merged with external events
+        self.cleanup_handle._handles.1.get_mut().input_bandwidth()
+    }
+
+    fn input_counters(&mut self) -> BTreeMap<DataId, MessageCounters> {
+        // also reachable after the event stream was merged with external events
+        self.cleanup_handle
+            ._handles
+            .1
+            .get_mut()
+            .input_counters()
+            .clone()
+    }
+
+    fn set_affinity(&mut self, cores: &[usize]) -> eyre::Result<()> {
+        // also reachable after the event stream was merged with external events
+        self.cleanup_handle._handles.1.get_mut().set_affinity(cores)
+    }
+
+    fn ack(&mut self, input: &DataId, metadata: &Metadata) -> eyre::Result<()> {
+        // also reachable after the event stream was merged with external events
+        self.cleanup_handle
+            ._handles
+            .1
+            .get_mut()
+            .ack(input, metadata)
+    }
+
+    fn ack_all(&mut self) -> eyre::Result<()> {
+        self.cleanup_handle._handles.1.get_mut().ack_all()
+    }
+
+    fn unacked_count(&mut self) -> usize {
+        self.cleanup_handle._handles.1.get_mut().unacked_count()
+    }
+
+    fn clear_unacked(&mut self) -> usize {
+        self.cleanup_handle._handles.1.get_mut().clear_unacked()
+    }
+
+    fn set_input_schedule(&mut self, schedule: InputSchedule) -> eyre::Result<()> {
+        self.cleanup_handle
+            ._handles
+            .1
+            .get_mut()
+            .set_input_schedule(schedule)
+    }
+
+    fn queue_depths(&mut self) -> BTreeMap<DataId, usize> {
+        match &mut self.inner {
+            EventsInner::Dora(events) => events.get_mut().queue_depths(),
+            EventsInner::Merged(_) => BTreeMap::new(),
+        }
+    }
+
+    fn clear_queues(&mut self) -> usize {
+        match &mut self.inner {
+            EventsInner::Dora(events) => events.get_mut().clear_queues(),
+            EventsInner::Merged(_) => 0,
+        }
+    }
+
+    fn buffered_bytes(&mut self) -> usize {
+        match &mut self.inner {
+            EventsInner::Dora(events) => events.get_mut().buffered_bytes(),
+            EventsInner::Merged(_) => 0,
+        }
+    }
+
+    fn recv(&mut self, timeout: Option<Duration>) -> Option<PyEvent> {
+        let event = match &mut self.inner {
+            EventsInner::Dora(events) => match timeout {
+                Some(timeout) => events
+                    .get_mut()
                     .recv_timeout(timeout)
                     .map(MergedEvent::Dora),
                 None => events.get_mut().recv().map(MergedEvent::Dora),
             },
-            EventsInner::Merged(events) => futures::executor::block_on(events.next()),
+            EventsInner::Merged(events) => futures::executor::block_on(events.next()),
+        };
+        self.track_closed_inputs(event.as_ref());
+        event.map(|event| PyEvent::new(event, Some(self.cleanup_handle.clone())))
+    }
+
+    /// Waits at most `duration` for the next event.
+    ///
+    /// Returns `Poll::Pending` if no event was received in time.
+    fn recv_for(&mut self, duration: Duration) -> Poll<Option<PyEvent>> {
+        let delay = Delay::new(duration);
+        let next = match &mut self.inner {
+            EventsInner::Dora(events) => {
+                let mut events = events.get_mut();
+                // bound before matching, so that the pending future is dropped
+                // before the guard that it borrows
+                let received =
+                    futures::executor::block_on(select(delay, Box::pin(events.recv_async())));
+                match received {
+                    Either::Left(_) => return Poll::Pending,
+                    Either::Right((event, _)) => event.map(MergedEvent::Dora),
+                }
+            }
+            EventsInner::Merged(events) => {
+                match futures::executor::block_on(select(delay, events.next())) {
+                    Either::Left(_) => return Poll::Pending,
+                    Either::Right((event, _)) => event,
+                }
+            }
+        };
+        self.track_closed_inputs(next.as_ref());
+        Poll::Ready(next.map(|event| PyEvent::new(event, Some(self.cleanup_handle.clone()))))
+    }
+}
+
+enum EventsInner {
+    Dora(DelayedCleanup<EventStream>),
+    Merged(Box<dyn Stream<Item = MergedEvent<ExternalEvent>> + Unpin + Send>),
+}
+
+impl<'a> MergeExternalSend<'a, ExternalEvent> for EventsInner {
+    type Item = MergedEvent<ExternalEvent>;
+
+    fn merge_external_send(
+        self,
+        external_events: impl Stream<Item = ExternalEvent> + Unpin + Send + 'a,
+    ) -> Box<dyn Stream<Item = Self::Item> + Unpin + Send + 'a> {
+        match self {
+            EventsInner::Dora(events) => events.merge_external_send(external_events),
+            EventsInner::Merged(events) => {
+                let merged = events.merge_external_send(external_events);
+                Box::new(merged.map(|event| match event {
+                    MergedEvent::Dora(e) => MergedEvent::Dora(e),
+                    MergedEvent::External(e) => MergedEvent::External(e.flatten()),
+                }))
+            }
+        }
+    }
+}
+
+impl Drop for Node {
+    fn drop(&mut self) {
+        if self.pid != std::process::id() {
+            // All resources of the node belong to the parent process, so they
+            // must not be cleaned up by a forked child: dropping the node and its
+            // event stream would close the connection of the parent, dropping the
+            // recorder would write to the shared recording file, and dropping the
+            // output capture would redirect the file descriptors of the child.
+            // Leak them instead, the operating system releases them on exit.
+            std::mem::forget(self.recorder.take());
+            std::mem::forget(self.output_capture.take());
+            return;
+        }
+        if self.emit_summary_on_close {
+            let summary = summarize(
+                &self.events.input_counters(),
+                self.node.get_mut().output_counters(),
+            );
+            // errors are ignored, as the daemon might be gone already
+            let _ = self.node.get_mut().send_summary(summary);
+        }
+        // SAFETY: the fields are not used anymore after this
+        unsafe {
+            ManuallyDrop::drop(&mut self.events);
+            ManuallyDrop::drop(&mut self.node);
+        }
+    }
+}
+
+fn parse_metadata(
+    metadata: Option<Bound<'_, PyDict>>,
+    strict: bool,
+    codec: Option<MetadataCodec>,
+) -> eyre::Result<MetadataParameters> {
+    if strict {
+        pydict_to_metadata_strict(metadata, codec)
+    } else {
+        pydict_to_metadata_with_codec(metadata, codec)
+    }
+}
+
+/// Arguments of `Node.__init__`, unset options use their default.
+#[derive(Default)]
+struct NodeOptions<'py> {
+    node_id: Option<String>,
+    max_buffered_bytes: Option<usize>,
+    assert_monotonic_timestamps: Option<Bound<'py, PyAny>>,
+    strict_metadata: Option<bool>,
+    watermarks: Option<bool>,
+    record: Option<PathBuf>,
+    compression: Option<String>,
+    dry_run: Option<bool>,
+    dead_letter: Option<String>,
+    manual_acks: Option<bool>,
+    always_copy: Option<bool>,
+    metadata_codec: Option<String>,
+    dataflow: Option<String>,
+    daemon_address: Option<String>,
+    emit_summary_on_close: Option<bool>,
+    on_decode_error: Option<String>,
+    input_schedule: Option<String>,
+    weights: Option<BTreeMap<String, f64>>,
+    event_class: Option<bool>,
+    /// Node of the parent process whose connection is taken over, see
+    /// `reinit_after_fork`.
+    fork_of: Option<&'py DoraNode>,
+}
+
+/// Value of an output that is sent through `Node::send_parameters_with`.
+enum OutputData {
+    /// Any value accepted by `Node.send_output`.
+    Python(PyObject),
+    /// A value that was already converted, e.g. a received input or a Flight frame.
+    Arrow(ArrayRef),
+}
+
+/// Keyword arguments of `Node.send_output` besides `metadata`.
+#[derive(Default)]
+struct SendOptions<'py> {
+    caused_by: Option<Vec<(String, u64)>>,
+    at: Option<f64>,
+    frame_id: Option<String>,
+    units: Option<String>,
+    priority: Option<String>,
+    attachments: Option<HashMap<String, Bound<'py, PyBytes>>>,
+    reliable: bool,
+    reliable_timeout: Option<f64>,
+    schema: Option<String>,
+    content_type: Option<String>,
+}
+
+/// Extracts an entry of `caused_by`, either an input event or an
+/// `(input_id, sequence_number)` tuple.
+fn lineage_reference(reference: &Bound<'_, PyAny>) -> PyResult<(String, u64)> {
+    match PyEvent::from_object(reference) {
+        Some(event) => event.borrow().id_and_seq().ok_or_else(|| {
+            PyValueError::new_err("`caused_by` events must be inputs with a sequence number")
+        }),
+        None => reference.extract(),
+    }
+}
+
+/// Sender for an output with a fixed arrow schema.
+///
+/// Created through `Node.record_batch_output`.
+#[pyclass]
+pub struct RecordBatchOutput {
+    output_id: DataId,
+    schema: SchemaRef,
+    /// Name of the registered schema, sent as `schema` metadata.
+    schema_name: Option<String>,
+    node: CleanupHandle<DoraNode>,
+    strict_metadata: bool,
+    metadata_codec: Option<MetadataCodec>,
+    pid: u32,
+}
+
+#[pymethods]
+impl RecordBatchOutput {
+    /// Sends a batch matching the schema of this output.
+    ///
+    /// The batch can be given as a `pyarrow.RecordBatch`, as a list of arrays in
+    /// schema order, or as a dict mapping column names to arrays. A
+    /// `dora.SchemaError` is raised if the columns don't match the names and types
+    /// of the schema, or if they differ in length.
+    ///
+    /// :type batch: pyarrow.RecordBatch | list[pyarrow.Array] | dict
+    /// :type metadata: dict, optional
+    /// :rtype: None
+    #[pyo3(signature = (batch, metadata=None))]
+    pub fn send(
+        &mut self,
+        batch: Bound<'_, PyAny>,
+        metadata: Option<Bound<'_, PyDict>>,
+    ) -> eyre::Result<()> {
+        if self.pid != std::process::id() {
+            eyre::bail!("output `{}` cannot be used after `fork`", self.output_id);
+        }
+        let mut parameters = parse_metadata(metadata, self.strict_metadata, self.metadata_codec)?;
+        if let Some(name) = &self.schema_name {
+            parameters.insert(SCHEMA_NAME.to_owned(), Parameter::String(name.clone()));
+        }
+        let fields = self.schema.fields();
+
+        let columns = if let Ok(dict) = batch.downcast::<PyDict>() {
+            if dict.len() != fields.len() {
+                return Err(schema_error(format!(
+                    "expected {} columns for output `{}`, got {}",
+                    fields.len(),
+                    self.output_id,
+                    dict.len()
+                )));
+            }
+            fields
+                .iter()
+                .map(|field| {
+                    let column = dict.get_item(field.name())?.ok_or_else(|| {
+                        schema_error(format!("missing column `{}`", field.name()))
+                    })?;
+                    array_from_pyarrow(&column)
+                })
+                .collect::<eyre::Result<Vec<_>>>()?
+        } else if let Ok(record_batch) = RecordBatch::from_pyarrow_bound(&batch) {
+            let names = record_batch.schema_ref().fields().iter().map(|f| f.name());
+            if !names.eq(fields.iter().map(|f| f.name())) {
+                return Err(schema_error(format!(
+                    "column names of batch don't match the schema of output `{}`",
+                    self.output_id
+                )));
+            }
+            record_batch.columns().to_vec()
+        } else if let Ok(columns) = batch.extract::<Vec<Bound<'_, PyAny>>>() {
+            columns
+                .iter()
+                .map(array_from_pyarrow)
+                .collect::<eyre::Result<Vec<_>>>()?
+        } else {
+            eyre::bail!("invalid `batch` type, must be a `RecordBatch`, a list, or a dict")
+        };
+
+        let record_batch = RecordBatch::try_new(self.schema.clone(), columns).map_err(|err| {
+            schema_error(format!(
+                "batch does not match the schema of output `{}`: {err}",
+                self.output_id
+            ))
+        })?;
+        self.node
+            .get_mut()
+            .send_output(
+                self.output_id.clone(),
+                parameters,
+                StructArray::from(record_batch),
+            )
+            .map_err(|err| dora_error(err, Operation::Send))
+    }
+
+    /// The schema of this output.
+    ///
+    /// :rtype: pyarrow.Schema
+    pub fn schema(&self) -> PyArrowType<Schema> {
+        PyArrowType(self.schema.as_ref().clone())
+    }
+}
+
+/// Detects input events whose timestamp goes backwards, see `assert_monotonic_timestamps`.
+struct TimestampCheck {
+    raise: bool,
+    last: HashMap<DataId, uhlc::Timestamp>,
+}
+
+impl TimestampCheck {
+    /// Parses the `assert_monotonic_timestamps` argument, returns `None` if disabled.
+    fn from_python(policy: &Bound<'_, PyAny>) -> eyre::Result<Option<Self>> {
+        let raise = if let Ok(enabled) = policy.extract::<bool>() {
+            if !enabled {
+                return Ok(None);
+            }
+            false
+        } else {
+            match policy.extract::<String>().ok().as_deref() {
+                Some("warn") => false,
+                Some("raise") => true,
+                _ => eyre::bail!(
+                    "invalid `assert_monotonic_timestamps` value `{policy}`, \
+                    expected a bool, `\"warn\"`, or `\"raise\"`"
+                ),
+            }
+        };
+        Ok(Some(Self {
+            raise,
+            last: HashMap::new(),
+        }))
+    }
+
+    fn check(&mut self, py: Python<'_>, id: &DataId, metadata: &Metadata) -> PyResult<()> {
+        let timestamp = metadata.timestamp();
+        let Some(previous) = self.last.insert(id.clone(), timestamp) else {
+            return Ok(());
+        };
+        if timestamp >= previous {
+            return Ok(());
+        }
+        let message = format!(
+            "timestamp of input `{id}` went backwards: {timestamp} is older than \
+            the previous timestamp {previous}"
+        );
+        if self.raise {
+            Err(PyRuntimeError::new_err(message))
+        } else {
+            PyErr::warn_bound(py, &py.get_type_bound::<PyRuntimeWarning>(), &message, 1)
+        }
+    }
+}
+
+/// Warns about a failure that does not prevent returning the received event.
+///
+/// If warnings are turned into exceptions, e.g. through `-W error`, the exception
+/// is stored in `error`, so that it is raised with the event attached.
+fn warn_received(py: Python<'_>, error: &mut Option<PyErr>, message: &str) {
+    let category = py.get_type_bound::<PyRuntimeWarning>();
+    if let Err(err) = PyErr::warn_bound(py, &category, message, 1) {
+        error.get_or_insert(err);
+    }
+}
+
+/// Sets the event that caused an exception as its `event` attribute, so that the
+/// event is not lost when the exception is raised from `next()`.
+fn with_event(py: Python<'_>, err: PyErr, event: PyObject) -> PyErr {
+    match err.value_bound(py).setattr("event", event) {
+        Ok(()) => err,
+        Err(err) => err,
+    }
+}
+
+/// Returns the schema of inputs with the given data type, see `Node.input_schema`.
+fn input_schema(data_type: &DataType) -> Schema {
+    match data_type {
+        DataType::Struct(fields) => Schema::new(fields.clone()),
+        other => Schema::new(vec![Field::new("value", other.clone(), true)]),
+    }
+}
+
+/// Iterator over the batches of an input, see `Node.record_batch_reader`.
+#[pyclass]
+struct RecordBatchInput {
+    node: Py<Node>,
+    input_id: DataId,
+    /// Batch that was received to determine the schema.
+    first: Option<RecordBatch>,
+}
+
+impl RecordBatchInput {
+    fn next_batch(&mut self, py: Python<'_>) -> eyre::Result<Option<RecordBatch>> {
+        if let Some(first) = self.first.take() {
+            return Ok(Some(first));
+        }
+        loop {
+            let Some(event) = self.node.borrow_mut(py).receive(py, None)? else {
+                return Ok(None);
+            };
+            let received = event.borrow(py);
+            match &received.event {
+                MergedEvent::Dora(Event::Input { id, data, .. }) if *id == self.input_id => {
+                    let array = data.as_struct_opt().ok_or_else(|| {
+                        eyre::eyre!(
+                            "input `{id}` is not a record batch, expected a struct array but got `{}`",
+                            data.data_type()
+                        )
+                    })?;
+                    return Ok(Some(RecordBatch::from(array)));
+                }
+                MergedEvent::Dora(Event::Error(err)) => eyre::bail!("{err}"),
+                _ => {}
+            }
+            // the end of the input is kept as well, so that `next` still sees it
+            let end = matches!(
+                &received.event,
+                MergedEvent::Dora(Event::InputClosed { id }) if *id == self.input_id
+            ) || matches!(received.event, MergedEvent::Dora(Event::Stop));
+            drop(received);
+            self.node.borrow_mut(py).pending.push_back(event);
+            if end {
+                return Ok(None);
+            }
+        }
+    }
+}
+
+#[pymethods]
+impl RecordBatchInput {
+    fn __iter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+        slf
+    }
+
+    fn __next__(&mut self, py: Python<'_>) -> eyre::Result<Option<PyObject>> {
+        match self.next_batch(py)? {
+            Some(batch) => Ok(Some(batch.to_pyarrow(py)?)),
+            None => Ok(None),
+        }
+    }
+}
+
+/// Converts the `data` of an output to arrow, `bytes` are sent as `uint8` array.
+pub(crate) fn data_to_array(data: &Bound<'_, PyAny>) -> eyre::Result<ArrayRef> {
+    if let Ok(py_bytes) = data.downcast::<PyBytes>() {
+        Ok(Arc::new(UInt8Array::from(py_bytes.as_bytes().to_vec())))
+    } else if let Some(arrow_array) = output_array_from_pyarrow(data)? {
+        Ok(arrow_array)
+    } else {
+        eyre::bail!("invalid `data` type, must be `PyBytes` or arrow array")
+    }
+}
+
+fn array_from_pyarrow(value: &Bound<'_, PyAny>) -> eyre::Result<ArrayRef> {
+    let data = ArrayData::from_pyarrow_bound(value).wrap_err("column is not an arrow array")?;
+    Ok(make_array(data))
+}
+
+/// Converts the `data` of an output to arrow, returns `None` if it's not an arrow array.
+///
+/// pyarrow splits arrays that exceed the 2GB limit of 32-bit offsets into a
+/// `ChunkedArray`. The chunks of such arrays are combined into a single array,
+/// which is switched to the equivalent type with 64-bit offsets, e.g. from
+/// `string` to `large_string`.
+///
+/// Lists of `decimal.Decimal` values are converted to a `Decimal128` array and
+/// `scipy.sparse` matrices to their sparse representation, see [`sparse`]. numpy
+/// structured arrays are converted to struct arrays, see [`structured`], and
+/// tables to Arrow IPC streams, see [`table`].
+fn output_array_from_pyarrow(data: &Bound<'_, PyAny>) -> eyre::Result<Option<ArrayRef>> {
+    if let Ok(array) = ArrayData::from_pyarrow_bound(data) {
+        return Ok(Some(make_array(array)));
+    }
+    if let Some(array) = decimal::decimal_array_from_python(data)? {
+        return Ok(Some(array));
+    }
+    if let Some(matrix) = sparse::sparse_from_python(data)? {
+        return Ok(Some(sparse::sparse_to_array(&matrix)?));
+    }
+    if let Some(array) = structured::structured_from_python(data)? {
+        return Ok(Some(array));
+    }
+    if let Some(array) = table::table_from_python(data)? {
+        return Ok(Some(array));
+    }
+    let chunked_array = data.py().import_bound("pyarrow")?.getattr("ChunkedArray")?;
+    if !data.is_instance(&chunked_array)? {
+        return Ok(None);
+    }
+
+    let data_type = DataType::from_pyarrow_bound(&data.getattr("type")?)
+        .wrap_err("unsupported chunked array type")?;
+    let chunks = data
+        .getattr("chunks")?
+        .iter()?
+        .map(|chunk| array_from_pyarrow(&chunk?))
+        .collect::<eyre::Result<Vec<_>>>()?;
+    combine_chunks(&chunks, &data_type).map(Some)
+}
+
+/// Converts a dataflow parameter to the type of `default`, if given.
+fn param_to_python(
+    py: Python,
+    name: &str,
+    value: ParamValue,
+    default: Option<&Bound<'_, PyAny>>,
+) -> PyResult<PyObject> {
+    let invalid = |ty: &str| {
+        PyValueError::new_err(format!(
+            "dataflow parameter `{name}` is not a valid {ty}: `{value}`"
+        ))
+    };
+    let Some(default) = default.filter(|default| !default.is_none()) else {
+        return Ok(match value {
+            ParamValue::Bool(bool) => bool.into_py(py),
+            ParamValue::Integer(int) => int.into_py(py),
+            ParamValue::Float(float) => float.into_py(py),
+            ParamValue::String(string) => string.into_py(py),
+        });
+    };
+    // check `bool` before `int`, since `bool` is a subclass of `int` in Python
+    if default.is_instance_of::<PyBool>() {
+        let bool = match &value {
+            ParamValue::Bool(bool) => *bool,
+            ParamValue::String(string) => match string.trim().to_lowercase().as_str() {
+                "true" | "1" | "yes" | "on" => true,
+                "false" | "0" | "no" | "off" => false,
+                _ => return Err(invalid("bool")),
+            },
+            _ => return Err(invalid("bool")),
+        };
+        Ok(bool.into_py(py))
+    } else if default.is_instance_of::<PyInt>() {
+        let int = match &value {
+            ParamValue::Integer(int) => *int,
+            ParamValue::String(string) => string.trim().parse().map_err(|_| invalid("int"))?,
+            _ => return Err(invalid("int")),
+        };
+        Ok(int.into_py(py))
+    } else if default.is_instance_of::<PyFloat>() {
+        let float = match &value {
+            ParamValue::Integer(int) => *int as f64,
+            ParamValue::Float(float) => *float,
+            ParamValue::String(string) => string.trim().parse().map_err(|_| invalid("float"))?,
+            ParamValue::Bool(_) => return Err(invalid("float")),
         };
-        event.map(|event| PyEvent {
-            event,
-            _cleanup: Some(self.cleanup_handle.clone()),
+        Ok(float.into_py(py))
+    } else if default.is_instance_of::<PyString>() {
+        Ok(value.to_string().into_py(py))
+    } else {
+        Err(PyTypeError::new_err(format!(
+            "unsupported type of default for dataflow parameter `{name}`, \
+            expected bool, int, float, or str"
+        )))
+    }
+}
+
+impl Node {
+    pub fn id(&self) -> String {
+        self.node_id.to_string()
+    }
+
+    /// Creates a node, see the docstring of `Node` for the options.
+    fn from_options(options: NodeOptions<'_>) -> eyre::Result<Self> {
+        let NodeOptions {
+            node_id,
+            max_buffered_bytes,
+            assert_monotonic_timestamps,
+            strict_metadata,
+            watermarks,
+            record,
+            compression,
+            dry_run,
+            dead_letter,
+            manual_acks,
+            always_copy,
+            metadata_codec,
+            dataflow,
+            daemon_address,
+            emit_summary_on_close,
+            on_decode_error,
+            input_schedule,
+            weights,
+            event_class,
+            fork_of,
+        } = options;
+        let timestamp_check = assert_monotonic_timestamps
+            .map(|policy| TimestampCheck::from_python(&policy))
+            .transpose()?
+            .flatten();
+        let metadata_codec = metadata_codec.as_deref().map(str::parse).transpose()?;
+        let on_decode_error = match on_decode_error.as_deref().unwrap_or("event") {
+            "event" => DecodeErrorPolicy::Event,
+            "ignore" => DecodeErrorPolicy::Ignore,
+            "raise" => DecodeErrorPolicy::Raise,
+            other => eyre::bail!(
+                "invalid `on_decode_error` policy `{other}`, expected `event`, `ignore`, or `raise`"
+            ),
+        };
+        let input_schedule = match (input_schedule.as_deref().unwrap_or("round_robin"), weights) {
+            ("round_robin", None) => InputSchedule::RoundRobin,
+            ("round_robin", Some(_)) => {
+                eyre::bail!("`weights` require `input_schedule=\"weighted_fair\"`")
+            }
+            ("weighted_fair", weights) => InputSchedule::WeightedFair {
+                weights: weights
+                    .unwrap_or_default()
+                    .into_iter()
+                    .map(|(id, weight)| (DataId::from(id), weight))
+                    .collect(),
+            },
+            (other, _) => eyre::bail!(
+                "invalid `input_schedule` `{other}`, expected `round_robin` or `weighted_fair`"
+            ),
+        };
+        let compression = parse_compression(compression.as_deref())?;
+        if compression.is_some() && record.is_none() {
+            eyre::bail!("`compression` requires a `record` path");
+        }
+        let recorder = record
+            .map(|path| EventRecorder::create(&path, compression))
+            .transpose()?;
+        let dataflow = dataflow
+            .as_deref()
+            .map(str::parse::<DataflowId>)
+            .transpose()
+            .context("invalid `dataflow` ID")?;
+        let daemon_address = daemon_address
+            .as_deref()
+            .map(str::parse::<SocketAddr>)
+            .transpose()
+            .context("invalid `daemon_address`")?
+            .or_else(|| {
+                dataflow.map(|_| (LOCALHOST, DORA_DAEMON_LOCAL_LISTEN_PORT_DEFAULT).into())
+            });
+        let mut dry_run_transport = None;
+        let (mut node, mut events) = if let Some(parent) = fork_of {
+            parent.init_after_fork()
+        } else if dry_run.unwrap_or(false) {
+            let node_id = NodeId::from(node_id.unwrap_or_else(|| "dry-run".to_owned()));
+            let (node, events, transport) = DoraNode::init_dry_run(node_id)?;
+            dry_run_transport = Some(transport);
+            Ok((node, events))
+        } else if let Some(address) = daemon_address {
+            let node_id = node_id.context("`dataflow` and `daemon_address` require a `node_id`")?;
+            DoraNode::init_from_daemon(address, dataflow, NodeId::from(node_id))
+                .context("Could not connect to the dynamic node of the given dataflow")
+        } else if let Some(node_id) = node_id {
+            DoraNode::init_flexible(NodeId::from(node_id))
+                .context("Could not setup node from node id. Make sure to have a running dataflow with this dynamic node")
+        } else {
+            DoraNode::init_from_env().context("Could not initiate node from environment variable. For dynamic node, please add a node id in the initialization function.")
+        }
+        .map_err(|err| dora_error(err, Operation::Init))?;
+        if let Some(output_id) = dead_letter {
+            node.set_dead_letter_output(Some(DataId::from(output_id)))?;
+        }
+        schema_registry::register_descriptor_schemas(&node.dataflow_descriptor().schemas)?;
+
+        let dataflow_id = *node.dataflow_id();
+        let node_id = node.id().clone();
+        let lifecycle_events = events.lifecycle_events();
+        let open_inputs = node.node_config().inputs.keys().cloned().collect();
+        events.set_max_buffered_bytes(max_buffered_bytes);
+        events.set_watermarks(watermarks.unwrap_or(false));
+        events.set_manual_acks(manual_acks.unwrap_or(false))?;
+        events.set_input_schedule(input_schedule.clone())?;
+        // received events are queued without the GIL, even while Python code runs
+        events.buffer_in_background();
+        let node = DelayedCleanup::new(node);
+        let events = DelayedCleanup::new(events);
+        let cleanup_handle = NodeCleanupHandle {
+            _handles: Arc::new((node.handle(), events.handle())),
+        };
+        Ok(Node {
+            events: ManuallyDrop::new(Events {
+                inner: EventsInner::Dora(events),
+                cleanup_handle,
+                open_inputs,
+                subscribed_patterns: false,
+                all_inputs_closed: false,
+                merged_sources: Vec::new(),
+            }),
+            dataflow_id,
+            node_id,
+            node: ManuallyDrop::new(node),
+            lifecycle_events,
+            lifecycle_subscribed: false,
+            timestamp_check,
+            strict_metadata: strict_metadata.unwrap_or(false),
+            metadata_codec,
+            max_buffered_bytes,
+            watermarks: watermarks.unwrap_or(false),
+            manual_acks: manual_acks.unwrap_or(false),
+            always_copy: always_copy.unwrap_or(false),
+            daemon_address,
+            emit_summary_on_close: emit_summary_on_close.unwrap_or(false),
+            pid: std::process::id(),
+            output_capture: None,
+            recorder,
+            decoders: HashMap::new(),
+            on_decode_error,
+            input_schedule,
+            delta_outputs: HashMap::new(),
+            delta_inputs: DeltaDecoder::default(),
+            on_all_inputs_closed: None,
+            input_types: HashMap::new(),
+            dry_run: dry_run_transport,
+            timers: Timers::default(),
+            windows: Windows::default(),
+            ticks: Ticks::default(),
+            pending: VecDeque::new(),
+            event_class: event_class.unwrap_or(false),
         })
     }
+
+    /// Converts an event into the object that is returned to Python, see
+    /// `event_class`.
+    fn to_python(&self, py: Python, event: Py<PyEvent>) -> PyResult<PyObject> {
+        PyEvent::to_python(event, py, self.event_class)
+    }
+
+    /// Returns the next pending event or receives the next event from the daemon.
+    fn next_event(&mut self, py: Python, timeout: Option<f32>) -> PyResult<Option<Py<PyEvent>>> {
+        self.check_process()?;
+        if let Some(event) = self.pending.pop_front() {
+            return Ok(Some(event));
+        }
+        self.receive(py, timeout)
+    }
+
+    /// Receives the next event from the daemon, bypassing the `pending` events.
+    fn receive(&mut self, py: Python, timeout: Option<f32>) -> PyResult<Option<Py<PyEvent>>> {
+        let deadline = timeout.map(|timeout| Instant::now() + Duration::from_secs_f32(timeout));
+        let event = loop {
+            let Some(mut received) = py
+                .allow_threads(|| self.recv_until(deadline))
+                .map_err(|err| dora_error(err, Operation::Receive))?
+            else {
+                break None;
+            };
+            if let MergedEvent::Dora(Event::Input { data, .. }) = &mut received.event {
+                if self.always_copy {
+                    *data = make_array(deep_copy(&data.0.to_data())).into();
+                }
+            }
+            let Some((id, error, exception)) = self.decode_input(py, &mut received) else {
+                break Some(received);
+            };
+            match self.on_decode_error {
+                DecodeErrorPolicy::Event => {
+                    let failed = Event::DecodeError { id, error };
+                    break Some(PyEvent::new(
+                        MergedEvent::Dora(failed),
+                        received._cleanup.clone(),
+                    ));
+                }
+                DecodeErrorPolicy::Ignore => continue,
+                DecodeErrorPolicy::Raise => {
+                    let event = self.to_python(py, Py::new(py, received)?)?;
+                    return Err(with_event(py, exception, event));
+                }
+            }
+        };
+        let mut error = None;
+        if let Some(PyEvent {
+            event: MergedEvent::Dora(Event::Input { id, data, .. }),
+            ..
+        }) = &event
+        {
+            if let Err(err) = self.timers.advance_sim_time(id, data.0.as_ref()) {
+                let message = format!("failed to advance the simulation time: {err:#}");
+                warn_received(py, &mut error, &message);
+            }
+        }
+        if let (
+            Some(check),
+            Some(PyEvent {
+                event: MergedEvent::Dora(Event::Input { id, metadata, .. }),
+                ..
+            }),
+        ) = (&mut self.timestamp_check, &event)
+        {
+            // raised after the event was processed, so that it can be attached
+            if let Err(err) = check.check(py, id, metadata) {
+                error.get_or_insert(err);
+            }
+        }
+        if let Some(PyEvent {
+            event: MergedEvent::Dora(Event::Input { id, metadata, data }),
+            ..
+        }) = &event
+        {
+            // inputs of types that are unknown to this node are not validated
+            let name = schema_registry::schema_name(metadata)
+                .filter(|name| schema_registry::get(name).is_some());
+            if let Some(name) = name {
+                if let Err(err) = schema_registry::validate(name, data.data_type()) {
+                    PyErr::warn_bound(
+                        py,
+                        &py.get_type_bound::<PyRuntimeWarning>(),
+                        &format!("input `{id}`: {err}"),
+                        1,
+                    )?;
+                }
+            }
+        }
+        if let (
+            Some(recorder),
+            Some(PyEvent {
+                event: MergedEvent::Dora(Event::Input { id, metadata, data }),
+                ..
+            }),
+        ) = (&mut self.recorder, &event)
+        {
+            if let Err(err) = recorder.record(id, metadata, data.0.clone()) {
+                let message = format!("failed to record input `{id}`: {err:#}");
+                warn_received(py, &mut error, &message);
+            }
+        }
+        if let Some(PyEvent {
+            event: MergedEvent::Dora(Event::Input { id, data, .. }),
+            ..
+        }) = &event
+        {
+            if self.input_types.get(id) != Some(data.data_type()) {
+                self.input_types
+                    .insert(id.clone(), data.data_type().clone());
+            }
+        }
+        if self.events.all_inputs_closed {
+            if let Some(callback) = self.on_all_inputs_closed.take() {
+                if let Err(err) = callback.call0(py) {
+                    error = error.or(Some(err));
+                }
+            }
+        }
+        let event = event.map(|event| Py::new(py, event)).transpose()?;
+        if let Some(event) = &event {
+            self.windows.record(py, event);
+        }
+        match (error, event) {
+            (Some(err), Some(event)) => Err(with_event(py, err, self.to_python(py, event)?)),
+            (Some(err), None) => Err(err),
+            (None, event) => Ok(event),
+        }
+    }
+
+    /// Reconstructs delta-encoded inputs and applies the decoder registered through
+    /// `set_decoder`.
+    ///
+    /// Returns the input ID, the error message, and the exception to raise if the
+    /// input cannot be decoded.
+    fn decode_input(
+        &mut self,
+        py: Python,
+        received: &mut PyEvent,
+    ) -> Option<(DataId, String, PyErr)> {
+        let MergedEvent::Dora(Event::Input { id, metadata, data }) = &mut received.event else {
+            return None;
+        };
+        if let Err(err) = self.delta_inputs.decode(id, metadata, data) {
+            let error = format!("{err:#}");
+            let exception = eyre::eyre!("failed to decode input `{id}`: {error}");
+            return Some((
+                id.clone(),
+                error,
+                dora_error(exception, Operation::Receive).into(),
+            ));
+        }
+        let id = id.clone();
+        let decoder = self.decoders.get(&id)?;
+        let err = received.decode_value(py, decoder.bind(py)).err()?;
+        Some((id, err.to_string(), err))
+    }
+
+    /// Sends an output with the options of `send_output`.
+    fn send_output_with<'py>(
+        &mut self,
+        output_id: String,
+        data: PyObject,
+        metadata: Option<Bound<'py, PyDict>>,
+        options: SendOptions<'py>,
+        py: Python<'py>,
+    ) -> eyre::Result<()> {
+        self.send_parameters_with(
+            output_id,
+            OutputData::Python(data),
+            MetadataParameters::default(),
+            metadata,
+            options,
+            py,
+        )
+    }
+
+    /// Sends an output with the options of `send_output`, adding the given
+    /// `metadata` to the already known `parameters`.
+    fn send_parameters_with<'py>(
+        &mut self,
+        output_id: String,
+        data: OutputData,
+        mut parameters: MetadataParameters,
+        metadata: Option<Bound<'py, PyDict>>,
+        options: SendOptions<'py>,
+        py: Python<'py>,
+    ) -> eyre::Result<()> {
+        let SendOptions {
+            caused_by,
+            at,
+            frame_id,
+            units,
+            priority,
+            attachments,
+            reliable,
+            reliable_timeout,
+            schema,
+            content_type,
+        } = options;
+        self.check_process()?;
+        self.forward_captured_output()?;
+        if reliable && at.is_some() {
+            eyre::bail!("`reliable` outputs cannot be scheduled through `at`");
+        }
+        parameters.extend(parse_metadata(
+            metadata,
+            self.strict_metadata,
+            self.metadata_codec,
+        )?);
+        if let Some(priority) = priority {
+            let priority: Priority = priority.parse().map_err(|err: String| eyre::eyre!(err))?;
+            parameters.insert(PRIORITY.to_owned(), priority.into());
+        }
+        if let Some(frame_id) = frame_id {
+            parameters.insert(FRAME_ID.to_owned(), Parameter::String(frame_id));
+        }
+        if let Some(units) = units {
+            if !KNOWN_UNITS.contains(&units.as_str()) {
+                PyErr::warn_bound(
+                    py,
+                    &py.get_type_bound::<PyRuntimeWarning>(),
+                    &format!("unknown units `{units}` for output `{output_id}`"),
+                    1,
+                )?;
+            }
+            parameters.insert(UNITS.to_owned(), Parameter::String(units));
+        }
+        if let Some(content_type) = content_type {
+            if !is_valid_content_type(&content_type) {
+                eyre::bail!(
+                    "invalid `content_type` `{content_type}`, expected a MIME type like `image/jpeg`"
+                );
+            }
+            parameters.insert(CONTENT_TYPE.to_owned(), Parameter::String(content_type));
+        }
+        for (name, bytes) in attachments.into_iter().flatten() {
+            parameters.insert(
+                attachment_key(&name),
+                Parameter::Bytes(bytes.as_bytes().to_vec()),
+            );
+        }
+        if let Some(caused_by) = caused_by {
+            parameters.insert(
+                CAUSED_BY.to_owned(),
+                caused_by_parameter(caused_by.iter().map(|(id, seq)| (id.as_str(), *seq))),
+            );
+        }
+        if let Some(now) = self.timers.sim_time() {
+            parameters
+                .entry(TIMESTAMP_NS.to_owned())
+                .or_insert(Parameter::Integer(timer::duration_to_nanos(now)));
+        }
+        let data = match data {
+            OutputData::Python(data) => {
+                let data = match cuda::cuda_to_host(data.bind(py))? {
+                    Some((host, cuda_parameters)) => {
+                        parameters.extend(cuda_parameters);
+                        host
+                    }
+                    None => data,
+                };
+                let data = match tensor::tensor_from_python(data.bind(py))? {
+                    Some((tensor, tensor_parameters)) => {
+                        parameters.extend(tensor_parameters);
+                        tensor
+                    }
+                    None => data,
+                };
+                if let (None, None, false, Ok(py_bytes)) =
+                    (&schema, at, reliable, data.downcast_bound::<PyBytes>(py))
+                {
+                    let data = py_bytes.as_bytes();
+                    return self
+                        .node
+                        .get_mut()
+                        .send_output_bytes(output_id.into(), parameters, data.len(), data)
+                        .wrap_err("failed to send output")
+                        .map_err(|err| dora_error(err, Operation::Send));
+                }
+                data_to_array(data.bind(py))?
+            }
+            OutputData::Arrow(array) => array,
+        };
+        if let Some(name) = schema {
+            schema_registry::validate(&name, data.data_type())
+                .map_err(|err| schema_error(err.to_string()))?;
+            parameters.insert(SCHEMA_NAME.to_owned(), Parameter::String(name));
+        }
+
+        if let Some(at) = at {
+            let at =
+                UNIX_EPOCH + Duration::try_from_secs_f64(at).context("invalid `at` timestamp")?;
+            self.node
+                .get_mut()
+                .send_output_at(output_id.into(), parameters, data, at)
+                .map_err(|err| dora_error(err, Operation::Send))?;
+        } else if reliable {
+            let timeout = Duration::try_from_secs_f64(reliable_timeout.unwrap_or(5.0))
+                .context("invalid `reliable_timeout`")?;
+            let node = &mut self.node;
+            py.allow_threads(|| {
+                node.get_mut()
+                    .send_output_reliable(output_id.into(), parameters, data, timeout)
+            })
+            .map_err(|err| dora_error(err, Operation::Send))?;
+        } else {
+            self.node
+                .get_mut()
+                .send_output(output_id.into(), parameters, data)
+                .map_err(|err| dora_error(err, Operation::Send))?;
+        }
+
+        Ok(())
+    }
+
+    /// Sends the lines that were captured through `capture_output` as log messages.
+    fn forward_captured_output(&mut self) -> eyre::Result<()> {
+        if let Some(capture) = &self.output_capture {
+            for line in capture.try_lines() {
+                self.node
+                    .get_mut()
+                    .log(line.level, Some(line.target.to_owned()), line.line)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn check_process(&self) -> eyre::Result<()> {
+        if self.pid != std::process::id() {
+            eyre::bail!(
+                "this node was created in process {} and cannot be used after `fork`, \
+                call `node.reinit_after_fork()` in the child process first",
+                self.pid
+            );
+        }
+        Ok(())
+    }
+
+    /// Receives the next event, sending scheduled outputs and timer ticks while
+    /// waiting.
+    fn recv_until(&mut self, deadline: Option<Instant>) -> eyre::Result<Option<PyEvent>> {
+        loop {
+            self.forward_captured_output()?;
+            if let Some(tick) = self.timers.poll() {
+                return Ok(Some(PyEvent::new(MergedEvent::Dora(tick), None)));
+            }
+            let next_scheduled = self.node.get_mut().send_scheduled_outputs()?;
+            let mut wake_up =
+                next_scheduled.map(|at| at.duration_since(SystemTime::now()).unwrap_or_default());
+            if let Some(tick) = self.timers.next_deadline() {
+                let tick = tick.saturating_duration_since(Instant::now());
+                wake_up = Some(wake_up.map_or(tick, |wake_up| wake_up.min(tick)));
+            }
+            if self.output_capture.is_some() {
+                wake_up = Some(wake_up.map_or(CAPTURE_FORWARD_INTERVAL, |wake_up| {
+                    wake_up.min(CAPTURE_FORWARD_INTERVAL)
+                }));
+            }
+            let timeout =
+                deadline.map(|deadline| deadline.saturating_duration_since(Instant::now()));
+            match wake_up {
+                Some(wake_up) if timeout.map_or(true, |timeout| wake_up < timeout) => {
+                    if let Poll::Ready(event) = self.events.recv_for(wake_up) {
+                        return Ok(event);
+                    }
+                }
+                _ => return Ok(self.events.recv(timeout)),
+            }
+        }
+    }
 }
 
-enum EventsInner {
-    Dora(DelayedCleanup<EventStream>),
-    Merged(Box<dyn Stream<Item = MergedEvent<PyObject>> + Unpin + Send>),
+/// Start a runtime for Operators
+///
+/// Returns `True` if all operators finished sending their outputs on shutdown, or
+/// `False` if the drain timeout (`DORA_RUNTIME_DRAIN_TIMEOUT`, in seconds) expired
+/// and remaining outputs were dropped.
+///
+/// :rtype: bool
+#[pyfunction]
+pub fn start_runtime(py: Python) -> eyre::Result<bool> {
+    let shutdown = py
+        .allow_threads(dora_runtime::main)
+        .wrap_err("Dora Runtime raised an error.")?;
+    Ok(shutdown == dora_runtime::Shutdown::Drained)
+}
+
+/// List the operators loaded by the runtime started with `start_runtime`.
+///
+/// Each operator is described by a dict containing its `id`, its `inputs` and
+/// `outputs`, its `status`, and statistics about its last tick.
+///
+/// ```python
+/// for operator in dora.list_operators():
+///     print(operator["id"], operator["status"], operator["last_input"])
+/// ```
+///
+/// :rtype: list[dict]
+#[pyfunction]
+pub fn list_operators(py: Python) -> eyre::Result<PyObject> {
+    Ok(pythonize::pythonize(py, &dora_runtime::registry::operators()).map(|x| x.unbind())?)
 }
 
-impl<'a> MergeExternalSend<'a, PyObject> for EventsInner {
-    type Item = MergedEvent<PyObject>;
+/// Inspect a single operator loaded by the runtime.
+///
+/// Returns `None` if no operator with the given id is loaded.
+///
+/// :type operator_id: str
+/// :rtype: dict
+#[pyfunction]
+pub fn operator_info(operator_id: String, py: Python) -> eyre::Result<Option<PyObject>> {
+    let operator_id = OperatorId::from(operator_id);
+    dora_runtime::registry::operator(&operator_id)
+        .map(|info| Ok(pythonize::pythonize(py, &info).map(|x| x.unbind())?))
+        .transpose()
+}
 
-    fn merge_external_send(
-        self,
-        external_events: impl Stream<Item = PyObject> + Unpin + Send + 'a,
-    ) -> Box<dyn Stream<Item = Self::Item> + Unpin + Send + 'a> {
-        match self {
-            EventsInner::Dora(events) => events.merge_external_send(external_events),
-            EventsInner::Merged(events) => {
-                let merged = events.merge_external_send(external_events);
-                Box::new(merged.map(|event| match event {
-                    MergedEvent::Dora(e) => MergedEvent::Dora(e),
-                    MergedEvent::External(e) => MergedEvent::External(e.flatten()),
-                }))
+/// Lists the dataflows that are currently running, as reported by the
+/// dora-coordinator.
+///
+/// This is a query of the control plane that does not require a node, e.g. for
+/// tools that monitor several dataflows. Each dataflow is described by a dict
+/// containing its `id`, its `name` or `None`, its `node_count`, and its
+/// `start_time` in seconds since the Unix epoch:
+///
+/// ```python
+/// for dataflow in dora.list_dataflows("10.0.0.2"):
+///     print(dataflow["id"], dataflow["name"], dataflow["node_count"])
+/// ```
+///
+/// The `coordinator_addr` is an IP address with an optional port, the default
+/// control port of the coordinator is used if no port is given. Defaults to the
+/// coordinator on the local machine.
+///
+/// :type coordinator_addr: str, optional
+/// :rtype: list[dict]
+#[pyfunction]
+#[pyo3(signature = (coordinator_addr=None))]
+pub fn list_dataflows(coordinator_addr: Option<&str>, py: Python) -> eyre::Result<Vec<Py<PyDict>>> {
+    let coordinator_addr = match coordinator_addr {
+        Some(addr) => addr
+            .parse::<SocketAddr>()
+            .or_else(|_| {
+                addr.parse::<IpAddr>()
+                    .map(|ip| (ip, DORA_COORDINATOR_PORT_CONTROL_DEFAULT).into())
+            })
+            .with_context(|| format!("invalid `coordinator_addr` `{addr}`"))?,
+        None => (LOCALHOST, DORA_COORDINATOR_PORT_CONTROL_DEFAULT).into(),
+    };
+    let dataflows = py.allow_threads(|| dora_node_api::list_dataflows(coordinator_addr))?;
+    dataflows
+        .into_iter()
+        .map(|dataflow| {
+            // dataflow IDs are time-ordered v7 UUIDs
+            let start_time = dataflow.id.uuid.get_timestamp().map(|timestamp| {
+                let (secs, nanos) = timestamp.to_unix();
+                secs as f64 + nanos as f64 / 1e9
+            });
+            let dict = PyDict::new_bound(py);
+            dict.set_item("id", dataflow.id.uuid.to_string())?;
+            dict.set_item("name", dataflow.id.name)?;
+            dict.set_item("node_count", dataflow.node_count)?;
+            dict.set_item("start_time", start_time)?;
+            Ok(dict.unbind())
+        })
+        .collect()
+}
+
+/// Converts the metadata of an input event, or a `dora.Metadata`, into a plain dict.
+///
+/// The dict contains the `timestamp` as string, the arrow `type_info` of the data,
+/// and the `parameters`, with each value tagged by its type:
+///
+/// ```python
+/// {
+///     "timestamp": "7340479876186425344/a6b5c2d4e4f64c0b",
+///     "type_info": {...},
+///     "parameters": {"open_telemetry_context": {"String": "..."}, "dora/sequence_number": {"Integer": 4}},
+/// }
+/// ```
+///
+/// The conversion is lossless: `dora.dict_to_metadata` restores the original metadata.
+///
+/// :type metadata: dora.Metadata | dict
+/// :rtype: dict
+#[pyfunction]
+pub fn metadata_to_dict(metadata: Bound<'_, PyAny>, py: Python) -> eyre::Result<Py<PyDict>> {
+    let metadata = if let Ok(metadata) = metadata.extract::<PyRef<PyMetadata>>() {
+        metadata.0.clone()
+    } else if let Some(event) = PyEvent::from_object(&metadata) {
+        match &event.borrow().event {
+            MergedEvent::Dora(Event::Input { metadata, .. }) => metadata.clone(),
+            _ => eyre::bail!("only input events have metadata"),
+        }
+    } else {
+        eyre::bail!("invalid `metadata` type, must be a `dora.Metadata` or an input event")
+    };
+
+    let dict = PyDict::new_bound(py);
+    dict.set_item("timestamp", metadata.timestamp().to_string())?;
+    dict.set_item("type_info", pythonize::pythonize(py, &metadata.type_info)?)?;
+    dict.set_item(
+        "parameters",
+        pythonize::pythonize(py, &metadata.parameters)?,
+    )?;
+    Ok(dict.unbind())
+}
+
+/// Creates a `dora.Metadata` from a dict in the format of `dora.metadata_to_dict`.
+///
+/// All keys are optional. A missing `timestamp` is set to the current time and a
+/// missing `type_info` describes empty data.
+///
+/// ```python
+/// metadata = dora.dict_to_metadata({"parameters": {"camera": {"String": "front"}}})
+/// assert dora.dict_to_metadata(dora.metadata_to_dict(metadata)) == metadata
+/// ```
+///
+/// :type metadata: dict
+/// :rtype: dora.Metadata
+#[pyfunction]
+pub fn dict_to_metadata(metadata: Bound<'_, PyDict>) -> eyre::Result<PyMetadata> {
+    let mut timestamp = None;
+    let mut type_info = None;
+    let mut parameters = None;
+    for (key, value) in metadata.iter() {
+        match key.extract::<String>()?.as_str() {
+            "timestamp" => {
+                let raw: String = value.extract()?;
+                timestamp = Some(
+                    raw.parse::<uhlc::Timestamp>()
+                        .map_err(|err| eyre::eyre!("invalid timestamp `{raw}`: {err:?}"))?,
+                );
+            }
+            "type_info" => {
+                type_info = Some(
+                    pythonize::depythonize::<ArrowTypeInfo>(&value)
+                        .context("invalid `type_info`")?,
+                )
+            }
+            "parameters" => {
+                parameters = Some(
+                    pythonize::depythonize::<MetadataParameters>(&value)
+                        .context("invalid `parameters`")?,
+                )
             }
+            other => eyre::bail!("unexpected metadata key `{other}`"),
         }
     }
+
+    Ok(PyMetadata(Metadata::from_parameters(
+        timestamp.unwrap_or_else(|| uhlc::HLC::default().new_timestamp()),
+        type_info.unwrap_or_else(ArrowTypeInfo::empty),
+        parameters.unwrap_or_default(),
+    )))
 }
 
-impl Node {
-    pub fn id(&self) -> String {
-        self.node_id.to_string()
+/// Converts a dataflow descriptor dict into YAML.
+///
+/// The dict must be in the format of the dataflow YAML file, as returned by
+/// `Node.dataflow_descriptor`. It is checked against the dataflow schema before
+/// it is converted. See `dora.builder.DataflowBuilder` to construct descriptors
+/// programmatically.
+///
+/// :type descriptor: dict
+/// :rtype: str
+#[pyfunction]
+pub fn descriptor_to_yaml(descriptor: Bound<'_, PyDict>) -> eyre::Result<String> {
+    pythonize::depythonize::<Descriptor>(&descriptor).context("invalid dataflow descriptor")?;
+    // serialize the original dict to avoid emitting defaults of omitted fields
+    let value = pythonize::depythonize::<serde_yaml::Value>(&descriptor)?;
+    serde_yaml::to_string(&value).context("failed to serialize dataflow descriptor")
+}
+
+/// Checks a dataflow descriptor for structural errors, without starting it.
+///
+/// The descriptor is either the path of a dataflow YAML file or a dict in the
+/// same format, e.g. as created through `dora.builder.DataflowBuilder`. The
+/// following problems are detected:
+///
+/// - invalid node definitions (`"invalid_node"`), e.g. nodes without `path`,
+/// - multiple nodes with the same ID (`"duplicate_node_id"`),
+/// - inputs mapped to nodes that don't exist (`"unknown_source"`),
+/// - inputs mapped to outputs that are not declared (`"undeclared_output"`),
+/// - cycles between nodes (`"cycle"`), unless `allow_cycles=True` is passed,
+/// - unknown or repeated nodes in the `shutdown_order`
+///   (`"invalid_shutdown_order"`).
+///
+/// Returns the list of all found errors, which is empty for valid dataflows.
+/// Each error is a dict of the form `{"type": str, "node": str | None,
+/// "message": str}`. A descriptor that cannot be parsed results in a single
+/// `"invalid_descriptor"` error:
+///
+/// ```python
+/// errors = dora.validate_dataflow("dataflow.yml")
+/// for error in errors:
+///     print(f"{error['type']}: {error['message']}")
+/// sys.exit(1 if errors else 0)
+/// ```
+///
+/// Dora can run dataflows with cycles, e.g. for feedback loops, so pass
+/// `allow_cycles=True` for such dataflows. Outputs that are declared at runtime
+/// through `Node.declare_dynamic_output` are reported as `"undeclared_output"`.
+/// In contrast to `dora check`, the existence of the node sources is not
+/// checked.
+///
+/// :type descriptor: str | os.PathLike | dict
+/// :type allow_cycles: bool, optional
+/// :rtype: list[dict]
+#[pyfunction]
+#[pyo3(signature = (descriptor, allow_cycles=false))]
+pub fn validate_dataflow(
+    descriptor: Bound<'_, PyAny>,
+    allow_cycles: bool,
+    py: Python,
+) -> eyre::Result<Py<PyList>> {
+    let parsed = if let Ok(dict) = descriptor.downcast::<PyDict>() {
+        pythonize::depythonize::<Descriptor>(dict).context("invalid dataflow descriptor")
+    } else {
+        let path: PathBuf = descriptor
+            .extract()
+            .context("`descriptor` must be a path or a dict")?;
+        let yaml =
+            std::fs::read(&path).with_context(|| format!("failed to read `{}`", path.display()))?;
+        Descriptor::parse(yaml)
+    };
+    let errors = PyList::empty_bound(py);
+    let error_dict = |ty: &str, node: Option<String>, message: String| {
+        let dict = PyDict::new_bound(py);
+        dict.set_item("type", ty)?;
+        dict.set_item("node", node)?;
+        dict.set_item("message", message)?;
+        eyre::Ok(dict)
+    };
+    match parsed {
+        Ok(descriptor) => {
+            for error in descriptor.check_structure(allow_cycles) {
+                errors.append(error_dict(
+                    error.kind.as_str(),
+                    error.node.map(|node| node.to_string()),
+                    error.message,
+                )?)?;
+            }
+        }
+        Err(err) => errors.append(error_dict("invalid_descriptor", None, format!("{err:?}"))?)?,
     }
+    Ok(errors.unbind())
 }
 
-/// Start a runtime for Operators
+/// Sets the log level of dora's internal logging for the given target.
+///
+/// The target is a Rust module path prefix, e.g. `dora_node_api` for the node
+/// API including its daemon connection. The level is one of `"trace"`,
+/// `"debug"`, `"info"`, `"warn"`, `"error"` and `"off"`, or `None` to remove
+/// the level of the target again. The messages are printed to stderr:
+///
+/// ```python
+/// dora.set_log_level("dora_node_api", "debug")
+/// node = Node()
+/// ```
+///
+/// The level only enables additional messages, messages that are enabled
+/// through `RUST_LOG` or the default `warn` level are still printed. Levels
+/// that are set before the node is created are applied once it is.
+///
+/// :type target: str
+/// :type level: str, optional
+/// :rtype: None
+#[pyfunction]
+#[pyo3(signature = (target, level=None))]
+pub fn set_log_level(target: &str, level: Option<&str>) -> eyre::Result<()> {
+    #[cfg(feature = "tracing")]
+    {
+        let level = level
+            .map(|level| {
+                level
+                    .parse()
+                    .map_err(|_| eyre::eyre!("invalid log level `{level}`"))
+            })
+            .transpose()?;
+        dora_node_api::set_log_level(target, level)
+    }
+    #[cfg(not(feature = "tracing"))]
+    {
+        let _ = (target, level);
+        eyre::bail!("dora was built without the `tracing` feature")
+    }
+}
+
+/// Returns the log level that was set for the target through `set_log_level`,
+/// or `None` if no level was set.
+///
+/// :type target: str
+/// :rtype: str
+#[pyfunction]
+pub fn get_log_level(target: &str) -> Option<String> {
+    #[cfg(feature = "tracing")]
+    {
+        dora_node_api::log_level(target).map(|level| level.to_string().to_lowercase())
+    }
+    #[cfg(not(feature = "tracing"))]
+    {
+        let _ = target;
+        None
+    }
+}
+
+/// Registers a schema of a named message type, e.g. `Detection`.
+///
+/// Outputs that are sent with `schema="Detection"` are validated against the
+/// schema, and so are the inputs that carry its name. Schemas can also be defined
+/// in the `schemas` section of the dataflow descriptor, which are registered when
+/// the node is created. Registering a different schema under an existing name
+/// raises an error:
+///
+/// ```python
+/// dora.register_schema("Detection", pa.schema([("label", pa.string()), ("score", pa.float32())]))
+/// node.send_output("detections", detections, schema="Detection")
+/// ```
 ///
+/// :type name: str
+/// :type schema: pyarrow.Schema
 /// :rtype: None
 #[pyfunction]
-pub fn start_runtime() -> eyre::Result<()> {
-    dora_runtime::main().wrap_err("Dora Runtime raised an error.")
+pub fn register_schema(name: &str, schema: PyArrowType<Schema>) -> eyre::Result<()> {
+    schema_registry::register(name, Arc::new(schema.0))
 }
 
 #[pymodule]
@@ -307,7 +4213,34 @@ fn dora(_py: Python, m: Bound<'_, PyModule>) -> PyResult<()> {
     dora_ros2_bridge_python::create_dora_ros2_bridge_module(&m)?;
 
     m.add_function(wrap_pyfunction!(start_runtime, &m)?)?;
+    m.add_function(wrap_pyfunction!(list_operators, &m)?)?;
+    m.add_function(wrap_pyfunction!(operator_info, &m)?)?;
+    m.add_function(wrap_pyfunction!(list_dataflows, &m)?)?;
+    m.add_function(wrap_pyfunction!(external_stream_from_async, &m)?)?;
+    m.add_function(wrap_pyfunction!(metadata_to_dict, &m)?)?;
+    m.add_function(wrap_pyfunction!(dict_to_metadata, &m)?)?;
+    m.add_function(wrap_pyfunction!(descriptor_to_yaml, &m)?)?;
+    m.add_function(wrap_pyfunction!(validate_dataflow, &m)?)?;
+    m.add_function(wrap_pyfunction!(set_log_level, &m)?)?;
+    m.add_function(wrap_pyfunction!(get_log_level, &m)?)?;
+    m.add_function(wrap_pyfunction!(register_schema, &m)?)?;
+    errors::register(&m)?;
+    m.add_class::<EventTee>()?;
+    m.add_class::<EventType>()?;
+    m.add_class::<ExternalEventStream>()?;
+    m.add_class::<LifecycleEvents>()?;
     m.add_class::<Node>()?;
+    m.add_class::<PyEvent>()?;
+    // events replace the dicts that were returned before, so they should still
+    // be recognized as mappings
+    m.py()
+        .import_bound("collections.abc")?
+        .getattr("Mapping")?
+        .call_method1("register", (m.getattr("Event")?,))?;
+    m.add_class::<PyMetadata>()?;
+    m.add_class::<RecordBatchOutput>()?;
+    m.add_class::<Recording>()?;
+    m.add_class::<Transaction>()?;
     m.setattr("__version__", env!("CARGO_PKG_VERSION"))?;
     m.setattr("__author__", "Dora-rs Authors")?;
 
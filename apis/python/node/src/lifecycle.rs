@@ -0,0 +1,98 @@
+//! Notifications about nodes joining or leaving the dataflow, and about their
+//! progress and statistics.
+
+use std::time::Duration;
+
+use dora_node_api::LifecycleEvent;
+use pyo3::{prelude::*, types::PyDict};
+
+/// Iterator over the lifecycle events of the dataflow.
+///
+/// Created through `Node.lifecycle_events`. Each event is a dict of the form
+/// `{"type": "NODE_JOINED" | "NODE_LEFT", "node": str}`, or
+/// `{"type": "PROGRESS", "node": str, "task": str, "progress": float, "message": str | None}`
+/// for progress reports. Nodes that were created with `emit_summary_on_close=True`
+/// report their statistics as `{"type": "SUMMARY", "node": str, "received": int,
+/// "received_bytes": int, "dropped": int, "sent": int, "sent_bytes": int,
+/// "mean_latency": float | None}` when they close, with the latency in seconds.
+///
+/// ```python
+/// roster = set()
+/// for event in node.lifecycle_events():
+///     if event["type"] == "NODE_JOINED":
+///         roster.add(event["node"])
+///     elif event["type"] == "NODE_LEFT":
+///         roster.discard(event["node"])
+/// ```
+#[pyclass]
+pub struct LifecycleEvents {
+    receiver: flume::Receiver<LifecycleEvent>,
+}
+
+impl LifecycleEvents {
+    pub fn new(receiver: flume::Receiver<LifecycleEvent>) -> Self {
+        Self { receiver }
+    }
+}
+
+#[pymethods]
+impl LifecycleEvents {
+    /// Waits for the next lifecycle event.
+    ///
+    /// Returns `None` if no event arrived within `timeout` seconds or if the
+    /// event stream of the node was closed.
+    ///
+    /// :type timeout: float, optional
+    /// :rtype: dict
+    #[pyo3(signature = (timeout=None))]
+    pub fn recv(&self, py: Python<'_>, timeout: Option<f32>) -> PyResult<Option<Py<PyDict>>> {
+        let receiver = &self.receiver;
+        let event = py.allow_threads(|| match timeout {
+            Some(timeout) => receiver.recv_timeout(Duration::from_secs_f32(timeout)).ok(),
+            None => receiver.recv().ok(),
+        });
+        event.map(|event| to_py_dict(py, event)).transpose()
+    }
+
+    fn __iter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+        slf
+    }
+
+    fn __next__(&self, py: Python<'_>) -> PyResult<Option<Py<PyDict>>> {
+        self.recv(py, None)
+    }
+}
+
+fn to_py_dict(py: Python<'_>, event: LifecycleEvent) -> PyResult<Py<PyDict>> {
+    let dict = PyDict::new_bound(py);
+    let (ty, node_id) = match event {
+        LifecycleEvent::NodeJoined { node_id } => ("NODE_JOINED", node_id),
+        LifecycleEvent::NodeLeft { node_id } => ("NODE_LEFT", node_id),
+        LifecycleEvent::Progress {
+            node_id,
+            task,
+            progress,
+            message,
+        } => {
+            dict.set_item("task", task)?;
+            dict.set_item("progress", progress)?;
+            dict.set_item("message", message)?;
+            ("PROGRESS", node_id)
+        }
+        LifecycleEvent::Summary { node_id, summary } => {
+            dict.set_item("received", summary.received)?;
+            dict.set_item("received_bytes", summary.received_bytes)?;
+            dict.set_item("dropped", summary.dropped)?;
+            dict.set_item("sent", summary.sent)?;
+            dict.set_item("sent_bytes", summary.sent_bytes)?;
+            dict.set_item(
+                "mean_latency",
+                summary.mean_latency.map(|latency| latency.as_secs_f64()),
+            )?;
+            ("SUMMARY", node_id)
+        }
+    };
+    dict.set_item("type", ty)?;
+    dict.set_item("node", node_id.to_string())?;
+    Ok(dict.unbind())
+}
@@ -0,0 +1,78 @@
+//! Barrier-style synchronization of inputs by tick number, see `Node.next_tick`.
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+use dora_node_api::{dora_core::config::DataId, Metadata, Parameter};
+use dora_operator_api_python::PyEvent;
+use pyo3::prelude::*;
+
+/// Input events that wait for the other inputs of their tick.
+#[derive(Default)]
+pub struct Ticks {
+    pending: BTreeMap<i64, HashMap<DataId, Py<PyEvent>>>,
+    /// Last tick that was returned, events of older ticks are stale.
+    last: Option<i64>,
+}
+
+/// A tick for which all inputs have an event.
+pub struct CompleteTick {
+    pub tick: i64,
+    pub events: HashMap<DataId, Py<PyEvent>>,
+    /// Older ticks that were discarded because they were still missing events.
+    pub skipped: Vec<i64>,
+}
+
+/// The tick number and the events per input ID, as returned by `Node.next_tick`.
+pub type CompleteTickEvents = (i64, HashMap<String, PyObject>);
+
+impl Ticks {
+    /// Buffers the event of an input until its tick is complete.
+    ///
+    /// Returns a warning if the event was discarded because its tick was
+    /// already returned, or if it replaced an event of the same input and tick.
+    pub fn insert(&mut self, id: DataId, tick: i64, event: Py<PyEvent>) -> Option<String> {
+        if self.last.is_some_and(|last| tick <= last) {
+            return Some(format!(
+                "discarding event of input `{id}` for tick {tick}, which was already returned"
+            ));
+        }
+        let previous = self
+            .pending
+            .entry(tick)
+            .or_default()
+            .insert(id.clone(), event);
+        previous.map(|_| format!("input `{id}` sent more than one event for tick {tick}"))
+    }
+
+    /// Removes the oldest tick that has an event for each of the `inputs`.
+    ///
+    /// Ticks are returned in order. Incomplete ticks before the returned tick
+    /// are discarded, as their missing events will not arrive anymore once all
+    /// inputs moved on to a newer tick.
+    pub fn take_complete(&mut self, inputs: &BTreeSet<DataId>) -> Option<CompleteTick> {
+        let tick = *self
+            .pending
+            .iter()
+            .find(|(_, events)| inputs.iter().all(|id| events.contains_key(id)))?
+            .0;
+        let mut newer = self.pending.split_off(&tick);
+        let mut events = newer.remove(&tick).unwrap_or_default();
+        let older = std::mem::replace(&mut self.pending, newer);
+        events.retain(|id, _| inputs.contains(id));
+        self.last = Some(tick);
+        Some(CompleteTick {
+            tick,
+            events,
+            skipped: older.into_keys().collect(),
+        })
+    }
+}
+
+/// Reads the tick number of an input from the metadata parameter `key`.
+pub fn tick(metadata: &Metadata, key: &str) -> eyre::Result<i64> {
+    match metadata.parameters.get(key) {
+        Some(Parameter::Integer(tick)) => Ok(*tick),
+        Some(other) => eyre::bail!("metadata `{key}` must be an integer, got {other:?}"),
+        None => eyre::bail!("missing `{key}` metadata"),
+    }
+}
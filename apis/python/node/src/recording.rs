@@ -0,0 +1,81 @@
+//! Replay of inputs that were recorded through `Node(record=...)`.
+
+use std::{
+    path::Path,
+    time::{Duration, Instant},
+};
+
+use dora_node_api::{merged::MergedEvent, uhlc, Event};
+use dora_operator_api_python::{recording::RecordingReader, PyEvent};
+use pyo3::prelude::*;
+
+/// Iterator over the inputs of a recording.
+///
+/// Created through `Node.from_recording`. Yields the recorded input events in
+/// the order in which they were received, including their original metadata.
+///
+/// ```python
+/// for event in Node.from_recording("events.arrow"):
+///     process(event["id"], event["value"])
+/// ```
+#[pyclass]
+pub struct Recording {
+    reader: RecordingReader,
+    /// Playback speed relative to the recorded timing, `0` for no delays.
+    speed: f64,
+    /// Playback start time and timestamp of the first recorded event.
+    start: Option<(Instant, uhlc::Timestamp)>,
+    /// Whether events are returned as `dora.Event` instead of a `dict`.
+    event_class: bool,
+}
+
+impl Recording {
+    pub fn open(path: &str, speed: f64, event_class: bool) -> eyre::Result<Self> {
+        if !speed.is_finite() || speed < 0.0 {
+            eyre::bail!("`speed` must be a non-negative number, got {speed}");
+        }
+        Ok(Self {
+            reader: RecordingReader::open(Path::new(path))?,
+            speed,
+            start: None,
+            event_class,
+        })
+    }
+
+    /// Returns how long to wait before the event with the given timestamp is due.
+    fn delay(&mut self, timestamp: uhlc::Timestamp) -> Option<Duration> {
+        if self.speed == 0.0 {
+            return None;
+        }
+        let (start, first) = *self.start.get_or_insert((Instant::now(), timestamp));
+        let offset = timestamp
+            .get_time()
+            .to_duration()
+            .saturating_sub(first.get_time().to_duration());
+        let due = start + offset.div_f64(self.speed);
+        due.checked_duration_since(Instant::now())
+    }
+}
+
+#[pymethods]
+impl Recording {
+    fn __iter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+        slf
+    }
+
+    fn __next__(&mut self, py: Python<'_>) -> eyre::Result<Option<PyObject>> {
+        let Some(input) = self.reader.next().transpose()? else {
+            return Ok(None);
+        };
+        if let Some(delay) = self.delay(input.metadata.timestamp()) {
+            py.allow_threads(|| std::thread::sleep(delay));
+        }
+        let event = Event::Input {
+            id: input.id,
+            metadata: input.metadata,
+            data: input.value.into(),
+        };
+        let event = Py::new(py, PyEvent::new(MergedEvent::Dora(event), None))?;
+        Ok(Some(PyEvent::to_python(event, py, self.event_class)?))
+    }
+}
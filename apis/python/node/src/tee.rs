@@ -0,0 +1,74 @@
+//! Forks of the event stream for tee-style processing.
+
+use std::time::Duration;
+
+use dora_node_api::{merged::MergedEvent, Event};
+use dora_operator_api_python::{NodeCleanupHandle, PyEvent};
+use pyo3::prelude::*;
+
+/// A fork of the event stream of a node.
+///
+/// Created through `Node.tee`. Receives a copy of every event that is returned
+/// by `Node.next()`, which allows processing the same inputs independently,
+/// e.g. in a separate logging thread:
+///
+/// ```python
+/// tee = node.tee()
+/// threading.Thread(target=lambda: [log(event) for event in tee], daemon=True).start()
+///
+/// for event in node:
+///     process(event)
+/// ```
+#[pyclass]
+pub struct EventTee {
+    receiver: flume::Receiver<Event>,
+    cleanup_handle: NodeCleanupHandle,
+    /// Whether events are returned as `dora.Event`, see `Node(event_class=...)`.
+    event_class: bool,
+}
+
+impl EventTee {
+    pub fn new(
+        receiver: flume::Receiver<Event>,
+        cleanup_handle: NodeCleanupHandle,
+        event_class: bool,
+    ) -> Self {
+        Self {
+            receiver,
+            cleanup_handle,
+            event_class,
+        }
+    }
+}
+
+#[pymethods]
+impl EventTee {
+    /// Waits for the next copied event.
+    ///
+    /// Returns `None` if no event arrived within `timeout` seconds. The copies
+    /// include the `STOP` event, after which no further events arrive.
+    ///
+    /// :type timeout: float, optional
+    /// :rtype: dict
+    #[pyo3(signature = (timeout=None))]
+    pub fn recv(&self, py: Python<'_>, timeout: Option<f32>) -> PyResult<Option<PyObject>> {
+        let receiver = &self.receiver;
+        let event = py.allow_threads(|| match timeout {
+            Some(timeout) => receiver.recv_timeout(Duration::from_secs_f32(timeout)).ok(),
+            None => receiver.recv().ok(),
+        });
+        let Some(event) = event else {
+            return Ok(None);
+        };
+        let event = PyEvent::new(MergedEvent::Dora(event), Some(self.cleanup_handle.clone()));
+        PyEvent::to_python(Py::new(py, event)?, py, self.event_class).map(Some)
+    }
+
+    fn __iter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+        slf
+    }
+
+    fn __next__(&self, py: Python<'_>) -> PyResult<Option<PyObject>> {
+        self.recv(py, None)
+    }
+}
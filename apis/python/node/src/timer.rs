@@ -0,0 +1,265 @@
+//! Periodic synthetic inputs that are created through `Node.add_timer`.
+//!
+//! Timers run on the monotonic wall clock by default. After `Node.use_sim_time`,
+//! they run on the simulation time instead, which only advances when the
+//! simulator publishes a new time, see [`Timers::advance_sim_time`].
+
+use std::time::{Duration, Instant};
+
+use arrow::{
+    array::{new_empty_array, Array, AsArray},
+    datatypes::{DataType, Float64Type, Int64Type, UInt64Type},
+};
+use dora_node_api::{
+    dora_core::{config::DataId, metadata::ArrowTypeInfoExt},
+    metadata::{ArrowTypeInfo, TIMESTAMP_NS},
+    uhlc, Event, Metadata, Parameter,
+};
+
+/// The timers of a node, which are polled while the node waits for events.
+pub struct Timers {
+    timers: Vec<Timer>,
+    clock: uhlc::HLC,
+    source: TimeSource,
+    /// Reference point of the deadlines of wall clock timers.
+    epoch: Instant,
+}
+
+/// The clock that drives the timers.
+enum TimeSource {
+    Wall,
+    Sim {
+        /// Input on which the simulator publishes the time.
+        input: DataId,
+        /// Latest published time, `None` until the first time was received.
+        now: Option<Duration>,
+    },
+}
+
+struct Timer {
+    id: DataId,
+    period: Duration,
+    /// Schedule ticks at multiples of `period` after `start` instead of one
+    /// `period` after the previous tick, so that delays don't accumulate.
+    drift_correct: bool,
+    /// Time of the clock when the timer was added.
+    start: Duration,
+    /// Number of periods after `start` of the next tick, if `drift_correct`.
+    next_index: u32,
+    deadline: Duration,
+    stats: TimerStats,
+}
+
+/// Timing statistics of a timer, see `Node.timer_stats`.
+#[derive(Debug, Clone, Default)]
+pub struct TimerStats {
+    pub ticks: u64,
+    /// Ticks that were skipped because the node did not wait for events in time.
+    pub missed: u64,
+    /// Sum of the delays between the deadline and the delivery of each tick.
+    pub total_jitter: Duration,
+    pub max_jitter: Duration,
+}
+
+impl TimerStats {
+    pub fn mean_jitter(&self) -> Duration {
+        self.total_jitter.div_f64(self.ticks.max(1) as f64)
+    }
+}
+
+impl Default for Timers {
+    fn default() -> Self {
+        Self {
+            timers: Vec::new(),
+            clock: uhlc::HLC::default(),
+            source: TimeSource::Wall,
+            epoch: Instant::now(),
+        }
+    }
+}
+
+impl Timers {
+    /// Drives the timers by the time that is published on the given input.
+    ///
+    /// Must be called before any timer is added.
+    pub fn use_sim_time(&mut self, input: DataId) -> eyre::Result<()> {
+        if !self.timers.is_empty() {
+            eyre::bail!("simulation time must be enabled before adding timers");
+        }
+        self.source = TimeSource::Sim { input, now: None };
+        Ok(())
+    }
+
+    /// Current simulation time, `None` if the timers run on the wall clock or
+    /// no time was published yet.
+    pub fn sim_time(&self) -> Option<Duration> {
+        match self.source {
+            TimeSource::Wall => None,
+            TimeSource::Sim { now, .. } => now,
+        }
+    }
+
+    /// Whether the timers run on the simulation time.
+    pub fn is_sim_time(&self) -> bool {
+        matches!(self.source, TimeSource::Sim { .. })
+    }
+
+    /// Current time of the clock that drives the timers, relative to `epoch` for
+    /// the wall clock.
+    fn now(&self) -> Option<Duration> {
+        match self.source {
+            TimeSource::Wall => Some(self.epoch.elapsed()),
+            TimeSource::Sim { now, .. } => now,
+        }
+    }
+
+    /// Advances the simulation time if the value was received on the input of
+    /// the simulation clock.
+    ///
+    /// The value must contain a single time since the UNIX epoch, either as
+    /// integer in nanoseconds or as float in seconds.
+    pub fn advance_sim_time(&mut self, id: &DataId, value: &dyn Array) -> eyre::Result<()> {
+        let TimeSource::Sim { input, now } = &mut self.source else {
+            return Ok(());
+        };
+        if input != id {
+            return Ok(());
+        }
+        if value.len() != 1 || value.is_null(0) {
+            eyre::bail!(
+                "simulation time on `{id}` must be a single value, got {} values",
+                value.len()
+            );
+        }
+        let time = if let Some(nanos) = value.as_primitive_opt::<Int64Type>() {
+            let nanos = u64::try_from(nanos.value(0))
+                .map_err(|_| eyre::eyre!("simulation time on `{id}` must not be negative"))?;
+            Duration::from_nanos(nanos)
+        } else if let Some(nanos) = value.as_primitive_opt::<UInt64Type>() {
+            Duration::from_nanos(nanos.value(0))
+        } else if let Some(secs) = value.as_primitive_opt::<Float64Type>() {
+            Duration::try_from_secs_f64(secs.value(0))
+                .map_err(|err| eyre::eyre!("invalid simulation time on `{id}`: {err}"))?
+        } else {
+            eyre::bail!(
+                "simulation time on `{id}` must be `int64`, `uint64`, or `float64`, got `{}`",
+                value.data_type()
+            );
+        };
+        match *now {
+            Some(previous) if time < previous => {
+                eyre::bail!("simulation time on `{id}` went backwards: {time:?} < {previous:?}")
+            }
+            Some(_) => {}
+            // timers that were added before the first time start at that time
+            None => {
+                for timer in &mut self.timers {
+                    timer.start += time;
+                    timer.deadline += time;
+                }
+            }
+        }
+        *now = Some(time);
+        Ok(())
+    }
+
+    pub fn add(&mut self, id: DataId, period: Duration, drift_correct: bool) -> eyre::Result<()> {
+        if period.is_zero() {
+            eyre::bail!("period of timer `{id}` must be positive");
+        }
+        if self.contains(&id) {
+            eyre::bail!("timer `{id}` already exists");
+        }
+        let start = self.now().unwrap_or_default();
+        self.timers.push(Timer {
+            id,
+            period,
+            drift_correct,
+            start,
+            next_index: 1,
+            deadline: start + period,
+            stats: TimerStats::default(),
+        });
+        Ok(())
+    }
+
+    pub fn remove(&mut self, id: &DataId) -> bool {
+        let len = self.timers.len();
+        self.timers.retain(|timer| &timer.id != id);
+        self.timers.len() != len
+    }
+
+    pub fn contains(&self, id: &DataId) -> bool {
+        self.timers.iter().any(|timer| &timer.id == id)
+    }
+
+    /// The deadline of the next tick of any timer.
+    ///
+    /// Always `None` for simulation time, since ticks only become due when the
+    /// time is advanced through [`Self::advance_sim_time`].
+    pub fn next_deadline(&self) -> Option<Instant> {
+        match self.source {
+            TimeSource::Wall => {
+                let deadline = self.timers.iter().map(|timer| timer.deadline).min()?;
+                Some(self.epoch + deadline)
+            }
+            TimeSource::Sim { .. } => None,
+        }
+    }
+
+    /// Returns the input event of the most overdue timer, if any timer is due.
+    ///
+    /// With simulation time, every tick is delivered, in deadline order, even if
+    /// the time advanced by multiple periods at once. The ticks carry their
+    /// simulation time as `TIMESTAMP_NS` parameter.
+    pub fn poll(&mut self) -> Option<Event> {
+        let now = self.now()?;
+        let sim_time = self.is_sim_time();
+        let timer = self
+            .timers
+            .iter_mut()
+            .filter(|timer| timer.deadline <= now)
+            .min_by_key(|timer| timer.deadline)?;
+
+        let jitter = now - timer.deadline;
+        timer.stats.ticks += 1;
+        timer.stats.total_jitter += jitter;
+        timer.stats.max_jitter = timer.stats.max_jitter.max(jitter);
+        let tick_time = timer.deadline;
+        if sim_time {
+            timer.deadline += timer.period;
+        } else if timer.drift_correct {
+            // skip the ticks that were missed instead of delivering them in a burst
+            let elapsed = (now - timer.start).as_nanos() / timer.period.as_nanos();
+            let next_index = u32::try_from(elapsed + 1).unwrap_or(u32::MAX);
+            timer.stats.missed += u64::from((next_index - timer.next_index).saturating_sub(1));
+            timer.next_index = next_index;
+            timer.deadline = timer.start + timer.period * next_index;
+        } else {
+            timer.deadline = now + timer.period;
+        }
+
+        let mut metadata = Metadata::new(self.clock.new_timestamp(), ArrowTypeInfo::empty());
+        if sim_time {
+            metadata.parameters.insert(
+                TIMESTAMP_NS.to_owned(),
+                Parameter::Integer(duration_to_nanos(tick_time)),
+            );
+        }
+        Some(Event::Input {
+            id: timer.id.clone(),
+            metadata,
+            data: new_empty_array(&DataType::Null).into(),
+        })
+    }
+
+    pub fn stats(&self) -> impl Iterator<Item = (&DataId, Duration, &TimerStats)> {
+        self.timers
+            .iter()
+            .map(|timer| (&timer.id, timer.period, &timer.stats))
+    }
+}
+
+pub fn duration_to_nanos(duration: Duration) -> i64 {
+    duration.as_nanos().try_into().unwrap_or(i64::MAX)
+}
@@ -0,0 +1,126 @@
+//! Staging of outputs that are sent together or not at all.
+
+use arrow::array::ArrayRef;
+use dora_node_api::{dora_core::config::DataId, DoraNode, MetadataParameters};
+use dora_operator_api_python::{metadata_codec::MetadataCodec, CleanupHandle};
+use pyo3::{prelude::*, types::PyDict};
+
+use crate::{
+    data_to_array,
+    errors::{dora_error, Operation},
+    parse_metadata,
+};
+
+/// A set of outputs that are sent together on success, or not at all.
+///
+/// Created through `Node.transaction`. Outputs sent through the transaction are
+/// buffered by the node and only sent when the `with` block exits normally. If
+/// the block raises an exception, e.g. because the node crashes mid-tick, the
+/// buffered outputs are discarded, so downstream nodes never see a partial set
+/// of related outputs:
+///
+/// ```python
+/// with node.transaction() as tx:
+///     tx.send_output("pose", pose)
+///     tx.send_output("covariance", covariance)
+/// ```
+///
+/// The data and metadata are validated when they are buffered, so invalid
+/// outputs raise an error before anything is sent.
+#[pyclass]
+pub struct Transaction {
+    node: CleanupHandle<DoraNode>,
+    strict_metadata: bool,
+    metadata_codec: Option<MetadataCodec>,
+    pid: u32,
+    staged: Vec<(DataId, MetadataParameters, ArrayRef)>,
+    finished: bool,
+}
+
+impl Transaction {
+    pub fn new(
+        node: CleanupHandle<DoraNode>,
+        strict_metadata: bool,
+        metadata_codec: Option<MetadataCodec>,
+        pid: u32,
+    ) -> Self {
+        Self {
+            node,
+            strict_metadata,
+            metadata_codec,
+            pid,
+            staged: Vec::new(),
+            finished: false,
+        }
+    }
+
+    fn commit(&mut self) -> eyre::Result<()> {
+        let mut node = self.node.get_mut();
+        for (output_id, parameters, data) in self.staged.drain(..) {
+            node.send_output(output_id, parameters, data)
+                .map_err(|err| dora_error(err, Operation::Send))?;
+        }
+        Ok(())
+    }
+}
+
+#[pymethods]
+impl Transaction {
+    /// Buffers an output until the transaction is committed.
+    ///
+    /// The `data` and `metadata` are interpreted like for `Node.send_output`.
+    ///
+    /// :type output_id: str
+    /// :type data: pyarrow.Array | bytes
+    /// :type metadata: dict, optional
+    /// :rtype: None
+    #[pyo3(signature = (output_id, data, metadata=None))]
+    pub fn send_output(
+        &mut self,
+        output_id: String,
+        data: Bound<'_, PyAny>,
+        metadata: Option<Bound<'_, PyDict>>,
+    ) -> eyre::Result<()> {
+        if self.finished {
+            eyre::bail!("transaction was already committed or discarded");
+        }
+        let parameters = parse_metadata(metadata, self.strict_metadata, self.metadata_codec)?;
+        let data = data_to_array(&data)?;
+        self.staged
+            .push((DataId::from(output_id), parameters, data));
+        Ok(())
+    }
+
+    /// Returns the number of buffered outputs.
+    ///
+    /// :rtype: int
+    pub fn __len__(&self) -> usize {
+        self.staged.len()
+    }
+
+    pub fn __enter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+        slf
+    }
+
+    /// Sends the buffered outputs if the `with` block exited normally, and
+    /// discards them otherwise.
+    ///
+    /// Exceptions of the `with` block are not suppressed.
+    #[pyo3(signature = (exc_type, _exc_value, _traceback))]
+    pub fn __exit__(
+        &mut self,
+        exc_type: Option<Bound<'_, PyAny>>,
+        _exc_value: Option<Bound<'_, PyAny>>,
+        _traceback: Option<Bound<'_, PyAny>>,
+    ) -> eyre::Result<bool> {
+        self.finished = true;
+        if self.pid != std::process::id() {
+            eyre::bail!("transaction cannot be committed after `fork`");
+        }
+        match exc_type {
+            None => self.commit()?,
+            Some(_) => self.staged.clear(),
+        }
+        Ok(false)
+    }
+}
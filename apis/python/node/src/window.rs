@@ -0,0 +1,52 @@
+//! Sliding windows over the most recent events of an input, see `Node.window`.
+
+use std::collections::{HashMap, VecDeque};
+
+use dora_node_api::{dora_core::config::DataId, merged::MergedEvent, Event};
+use dora_operator_api_python::PyEvent;
+use pyo3::prelude::*;
+
+/// Ring buffers of the events that were returned by `Node.next`, per input.
+#[derive(Default)]
+pub struct Windows {
+    windows: HashMap<DataId, Window>,
+}
+
+struct Window {
+    capacity: usize,
+    events: VecDeque<Py<PyEvent>>,
+}
+
+impl Windows {
+    /// Returns the last `n` events of the input, oldest first.
+    ///
+    /// Starts buffering the events of the input on the first call and grows
+    /// the buffer if `n` is larger than on previous calls.
+    pub fn get(&mut self, py: Python<'_>, input_id: DataId, n: usize) -> Vec<Py<PyEvent>> {
+        let window = self.windows.entry(input_id).or_insert_with(|| Window {
+            capacity: n,
+            events: VecDeque::with_capacity(n),
+        });
+        window.capacity = window.capacity.max(n);
+        let skip = window.events.len().saturating_sub(n);
+        window
+            .events
+            .iter()
+            .skip(skip)
+            .map(|event| event.clone_ref(py))
+            .collect()
+    }
+
+    /// Adds the event to the window of its input, if there is one.
+    pub fn record(&mut self, py: Python<'_>, event: &Py<PyEvent>) {
+        let MergedEvent::Dora(Event::Input { id, .. }) = &event.borrow(py).event else {
+            return;
+        };
+        if let Some(window) = self.windows.get_mut(id) {
+            if window.events.len() == window.capacity {
+                window.events.pop_front();
+            }
+            window.events.push_back(event.clone_ref(py));
+        }
+    }
+}
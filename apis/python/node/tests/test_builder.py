@@ -0,0 +1,36 @@
+import pytest
+
+from dora.builder import DataflowBuilder, DataflowBuilderError
+
+
+def test_namespaced_source():
+    dataflow = DataflowBuilder()
+    camera = dataflow.add_node("perception/camera", path="camera.py")
+    camera.add_output("image")
+    plot = dataflow.add_node("plot", path="plot.py")
+    plot.add_input("image", camera.output("image"))
+
+    dataflow.validate()
+    assert dataflow.to_dict()["nodes"][1]["inputs"] == {
+        "image": "perception/camera/image"
+    }
+
+
+def test_namespaced_source_uses_longest_node_id():
+    dataflow = DataflowBuilder()
+    dataflow.add_node("perception", path="perception.py").add_output("camera/image")
+    dataflow.add_node("perception/camera", path="camera.py").add_output("depth")
+    plot = dataflow.add_node("plot", path="plot.py")
+    plot.add_input("image", "perception/camera/image")
+
+    with pytest.raises(DataflowBuilderError, match="undeclared output"):
+        dataflow.validate()
+
+
+def test_unknown_source_node():
+    dataflow = DataflowBuilder()
+    plot = dataflow.add_node("plot", path="plot.py")
+    plot.add_input("image", "perception/camera/image")
+
+    with pytest.raises(DataflowBuilderError, match="unknown node `perception`"):
+        dataflow.validate()
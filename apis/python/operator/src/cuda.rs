@@ -0,0 +1,91 @@
+//! Sending of GPU buffers that implement the CUDA array interface, e.g. `cupy`
+//! arrays or `torch` tensors on a CUDA device.
+//!
+//! Device memory is not shared with the receivers, so the buffer is copied to
+//! host memory and sent as a flat arrow array. The shape, element type, and
+//! device of the buffer are recorded in the metadata, so that receivers can
+//! restore the array and move it back to the GPU.
+
+use dora_node_api::{MetadataParameters, Parameter};
+use eyre::{Context, Result};
+use pyo3::prelude::*;
+
+/// Metadata parameter key of the shape of a sent GPU buffer.
+pub const CUDA_SHAPE: &str = "cuda_shape";
+/// Metadata parameter key of the element type of a sent GPU buffer, as numpy
+/// type string, e.g. `<f4`.
+pub const CUDA_TYPESTR: &str = "cuda_typestr";
+/// Metadata parameter key of the ordinal of the CUDA device that held a sent
+/// buffer, if known.
+pub const CUDA_DEVICE: &str = "cuda_device";
+
+/// Copies a buffer with a `__cuda_array_interface__` to host memory, returns
+/// `None` for other values.
+///
+/// Returns the flattened data as `pyarrow` array and the metadata parameters
+/// that describe the buffer.
+pub fn cuda_to_host(value: &Bound<'_, PyAny>) -> Result<Option<(PyObject, MetadataParameters)>> {
+    let Ok(interface) = value.getattr("__cuda_array_interface__") else {
+        return Ok(None);
+    };
+    let py = value.py();
+    let shape: Vec<i64> = interface
+        .get_item("shape")?
+        .extract()
+        .context("invalid `shape` in `__cuda_array_interface__`")?;
+    let typestr: String = interface
+        .get_item("typestr")?
+        .extract()
+        .context("invalid `typestr` in `__cuda_array_interface__`")?;
+
+    let host = copy_to_host(value)?;
+    let flat = host.call_method1("reshape", (-1,))?;
+    let array = py.import_bound("pyarrow")?.call_method1("array", (flat,))?;
+
+    let mut parameters = MetadataParameters::new();
+    parameters.insert(CUDA_SHAPE.to_owned(), Parameter::ListInt(shape));
+    parameters.insert(CUDA_TYPESTR.to_owned(), Parameter::String(typestr));
+    if let Some(device) = device_ordinal(value) {
+        parameters.insert(CUDA_DEVICE.to_owned(), Parameter::Integer(device));
+    }
+    Ok(Some((array.unbind(), parameters)))
+}
+
+/// Copies the buffer into a numpy array, using the library of the buffer if
+/// possible.
+fn copy_to_host<'py>(value: &Bound<'py, PyAny>) -> Result<Bound<'py, PyAny>> {
+    let py = value.py();
+    // `cupy` arrays
+    if value.hasattr("get")? && value.hasattr("device")? && !value.hasattr("cpu")? {
+        return Ok(value.call_method0("get")?);
+    }
+    // `torch` tensors
+    if value.hasattr("cpu")? && value.hasattr("detach")? {
+        return Ok(value
+            .call_method0("detach")?
+            .call_method0("cpu")?
+            .call_method0("numpy")?);
+    }
+    // other libraries, through the generic interface of `cupy` or `numba`
+    if let Ok(cupy) = py.import_bound("cupy") {
+        let array = cupy.call_method1("asarray", (value,))?;
+        return Ok(cupy.call_method1("asnumpy", (array,))?);
+    }
+    if let Ok(cuda) = py.import_bound("numba.cuda") {
+        let array = cuda.call_method1("as_cuda_array", (value,))?;
+        return Ok(array.call_method0("copy_to_host")?);
+    }
+    eyre::bail!(
+        "failed to copy GPU buffer of type `{}` to host memory, install `cupy` or `numba`",
+        value.get_type().name()?
+    )
+}
+
+/// The CUDA device of the buffer, as reported by `cupy` (`device.id`) or
+/// `torch` (`device.index`).
+fn device_ordinal(value: &Bound<'_, PyAny>) -> Option<i64> {
+    let device = value.getattr("device").ok()?;
+    ["id", "index"]
+        .into_iter()
+        .find_map(|attr| device.getattr(attr).ok()?.extract().ok())
+}
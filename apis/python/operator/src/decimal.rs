@@ -0,0 +1,121 @@
+//! Transfer of exact decimal values, e.g. prices or measurements that must not be
+//! rounded to binary floating point.
+//!
+//! A list of `decimal.Decimal` values is sent as a `Decimal128` array with the
+//! smallest precision and scale that represent all values exactly. Receivers get
+//! `decimal.Decimal` values back through pyarrow.
+
+use std::sync::Arc;
+
+use arrow::array::{ArrayRef, Decimal128Array};
+use eyre::{Context, ContextCompat, Result};
+use pyo3::{
+    prelude::*,
+    types::{PyList, PyTuple},
+};
+
+/// Maximum number of digits of a `Decimal128` value.
+const MAX_PRECISION: usize = 38;
+
+/// Converts a list of `decimal.Decimal` values to a `Decimal128` array, returns
+/// `None` for other values.
+///
+/// Lists are detected by their first element, all other elements must be
+/// `decimal.Decimal` values or `None`, which is sent as null.
+pub fn decimal_array_from_python(data: &Bound<'_, PyAny>) -> Result<Option<ArrayRef>> {
+    if !(data.is_instance_of::<PyList>() || data.is_instance_of::<PyTuple>()) || data.len()? == 0 {
+        return Ok(None);
+    }
+    let decimal = data.py().import_bound("decimal")?.getattr("Decimal")?;
+    if !data.get_item(0)?.is_instance(&decimal)? {
+        return Ok(None);
+    }
+
+    let mut values = Vec::new();
+    for (index, value) in data.iter()?.enumerate() {
+        let value = value?;
+        if value.is_none() {
+            values.push(None);
+        } else if value.is_instance(&decimal)? {
+            let parts = DecimalParts::from_python(&value)
+                .with_context(|| format!("invalid decimal value at index {index}"))?;
+            values.push(Some(parts));
+        } else {
+            eyre::bail!(
+                "decimal lists must only contain `decimal.Decimal` values or `None`, \
+                got `{}` at index {index}",
+                value.get_type().name()?
+            );
+        }
+    }
+
+    let parts = values.iter().flatten();
+    let integer_digits = parts.clone().map(|p| p.integer_digits()).max();
+    let scale = parts.map(|p| p.scale()).max().unwrap_or(0);
+    let precision = (integer_digits.unwrap_or(0) + scale).max(1);
+    if precision > MAX_PRECISION {
+        eyre::bail!(
+            "decimal values need to fit into `Decimal128` with at most \
+            {MAX_PRECISION} digits, got {precision} digits"
+        );
+    }
+    let values: Vec<_> = values
+        .into_iter()
+        .map(|parts| parts.map(|p| p.unscaled(scale)).transpose())
+        .collect::<Result<_>>()?;
+    let array = Decimal128Array::from(values)
+        .with_precision_and_scale(precision as u8, scale as i8)
+        .context("failed to create `Decimal128` array")?;
+    Ok(Some(Arc::new(array)))
+}
+
+/// A finite decimal value, as returned by `Decimal.as_tuple()`.
+struct DecimalParts {
+    negative: bool,
+    digits: Vec<u8>,
+    exponent: i64,
+}
+
+impl DecimalParts {
+    fn from_python(value: &Bound<'_, PyAny>) -> Result<Self> {
+        let (sign, digits, exponent): (u8, Vec<u8>, Bound<'_, PyAny>) =
+            value.call_method0("as_tuple")?.extract()?;
+        // the exponent of `NaN` and `Infinity` is a string
+        let exponent = exponent
+            .extract()
+            .map_err(|_| eyre::eyre!("cannot send non-finite decimal `{value}`"))?;
+        Ok(Self {
+            negative: sign == 1,
+            digits,
+            exponent,
+        })
+    }
+
+    /// Number of digits in front of the decimal point.
+    fn integer_digits(&self) -> usize {
+        (self.digits.len() as i64 + self.exponent).max(0) as usize
+    }
+
+    /// Number of digits after the decimal point.
+    fn scale(&self) -> usize {
+        (-self.exponent).max(0) as usize
+    }
+
+    /// The value multiplied by `10^scale`.
+    fn unscaled(&self, scale: usize) -> Result<i128> {
+        let shift = scale as i64 + self.exponent;
+        let digits = self
+            .digits
+            .iter()
+            .copied()
+            .chain(std::iter::repeat(0).take(shift as usize));
+        let mut unscaled: i128 = 0;
+        for digit in digits {
+            unscaled = unscaled
+                .checked_mul(10)
+                .and_then(|unscaled| unscaled.checked_add(digit.into()))
+                .context("decimal value does not fit into `Decimal128`")?;
+        }
+        Ok(if self.negative { -unscaled } else { unscaled })
+    }
+}
@@ -0,0 +1,231 @@
+//! Delta encoding of large values that change slowly, e.g. costmaps.
+//!
+//! The sender compares each value with the previously sent value of the same
+//! output and only sends the changed regions of the value buffer, see
+//! [`DeltaEncoder`]. The receiver keeps the last value per input and applies
+//! the changes to it, see [`DeltaDecoder`]. Every message has a
+//! [`DELTA_VERSION`]. Messages with a [`DELTA_BASE`] are deltas that can only be
+//! applied to the value of that version, other messages contain the full value.
+//!
+//! A delta is encoded as list of regions, each of which consists of the byte
+//! offset and the length of the region as little-endian `u64` followed by the
+//! new bytes of the region.
+
+use std::collections::HashMap;
+
+use arrow::{
+    array::{make_array, ArrayData, AsArray},
+    buffer::Buffer,
+    datatypes::{DataType, UInt8Type},
+};
+use dora_node_api::{dora_core::config::DataId, ArrowData, Metadata, Parameter};
+use eyre::{Context, ContextCompat, Result};
+
+/// Metadata parameter key of the version of a delta-encoded value.
+pub const DELTA_VERSION: &str = "delta_version";
+/// Metadata parameter key of the version that a delta applies to. Not set for
+/// full values.
+pub const DELTA_BASE: &str = "delta_base";
+
+/// Granularity in bytes in which values are compared.
+const BLOCK_SIZE: usize = 64;
+/// Size of the offset and length of a region in the encoded delta.
+const REGION_HEADER_SIZE: usize = 16;
+
+/// A value to send, as decided by [`DeltaEncoder::encode`].
+#[derive(Debug, PartialEq, Eq)]
+pub enum DeltaFrame {
+    /// The value must be sent in full.
+    Full { version: i64 },
+    /// The encoded changes to the value of version `base` must be sent.
+    Delta {
+        version: i64,
+        base: i64,
+        delta: Vec<u8>,
+    },
+}
+
+/// Sender side of the delta encoding of an output.
+#[derive(Debug, Default)]
+pub struct DeltaEncoder {
+    version: i64,
+    /// Number of deltas that were sent since the last full value.
+    deltas_since_full: usize,
+    previous: Option<(DataType, Vec<u8>)>,
+}
+
+impl DeltaEncoder {
+    /// Compares the value with the previously encoded value.
+    ///
+    /// The full value is sent if there is no previous value, if the type or the
+    /// length of the value changed, if the delta would not be smaller than the
+    /// value, or if `full_every - 1` deltas were sent since the last full value.
+    pub fn encode(&mut self, value: &ArrayData, full_every: Option<usize>) -> Result<DeltaFrame> {
+        let bytes = value_bytes(value)?;
+        let refresh = full_every.is_some_and(|every| self.deltas_since_full + 1 >= every);
+        let delta = match &self.previous {
+            Some((data_type, previous))
+                if !refresh && data_type == value.data_type() && previous.len() == bytes.len() =>
+            {
+                Some(diff(previous, bytes)).filter(|delta| delta.len() < bytes.len())
+            }
+            _ => None,
+        };
+        self.version += 1;
+        self.previous = Some((value.data_type().clone(), bytes.to_vec()));
+        match delta {
+            Some(delta) => {
+                self.deltas_since_full += 1;
+                Ok(DeltaFrame::Delta {
+                    version: self.version,
+                    base: self.version - 1,
+                    delta,
+                })
+            }
+            None => {
+                self.deltas_since_full = 0;
+                Ok(DeltaFrame::Full {
+                    version: self.version,
+                })
+            }
+        }
+    }
+
+    /// Forgets the previously encoded value, so that the next value is sent in
+    /// full. Must be called if a value returned by [`Self::encode`] could not be
+    /// sent, since receivers cannot apply deltas based on it.
+    pub fn reset(&mut self) {
+        self.deltas_since_full = 0;
+        self.previous = None;
+    }
+}
+
+/// Receiver side of the delta encoding, keeps the last value per input.
+#[derive(Debug, Default)]
+pub struct DeltaDecoder {
+    values: HashMap<DataId, (i64, DataType, Buffer)>,
+}
+
+impl DeltaDecoder {
+    /// Replaces the data of a delta-encoded input by the reconstructed value.
+    ///
+    /// Inputs without [`DELTA_VERSION`] are left unchanged. Fails if the delta
+    /// does not apply to the last value of the input, e.g. because the previous
+    /// message was dropped. The following deltas fail too, until the next full
+    /// value is received.
+    pub fn decode(&mut self, id: &DataId, metadata: &Metadata, data: &mut ArrowData) -> Result<()> {
+        let Some(Parameter::Integer(version)) = metadata.parameters.get(DELTA_VERSION) else {
+            return Ok(());
+        };
+        let base = match metadata.parameters.get(DELTA_BASE) {
+            None => {
+                let value = data.to_data();
+                // copied, so that shared memory is not kept alive until the next message
+                let bytes = Buffer::from_slice_ref(value_bytes(&value)?);
+                self.values
+                    .insert(id.clone(), (*version, value.data_type().clone(), bytes));
+                return Ok(());
+            }
+            Some(Parameter::Integer(base)) => *base,
+            Some(other) => eyre::bail!("invalid `{DELTA_BASE}` {other:?}"),
+        };
+        let (data_type, previous) = match self.values.remove(id) {
+            Some((previous_version, data_type, previous)) if previous_version == base => {
+                (data_type, previous)
+            }
+            _ => eyre::bail!(
+                "missing version {base} of delta-encoded input, waiting for the next full value"
+            ),
+        };
+        let delta = data
+            .as_primitive_opt::<UInt8Type>()
+            .context("delta must be a uint8 array")?
+            .values();
+        let bytes = Buffer::from_vec(apply(&previous, delta)?);
+        let width = data_type.primitive_width().unwrap_or(1);
+        let value = ArrayData::builder(data_type.clone())
+            .len(bytes.len() / width)
+            .add_buffer(bytes.clone())
+            .build()
+            .context("failed to reconstruct delta-encoded value")?;
+        self.values.insert(id.clone(), (*version, data_type, bytes));
+        *data = make_array(value).into();
+        Ok(())
+    }
+}
+
+/// Returns the value buffer of a primitive array without nulls.
+fn value_bytes(value: &ArrayData) -> Result<&[u8]> {
+    let width = value
+        .data_type()
+        .primitive_width()
+        .filter(|_| value.buffers().len() == 1 && value.null_count() == 0)
+        .with_context(|| {
+            format!(
+                "delta encoding requires bytes or a primitive array without nulls, got {}",
+                value.data_type()
+            )
+        })?;
+    let start = value.offset() * width;
+    Ok(&value.buffers()[0].as_slice()[start..start + value.len() * width])
+}
+
+/// Encodes the regions in which `current` differs from `previous`, which must
+/// have the same length.
+pub fn diff(previous: &[u8], current: &[u8]) -> Vec<u8> {
+    let mut delta = Vec::new();
+    // start of the current region of adjacent changed blocks
+    let mut region_start = None;
+    let blocks = previous.chunks(BLOCK_SIZE).zip(current.chunks(BLOCK_SIZE));
+    for (index, (old, new)) in blocks.enumerate() {
+        let start = index * BLOCK_SIZE;
+        match (old != new, region_start) {
+            (true, None) => region_start = Some(start),
+            (false, Some(region)) => {
+                push_region(&mut delta, region, &current[region..start]);
+                region_start = None;
+            }
+            _ => {}
+        }
+    }
+    if let Some(region) = region_start {
+        push_region(&mut delta, region, &current[region..]);
+    }
+    delta
+}
+
+fn push_region(delta: &mut Vec<u8>, offset: usize, bytes: &[u8]) {
+    delta.extend_from_slice(&(offset as u64).to_le_bytes());
+    delta.extend_from_slice(&(bytes.len() as u64).to_le_bytes());
+    delta.extend_from_slice(bytes);
+}
+
+/// Applies an encoded delta to a copy of `previous`.
+pub fn apply(previous: &[u8], mut delta: &[u8]) -> Result<Vec<u8>> {
+    let mut value = previous.to_vec();
+    while !delta.is_empty() {
+        if delta.len() < REGION_HEADER_SIZE {
+            eyre::bail!("truncated delta region header");
+        }
+        let (header, rest) = delta.split_at(REGION_HEADER_SIZE);
+        let offset = u64::from_le_bytes(header[..8].try_into().unwrap()) as usize;
+        let len = u64::from_le_bytes(header[8..].try_into().unwrap()) as usize;
+        if rest.len() < len {
+            eyre::bail!("truncated delta region");
+        }
+        let (bytes, rest) = rest.split_at(len);
+        value
+            .get_mut(offset..)
+            .and_then(|target| target.get_mut(..len))
+            .with_context(|| {
+                format!(
+                    "delta region {offset}..{} is out of bounds for value of {} bytes",
+                    offset.saturating_add(len),
+                    previous.len()
+                )
+            })?
+            .copy_from_slice(bytes);
+        delta = rest;
+    }
+    Ok(value)
+}
@@ -0,0 +1,118 @@
+//! Conversion between dora messages and Apache Arrow Flight frames.
+//!
+//! A dora message is represented as the triple that `pyarrow.flight` writers
+//! and readers operate on:
+//!
+//! - a `FlightDescriptor` whose path is the input or output id,
+//! - a `RecordBatch` with the payload, and
+//! - the `app_metadata` bytes, which contain the JSON-encoded dora metadata.
+//!
+//! Struct arrays are mapped to record batches with one column per field. All
+//! other arrays are wrapped in a record batch with a single `value` column.
+
+use std::sync::Arc;
+
+use arrow::{
+    array::{Array, ArrayRef, StructArray},
+    pyarrow::ToPyArrow,
+    record_batch::RecordBatch,
+};
+use arrow_schema::{DataType, Field, Schema};
+use dora_node_api::{Metadata, MetadataParameters};
+use eyre::{Context, Result};
+use pyo3::{
+    prelude::*,
+    types::{PyBytes, PyTuple},
+};
+
+/// Name of the column of record batches that wrap non-struct arrays.
+pub const VALUE_COLUMN: &str = "value";
+
+/// Wraps the given array into a record batch suitable for Arrow Flight.
+pub fn array_to_flight_batch(array: ArrayRef) -> Result<RecordBatch> {
+    if let DataType::Struct(_) = array.data_type() {
+        if array.null_count() == 0 {
+            let array = array
+                .as_any()
+                .downcast_ref::<StructArray>()
+                .expect("array of type struct is no StructArray");
+            return Ok(RecordBatch::from(array));
+        }
+    }
+    let schema = Schema::new(vec![Field::new(
+        VALUE_COLUMN,
+        array.data_type().clone(),
+        true,
+    )]);
+    RecordBatch::try_new(Arc::new(schema), vec![array])
+        .context("failed to create record batch for Arrow Flight")
+}
+
+/// Inverse of [`array_to_flight_batch`].
+pub fn flight_batch_to_array(batch: RecordBatch) -> ArrayRef {
+    let schema = batch.schema();
+    if let [field] = schema.fields().as_ref() {
+        if field.name() == VALUE_COLUMN {
+            return batch.column(0).clone();
+        }
+    }
+    Arc::new(StructArray::from(batch))
+}
+
+/// Serializes the given metadata into the `app_metadata` of a Flight frame.
+pub fn metadata_to_app_metadata(metadata: &Metadata) -> Result<Vec<u8>> {
+    serde_json::to_vec(metadata).context("failed to serialize metadata")
+}
+
+/// Parses the `app_metadata` of a Flight frame that was created through
+/// [`metadata_to_app_metadata`].
+///
+/// Only the parameters are returned as the timestamp and the type info are
+/// reassigned when the data is sent as a dora output.
+pub fn app_metadata_to_parameters(app_metadata: &[u8]) -> Result<MetadataParameters> {
+    let metadata: Metadata = serde_json::from_slice(app_metadata)
+        .context("`app_metadata` does not contain dora metadata")?;
+    Ok(metadata.parameters)
+}
+
+/// Creates the `(descriptor, batch, app_metadata)` Flight frame of a message.
+pub fn to_flight_data(
+    py: Python<'_>,
+    id: &str,
+    metadata: &Metadata,
+    data: ArrayRef,
+) -> Result<Py<PyTuple>> {
+    let flight = py
+        .import_bound("pyarrow.flight")
+        .context("failed to import `pyarrow.flight`")?;
+    let descriptor = flight
+        .getattr("FlightDescriptor")?
+        .call_method1("for_path", (id,))?;
+    let batch = array_to_flight_batch(data)?.to_pyarrow(py)?;
+    let app_metadata = PyBytes::new_bound(py, &metadata_to_app_metadata(metadata)?);
+    Ok(PyTuple::new_bound(
+        py,
+        [descriptor.unbind(), batch, app_metadata.into_any().unbind()],
+    )
+    .unbind())
+}
+
+/// Returns the dora id that is encoded in the path of the given `FlightDescriptor`.
+pub fn descriptor_to_id(descriptor: &Bound<'_, PyAny>) -> Result<String> {
+    let path = descriptor
+        .getattr("path")
+        .context("`descriptor` must be a `pyarrow.flight.FlightDescriptor`")?;
+    if path.is_none() {
+        eyre::bail!(
+            "`descriptor` must be a path descriptor, created through `FlightDescriptor.for_path`"
+        );
+    }
+    let path: Vec<Vec<u8>> = path.extract().context("invalid descriptor path")?;
+    let [id] = path.as_slice() else {
+        eyre::bail!(
+            "descriptor path must consist of a single output id, got {} elements",
+            path.len()
+        );
+    };
+    String::from_utf8(id.clone()).context("descriptor path is not valid UTF-8")
+}
@@ -1,25 +1,86 @@
 use std::{
     collections::{BTreeMap, HashMap},
     sync::{Arc, Mutex},
+    time::{Duration, UNIX_EPOCH},
 };
 
 use arrow::pyarrow::ToPyArrow;
 use dora_node_api::{
+    arrow_utils::required_data_size,
     merged::{MergeExternalSend, MergedEvent},
-    DoraNode, Event, EventStream, Metadata, MetadataParameters, Parameter,
+    metadata::{
+        serialize_baggage, timestamp_to_nanos, CAUSED_BY, FRAME_ID, OPEN_TELEMETRY_BAGGAGE,
+        PRIORITY, PROTO_TYPE, TIMESTAMP_NS, UNITS,
+    },
+    uhlc, ArrowData, DoraNode, Event, EventStream, Metadata, MetadataParameters, Parameter,
 };
 use eyre::{Context, Result};
 use futures::{Stream, StreamExt};
 use futures_concurrency::stream::Merge as _;
+use metadata_codec::{MetadataCodec, ENCODED_METADATA_KEYS, METADATA_CODEC};
 use pyo3::{
+    exceptions::{PyIndexError, PyKeyError, PyRuntimeError, PyValueError},
     prelude::*,
-    types::{IntoPyDict, PyBool, PyDict, PyInt, PyList, PyString, PyTuple},
+    sync::GILOnceCell,
+    type_object::PyTypeInfo,
+    types::{IntoPyDict, PyBool, PyBytes, PyDict, PyInt, PyList, PyString, PyTuple},
 };
 
+pub mod cuda;
+pub mod decimal;
+pub mod delta;
+pub mod flight;
+pub mod metadata_codec;
+pub mod protobuf;
+pub mod recording;
+pub mod ros2;
+pub mod schema_registry;
+pub mod sparse;
+pub mod structured;
+pub mod table;
+pub mod tensor;
+pub mod torch;
+
 /// Dora Event
+///
+/// Returned instead of a plain `dict` by nodes that were created with
+/// `event_class=True`. Events can still be accessed like a dictionary, e.g.
+/// `event["type"]`, `event["id"]`, `event["value"]`, or `event["metadata"]`.
+/// They are registered as `collections.abc.Mapping` and can be converted into
+/// a plain `dict` through `copy()`.
+///
+/// The value and the metadata are decoded on first access and cached for the
+/// lifetime of the event, so repeated accesses are cheap. Reading the metadata
+/// does not decode the value.
+#[pyclass(name = "Event", module = "dora", mapping)]
 pub struct PyEvent {
-    pub event: MergedEvent<PyObject>,
+    pub event: MergedEvent<ExternalEvent>,
     pub _cleanup: Option<NodeCleanupHandle>,
+    dict: GILOnceCell<Py<PyDict>>,
+    /// Decoded `value` and `metadata`, cached separately so that accessing one of
+    /// them does not decode the other.
+    value: GILOnceCell<Option<PyObject>>,
+    metadata: GILOnceCell<Option<PyObject>>,
+}
+
+/// Event of an external stream that was merged into the dora event stream.
+pub enum ExternalEvent {
+    /// A value received from the external stream.
+    Value { source: String, value: PyObject },
+    /// The external stream reported an error.
+    Error { source: String, error: String },
+    /// The external stream did not yield any value within its idle timeout.
+    IdleTimeout { source: String, timeout: Duration },
+}
+
+impl ExternalEvent {
+    pub fn source(&self) -> &str {
+        match self {
+            ExternalEvent::Value { source, .. }
+            | ExternalEvent::Error { source, .. }
+            | ExternalEvent::IdleTimeout { source, .. } => source,
+        }
+    }
 }
 
 /// Keeps the dora node alive until all event objects have been dropped.
@@ -74,11 +135,106 @@ where
     }
 }
 
-#[allow(dead_code)]
 pub struct CleanupHandle<T>(Arc<Mutex<T>>);
 
+impl<T> CleanupHandle<T> {
+    pub fn get_mut(&self) -> std::sync::MutexGuard<T> {
+        self.0.try_lock().expect("failed to lock CleanupHandle")
+    }
+}
+
 impl PyEvent {
-    pub fn to_py_dict(self, py: Python<'_>) -> PyResult<Py<PyDict>> {
+    pub fn new(event: MergedEvent<ExternalEvent>, cleanup: Option<NodeCleanupHandle>) -> Self {
+        Self {
+            event,
+            _cleanup: cleanup,
+            dict: GILOnceCell::new(),
+            value: GILOnceCell::new(),
+            metadata: GILOnceCell::new(),
+        }
+    }
+
+    /// Converts the event into the object that is passed to Python code.
+    ///
+    /// Events are plain `dict`s, unless `event_class` is set. The dict references
+    /// the event under the `_event` key, so that it can be passed to methods that
+    /// take an event, see [`from_object`](Self::from_object).
+    pub fn to_python(event: Py<Self>, py: Python<'_>, event_class: bool) -> PyResult<PyObject> {
+        if event_class {
+            return Ok(event.into_py(py));
+        }
+        let dict = event.borrow(py).build_dict(py)?;
+        dict.bind(py).set_item("_event", event)?;
+        Ok(dict.into_any())
+    }
+
+    /// Returns the event of a `dora.Event` or of an event `dict`, see
+    /// [`to_python`](Self::to_python).
+    pub fn from_object<'py>(object: &Bound<'py, PyAny>) -> Option<Bound<'py, PyEvent>> {
+        if let Ok(event) = object.downcast::<PyEvent>() {
+            return Some(event.clone());
+        }
+        let event = object
+            .downcast::<PyDict>()
+            .ok()?
+            .get_item("_event")
+            .ok()??;
+        event.downcast_into::<PyEvent>().ok()
+    }
+
+    /// Returns the dictionary representation of this event, creating it on first access.
+    fn dict(&self, py: Python<'_>) -> PyResult<&Py<PyDict>> {
+        self.dict.get_or_try_init(py, || self.build_dict(py))
+    }
+
+    /// Looks up `key` in the dictionary representation of this event.
+    ///
+    /// The common keys are answered without building the full dictionary, so that
+    /// e.g. reading `event["metadata"]` does not convert the value to arrow.
+    fn lookup(&self, key: &str, py: Python<'_>) -> PyResult<Option<PyObject>> {
+        if let (None, MergedEvent::Dora(event)) = (self.dict.get(py), &self.event) {
+            match key {
+                "kind" => return Ok(Some("dora".to_object(py))),
+                "type" => return Ok(Some(Self::ty(event).to_object(py))),
+                "id" => return Ok(Self::id(event).map(|id| id.to_object(py))),
+                "value" => return Ok(self.cached_value(py)?.map(|v| v.clone_ref(py))),
+                "metadata" => return Ok(self.cached_metadata(py)?.map(|m| m.clone_ref(py))),
+                _ => {}
+            }
+        }
+        Ok(self.dict(py)?.bind(py).get_item(key)?.map(Bound::unbind))
+    }
+
+    /// Replaces the value of an input event with the result of `decoder`, which is
+    /// called with the arrow array of the value.
+    ///
+    /// Must be called before the value is accessed for the first time.
+    pub fn decode_value(&self, py: Python<'_>, decoder: &Bound<'_, PyAny>) -> PyResult<()> {
+        let Some(raw) = self.value(py)? else {
+            return Ok(());
+        };
+        let decoded = decoder.call1((raw,))?.unbind();
+        self.value
+            .set(py, Some(decoded))
+            .map_err(|_| PyRuntimeError::new_err("event value was already decoded"))
+    }
+
+    fn cached_value(&self, py: Python<'_>) -> PyResult<Option<&PyObject>> {
+        self.value
+            .get_or_try_init(py, || self.value(py))
+            .map(Option::as_ref)
+    }
+
+    fn cached_metadata(&self, py: Python<'_>) -> PyResult<Option<&PyObject>> {
+        let MergedEvent::Dora(event) = &self.event else {
+            return Ok(None);
+        };
+        self.metadata
+            .get_or_try_init(py, || Ok::<_, PyErr>(Self::metadata(event, py)?))
+            .map(Option::as_ref)
+    }
+
+    fn build_dict(&self, py: Python<'_>) -> PyResult<Py<PyDict>> {
         let mut pydict = HashMap::new();
         match &self.event {
             MergedEvent::Dora(_) => pydict.insert("kind", "dora".to_object(py)),
@@ -91,18 +247,41 @@ impl PyEvent {
                 }
                 pydict.insert("type", Self::ty(event).to_object(py));
 
-                if let Some(value) = self.value(py)? {
-                    pydict.insert("value", value);
+                if let Some(value) = self.cached_value(py)? {
+                    pydict.insert("value", value.clone_ref(py));
                 }
-                if let Some(metadata) = Self::metadata(event, py)? {
-                    pydict.insert("metadata", metadata);
+                if let Some(metadata) = self.cached_metadata(py)? {
+                    pydict.insert("metadata", metadata.clone_ref(py));
                 }
                 if let Some(error) = Self::error(event) {
                     pydict.insert("error", error.to_object(py));
                 }
+                if let Event::Watermark { time } = event {
+                    pydict.insert("time", timestamp_to_secs(*time).to_object(py));
+                }
             }
             MergedEvent::External(event) => {
-                pydict.insert("value", event.clone_ref(py));
+                pydict.insert("source", event.source().to_object(py));
+                match event {
+                    ExternalEvent::Value { value, .. } => {
+                        pydict.insert("value", value.clone_ref(py));
+                    }
+                    ExternalEvent::Error { error, .. } => {
+                        pydict.insert("type", EventType::Error.as_str().to_object(py));
+                        pydict.insert("error", error.to_object(py));
+                    }
+                    ExternalEvent::IdleTimeout { source, timeout } => {
+                        pydict.insert("type", EventType::Warning.as_str().to_object(py));
+                        pydict.insert(
+                            "warning",
+                            format!(
+                                "no event received from `{source}` within {:.3}s",
+                                timeout.as_secs_f64()
+                            )
+                            .to_object(py),
+                        );
+                    }
+                }
             }
         }
 
@@ -114,19 +293,14 @@ impl PyEvent {
     }
 
     fn ty(event: &Event) -> &str {
-        match event {
-            Event::Stop => "STOP",
-            Event::Input { .. } => "INPUT",
-            Event::InputClosed { .. } => "INPUT_CLOSED",
-            Event::Error(_) => "ERROR",
-            _other => "UNKNOWN",
-        }
+        EventType::of(event).as_str()
     }
 
     fn id(event: &Event) -> Option<&str> {
         match event {
             Event::Input { id, .. } => Some(id),
             Event::InputClosed { id } => Some(id),
+            Event::DecodeError { id, .. } => Some(id),
             _ => None,
         }
     }
@@ -157,49 +331,831 @@ impl PyEvent {
     fn error(event: &Event) -> Option<&str> {
         match event {
             Event::Error(error) => Some(error),
+            Event::DecodeError { error, .. } => Some(error),
             _other => None,
         }
     }
 }
 
+#[pymethods]
+impl PyEvent {
+    fn __getitem__(&self, key: &str, py: Python<'_>) -> PyResult<PyObject> {
+        self.lookup(key, py)?
+            .ok_or_else(|| PyKeyError::new_err(key.to_owned()))
+    }
+
+    fn __contains__(&self, key: &str, py: Python<'_>) -> PyResult<bool> {
+        Ok(self.lookup(key, py)?.is_some())
+    }
+
+    fn __len__(&self, py: Python<'_>) -> PyResult<usize> {
+        Ok(self.dict(py)?.bind(py).len())
+    }
+
+    fn __iter__(&self, py: Python<'_>) -> PyResult<PyObject> {
+        Ok(self.dict(py)?.bind(py).call_method0("__iter__")?.unbind())
+    }
+
+    fn __repr__(&self, py: Python<'_>) -> PyResult<String> {
+        Ok(format!("Event({})", self.dict(py)?.bind(py).repr()?))
+    }
+
+    fn __setitem__(&self, key: &str, value: PyObject, py: Python<'_>) -> PyResult<()> {
+        self.dict(py)?.bind(py).set_item(key, value)
+    }
+
+    fn __delitem__(&self, key: &str, py: Python<'_>) -> PyResult<()> {
+        self.dict(py)?.bind(py).del_item(key)
+    }
+
+    fn __eq__(&self, other: &Bound<'_, PyAny>, py: Python<'_>) -> PyResult<bool> {
+        let dict = self.dict(py)?.bind(py);
+        match other.downcast::<PyEvent>() {
+            Ok(other) => {
+                let other = other.borrow();
+                dict.eq(other.dict(py)?)
+            }
+            Err(_) => dict.eq(other),
+        }
+    }
+
+    /// Returns the dictionary representation of this event as a plain `dict`.
+    ///
+    /// :rtype: dict
+    fn copy(&self, py: Python<'_>) -> PyResult<Py<PyDict>> {
+        Ok(self.dict(py)?.bind(py).copy()?.unbind())
+    }
+
+    /// Returns the value for `key` if the event contains it, else `default`.
+    ///
+    /// :type key: str
+    /// :type default: typing.Any, optional
+    /// :rtype: typing.Any
+    #[pyo3(signature = (key, default=None))]
+    fn get(&self, key: &str, default: Option<PyObject>, py: Python<'_>) -> PyResult<PyObject> {
+        Ok(match self.lookup(key, py)? {
+            Some(value) => value,
+            None => default.unwrap_or_else(|| py.None()),
+        })
+    }
+
+    /// :rtype: list
+    fn keys(&self, py: Python<'_>) -> PyResult<PyObject> {
+        Ok(self.dict(py)?.bind(py).keys().into_any().unbind())
+    }
+
+    /// :rtype: list
+    fn values(&self, py: Python<'_>) -> PyResult<PyObject> {
+        Ok(self.dict(py)?.bind(py).values().into_any().unbind())
+    }
+
+    /// :rtype: list
+    fn items(&self, py: Python<'_>) -> PyResult<PyObject> {
+        Ok(self.dict(py)?.bind(py).items().into_any().unbind())
+    }
+
+    /// The type of the event as `dora.EventType`.
+    ///
+    /// This is the typed equivalent of `event["type"]`, which is kept as a string
+    /// for backwards compatibility:
+    ///
+    /// ```python
+    /// match event.type:
+    ///     case EventType.INPUT:
+    ///         process(event["id"], event["value"])
+    ///     case EventType.STOP:
+    ///         break
+    /// ```
+    ///
+    /// Returns `None` for values of external event streams, which have no type.
+    ///
+    /// :rtype: dora.EventType
+    #[getter]
+    fn r#type(&self) -> Option<EventType> {
+        match &self.event {
+            MergedEvent::Dora(event) => Some(EventType::of(event)),
+            MergedEvent::External(ExternalEvent::Value { .. }) => None,
+            MergedEvent::External(ExternalEvent::Error { .. }) => Some(EventType::Error),
+            MergedEvent::External(ExternalEvent::IdleTimeout { .. }) => Some(EventType::Warning),
+        }
+    }
+
+    /// Returns a stable identifier of an input event as `(input_id, sequence_number)`.
+    ///
+    /// The sequence number is assigned per output by the sending node. Pass the
+    /// identifier as `caused_by` when sending an output to record its lineage:
+    ///
+    /// ```python
+    /// node.send_output("command", data, caused_by=[event.id_and_seq()])
+    /// ```
+    ///
+    /// Returns `None` for non-input events and for inputs without sequence number,
+    /// e.g. timer inputs.
+    ///
+    /// :rtype: tuple[str, int]
+    pub fn id_and_seq(&self) -> Option<(String, u64)> {
+        match &self.event {
+            MergedEvent::Dora(Event::Input { id, metadata, .. }) => metadata
+                .sequence_number()
+                .map(|seq| (id.to_string(), seq)),
+            _ => None,
+        }
+    }
+
+    /// Returns the number of events of the same input that were dropped before this one
+    /// because the input queue was full, see the `queue_size` option of inputs.
+    ///
+    /// Returns `0` for non-input events.
+    ///
+    /// :rtype: int
+    fn dropped_count(&self) -> u64 {
+        match &self.event {
+            MergedEvent::Dora(Event::Input { metadata, .. }) => metadata.dropped_count(),
+            _ => 0,
+        }
+    }
+
+    /// Returns the OpenTelemetry baggage propagated with an input event.
+    ///
+    /// The baggage is also available in W3C format as `event["metadata"]["open_telemetry_baggage"]`,
+    /// which can be passed to the OpenTelemetry propagator:
+    ///
+    /// ```python
+    /// from opentelemetry import propagate
+    ///
+    /// ctx = propagate.extract({"baggage": event["metadata"]["open_telemetry_baggage"]})
+    /// ```
+    ///
+    /// :rtype: dict
+    fn baggage(&self) -> BTreeMap<String, String> {
+        match &self.event {
+            MergedEvent::Dora(Event::Input { metadata, .. }) => metadata.open_telemetry_baggage(),
+            _ => BTreeMap::new(),
+        }
+    }
+
+    /// Returns the timestamp of an input event or the time of a watermark event,
+    /// in seconds since the UNIX epoch (like `time.time()`).
+    ///
+    /// Input timestamps are assigned by the sending node. They can be compared to
+    /// the `time` of `WATERMARK` events, e.g. to assign inputs to time windows.
+    ///
+    /// :rtype: float
+    fn timestamp(&self) -> Option<f64> {
+        match &self.event {
+            MergedEvent::Dora(Event::Input { metadata, .. }) => {
+                Some(timestamp_to_secs(metadata.timestamp()))
+            }
+            MergedEvent::Dora(Event::Watermark { time }) => Some(timestamp_to_secs(*time)),
+            _ => None,
+        }
+    }
+
+    /// Returns the timestamp of an input event or the time of a watermark event,
+    /// as an integer number of nanoseconds since the UNIX epoch.
+    ///
+    /// Unlike `timestamp()`, the value is not rounded to a float. If the sender
+    /// set the `timestamp_ns` metadata key, e.g. to the acquisition time of a
+    /// sensor reading, that value is returned exactly:
+    ///
+    /// ```python
+    /// node.send_output("imu", data, {"timestamp_ns": time.time_ns()})
+    /// ...
+    /// acquired = event.timestamp_ns()
+    /// ```
+    ///
+    /// Otherwise, the timestamp that was assigned when the input was sent is
+    /// returned.
+    ///
+    /// :rtype: int
+    fn timestamp_ns(&self) -> Option<u64> {
+        match &self.event {
+            MergedEvent::Dora(Event::Input { metadata, .. }) => Some(metadata.timestamp_ns()),
+            MergedEvent::Dora(Event::Watermark { time }) => Some(timestamp_to_nanos(*time)),
+            _ => None,
+        }
+    }
+
+    /// Returns the coordinate frame of an input event, as set through the
+    /// `frame_id` argument of `send_output`.
+    ///
+    /// Returns `None` if the sender did not specify a frame.
+    ///
+    /// :rtype: str
+    fn frame_id(&self) -> Option<&str> {
+        match &self.event {
+            MergedEvent::Dora(Event::Input { metadata, .. }) => metadata.frame_id(),
+            _ => None,
+        }
+    }
+
+    /// Returns the physical units of an input event, e.g. `"m"` or `"rad"`, as set
+    /// through the `units` argument of `send_output`.
+    ///
+    /// Returns `None` if the sender did not specify units.
+    ///
+    /// :rtype: str
+    fn units(&self) -> Option<&str> {
+        match &self.event {
+            MergedEvent::Dora(Event::Input { metadata, .. }) => metadata.units(),
+            _ => None,
+        }
+    }
+
+    /// Returns the MIME type of the encoded data of an input event, e.g.
+    /// `"image/jpeg"`, as set through the `content_type` argument of `send_output`.
+    ///
+    /// Returns `None` if the sender did not specify a content type.
+    ///
+    /// :rtype: str
+    fn content_type(&self) -> Option<&str> {
+        match &self.event {
+            MergedEvent::Dora(Event::Input { metadata, .. }) => metadata.content_type(),
+            _ => None,
+        }
+    }
+
+    /// Returns the binary attachment with the given name, as set through the
+    /// `attachments` argument of `send_output`.
+    ///
+    /// Returns `None` if the event has no attachment of that name.
+    ///
+    /// :type name: str
+    /// :rtype: bytes
+    fn attachment<'py>(&self, name: &str, py: Python<'py>) -> Option<Bound<'py, PyBytes>> {
+        match &self.event {
+            MergedEvent::Dora(Event::Input { metadata, .. }) => metadata
+                .attachment(name)
+                .map(|bytes| PyBytes::new_bound(py, bytes)),
+            _ => None,
+        }
+    }
+
+    /// Returns the size of the payload of an input event in bytes, without
+    /// converting the value into a Python object.
+    ///
+    /// The size is the length of the received arrow buffers, so it's cheap to
+    /// check, e.g. to skip large messages before decoding them:
+    ///
+    /// ```python
+    /// if event.byte_len() > 10_000_000:
+    ///     continue
+    /// ```
+    ///
+    /// Slices created through `slice` report the size of the buffers that they
+    /// share with the original event. Returns `0` for inputs without payload and
+    /// for non-input events.
+    ///
+    /// :rtype: int
+    fn byte_len(&self) -> usize {
+        match &self.event {
+            MergedEvent::Dora(Event::Input { data, .. }) => required_data_size(&data.to_data()),
+            _ => 0,
+        }
+    }
+
+    /// Returns a new input event whose value is the sub-range `[start, start + length)`
+    /// of this event's value.
+    ///
+    /// The slice is zero-copy: it shares the underlying arrow buffer with this event,
+    /// which is kept alive for as long as the slice exists. The `id` and `metadata` of
+    /// the event are preserved.
+    ///
+    /// ```python
+    /// detections = event.slice(4, 2)
+    /// ```
+    ///
+    /// :type start: int
+    /// :type length: int
+    /// :rtype: dora.Event
+    fn slice(&self, start: usize, length: usize) -> PyResult<PyEvent> {
+        let MergedEvent::Dora(Event::Input { id, metadata, data }) = &self.event else {
+            return Err(PyValueError::new_err("only input events can be sliced"));
+        };
+        if start.checked_add(length).map_or(true, |end| end > data.len()) {
+            return Err(PyIndexError::new_err(format!(
+                "slice [{start}, {start} + {length}) is out of bounds for value of length {}",
+                data.len()
+            )));
+        }
+        let event = Event::Input {
+            id: id.clone(),
+            metadata: metadata.clone(),
+            data: ArrowData(data.slice(start, length)),
+        };
+        Ok(PyEvent::new(MergedEvent::Dora(event), self._cleanup.clone()))
+    }
+
+    /// Converts an input event into an Arrow Flight frame.
+    ///
+    /// Returns a `(descriptor, batch, app_metadata)` tuple that can be written to a
+    /// `pyarrow.flight` stream. The descriptor path is the input id, and the
+    /// `app_metadata` contains the dora metadata of the event:
+    ///
+    /// ```python
+    /// descriptor, batch, app_metadata = event.to_flight_data()
+    /// writer, _ = client.do_put(descriptor, batch.schema)
+    /// writer.write_with_metadata(batch, app_metadata)
+    /// ```
+    ///
+    /// Struct arrays are converted to record batches with one column per field,
+    /// all other values to a record batch with a single `value` column. Use
+    /// `Node.send_flight_data` to send a received frame as dora output.
+    ///
+    /// :rtype: tuple[pyarrow.flight.FlightDescriptor, pyarrow.RecordBatch, bytes]
+    fn to_flight_data(&self, py: Python<'_>) -> Result<Py<PyTuple>> {
+        let MergedEvent::Dora(Event::Input { id, metadata, data }) = &self.event else {
+            eyre::bail!("only input events can be converted to Flight data");
+        };
+        flight::to_flight_data(py, id, metadata, data.0.clone())
+    }
+
+    /// Converts the value of an input that was sent as a `scipy.sparse` matrix
+    /// back to a matrix, without densifying it.
+    ///
+    /// CSR and COO matrices keep their format, matrices of other formats are
+    /// received in CSR format. Requires `scipy` to be installed:
+    ///
+    /// ```python
+    /// matrix = event.to_sparse()
+    /// ```
+    ///
+    /// :rtype: scipy.sparse.csr_matrix | scipy.sparse.coo_matrix
+    fn to_sparse(&self, py: Python<'_>) -> Result<PyObject> {
+        let MergedEvent::Dora(Event::Input { id, data, .. }) = &self.event else {
+            eyre::bail!("only input events can be converted to sparse matrices");
+        };
+        sparse::sparse_to_python(py, &data.0)
+            .wrap_err_with(|| format!("input `{id}` is not a sparse matrix"))
+    }
+
+    /// Converts the value of an input that was sent as a numpy structured array
+    /// back to a structured array.
+    ///
+    /// The dtype has the same field names and types as the sent array, but is
+    /// packed, since the padding of aligned dtypes is not transferred:
+    ///
+    /// ```python
+    /// records = event.to_structured()
+    /// print(records["pos"])
+    /// ```
+    ///
+    /// :rtype: numpy.ndarray
+    fn to_structured(&self, py: Python<'_>) -> Result<PyObject> {
+        let MergedEvent::Dora(Event::Input { id, data, .. }) = &self.event else {
+            eyre::bail!("only input events can be converted to structured arrays");
+        };
+        structured::structured_to_python(py, &data.0)
+            .wrap_err_with(|| format!("input `{id}` is not a structured array"))
+    }
+
+    /// Converts the value of an input that was sent as a numpy array with two or
+    /// more dimensions back to an array of the same shape.
+    ///
+    /// Such arrays are sent as `arrow.fixed_shape_tensor`. Arrays in Fortran order
+    /// and other permutations of row-major order, e.g. transposed arrays, keep
+    /// their strides. The returned array is a read-only view into the received
+    /// value if possible:
+    ///
+    /// ```python
+    /// image = event.to_ndarray()
+    /// print(image.shape, image.strides)
+    /// ```
+    ///
+    /// :rtype: numpy.ndarray
+    fn to_ndarray(&self, py: Python<'_>) -> Result<PyObject> {
+        let MergedEvent::Dora(Event::Input { id, metadata, data }) = &self.event else {
+            eyre::bail!("only input events can be converted to numpy arrays");
+        };
+        tensor::tensor_to_python(py, &data.0, metadata)
+            .wrap_err_with(|| format!("input `{id}` is not a tensor"))
+    }
+
+    /// Converts the value of an input that was sent as a `pyarrow.Table` or as a
+    /// list of `pyarrow.RecordBatch` back to a table.
+    ///
+    /// The batches of the table are the batches that were sent:
+    ///
+    /// ```python
+    /// table = event.to_table()
+    /// print(table.num_rows, table.schema)
+    /// ```
+    ///
+    /// :rtype: pyarrow.Table
+    fn to_table(&self, py: Python<'_>) -> Result<PyObject> {
+        let MergedEvent::Dora(Event::Input { id, data, .. }) = &self.event else {
+            eyre::bail!("only input events can be converted to tables");
+        };
+        table::table_to_python(py, &data.0).wrap_err_with(|| format!("input `{id}` is not a table"))
+    }
+
+    /// Converts the value of an input into a `torch` tensor without copying it.
+    ///
+    /// The tensor shares the memory of the received value through DLPack and
+    /// keeps it alive, so it should be treated as read-only. Numpy arrays with
+    /// two or more dimensions keep their shape and strides, see `to_ndarray`.
+    /// Values that were sent from a GPU buffer, see `Node.send_output`, get
+    /// their shape back and are copied to the CUDA device again. Pass a `device`
+    /// to move the tensor to another device, e.g. `"cpu"` or `"cuda:1"`:
+    ///
+    /// ```python
+    /// image = event.to_torch(device="cuda")
+    /// prediction = model(image.unsqueeze(0))
+    /// ```
+    ///
+    /// Only primitive arrays without nulls can be converted. Requires `torch` and
+    /// `pyarrow` 15 or later.
+    ///
+    /// :type device: str, optional
+    /// :rtype: torch.Tensor
+    #[pyo3(signature = (device=None))]
+    fn to_torch(&self, py: Python<'_>, device: Option<&str>) -> Result<PyObject> {
+        let MergedEvent::Dora(Event::Input { id, metadata, data }) = &self.event else {
+            eyre::bail!("only input events can be converted to tensors");
+        };
+        torch::to_torch(py, &data.0, metadata, device)
+            .wrap_err_with(|| format!("failed to convert input `{id}` to a torch tensor"))
+    }
+
+    /// Converts an input into an instance of the given ROS 2 message class of
+    /// `rclpy`, e.g. to publish it through an `rclpy` publisher in the same
+    /// process.
+    ///
+    /// Struct values, e.g. sent through `Node.send_output_from_ros2`, must have a
+    /// single element, whose fields are assigned to the fields of the message.
+    /// Other values are assigned to the `data` field of the message. If the
+    /// message has a `header` that is not part of the value, its `stamp` is set
+    /// to `timestamp_ns()` and its `frame_id` to the `frame_id` metadata:
+    ///
+    /// ```python
+    /// from geometry_msgs.msg import PoseStamped
+    ///
+    /// publisher.publish(event.to_ros2(PoseStamped))
+    /// ```
+    ///
+    /// Requires `rosidl_runtime_py`, which is installed with ROS 2.
+    ///
+    /// :type msg_type: type
+    /// :rtype: typing.Any
+    fn to_ros2(&self, msg_type: Bound<'_, PyAny>) -> Result<PyObject> {
+        let MergedEvent::Dora(Event::Input { id, metadata, data }) = &self.event else {
+            eyre::bail!("only input events can be converted to ROS 2 messages");
+        };
+        ros2::to_ros2_message(msg_type.py(), &data.0, metadata, &msg_type)
+            .wrap_err_with(|| format!("failed to convert input `{id}` to a ROS 2 message"))
+    }
+
+    /// Parses an input that was sent through `Node.send_proto` as a message of
+    /// the given protobuf class:
+    ///
+    /// ```python
+    /// from robot_pb2 import Command
+    ///
+    /// command = event.as_proto(Command)
+    /// ```
+    ///
+    /// Raises a `dora.SchemaError` if the sender attached a different message
+    /// type. Inputs that were sent as plain bytes are parsed without this check.
+    ///
+    /// :type msg_class: type
+    /// :rtype: google.protobuf.message.Message
+    fn as_proto(&self, msg_class: Bound<'_, PyAny>) -> PyResult<PyObject> {
+        let MergedEvent::Dora(Event::Input { metadata, data, .. }) = &self.event else {
+            return Err(PyValueError::new_err(
+                "only input events can be parsed as protobuf messages",
+            ));
+        };
+        protobuf::proto_from_array(msg_class.py(), &data.0, metadata, &msg_class)
+    }
+
+    /// Checks the value of an input against a schema of the registry, see
+    /// `dora.register_schema`.
+    ///
+    /// Without a `name`, the value is checked against the schema that the sender
+    /// passed to `send_output`. Raises a `dora.SchemaError` if the value does not
+    /// match the schema or if the schema is not registered:
+    ///
+    /// ```python
+    /// event.validate_against("Detection")
+    /// detections = event["value"]
+    /// ```
+    ///
+    /// :type name: str, optional
+    /// :rtype: None
+    #[pyo3(signature = (name=None))]
+    fn validate_against(&self, py: Python<'_>, name: Option<&str>) -> PyResult<()> {
+        let MergedEvent::Dora(Event::Input { id, metadata, data }) = &self.event else {
+            return Err(PyValueError::new_err("only input events can be validated"));
+        };
+        let Some(name) = name.or_else(|| schema_registry::schema_name(metadata)) else {
+            return Err(PyValueError::new_err(format!(
+                "input `{id}` was sent without schema, pass a schema `name`"
+            )));
+        };
+        schema_registry::validate(name, data.data_type())
+            .map_err(|err| schema_registry::schema_error(py, format!("input `{id}`: {err}")))
+    }
+}
+
+/// Type of a dora event, see `Event.type`.
+///
+/// `str(event_type)` is the string that is returned by `event["type"]`.
+#[pyclass(eq, eq_int, module = "dora")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EventType {
+    #[pyo3(name = "INPUT")]
+    Input,
+    #[pyo3(name = "INPUT_CLOSED")]
+    InputClosed,
+    #[pyo3(name = "STOP")]
+    Stop,
+    #[pyo3(name = "ERROR")]
+    Error,
+    #[pyo3(name = "WARNING")]
+    Warning,
+    #[pyo3(name = "WATERMARK")]
+    Watermark,
+    /// The value of an input could not be decoded, see `Node(on_decode_error=...)`.
+    #[pyo3(name = "DECODE_ERROR")]
+    DecodeError,
+    /// An event type that is not supported by this version of the Python API.
+    #[pyo3(name = "UNKNOWN")]
+    Unknown,
+}
+
+impl EventType {
+    fn of(event: &Event) -> Self {
+        match event {
+            Event::Stop => EventType::Stop,
+            Event::Input { .. } => EventType::Input,
+            Event::InputClosed { .. } => EventType::InputClosed,
+            Event::Watermark { .. } => EventType::Watermark,
+            Event::DecodeError { .. } => EventType::DecodeError,
+            Event::Error(_) => EventType::Error,
+            _other => EventType::Unknown,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EventType::Input => "INPUT",
+            EventType::InputClosed => "INPUT_CLOSED",
+            EventType::Stop => "STOP",
+            EventType::Error => "ERROR",
+            EventType::Warning => "WARNING",
+            EventType::Watermark => "WATERMARK",
+            EventType::DecodeError => "DECODE_ERROR",
+            EventType::Unknown => "UNKNOWN",
+        }
+    }
+}
+
+#[pymethods]
+impl EventType {
+    fn __str__(&self) -> &'static str {
+        self.as_str()
+    }
+}
+
+/// Metadata of a dora message.
+///
+/// Use `dora.metadata_to_dict` and `dora.dict_to_metadata` to convert it from
+/// and to a plain dict.
+#[pyclass(name = "Metadata", module = "dora")]
+#[derive(Clone)]
+pub struct PyMetadata(pub Metadata);
+
+#[pymethods]
+impl PyMetadata {
+    /// Returns the hybrid logical clock timestamp of the message.
+    ///
+    /// :rtype: str
+    fn timestamp(&self) -> String {
+        self.0.timestamp().to_string()
+    }
+
+    /// Returns the parameters of the message, in the same format as `event["metadata"]`.
+    ///
+    /// :rtype: dict
+    fn parameters<'py>(&self, py: Python<'py>) -> Result<Bound<'py, PyDict>> {
+        metadata_to_pydict(&self.0, py)
+    }
+
+    fn __eq__(&self, other: &Self) -> bool {
+        self.0 == other.0
+    }
+
+    fn __repr__(&self, py: Python<'_>) -> Result<String> {
+        Ok(format!(
+            "Metadata(timestamp={}, parameters={})",
+            self.0.timestamp(),
+            metadata_to_pydict(&self.0, py)?.repr()?
+        ))
+    }
+}
+
 pub fn pydict_to_metadata(dict: Option<Bound<'_, PyDict>>) -> Result<MetadataParameters> {
+    pydict_to_metadata_with_codec(dict, None)
+}
+
+/// Like [`pydict_to_metadata`], but encodes values that have no native
+/// [`Parameter`] type with the given codec instead of converting them to
+/// strings, see [`metadata_codec`].
+pub fn pydict_to_metadata_with_codec(
+    dict: Option<Bound<'_, PyDict>>,
+    codec: Option<MetadataCodec>,
+) -> Result<MetadataParameters> {
     let mut parameters = BTreeMap::default();
+    let mut encoded_keys = Vec::new();
     if let Some(pymetadata) = dict {
         for (key, value) in pymetadata.iter() {
             let key = key.extract::<String>().context("Parsing metadata keys")?;
-            if value.is_exact_instance_of::<PyBool>() {
-                parameters.insert(key, Parameter::Bool(value.extract()?))
-            } else if value.is_instance_of::<PyInt>() {
-                parameters.insert(key, Parameter::Integer(value.extract::<i64>()?))
-            } else if value.is_instance_of::<PyString>() {
-                parameters.insert(key, Parameter::String(value.extract()?))
-            } else if value.is_instance_of::<PyTuple>()
-                && value.len()? > 0
-                && value.get_item(0)?.is_exact_instance_of::<PyInt>()
-            {
-                let list: Vec<i64> = value.extract()?;
-                parameters.insert(key, Parameter::ListInt(list))
-            } else if value.is_instance_of::<PyList>()
-                && value.len()? > 0
-                && value.get_item(0)?.is_exact_instance_of::<PyInt>()
-            {
-                let list: Vec<i64> = value.extract()?;
-                parameters.insert(key, Parameter::ListInt(list))
-            } else {
-                println!("could not convert type {value}");
-                parameters.insert(key, Parameter::String(value.str()?.to_string()))
-            };
+            let parameter = pyvalue_to_parameter(&key, &value, false, codec, &mut encoded_keys)?;
+            parameters.insert(key, parameter);
+        }
+    }
+    if let Some(codec) = codec {
+        metadata_codec::insert_codec_parameters(&mut parameters, codec, encoded_keys);
+    }
+    Ok(parameters)
+}
+
+/// Metadata keys that have a meaning to dora.
+pub const KNOWN_METADATA_KEYS: &[&str] = &[
+    "open_telemetry_context",
+    OPEN_TELEMETRY_BAGGAGE,
+    CAUSED_BY,
+    FRAME_ID,
+    UNITS,
+    PRIORITY,
+    TIMESTAMP_NS,
+    PROTO_TYPE,
+    delta::DELTA_VERSION,
+    delta::DELTA_BASE,
+];
+
+/// Key of the dict that contains user-defined metadata in strict mode.
+pub const USER_PARAMETERS_KEY: &str = "parameters";
+
+/// Like [`pydict_to_metadata`], but rejects unknown keys and values that cannot
+/// be represented as metadata.
+///
+/// Only the [`KNOWN_METADATA_KEYS`] are allowed at the top level. User-defined
+/// metadata must be passed as a dict under the [`USER_PARAMETERS_KEY`] key. If
+/// a `codec` is given, user-defined values that have no native [`Parameter`]
+/// type are encoded with it instead of being rejected.
+pub fn pydict_to_metadata_strict(
+    dict: Option<Bound<'_, PyDict>>,
+    codec: Option<MetadataCodec>,
+) -> Result<MetadataParameters> {
+    let mut parameters = BTreeMap::default();
+    let mut encoded_keys = Vec::new();
+    let Some(pymetadata) = dict else {
+        return Ok(parameters);
+    };
+    for (key, value) in pymetadata.iter() {
+        let key = key.extract::<String>().context("Parsing metadata keys")?;
+        if key == USER_PARAMETERS_KEY {
+            let user_parameters = value
+                .downcast::<PyDict>()
+                .map_err(|_| eyre::eyre!("`{USER_PARAMETERS_KEY}` metadata must be a dict"))?;
+            for (key, value) in user_parameters.iter() {
+                let key = key.extract::<String>().context("Parsing metadata keys")?;
+                if KNOWN_METADATA_KEYS.contains(&key.as_str()) {
+                    eyre::bail!(
+                        "metadata key `{key}` is reserved, pass it outside of `{USER_PARAMETERS_KEY}`"
+                    );
+                }
+                let parameter = pyvalue_to_parameter(&key, &value, true, codec, &mut encoded_keys)?;
+                parameters.insert(key, parameter);
+            }
+        } else if KNOWN_METADATA_KEYS.contains(&key.as_str()) {
+            let parameter = pyvalue_to_parameter(&key, &value, true, None, &mut encoded_keys)?;
+            parameters.insert(key, parameter);
+        } else {
+            eyre::bail!(
+                "unknown metadata key `{key}`, pass user-defined metadata in the \
+                `{USER_PARAMETERS_KEY}` dict (known keys: {})",
+                KNOWN_METADATA_KEYS.join(", ")
+            );
         }
     }
+    if let Some(codec) = codec {
+        metadata_codec::insert_codec_parameters(&mut parameters, codec, encoded_keys);
+    }
     Ok(parameters)
 }
 
-pub fn metadata_to_pydict<'a>(
-    metadata: &'a Metadata,
-    py: Python<'a>,
-) -> Result<pyo3::Bound<'a, PyDict>> {
+/// Converts a python metadata value into a [`Parameter`].
+///
+/// Unsupported values are encoded with the `codec` and their key is added to
+/// `encoded_keys`. Without a codec, they are converted to strings, unless
+/// `strict` is set.
+fn pyvalue_to_parameter(
+    key: &str,
+    value: &Bound<'_, PyAny>,
+    strict: bool,
+    codec: Option<MetadataCodec>,
+    encoded_keys: &mut Vec<String>,
+) -> Result<Parameter> {
+    let parameter = if key == OPEN_TELEMETRY_BAGGAGE && value.is_instance_of::<PyDict>() {
+        let entries: BTreeMap<String, String> = value
+            .extract()
+            .context("OpenTelemetry baggage must map strings to strings")?;
+        let header = serialize_baggage(entries.iter().map(|(k, v)| (k.as_str(), v.as_str())));
+        Parameter::String(header)
+    } else if key == TIMESTAMP_NS {
+        // floats can't represent nanosecond timestamps exactly
+        if !value.is_instance_of::<PyInt>() || value.is_instance_of::<PyBool>() {
+            eyre::bail!(
+                "`{TIMESTAMP_NS}` metadata must be an integer number of nanoseconds, got {}",
+                value.repr()?
+            );
+        }
+        let nanos: i64 = value
+            .extract()
+            .context("`timestamp_ns` metadata must fit into a 64-bit integer")?;
+        if nanos < 0 {
+            eyre::bail!("`{TIMESTAMP_NS}` metadata must not be negative");
+        }
+        Parameter::Integer(nanos)
+    } else if value.is_exact_instance_of::<PyBool>() {
+        Parameter::Bool(value.extract()?)
+    } else if value.is_instance_of::<PyInt>() {
+        Parameter::Integer(value.extract::<i64>()?)
+    } else if value.is_instance_of::<PyString>() {
+        Parameter::String(value.extract()?)
+    } else if value.is_instance_of::<PyBytes>() {
+        Parameter::Bytes(value.extract::<&[u8]>()?.to_vec())
+    } else if is_list_of::<PyInt>(value)? {
+        let list: Vec<i64> = value.extract()?;
+        Parameter::ListInt(list)
+    } else if is_list_of::<PyString>(value)? {
+        let list: Vec<String> = value.extract()?;
+        Parameter::ListString(list)
+    } else if let Some(codec) = codec {
+        let bytes = codec
+            .encode_python(value)
+            .wrap_err_with(|| format!("failed to encode value of metadata key `{key}`"))?;
+        encoded_keys.push(key.to_owned());
+        Parameter::Bytes(bytes)
+    } else if strict {
+        eyre::bail!(
+            "cannot represent value of metadata key `{key}` as metadata: {}",
+            value.repr()?
+        );
+    } else {
+        println!("could not convert type {value}");
+        Parameter::String(value.str()?.to_string())
+    };
+    Ok(parameter)
+}
+
+/// Whether `value` is a non-empty list or tuple whose elements are all exact
+/// instances of `T`, e.g. no `bool`s for `int`.
+fn is_list_of<T: PyTypeInfo>(value: &Bound<'_, PyAny>) -> PyResult<bool> {
+    let is_list = value.is_instance_of::<PyList>() || value.is_instance_of::<PyTuple>();
+    if !is_list || value.len()? == 0 {
+        return Ok(false);
+    }
+    for item in value.iter()? {
+        if !item?.is_exact_instance_of::<T>() {
+            return Ok(false);
+        }
+    }
+    Ok(true)
+}
+
+/// Converts the given timestamp to seconds since the UNIX epoch.
+fn timestamp_to_secs(timestamp: uhlc::Timestamp) -> f64 {
+    timestamp
+        .get_time()
+        .to_system_time()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs_f64()
+}
+
+pub fn metadata_to_pydict<'py>(
+    metadata: &Metadata,
+    py: Python<'py>,
+) -> Result<pyo3::Bound<'py, PyDict>> {
     let dict = PyDict::new_bound(py);
+    let encoded = metadata_codec::encoded_keys(&metadata.parameters)?;
     for (k, v) in metadata.parameters.iter() {
+        if let Some((codec, keys)) = encoded {
+            if k == METADATA_CODEC || k == ENCODED_METADATA_KEYS {
+                continue;
+            }
+            if let (true, Parameter::Bytes(bytes)) = (keys.contains(k), v) {
+                let value = codec
+                    .decode_python(py, bytes)
+                    .wrap_err_with(|| format!("failed to decode value of metadata key `{k}`"))?;
+                dict.set_item(k, value)
+                    .context("Could not insert metadata into python dictionary")?;
+                continue;
+            }
+        }
         match v {
             Parameter::Bool(bool) => dict
                 .set_item(k, bool)
@@ -213,6 +1169,12 @@ pub fn metadata_to_pydict<'a>(
             Parameter::ListInt(l) => dict
                 .set_item(k, l)
                 .context("Could not insert metadata into python dictionary")?,
+            Parameter::ListString(l) => dict
+                .set_item(k, l)
+                .context("Could not insert metadata into python dictionary")?,
+            Parameter::Bytes(b) => dict
+                .set_item(k, PyBytes::new_bound(py, b))
+                .context("Could not insert metadata into python dictionary")?,
         }
     }
 
@@ -221,23 +1183,51 @@ pub fn metadata_to_pydict<'a>(
 
 #[cfg(test)]
 mod tests {
-    use std::sync::Arc;
+    use std::{collections::BTreeMap, sync::Arc};
 
     use aligned_vec::{AVec, ConstAlign};
     use arrow::{
         array::{
-            ArrayData, ArrayRef, BooleanArray, Float64Array, Int32Array, Int64Array, Int8Array,
-            ListArray, StructArray,
+            Array, ArrayData, ArrayRef, BooleanArray, Decimal128Array, Float64Array, Int32Array,
+            Int64Array, Int8Array, LargeBinaryArray, LargeListArray, LargeStringArray, ListArray,
+            NullArray, StructArray, UInt8Array,
         },
         buffer::Buffer,
+        datatypes::{Decimal128Type, Int32Type},
     };
 
-    use arrow_schema::{DataType, Field};
+    use arrow_schema::{DataType, Field, Schema};
     use dora_node_api::{
-        arrow_utils::{copy_array_into_sample, required_data_size},
-        RawData,
+        arrow_utils::{
+            check_offsets, combine_chunks, copy_array_into_sample, large_offsets_type,
+            required_data_size,
+        },
+        dora_core::{config::DataId, descriptor::SchemaField, metadata::ArrowTypeInfoExt},
+        metadata::ArrowTypeInfo,
+        metadata::TIMESTAMP_NS,
+        uhlc, Event, Metadata, Parameter, RawData,
+    };
+    use eyre::{Context, ContextCompat, Result};
+    use pyo3::{prelude::*, types::PyList};
+
+    use crate::decimal::decimal_array_from_python;
+    use crate::flight::{
+        app_metadata_to_parameters, array_to_flight_batch, flight_batch_to_array,
+        metadata_to_app_metadata, VALUE_COLUMN,
+    };
+    use crate::recording::{
+        parse_compression, rotated_path, EventRecorder, RecordingReader, RotationPolicy,
     };
-    use eyre::{Context, Result};
+    use crate::schema_registry::{
+        register, register_descriptor_schemas, schema_from_fields, validate,
+    };
+    use crate::sparse::{array_to_sparse, sparse_to_array, SparseFormat, SparseMatrix};
+    use crate::structured::{nest_fixed_size, unnest_fixed_size};
+    use crate::table::{batches_to_ipc, ipc_to_batches};
+    use crate::tensor::{
+        inverse_permutation, memory_layout, parse_tensor_metadata, tensor_metadata,
+    };
+    use crate::{MergedEvent, PyEvent};
 
     fn assert_roundtrip(arrow_array: &ArrayData) -> Result<()> {
         let size = required_data_size(arrow_array);
@@ -308,4 +1298,707 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn serialize_deserialize_decimal() -> Result<()> {
+        // 12345.6789, -0.0001, null, and 10^29 - 0.0001 at precision 38, scale 4
+        let array = Decimal128Array::from(vec![
+            Some(123_456_789),
+            Some(-1),
+            None,
+            Some(10i128.pow(33) - 1),
+        ])
+        .with_precision_and_scale(38, 4)?;
+        let arrow_array = array.to_data();
+        assert_roundtrip(&arrow_array).context("Decimal128Array roundtrip failed")?;
+
+        let size = required_data_size(&arrow_array);
+        let mut sample: AVec<u8, ConstAlign<128>> = AVec::__from_elem(128, 0, size);
+        let info = copy_array_into_sample(&mut sample, &arrow_array);
+        let received = Decimal128Array::from(RawData::Vec(sample).into_arrow_array(&info)?);
+        assert_eq!(received.data_type(), &DataType::Decimal128(38, 4));
+        assert_eq!(received.value_as_string(0), "12345.6789");
+        assert_eq!(received.value_as_string(1), "-0.0001");
+        assert!(received.is_null(2));
+        assert_eq!(
+            received.value_as_string(3),
+            "99999999999999999999999999999.9999"
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn decimal_list_roundtrip() -> Result<()> {
+        use arrow::array::AsArray;
+
+        pyo3::prepare_freethreaded_python();
+        Python::with_gil(|py| {
+            let decimal = py.import_bound("decimal")?.getattr("Decimal")?;
+            let values = ["12.3456", "-0.0001", "1E+3", "0.50"]
+                .into_iter()
+                .map(|value| decimal.call1((value,)))
+                .collect::<PyResult<Vec<_>>>()?;
+            let list = PyList::new_bound(py, &values);
+
+            let array = decimal_array_from_python(list.as_any())?.context("not converted")?;
+            assert_eq!(array.data_type(), &DataType::Decimal128(8, 4));
+            let array = array.as_primitive::<Decimal128Type>();
+            for (index, value) in values.iter().enumerate() {
+                let received = decimal.call1((array.value_as_string(index),))?;
+                assert!(received.eq(value)?, "{received} != {value}");
+            }
+
+            let float = 1.5f64.into_py(py).into_bound(py);
+            let mixed = PyList::new_bound(py, [values[0].clone(), float]);
+            assert!(decimal_array_from_python(mixed.as_any()).is_err());
+            let nan = PyList::new_bound(py, [values[0].clone(), decimal.call1(("NaN",))?]);
+            assert!(decimal_array_from_python(nan.as_any()).is_err());
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn serialize_deserialize_empty_arrow() -> Result<()> {
+        // empty bytes, e.g. sent as a pure trigger
+        let arrow_array = UInt8Array::from(Vec::<u8>::new()).into();
+        assert_roundtrip(&arrow_array).context("empty UInt8Array roundtrip failed")?;
+
+        let arrow_array = Int32Array::from(Vec::<i32>::new()).into();
+        assert_roundtrip(&arrow_array).context("empty Int32Array roundtrip failed")?;
+
+        // no data buffers, but non-zero length
+        let arrow_array = NullArray::new(3).into();
+        assert_roundtrip(&arrow_array).context("NullArray roundtrip failed")?;
+
+        for data in [
+            UInt8Array::from(Vec::<u8>::new()).into(),
+            NullArray::new(3).into(),
+        ] {
+            let event = PyEvent::new(
+                MergedEvent::Dora(Event::Input {
+                    id: DataId::from("tick".to_owned()),
+                    metadata: Metadata::new(
+                        uhlc::HLC::default().new_timestamp(),
+                        ArrowTypeInfo::empty(),
+                    ),
+                    data: arrow::array::make_array(data).into(),
+                }),
+                None,
+            );
+            assert_eq!(event.byte_len(), 0);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn float_special_values_roundtrip() -> Result<()> {
+        use arrow::{
+            array::{AsArray, Float32Array},
+            datatypes::{Float32Type, Float64Type},
+        };
+
+        // quiet NaN, NaN with sign bit and payload, signaling NaN, infinities, -0.0
+        let f64_bits = [
+            f64::NAN.to_bits(),
+            0xfff8_0000_dead_beef,
+            0x7ff0_0000_0000_0001,
+            f64::INFINITY.to_bits(),
+            f64::NEG_INFINITY.to_bits(),
+            (-0.0f64).to_bits(),
+        ];
+        let f32_bits = [
+            f32::NAN.to_bits(),
+            0xffc0_beef,
+            0x7f80_0001,
+            f32::INFINITY.to_bits(),
+            f32::NEG_INFINITY.to_bits(),
+            (-0.0f32).to_bits(),
+        ];
+        // `assert_roundtrip` can't be used since NaN is not equal to itself
+        let roundtrip = |array: ArrayRef| -> Result<Vec<ArrayRef>> {
+            let data = array.to_data();
+            let size = required_data_size(&data);
+            let mut sample: AVec<u8, ConstAlign<128>> = AVec::__from_elem(128, 0, size);
+            let info = copy_array_into_sample(&mut sample, &data);
+            let received = arrow::array::make_array(RawData::Vec(sample).into_arrow_array(&info)?);
+            let flight = flight_batch_to_array(array_to_flight_batch(array)?);
+            Ok(vec![received, flight])
+        };
+
+        let array = Float64Array::from_iter_values(f64_bits.map(f64::from_bits));
+        for received in roundtrip(Arc::new(array))? {
+            let values = received.as_primitive::<Float64Type>().values();
+            let bits: Vec<u64> = values.iter().map(|value| value.to_bits()).collect();
+            assert_eq!(bits, f64_bits);
+        }
+
+        let array = Float32Array::from_iter_values(f32_bits.map(f32::from_bits));
+        for received in roundtrip(Arc::new(array))? {
+            let values = received.as_primitive::<Float32Type>().values();
+            let bits: Vec<u32> = values.iter().map(|value| value.to_bits()).collect();
+            assert_eq!(bits, f32_bits);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn serialize_deserialize_large_offsets() -> Result<()> {
+        let arrow_array = LargeStringArray::from(vec!["a", "bc", ""]).into();
+        assert_roundtrip(&arrow_array).context("LargeStringArray roundtrip failed")?;
+
+        let arrow_array = LargeBinaryArray::from(vec![b"a".as_slice(), b"bc"]).into();
+        assert_roundtrip(&arrow_array).context("LargeBinaryArray roundtrip failed")?;
+
+        let arrow_array = LargeListArray::from_iter_primitive::<Int32Type, _, _>(vec![
+            Some(vec![Some(0), Some(1)]),
+            None,
+            Some(vec![Some(2)]),
+        ])
+        .into();
+        assert_roundtrip(&arrow_array).context("LargeListArray roundtrip failed")?;
+
+        let list_type = DataType::List(Arc::new(Field::new("item", DataType::Utf8, true)));
+        assert_eq!(
+            large_offsets_type(&list_type),
+            Some(DataType::LargeList(Arc::new(Field::new(
+                "item",
+                DataType::LargeUtf8,
+                true
+            ))))
+        );
+        assert_eq!(large_offsets_type(&DataType::Int32), None);
+
+        Ok(())
+    }
+
+    #[test]
+    fn chunked_arrays_switch_to_large_offsets() -> Result<()> {
+        use arrow::array::{AsArray, StringArray};
+
+        // pyarrow only chunks arrays that exceed 2GB, which is too large for a
+        // test, so the switch is checked on small chunks
+        let chunks: Vec<ArrayRef> = vec![
+            Arc::new(StringArray::from(vec!["a", "bc"])),
+            Arc::new(StringArray::from(vec![Some("def"), None])),
+        ];
+        let combined = combine_chunks(&chunks, &DataType::Utf8)?;
+        assert_eq!(combined.data_type(), &DataType::LargeUtf8);
+        let combined = combined.as_string::<i64>();
+        assert_eq!(combined.len(), 4);
+        assert_eq!(combined.value(2), "def");
+        assert!(combined.is_null(3));
+        assert_roundtrip(&combined.to_data()).context("combined chunks roundtrip failed")?;
+
+        let list_type = DataType::List(Arc::new(Field::new("item", DataType::Int32, true)));
+        let chunks: Vec<ArrayRef> = vec![Arc::new(
+            ListArray::from_iter_primitive::<Int32Type, _, _>(vec![Some(vec![Some(1), Some(2)])]),
+        )];
+        let combined = combine_chunks(&chunks, &list_type)?;
+        assert_eq!(
+            combined.data_type(),
+            &large_offsets_type(&list_type).context("no large offsets type")?
+        );
+
+        let empty = combine_chunks(&[], &DataType::Binary)?;
+        assert_eq!(empty.data_type(), &DataType::LargeBinary);
+        assert!(empty.is_empty());
+
+        Ok(())
+    }
+
+    #[test]
+    fn overflowed_offsets_are_rejected() {
+        // offsets beyond `i32::MAX` wrap around to negative values
+        let offsets = Buffer::from_slice_ref([0i32, 3, i32::MIN]);
+        let values = Buffer::from_slice_ref(b"abc");
+        let arrow_array = unsafe {
+            ArrayData::builder(DataType::Utf8)
+                .len(2)
+                .add_buffer(offsets)
+                .add_buffer(values)
+                .build_unchecked()
+        };
+
+        let err = check_offsets(&arrow_array).unwrap_err();
+        assert!(err.to_string().contains("LargeUtf8"), "{err}");
+    }
+
+    #[test]
+    fn flight_batch_roundtrip() -> Result<()> {
+        let array: ArrayRef = Arc::new(Float64Array::from(vec![0.5, 1.5]));
+        let batch = array_to_flight_batch(array.clone())?;
+        assert_eq!(batch.schema().field(0).name(), VALUE_COLUMN);
+        assert_eq!(&flight_batch_to_array(batch), &array);
+
+        let array: ArrayRef = Arc::new(StructArray::from(vec![
+            (
+                Arc::new(Field::new("x", DataType::Int64, false)),
+                Arc::new(Int64Array::from(vec![1, 2])) as ArrayRef,
+            ),
+            (
+                Arc::new(Field::new("ok", DataType::Boolean, false)),
+                Arc::new(BooleanArray::from(vec![true, false])) as ArrayRef,
+            ),
+        ]));
+        let batch = array_to_flight_batch(array.clone())?;
+        assert_eq!(batch.num_columns(), 2);
+        assert_eq!(&flight_batch_to_array(batch), &array);
+
+        Ok(())
+    }
+
+    #[test]
+    fn flight_app_metadata_roundtrip() -> Result<()> {
+        let parameters = [("camera".to_owned(), Parameter::String("front".into()))].into();
+        let metadata = Metadata::from_parameters(
+            uhlc::HLC::default().new_timestamp(),
+            ArrowTypeInfo::empty(),
+            parameters,
+        );
+        let app_metadata = metadata_to_app_metadata(&metadata)?;
+        assert_eq!(
+            app_metadata_to_parameters(&app_metadata)?,
+            metadata.parameters
+        );
+        assert!(app_metadata_to_parameters(b"not metadata").is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn compressed_recording_roundtrip() -> Result<()> {
+        let clock = uhlc::HLC::default();
+        let inputs: Vec<(DataId, Metadata, ArrayRef)> = (0..100)
+            .map(|i| {
+                let parameters = [("frame".to_owned(), Parameter::Integer(i))].into();
+                let metadata = Metadata::from_parameters(
+                    clock.new_timestamp(),
+                    ArrowTypeInfo::empty(),
+                    parameters,
+                );
+                let value: ArrayRef = Arc::new(Int64Array::from(vec![i; 1000]));
+                (DataId::from("image".to_owned()), metadata, value)
+            })
+            .collect();
+
+        let dir = std::env::temp_dir();
+        let mut sizes = Vec::new();
+        for compression in [None, Some("zstd"), Some("lz4")] {
+            let path = dir.join(format!(
+                "dora-recording-{}-{}.arrow",
+                std::process::id(),
+                compression.unwrap_or("none")
+            ));
+            let mut recorder = EventRecorder::create(&path, parse_compression(compression)?)?;
+            for (id, metadata, value) in &inputs {
+                recorder.record(id, metadata, value.clone())?;
+            }
+            drop(recorder);
+            sizes.push(std::fs::metadata(&path)?.len());
+
+            // compressed recordings are decompressed transparently
+            let recorded = RecordingReader::open(&path)?.collect::<Result<Vec<_>>>()?;
+            std::fs::remove_file(&path)?;
+            assert_eq!(recorded.len(), inputs.len());
+            for (recorded, (id, metadata, value)) in recorded.iter().zip(&inputs) {
+                assert_eq!(&recorded.id, id);
+                assert_eq!(&recorded.metadata, metadata);
+                assert_eq!(&recorded.value, value);
+            }
+        }
+        assert!(sizes[1] < sizes[0] / 2);
+        assert!(sizes[2] < sizes[0] / 2);
+        assert!(parse_compression(Some("gzip")).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn recording_rotation() -> Result<()> {
+        let clock = uhlc::HLC::default();
+        let id = DataId::from("image".to_owned());
+        let metadata = Metadata::new(clock.new_timestamp(), ArrowTypeInfo::empty());
+        let value: ArrayRef = Arc::new(Int64Array::from(vec![0; 1000]));
+
+        let path = std::env::temp_dir().join(format!("dora-rotation-{}.arrow", std::process::id()));
+        let mut recorder = EventRecorder::create(&path, None)?;
+        recorder.record(&id, &metadata, value.clone())?;
+        let rotated = recorder.rotate(None)?;
+        assert_eq!(rotated, rotated_path(&path, 1));
+        assert!(rotated.ends_with(format!("dora-rotation-{}_001.arrow", std::process::id())));
+
+        // every input of the size limit starts a new file
+        recorder.set_rotation(RotationPolicy {
+            max_bytes: Some(1),
+            max_duration: None,
+        });
+        recorder.record(&id, &metadata, value.clone())?;
+        assert_eq!(recorder.path(), rotated_path(&path, 2));
+        drop(recorder);
+
+        let files = [(path.clone(), 1), (rotated, 1), (rotated_path(&path, 2), 0)];
+        for (path, len) in files {
+            let recorded = RecordingReader::open(&path)?.collect::<Result<Vec<_>>>()?;
+            std::fs::remove_file(&path)?;
+            assert_eq!(recorded.len(), len);
+            assert!(recorded
+                .iter()
+                .all(|input| input.value.as_ref() == value.as_ref()));
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn nanosecond_timestamp_roundtrip() -> Result<()> {
+        // not representable as `f64` seconds
+        let nanos: u64 = 1_700_000_000_123_456_789;
+        assert_ne!((nanos as f64 / 1e9 * 1e9) as u64, nanos);
+
+        let timestamp = uhlc::HLC::default().new_timestamp();
+        let parameters = [(TIMESTAMP_NS.to_owned(), Parameter::Integer(nanos as i64))].into();
+        let metadata = Metadata::from_parameters(timestamp, ArrowTypeInfo::empty(), parameters);
+        let metadata: Metadata = serde_json::from_slice(&serde_json::to_vec(&metadata)?)?;
+        let value: ArrayRef = Arc::new(Int64Array::from(vec![1]));
+        let event = PyEvent::new(
+            MergedEvent::Dora(Event::Input {
+                id: DataId::from("imu".to_owned()),
+                metadata,
+                data: value.into(),
+            }),
+            None,
+        );
+        assert_eq!(event.timestamp_ns(), Some(nanos));
+        assert_eq!(event.byte_len(), 8);
+
+        // without the parameter, the clock timestamp is converted exactly
+        let metadata = Metadata::new(timestamp, ArrowTypeInfo::empty());
+        assert_eq!(
+            u128::from(metadata.timestamp_ns()),
+            timestamp.get_time().to_duration().as_nanos()
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn sparse_matrix_roundtrip() -> Result<()> {
+        // [[1, 0, 0, 2],
+        //  [0, 0, 0, 0],
+        //  [0, 3, 0, 0]]
+        let matrix = SparseMatrix {
+            format: SparseFormat::Csr,
+            shape: [3, 4],
+            indices: [
+                Arc::new(Int64Array::from(vec![0, 2, 2, 3])),
+                Arc::new(Int64Array::from(vec![0, 3, 1])),
+            ],
+            data: Arc::new(Float64Array::from(vec![1.0, 2.0, 3.0])),
+        };
+        let array = sparse_to_array(&matrix)?;
+        assert_roundtrip(&array.to_data())?;
+
+        let size = required_data_size(&array.to_data());
+        let mut sample: AVec<u8, ConstAlign<128>> = AVec::__from_elem(128, 0, size);
+        let info = copy_array_into_sample(&mut sample, &array.to_data());
+        let received = arrow::array::make_array(RawData::Vec(sample).into_arrow_array(&info)?);
+        assert_eq!(array_to_sparse(&received)?, matrix);
+
+        // index arrays must be `int64`
+        let invalid = SparseMatrix {
+            indices: [
+                Arc::new(Int32Array::from(vec![0, 2, 2, 3])),
+                matrix.indices[1].clone(),
+            ],
+            ..matrix
+        };
+        assert!(sparse_to_array(&invalid).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn structured_array_roundtrip() -> Result<()> {
+        use arrow::array::AsArray;
+
+        // dtype([("id", "u4"), ("pose", "f4", (2, 3))]) with two records
+        let pose: ArrayRef = Arc::new(arrow::array::Float32Array::from_iter_values(
+            (0..12).map(|i| i as f32),
+        ));
+        let pose = nest_fixed_size(pose, &[2, 3])?;
+        assert_eq!(pose.len(), 2);
+        let id: ArrayRef = Arc::new(arrow::array::UInt32Array::from(vec![7, 8]));
+        let array = StructArray::from(vec![
+            (Arc::new(Field::new("id", DataType::UInt32, true)), id),
+            (
+                Arc::new(Field::new("pose", pose.data_type().clone(), true)),
+                pose.clone(),
+            ),
+        ]);
+        assert_roundtrip(&array.to_data())?;
+
+        let (values, shape) = unnest_fixed_size(&pose);
+        assert_eq!(shape, [2, 3]);
+        assert_eq!(values.len(), 12);
+        assert_eq!(values.data_type(), &DataType::Float32);
+
+        // sliced records keep their subarrays
+        let (values, shape) = unnest_fixed_size(&pose.slice(1, 1));
+        assert_eq!(shape, [2, 3]);
+        assert_eq!(
+            values
+                .as_primitive::<arrow::datatypes::Float32Type>()
+                .value(0),
+            6.0
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn table_ipc_roundtrip() -> Result<()> {
+        use arrow::record_batch::RecordBatch;
+        use arrow_schema::Schema;
+
+        let schema = Arc::new(Schema::new(vec![Field::new("x", DataType::Int64, false)]));
+        let batches = vec![
+            RecordBatch::try_new(schema.clone(), vec![Arc::new(Int64Array::from(vec![1, 2]))])?,
+            RecordBatch::try_new(schema.clone(), vec![Arc::new(Int64Array::from(vec![3]))])?,
+        ];
+        let array = batches_to_ipc(&schema, &batches)?;
+        assert_eq!(array.data_type(), &DataType::UInt8);
+        assert_roundtrip(&array.to_data())?;
+
+        let (received_schema, received) = ipc_to_batches(&array)?;
+        assert_eq!(received_schema, schema);
+        assert_eq!(received, batches);
+
+        // all batches must have the same schema
+        let other = Arc::new(Schema::new(vec![Field::new("y", DataType::Int64, false)]));
+        let mismatch = RecordBatch::try_new(other, vec![Arc::new(Int64Array::from(vec![4]))])?;
+        assert!(batches_to_ipc(&schema, &[batches[0].clone(), mismatch]).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn metadata_codec_roundtrip() -> Result<()> {
+        use crate::metadata_codec::{
+            encoded_keys, insert_codec_parameters, MetadataCodec, ENCODED_METADATA_KEYS,
+        };
+        use dora_node_api::MetadataParameters;
+
+        let value = serde_json::json!({
+            "covariance": [[0.1, 0.0], [0.0, 0.1]],
+            "label": "car",
+            "score": 0.93,
+            "valid": true,
+            "parent": null,
+        });
+        for codec in [
+            MetadataCodec::Json,
+            MetadataCodec::Msgpack,
+            MetadataCodec::Cbor,
+        ] {
+            let bytes = codec.encode(&value)?;
+            assert_eq!(codec.decode(&bytes)?, value, "codec `{codec}`");
+            assert_eq!(codec.to_string().parse::<MetadataCodec>()?, codec);
+
+            let mut parameters = MetadataParameters::default();
+            parameters.insert("pose".to_owned(), Parameter::Bytes(bytes));
+            insert_codec_parameters(&mut parameters, codec, vec!["pose".to_owned()]);
+            let (received_codec, keys) = encoded_keys(&parameters)?.expect("codec not recorded");
+            assert_eq!(received_codec, codec);
+            assert_eq!(keys, ["pose".to_owned()]);
+        }
+
+        // the codec is only recorded if a value was encoded
+        let mut parameters = MetadataParameters::default();
+        insert_codec_parameters(&mut parameters, MetadataCodec::Msgpack, Vec::new());
+        assert!(!parameters.contains_key(ENCODED_METADATA_KEYS));
+        assert!(encoded_keys(&parameters)?.is_none());
+
+        assert!("yaml".parse::<MetadataCodec>().is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn tensor_metadata_roundtrip() -> Result<()> {
+        let metadata = tensor_metadata(&[2, 3, 4], None);
+        assert_eq!(metadata, r#"{"shape":[2,3,4]}"#);
+        assert_eq!(parse_tensor_metadata(&metadata)?, (vec![2, 3, 4], None));
+
+        // e.g. a Fortran-ordered array of logical shape [4, 3, 2]
+        let metadata = tensor_metadata(&[2, 3, 4], Some(&[2, 1, 0]));
+        assert_eq!(
+            parse_tensor_metadata(&metadata)?,
+            (vec![2, 3, 4], Some(vec![2, 1, 0]))
+        );
+        assert!(parse_tensor_metadata(r#"{"shape":[2,3],"permutation":[0,2]}"#).is_err());
+
+        assert_eq!(inverse_permutation(&[2, 0, 1]), [1, 2, 0]);
+        assert_eq!(inverse_permutation(&[1, 2, 0]), [2, 0, 1]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn transposed_tensor_roundtrip() -> Result<()> {
+        // `np.zeros((2, 3, 4)).transpose(1, 2, 0)`, which keeps the strides of
+        // the row-major (2, 3, 4) array
+        let shape = [3, 4, 2];
+        let strides = [32, 8, 96];
+        let (physical_shape, permutation) = memory_layout(&shape, &strides);
+        assert_eq!(physical_shape, [2, 3, 4]);
+        assert_eq!(permutation.as_deref(), Some(&[1, 2, 0][..]));
+
+        // receivers apply the permutation to the physical shape
+        let metadata = tensor_metadata(&physical_shape, permutation.as_deref());
+        assert_eq!(metadata, r#"{"permutation":[1,2,0],"shape":[2,3,4]}"#);
+        let (received_shape, received_permutation) = parse_tensor_metadata(&metadata)?;
+        let logical_shape: Vec<usize> = received_permutation
+            .unwrap()
+            .iter()
+            .map(|&dim| received_shape[dim])
+            .collect();
+        assert_eq!(logical_shape, shape);
+
+        // row-major arrays need no permutation
+        assert_eq!(memory_layout(&[2, 3], &[24, 8]), (vec![2, 3], None));
+
+        Ok(())
+    }
+
+    #[test]
+    fn schema_registry_validation() -> Result<()> {
+        let fields = vec![
+            SchemaField {
+                name: "label".to_owned(),
+                data_type: "Utf8".to_owned(),
+                nullable: None,
+            },
+            SchemaField {
+                name: "score".to_owned(),
+                data_type: "Float32".to_owned(),
+                nullable: Some(false),
+            },
+        ];
+        let schemas = BTreeMap::from([("TestDetection".to_owned(), fields.clone())]);
+        register_descriptor_schemas(&schemas)?;
+        // registering the same schema again is allowed
+        register_descriptor_schemas(&schemas)?;
+
+        let detection = DataType::Struct(schema_from_fields(&fields)?.fields().clone());
+        validate("TestDetection", &detection)?;
+        assert!(validate("TestDetection", &DataType::Float32).is_err());
+        assert!(validate("TestUnknown", &detection).is_err());
+
+        let other = Arc::new(Schema::new(vec![Field::new("x", DataType::Int32, true)]));
+        assert!(register("TestDetection", other.clone()).is_err());
+        register("TestScalar", other)?;
+        validate("TestScalar", &DataType::Int32)?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn delta_encoding_roundtrip() -> Result<()> {
+        use crate::delta::{
+            apply, diff, DeltaDecoder, DeltaEncoder, DeltaFrame, DELTA_BASE, DELTA_VERSION,
+        };
+        use arrow::{
+            array::{make_array, AsArray, Float32Array},
+            datatypes::Float32Type,
+        };
+        use dora_node_api::{ArrowData, MetadataParameters};
+
+        let previous: Vec<u8> = (0..=255).cycle().take(1000).collect();
+        let mut current = previous.clone();
+        current[10] = 0;
+        current[500..700].fill(1);
+        current[999] = 2;
+        let delta = diff(&previous, &current);
+        assert!(delta.len() < current.len());
+        assert_eq!(apply(&previous, &delta)?, current);
+        assert!(diff(&current, &current).is_empty());
+        assert!(apply(&previous[..100], &delta).is_err());
+        assert!(apply(&previous, &delta[..20]).is_err());
+
+        let mut encoder = DeltaEncoder::default();
+        let mut decoder = DeltaDecoder::default();
+        let id = DataId::from("costmap".to_owned());
+        let mut send = |values: Vec<f32>, full_every| -> Result<(DeltaFrame, Vec<f32>)> {
+            let value = Float32Array::from(values).into_data();
+            let frame = encoder.encode(&value, full_every)?;
+            let (mut data, parameters): (ArrowData, MetadataParameters) = match &frame {
+                DeltaFrame::Full { version } => (
+                    make_array(value).into(),
+                    [(DELTA_VERSION.to_owned(), Parameter::Integer(*version))].into(),
+                ),
+                DeltaFrame::Delta {
+                    version,
+                    base,
+                    delta,
+                } => (
+                    make_array(UInt8Array::from(delta.clone()).into_data()).into(),
+                    [
+                        (DELTA_VERSION.to_owned(), Parameter::Integer(*version)),
+                        (DELTA_BASE.to_owned(), Parameter::Integer(*base)),
+                    ]
+                    .into(),
+                ),
+            };
+            let metadata = Metadata::from_parameters(
+                uhlc::HLC::default().new_timestamp(),
+                ArrowTypeInfo::empty(),
+                parameters,
+            );
+            decoder.decode(&id, &metadata, &mut data)?;
+            let received = data.as_primitive::<Float32Type>().values().to_vec();
+            Ok((frame, received))
+        };
+
+        let mut costmap = vec![0.0; 1024];
+        let (frame, received) = send(costmap.clone(), Some(3))?;
+        assert_eq!(frame, DeltaFrame::Full { version: 1 });
+        assert_eq!(received, costmap);
+        costmap[100] = 1.0;
+        let (frame, received) = send(costmap.clone(), Some(3))?;
+        assert!(matches!(frame, DeltaFrame::Delta { base: 1, .. }));
+        assert_eq!(received, costmap);
+        costmap[900] = 2.0;
+        let (frame, received) = send(costmap.clone(), Some(3))?;
+        assert!(matches!(frame, DeltaFrame::Delta { base: 2, .. }));
+        assert_eq!(received, costmap);
+        // periodic full refresh
+        let (frame, received) = send(costmap.clone(), Some(3))?;
+        assert_eq!(frame, DeltaFrame::Full { version: 4 });
+        assert_eq!(received, costmap);
+        // the value after a failed send is sent in full
+        encoder.reset();
+        let value = Float32Array::from(costmap).into_data();
+        assert_eq!(
+            encoder.encode(&value, None)?,
+            DeltaFrame::Full { version: 5 }
+        );
+
+        // a delta without its base value is rejected
+        let delta = make_array(UInt8Array::from(Vec::<u8>::new()).into_data());
+        let metadata = Metadata::from_parameters(
+            uhlc::HLC::default().new_timestamp(),
+            ArrowTypeInfo::empty(),
+            [
+                (DELTA_VERSION.to_owned(), Parameter::Integer(6)),
+                (DELTA_BASE.to_owned(), Parameter::Integer(5)),
+            ]
+            .into(),
+        );
+        assert!(decoder.decode(&id, &metadata, &mut delta.into()).is_err());
+
+        Ok(())
+    }
 }
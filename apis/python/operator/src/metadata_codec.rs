@@ -0,0 +1,121 @@
+//! Encoding of metadata values that have no native [`Parameter`] type.
+//!
+//! By default, such values, e.g. floats or nested dicts, are converted to their
+//! string representation. With a codec, they are serialized as JSON, MessagePack,
+//! or CBOR instead and stored as [`Parameter::Bytes`]. The codec and the encoded
+//! keys are stored in the [`METADATA_CODEC`] and [`ENCODED_METADATA_KEYS`]
+//! parameters, so that receivers decode the values without any configuration.
+
+use std::{fmt, str::FromStr};
+
+use dora_node_api::{MetadataParameters, Parameter};
+use eyre::{Context, Result};
+use pyo3::prelude::*;
+
+/// Metadata parameter key of the name of the codec of the encoded values.
+pub const METADATA_CODEC: &str = "metadata_codec";
+/// Metadata parameter key of the list of keys whose values are encoded.
+pub const ENCODED_METADATA_KEYS: &str = "encoded_metadata_keys";
+
+/// Serialization format of metadata values that have no native [`Parameter`] type.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MetadataCodec {
+    Json,
+    Msgpack,
+    Cbor,
+}
+
+impl MetadataCodec {
+    pub fn encode(self, value: &serde_json::Value) -> Result<Vec<u8>> {
+        match self {
+            MetadataCodec::Json => serde_json::to_vec(value).context("failed to encode JSON"),
+            MetadataCodec::Msgpack => {
+                rmp_serde::to_vec(value).context("failed to encode MessagePack")
+            }
+            MetadataCodec::Cbor => {
+                let mut bytes = Vec::new();
+                ciborium::into_writer(value, &mut bytes).context("failed to encode CBOR")?;
+                Ok(bytes)
+            }
+        }
+    }
+
+    pub fn decode(self, bytes: &[u8]) -> Result<serde_json::Value> {
+        match self {
+            MetadataCodec::Json => serde_json::from_slice(bytes).context("failed to decode JSON"),
+            MetadataCodec::Msgpack => {
+                rmp_serde::from_slice(bytes).context("failed to decode MessagePack")
+            }
+            MetadataCodec::Cbor => ciborium::from_reader(bytes).context("failed to decode CBOR"),
+        }
+    }
+
+    /// Encodes a python value, e.g. a dict of floats.
+    pub fn encode_python(self, value: &Bound<'_, PyAny>) -> Result<Vec<u8>> {
+        let value: serde_json::Value = pythonize::depythonize(value)
+            .context("value cannot be converted to a JSON-like value")?;
+        self.encode(&value)
+    }
+
+    /// Decodes a value that was encoded through [`Self::encode_python`].
+    pub fn decode_python(self, py: Python<'_>, bytes: &[u8]) -> Result<PyObject> {
+        let value = self.decode(bytes)?;
+        Ok(pythonize::pythonize(py, &value)?.unbind())
+    }
+}
+
+impl FromStr for MetadataCodec {
+    type Err = eyre::Report;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "json" => Ok(MetadataCodec::Json),
+            "msgpack" => Ok(MetadataCodec::Msgpack),
+            "cbor" => Ok(MetadataCodec::Cbor),
+            other => eyre::bail!(
+                "unsupported metadata codec `{other}`, expected `json`, `msgpack`, or `cbor`"
+            ),
+        }
+    }
+}
+
+impl fmt::Display for MetadataCodec {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            MetadataCodec::Json => "json",
+            MetadataCodec::Msgpack => "msgpack",
+            MetadataCodec::Cbor => "cbor",
+        };
+        f.write_str(name)
+    }
+}
+
+/// Records the codec and the encoded keys in the parameters, if any value was encoded.
+pub fn insert_codec_parameters(
+    parameters: &mut MetadataParameters,
+    codec: MetadataCodec,
+    encoded_keys: Vec<String>,
+) {
+    if encoded_keys.is_empty() {
+        return;
+    }
+    parameters.insert(
+        METADATA_CODEC.to_owned(),
+        Parameter::String(codec.to_string()),
+    );
+    parameters.insert(
+        ENCODED_METADATA_KEYS.to_owned(),
+        Parameter::ListString(encoded_keys),
+    );
+}
+
+/// Returns the codec and the keys of the encoded values of the parameters.
+pub fn encoded_keys(parameters: &MetadataParameters) -> Result<Option<(MetadataCodec, &[String])>> {
+    let (Some(Parameter::String(codec)), Some(Parameter::ListString(keys))) = (
+        parameters.get(METADATA_CODEC),
+        parameters.get(ENCODED_METADATA_KEYS),
+    ) else {
+        return Ok(None);
+    };
+    Ok(Some((codec.parse()?, keys)))
+}
@@ -0,0 +1,79 @@
+//! Transfer of protobuf messages as serialized bytes.
+//!
+//! Messages are sent as `uint8` arrays with the `application/x-protobuf`
+//! [`CONTENT_TYPE`](dora_node_api::metadata::CONTENT_TYPE). The full name of
+//! the message type is stored in the
+//! [`PROTO_TYPE`](dora_node_api::metadata::PROTO_TYPE) metadata parameter, so
+//! that receivers notice if they parse a message with the wrong class.
+
+use arrow::{
+    array::{ArrayRef, AsArray},
+    datatypes::UInt8Type,
+};
+use dora_node_api::Metadata;
+use eyre::{Context, ContextCompat, Result};
+use pyo3::{prelude::*, types::PyBytes};
+
+use crate::schema_registry::schema_error;
+
+/// Content type of serialized protobuf messages.
+pub const PROTOBUF_CONTENT_TYPE: &str = "application/x-protobuf";
+
+/// Serializes a protobuf message of the `protobuf` package.
+///
+/// Returns the serialized bytes and the full name of the message type.
+pub fn proto_to_bytes<'py>(message: &Bound<'py, PyAny>) -> Result<(Bound<'py, PyBytes>, String)> {
+    let type_name = message_type_name(message).context("value is not a protobuf message")?;
+    let bytes = message
+        .call_method0("SerializeToString")
+        .context("failed to serialize protobuf message")?
+        .downcast_into::<PyBytes>()
+        .map_err(|_| eyre::eyre!("`SerializeToString` did not return bytes"))?;
+    Ok((bytes, type_name))
+}
+
+/// Parses a message that was sent through [`proto_to_bytes`] with the given
+/// message class.
+///
+/// Raises a `dora.SchemaError` if the sender attached a different message type.
+/// Messages without a [`PROTO_TYPE`](dora_node_api::metadata::PROTO_TYPE),
+/// e.g. sent as plain bytes, are parsed without this check.
+pub fn proto_from_array(
+    py: Python<'_>,
+    array: &ArrayRef,
+    metadata: &Metadata,
+    msg_class: &Bound<'_, PyAny>,
+) -> PyResult<PyObject> {
+    let expected =
+        message_type_name(msg_class).context("`msg_class` is not a protobuf message class")?;
+    if let Some(actual) = metadata.proto_type() {
+        if actual != expected {
+            return Err(schema_error(
+                py,
+                format!("message is a `{actual}`, but was parsed as `{expected}`"),
+            ));
+        }
+    }
+    let bytes = array
+        .as_primitive_opt::<UInt8Type>()
+        .with_context(|| {
+            format!(
+                "protobuf message must be a uint8 array, got {}",
+                array.data_type()
+            )
+        })?
+        .values();
+    let message = msg_class
+        .call_method1("FromString", (PyBytes::new_bound(py, bytes),))
+        .with_context(|| format!("failed to parse `{expected}` message"))?;
+    Ok(message.unbind())
+}
+
+/// The full name of the type of a protobuf message or message class, e.g.
+/// `robot.Command`.
+fn message_type_name(message: &Bound<'_, PyAny>) -> Result<String> {
+    Ok(message
+        .getattr("DESCRIPTOR")?
+        .getattr("full_name")?
+        .extract()?)
+}
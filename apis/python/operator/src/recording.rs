@@ -0,0 +1,327 @@
+//! Recording of received inputs into Arrow IPC files, and replay of such files.
+//!
+//! A recording is an Arrow IPC stream with one row per input and the columns
+//!
+//! - `id`: the input id,
+//! - `metadata`: the JSON-encoded dora metadata, and
+//! - `value`: the value of the input, encoded as a nested Arrow IPC stream with
+//!   a single `value` column since the values of different inputs can have
+//!   different types.
+//!
+//! The record batches can be compressed through the built-in compression of the
+//! Arrow IPC format. Readers detect the compression from the batch headers, so
+//! compressed recordings are replayed without any additional configuration.
+//!
+//! Long recordings can be split into several files by rotating the recorder,
+//! either explicitly or once a file reaches a size or age limit. Every file is a
+//! complete recording on its own.
+
+use std::{
+    fs::File,
+    io::{BufReader, BufWriter, Write},
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use arrow::{
+    array::{Array, ArrayRef, BinaryArray, StringArray},
+    ipc::{
+        reader::StreamReader,
+        writer::{IpcWriteOptions, StreamWriter},
+        CompressionType,
+    },
+    record_batch::RecordBatch,
+};
+use arrow_schema::{DataType, Field, Schema, SchemaRef};
+use dora_node_api::{dora_core::config::DataId, Metadata};
+use eyre::{Context, ContextCompat, Result};
+
+const ID_COLUMN: &str = "id";
+const METADATA_COLUMN: &str = "metadata";
+const VALUE_COLUMN: &str = "value";
+
+/// Parses the name of an Arrow IPC compression codec.
+///
+/// Supported are `"zstd"` and `"lz4"`. `None` disables the compression.
+pub fn parse_compression(name: Option<&str>) -> Result<Option<CompressionType>> {
+    match name {
+        None => Ok(None),
+        Some("zstd") => Ok(Some(CompressionType::ZSTD)),
+        Some("lz4") => Ok(Some(CompressionType::LZ4_FRAME)),
+        Some(other) => {
+            eyre::bail!("unsupported compression `{other}`, expected `zstd` or `lz4`")
+        }
+    }
+}
+
+fn recording_schema() -> SchemaRef {
+    Arc::new(Schema::new(vec![
+        Field::new(ID_COLUMN, DataType::Utf8, false),
+        Field::new(METADATA_COLUMN, DataType::Binary, false),
+        Field::new(VALUE_COLUMN, DataType::Binary, false),
+    ]))
+}
+
+/// Appends received inputs to a recording file.
+pub struct EventRecorder {
+    writer: StreamWriter<BufWriter<File>>,
+    compression: Option<CompressionType>,
+    /// Path that was passed to [`EventRecorder::create`], the base of the
+    /// names of rotated files.
+    base_path: PathBuf,
+    path: PathBuf,
+    /// Number of files that were created through rotation.
+    rotations: u32,
+    opened_at: Instant,
+    rotation: RotationPolicy,
+}
+
+/// Limits after which the recorder rotates to a new file automatically.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RotationPolicy {
+    /// Maximum size of a file in bytes.
+    pub max_bytes: Option<u64>,
+    /// Maximum time span that is recorded into a file.
+    pub max_duration: Option<Duration>,
+}
+
+impl EventRecorder {
+    /// Creates the recording file at `path`, overwriting existing files.
+    pub fn create(path: &Path, compression: Option<CompressionType>) -> Result<Self> {
+        Ok(Self {
+            writer: create_writer(path, compression)?,
+            compression,
+            base_path: path.to_owned(),
+            path: path.to_owned(),
+            rotations: 0,
+            opened_at: Instant::now(),
+            rotation: RotationPolicy::default(),
+        })
+    }
+
+    /// Path of the file that inputs are currently recorded into.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Sets the limits after which [`Self::record`] rotates the file.
+    pub fn set_rotation(&mut self, rotation: RotationPolicy) {
+        self.rotation = rotation;
+    }
+
+    /// Finishes the current file and continues the recording in a new file.
+    ///
+    /// Without a `path`, the new file is named after the original path with a
+    /// sequential number, e.g. `events_001.arrow` for `events.arrow`. The new
+    /// file is created before the current one is finished, so no input is lost
+    /// if the new file cannot be created. Returns the path of the new file.
+    pub fn rotate(&mut self, path: Option<&Path>) -> Result<PathBuf> {
+        let path = match path {
+            Some(path) => path.to_owned(),
+            None => rotated_path(&self.base_path, self.rotations + 1),
+        };
+        if path == self.path {
+            eyre::bail!("recording is already written to `{}`", path.display());
+        }
+        let writer = create_writer(&path, self.compression)?;
+        let mut previous = std::mem::replace(&mut self.writer, writer);
+        previous
+            .finish()
+            .with_context(|| format!("failed to finish recording `{}`", self.path.display()))?;
+        self.rotations += 1;
+        self.path = path.clone();
+        self.opened_at = Instant::now();
+        Ok(path)
+    }
+
+    /// Appends the given input to the recording.
+    ///
+    /// The input is flushed to the file immediately, so that the recording stays
+    /// readable if the node exits without dropping the recorder.
+    pub fn record(&mut self, id: &DataId, metadata: &Metadata, value: ArrayRef) -> Result<()> {
+        let metadata = serde_json::to_vec(metadata).context("failed to serialize metadata")?;
+        let value = encode_value(value)?;
+        let batch = RecordBatch::try_new(
+            recording_schema(),
+            vec![
+                Arc::new(StringArray::from(vec![id.as_str()])),
+                Arc::new(BinaryArray::from(vec![metadata.as_slice()])),
+                Arc::new(BinaryArray::from(vec![value.as_slice()])),
+            ],
+        )?;
+        self.writer
+            .write(&batch)
+            .context("failed to write input to recording")?;
+        self.writer
+            .get_mut()
+            .flush()
+            .context("failed to flush recording")?;
+        if self.rotation_due()? {
+            self.rotate(None)?;
+        }
+        Ok(())
+    }
+
+    fn rotation_due(&self) -> Result<bool> {
+        let RotationPolicy {
+            max_bytes,
+            max_duration,
+        } = self.rotation;
+        if max_duration.is_some_and(|max| self.opened_at.elapsed() >= max) {
+            return Ok(true);
+        }
+        match max_bytes {
+            Some(max) => {
+                let size = self
+                    .writer
+                    .get_ref()
+                    .get_ref()
+                    .metadata()
+                    .context("failed to read size of recording")?
+                    .len();
+                Ok(size >= max)
+            }
+            None => Ok(false),
+        }
+    }
+}
+
+fn create_writer(
+    path: &Path,
+    compression: Option<CompressionType>,
+) -> Result<StreamWriter<BufWriter<File>>> {
+    let file = File::create(path)
+        .with_context(|| format!("failed to create recording file `{}`", path.display()))?;
+    let options = IpcWriteOptions::default()
+        .try_with_compression(compression)
+        .context("failed to set up compression of recording")?;
+    StreamWriter::try_new_with_options(BufWriter::new(file), &recording_schema(), options)
+        .context("failed to write header of recording")
+}
+
+/// Path of the `index`-th rotated file of a recording, e.g. `events_002.arrow`.
+pub fn rotated_path(base: &Path, index: u32) -> PathBuf {
+    let stem = base.file_stem().unwrap_or_default().to_string_lossy();
+    let name = match base.extension() {
+        Some(extension) => format!("{stem}_{index:03}.{}", extension.to_string_lossy()),
+        None => format!("{stem}_{index:03}"),
+    };
+    base.with_file_name(name)
+}
+
+impl Drop for EventRecorder {
+    fn drop(&mut self) {
+        if let Err(err) = self.writer.finish() {
+            eprintln!("failed to finish recording: {err}");
+        }
+    }
+}
+
+/// An input that was read from a recording.
+pub struct RecordedInput {
+    pub id: DataId,
+    pub metadata: Metadata,
+    pub value: ArrayRef,
+}
+
+/// Reads the inputs of a recording file in the order in which they were recorded.
+pub struct RecordingReader {
+    reader: StreamReader<BufReader<File>>,
+    /// Rows of the current batch that were not returned yet.
+    batch: Option<(RecordBatch, usize)>,
+}
+
+impl RecordingReader {
+    pub fn open(path: &Path) -> Result<Self> {
+        let file = File::open(path)
+            .with_context(|| format!("failed to open recording `{}`", path.display()))?;
+        let reader = StreamReader::try_new_buffered(file, None)
+            .with_context(|| format!("`{}` is not a dora recording", path.display()))?;
+        if reader.schema() != recording_schema() {
+            eyre::bail!(
+                "`{}` is not a dora recording: unexpected schema",
+                path.display()
+            );
+        }
+        Ok(Self {
+            reader,
+            batch: None,
+        })
+    }
+
+    fn next_row(&mut self) -> Result<Option<RecordedInput>> {
+        loop {
+            if let Some((batch, row)) = &mut self.batch {
+                if *row < batch.num_rows() {
+                    let input = decode_row(batch, *row)?;
+                    *row += 1;
+                    return Ok(Some(input));
+                }
+            }
+            match self.reader.next().transpose()? {
+                Some(batch) => self.batch = Some((batch, 0)),
+                None => return Ok(None),
+            }
+        }
+    }
+}
+
+impl Iterator for RecordingReader {
+    type Item = Result<RecordedInput>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_row()
+            .context("failed to read recording")
+            .transpose()
+    }
+}
+
+fn decode_row(batch: &RecordBatch, row: usize) -> Result<RecordedInput> {
+    let column = |name| {
+        batch
+            .column_by_name(name)
+            .with_context(|| format!("recording has no `{name}` column"))
+    };
+    let id = column(ID_COLUMN)?
+        .as_any()
+        .downcast_ref::<StringArray>()
+        .context("invalid `id` column")?
+        .value(row);
+    let metadata = column(METADATA_COLUMN)?
+        .as_any()
+        .downcast_ref::<BinaryArray>()
+        .context("invalid `metadata` column")?
+        .value(row);
+    let value = column(VALUE_COLUMN)?
+        .as_any()
+        .downcast_ref::<BinaryArray>()
+        .context("invalid `value` column")?
+        .value(row);
+    Ok(RecordedInput {
+        id: DataId::from(id.to_owned()),
+        metadata: serde_json::from_slice(metadata).context("failed to parse metadata")?,
+        value: decode_value(value)?,
+    })
+}
+
+fn encode_value(value: ArrayRef) -> Result<Vec<u8>> {
+    let schema = Schema::new(vec![Field::new(
+        VALUE_COLUMN,
+        value.data_type().clone(),
+        true,
+    )]);
+    let batch = RecordBatch::try_new(Arc::new(schema), vec![value])?;
+    let mut writer = StreamWriter::try_new(Vec::new(), &batch.schema())?;
+    writer.write(&batch)?;
+    writer.into_inner().context("failed to encode value")
+}
+
+fn decode_value(bytes: &[u8]) -> Result<ArrayRef> {
+    let mut reader = StreamReader::try_new(bytes, None).context("failed to decode value")?;
+    let batch = reader
+        .next()
+        .context("recorded value is empty")?
+        .context("failed to decode value")?;
+    Ok(batch.column(0).clone())
+}
@@ -0,0 +1,128 @@
+//! Conversion between dora messages and ROS 2 messages of `rclpy`.
+//!
+//! Messages are converted through dicts of their fields, using the helpers of
+//! `rosidl_runtime_py`, which is installed with ROS 2. Messages are sent as
+//! struct arrays with a single element, like for the publishers of the ROS 2
+//! bridge. The `header` of stamped messages is mapped to the `frame_id` and
+//! `timestamp_ns` metadata parameters.
+
+use arrow::{
+    array::{ArrayRef, AsArray},
+    pyarrow::ToPyArrow,
+};
+use dora_node_api::{metadata::FRAME_ID, Metadata, Parameter};
+use eyre::{Context, ContextCompat, Result};
+use pyo3::{prelude::*, types::PyDict};
+
+/// A ROS 2 message converted to a dora output.
+pub struct Ros2Output {
+    /// The fields of the message as `pyarrow` struct array with one element.
+    pub data: PyObject,
+    /// The `frame_id` of the header, if set.
+    pub frame_id: Option<String>,
+    /// The `stamp` of the header in nanoseconds since the UNIX epoch, if set.
+    pub timestamp_ns: Option<i64>,
+}
+
+/// Creates an instance of the given `rclpy` message class from a received value.
+///
+/// Struct values must have a single element, whose fields are assigned to the
+/// fields of the message. Other values are assigned to the `data` field, e.g.
+/// of `std_msgs/Float32MultiArray`. If the message has a `header` that is not
+/// part of the value, it is filled from the metadata.
+pub fn to_ros2_message(
+    py: Python<'_>,
+    value: &ArrayRef,
+    metadata: &Metadata,
+    msg_type: &Bound<'_, PyAny>,
+) -> Result<PyObject> {
+    if !msg_type.hasattr("get_fields_and_field_types")? {
+        eyre::bail!("`msg_type` must be a ROS 2 message class, e.g. `geometry_msgs.msg.Twist`");
+    }
+    let field_types = msg_type.call_method0("get_fields_and_field_types")?;
+    let field_types = field_types
+        .downcast::<PyDict>()
+        .map_err(|_| eyre::eyre!("invalid field types of ROS 2 message class"))?;
+
+    let array = value.to_data().to_pyarrow(py)?.into_bound(py);
+    let fields = if value.as_struct_opt().is_some() {
+        if value.len() != 1 {
+            eyre::bail!("expected a single message, got {} values", value.len());
+        }
+        array
+            .get_item(0)?
+            .call_method0("as_py")?
+            .downcast_into::<PyDict>()
+            .map_err(|_| eyre::eyre!("failed to convert struct value to dict"))?
+    } else {
+        let data_type: String = field_types
+            .get_item("data")?
+            .context("non-struct values can only be converted to messages with a `data` field")?
+            .extract()?;
+        let mut data = array.call_method0("to_pylist")?;
+        // e.g. `std_msgs/Float32`, as opposed to `sequence<float>` or `float[3]`
+        let is_sequence = data_type.starts_with("sequence<") || data_type.ends_with(']');
+        if !is_sequence {
+            if value.len() != 1 {
+                eyre::bail!(
+                    "expected a single value for field `data`, got {}",
+                    value.len()
+                );
+            }
+            data = data.get_item(0)?;
+        }
+        let fields = PyDict::new_bound(py);
+        fields.set_item("data", data)?;
+        fields
+    };
+    if field_types.contains("header")? && !fields.contains("header")? {
+        let nanos = metadata.timestamp_ns();
+        let stamp = PyDict::new_bound(py);
+        stamp.set_item("sec", nanos / 1_000_000_000)?;
+        stamp.set_item("nanosec", nanos % 1_000_000_000)?;
+        let header = PyDict::new_bound(py);
+        header.set_item("stamp", stamp)?;
+        if let Some(Parameter::String(frame_id)) = metadata.parameters.get(FRAME_ID) {
+            header.set_item("frame_id", frame_id)?;
+        }
+        fields.set_item("header", header)?;
+    }
+
+    let message = msg_type.call0()?;
+    py.import_bound("rosidl_runtime_py.set_message")
+        .context("failed to import `rosidl_runtime_py`, which is installed with ROS 2")?
+        .call_method1("set_message_fields", (&message, fields))
+        .context("value does not match the fields of the ROS 2 message")?;
+    Ok(message.unbind())
+}
+
+/// Converts an `rclpy` message to a dora output.
+pub fn from_ros2_message(message: &Bound<'_, PyAny>) -> Result<Ros2Output> {
+    let py = message.py();
+    if !message.hasattr("get_fields_and_field_types")? {
+        eyre::bail!("`message` must be a ROS 2 message, e.g. `geometry_msgs.msg.Twist()`");
+    }
+    let fields = py
+        .import_bound("rosidl_runtime_py.convert")
+        .context("failed to import `rosidl_runtime_py`, which is installed with ROS 2")?
+        .call_method1("message_to_ordereddict", (message,))?;
+    let data = py
+        .import_bound("pyarrow")?
+        .call_method1("array", (vec![fields],))
+        .context("failed to convert ROS 2 message to arrow")?;
+
+    let mut output = Ros2Output {
+        data: data.unbind(),
+        frame_id: None,
+        timestamp_ns: None,
+    };
+    if let Ok(header) = message.getattr("header") {
+        let frame_id: String = header.getattr("frame_id")?.extract()?;
+        let stamp = header.getattr("stamp")?;
+        let sec: i64 = stamp.getattr("sec")?.extract()?;
+        let nanosec: i64 = stamp.getattr("nanosec")?.extract()?;
+        output.frame_id = Some(frame_id).filter(|frame_id| !frame_id.is_empty());
+        output.timestamp_ns = Some(sec * 1_000_000_000 + nanosec).filter(|nanos| *nanos > 0);
+    }
+    Ok(output)
+}
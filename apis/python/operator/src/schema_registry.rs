@@ -0,0 +1,139 @@
+//! Process-wide registry of named message types.
+//!
+//! Schemas are registered at runtime through `dora.register_schema` or from the
+//! `schemas` section of the dataflow descriptor when a node is created. Outputs
+//! that are sent with a schema name carry it in the [`SCHEMA_NAME`] metadata
+//! parameter, so that receivers can validate them against the same schema.
+
+use std::{
+    collections::BTreeMap,
+    str::FromStr,
+    sync::{Arc, Mutex},
+};
+
+use arrow::datatypes::DataType;
+use arrow_schema::{Field, Fields, Schema, SchemaRef};
+use dora_node_api::{
+    dora_core::descriptor::SchemaField, metadata::SCHEMA_NAME, Metadata, Parameter,
+};
+use eyre::{Context, ContextCompat, Result};
+use pyo3::{exceptions::PyValueError, prelude::*, types::PyType};
+
+static REGISTRY: Mutex<BTreeMap<String, SchemaRef>> = Mutex::new(BTreeMap::new());
+
+/// Registers the schema under the given name.
+///
+/// Registering the same schema again is a no-op. Fails if a different schema
+/// is already registered under the name, so that all nodes of a process agree
+/// on the definition of a type.
+pub fn register(name: &str, schema: SchemaRef) -> Result<()> {
+    let mut registry = REGISTRY.lock().unwrap();
+    match registry.get(name) {
+        Some(existing) if existing.fields() != schema.fields() => eyre::bail!(
+            "schema `{name}` is already registered with different fields: {}",
+            describe(existing.fields())
+        ),
+        Some(_) => {}
+        None => {
+            registry.insert(name.to_owned(), schema);
+        }
+    }
+    Ok(())
+}
+
+/// Registers the schemas of the `schemas` section of a dataflow descriptor.
+pub fn register_descriptor_schemas(schemas: &BTreeMap<String, Vec<SchemaField>>) -> Result<()> {
+    for (name, fields) in schemas {
+        let schema = schema_from_fields(fields)
+            .wrap_err_with(|| format!("invalid schema `{name}` in dataflow descriptor"))?;
+        register(name, Arc::new(schema))?;
+    }
+    Ok(())
+}
+
+pub fn get(name: &str) -> Option<SchemaRef> {
+    REGISTRY.lock().unwrap().get(name).cloned()
+}
+
+/// Converts the fields of a descriptor schema into an arrow schema.
+pub fn schema_from_fields(fields: &[SchemaField]) -> Result<Schema> {
+    let fields = fields
+        .iter()
+        .map(|field| {
+            let data_type = DataType::from_str(&field.data_type).wrap_err_with(|| {
+                format!(
+                    "invalid type `{}` of field `{}`",
+                    field.data_type, field.name
+                )
+            })?;
+            Ok(Field::new(
+                &field.name,
+                data_type,
+                field.nullable.unwrap_or(true),
+            ))
+        })
+        .collect::<Result<Vec<_>>>()?;
+    Ok(Schema::new(fields))
+}
+
+/// Checks that values of the given data type match the registered schema.
+///
+/// Struct arrays must have the fields of the schema, with the same names and
+/// types in the same order. Other arrays match schemas with a single field of
+/// the same type.
+pub fn validate(name: &str, data_type: &DataType) -> Result<()> {
+    let schema = get(name).with_context(|| format!("no schema registered as `{name}`"))?;
+    let matches = match data_type {
+        DataType::Struct(fields) => {
+            fields.len() == schema.fields().len()
+                && fields
+                    .iter()
+                    .zip(schema.fields())
+                    .all(|(actual, expected)| {
+                        actual.name() == expected.name()
+                            && actual.data_type() == expected.data_type()
+                    })
+        }
+        other => schema.fields().len() == 1 && schema.field(0).data_type() == other,
+    };
+    if !matches {
+        let actual = match data_type {
+            DataType::Struct(fields) => describe(fields),
+            other => other.to_string(),
+        };
+        eyre::bail!(
+            "value does not match schema `{name}`: expected {}, got {actual}",
+            describe(schema.fields())
+        );
+    }
+    Ok(())
+}
+
+/// The schema name that the sender attached to the message, if any.
+pub fn schema_name(metadata: &Metadata) -> Option<&str> {
+    match metadata.parameters.get(SCHEMA_NAME) {
+        Some(Parameter::String(name)) => Some(name),
+        _ => None,
+    }
+}
+
+fn describe(fields: &Fields) -> String {
+    let fields: Vec<_> = fields
+        .iter()
+        .map(|field| format!("{}: {}", field.name(), field.data_type()))
+        .collect();
+    format!("{{{}}}", fields.join(", "))
+}
+
+/// Creates a `dora.SchemaError`, which is defined by the node API, or a
+/// `ValueError` if the `dora` module is not available.
+pub fn schema_error(py: Python<'_>, message: String) -> PyErr {
+    match py
+        .import_bound("dora")
+        .and_then(|dora| dora.getattr("SchemaError"))
+        .and_then(|class| Ok(class.downcast_into::<PyType>()?))
+    {
+        Ok(class) => PyErr::from_type_bound(class, message),
+        Err(_) => PyValueError::new_err(message),
+    }
+}
@@ -0,0 +1,230 @@
+//! Transfer of sparse matrices without densifying them.
+//!
+//! A sparse matrix is sent as a struct array with a single row and the fields
+//!
+//! - `format`: the storage format, `csr` or `coo`,
+//! - `shape`: the number of rows and columns,
+//! - `indptr` and `indices` for CSR, or `row` and `col` for COO, as `int64` lists,
+//! - `data`: the stored values.
+//!
+//! Large lists are used, so matrices with more than 2^31 stored values are
+//! supported as well.
+
+use std::sync::Arc;
+
+use arrow::{
+    array::{
+        make_array, Array, ArrayData, ArrayRef, AsArray, Int64Array, LargeListArray, StringArray,
+        StructArray,
+    },
+    buffer::OffsetBuffer,
+    pyarrow::{FromPyArrow, ToPyArrow},
+};
+use arrow_schema::{DataType, Field, Fields};
+use eyre::{Context, ContextCompat, Result};
+use pyo3::{prelude::*, types::IntoPyDict};
+
+/// Storage format of a [`SparseMatrix`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SparseFormat {
+    /// Compressed sparse row format.
+    Csr,
+    /// Coordinate format.
+    Coo,
+}
+
+impl SparseFormat {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SparseFormat::Csr => "csr",
+            SparseFormat::Coo => "coo",
+        }
+    }
+
+    /// Names of the two index arrays of the format.
+    pub fn index_names(&self) -> [&'static str; 2] {
+        match self {
+            SparseFormat::Csr => ["indptr", "indices"],
+            SparseFormat::Coo => ["row", "col"],
+        }
+    }
+}
+
+impl std::str::FromStr for SparseFormat {
+    type Err = eyre::Report;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "csr" => Ok(SparseFormat::Csr),
+            "coo" => Ok(SparseFormat::Coo),
+            other => eyre::bail!("unsupported sparse format `{other}`, expected `csr` or `coo`"),
+        }
+    }
+}
+
+/// A sparse matrix in CSR or COO format.
+#[derive(Debug, Clone)]
+pub struct SparseMatrix {
+    pub format: SparseFormat,
+    /// Number of rows and columns.
+    pub shape: [i64; 2],
+    /// The `int64` index arrays, named as in [`SparseFormat::index_names`].
+    pub indices: [ArrayRef; 2],
+    /// The stored values.
+    pub data: ArrayRef,
+}
+
+impl PartialEq for SparseMatrix {
+    fn eq(&self, other: &Self) -> bool {
+        self.format == other.format
+            && self.shape == other.shape
+            && self
+                .indices
+                .iter()
+                .zip(&other.indices)
+                .all(|(a, b)| a.to_data() == b.to_data())
+            && self.data.to_data() == other.data.to_data()
+    }
+}
+
+/// Converts the given matrix into its arrow representation.
+pub fn sparse_to_array(matrix: &SparseMatrix) -> Result<ArrayRef> {
+    for (name, index) in matrix.format.index_names().iter().zip(&matrix.indices) {
+        if index.data_type() != &DataType::Int64 {
+            eyre::bail!(
+                "sparse index `{name}` must be of type `Int64`, got `{}`",
+                index.data_type()
+            );
+        }
+    }
+    let [first, second] = matrix.format.index_names();
+    let shape: ArrayRef = Arc::new(Int64Array::from(matrix.shape.to_vec()));
+    let columns = [
+        ("shape", shape),
+        (first, matrix.indices[0].clone()),
+        (second, matrix.indices[1].clone()),
+        ("data", matrix.data.clone()),
+    ];
+
+    let mut fields = vec![Field::new("format", DataType::Utf8, false)];
+    let mut arrays: Vec<ArrayRef> = vec![Arc::new(StringArray::from(vec![matrix.format.as_str()]))];
+    for (name, values) in columns {
+        let list = single_list(values)?;
+        fields.push(Field::new(name, list.data_type().clone(), false));
+        arrays.push(list);
+    }
+    let array = StructArray::try_new(Fields::from(fields), arrays, None)
+        .context("failed to create sparse matrix array")?;
+    Ok(Arc::new(array))
+}
+
+/// Inverse of [`sparse_to_array`].
+pub fn array_to_sparse(array: &ArrayRef) -> Result<SparseMatrix> {
+    let array = array
+        .as_struct_opt()
+        .context("sparse matrix must be a struct array")?;
+    if array.len() != 1 {
+        eyre::bail!(
+            "sparse matrix array must have a single row, got {}",
+            array.len()
+        );
+    }
+    let format: SparseFormat = array
+        .column_by_name("format")
+        .and_then(|format| format.as_string_opt::<i32>())
+        .context("sparse matrix array has no `format` field")?
+        .value(0)
+        .parse()?;
+    let list = |name: &str| -> Result<ArrayRef> {
+        let list = array
+            .column_by_name(name)
+            .and_then(|list| list.as_list_opt::<i64>())
+            .with_context(|| format!("sparse matrix array has no `{name}` list field"))?;
+        Ok(list.value(0))
+    };
+    let shape = list("shape")?;
+    let shape = shape
+        .as_primitive_opt::<arrow::datatypes::Int64Type>()
+        .filter(|shape| shape.len() == 2)
+        .context("sparse matrix `shape` must consist of two `int64` values")?;
+    let [first, second] = format.index_names();
+    Ok(SparseMatrix {
+        format,
+        shape: [shape.value(0), shape.value(1)],
+        indices: [list(first)?, list(second)?],
+        data: list("data")?,
+    })
+}
+
+/// Wraps the given values into a list array with a single entry.
+fn single_list(values: ArrayRef) -> Result<ArrayRef> {
+    let field = Arc::new(Field::new("item", values.data_type().clone(), true));
+    let offsets = OffsetBuffer::<i64>::from_lengths([values.len()]);
+    let list = LargeListArray::try_new(field, offsets, values, None)
+        .context("failed to create sparse matrix list")?;
+    Ok(Arc::new(list))
+}
+
+/// Converts a `scipy.sparse` matrix or array, returns `None` for other values.
+///
+/// COO matrices keep their format, all other formats are converted to CSR.
+pub fn sparse_from_python(value: &Bound<'_, PyAny>) -> Result<Option<SparseMatrix>> {
+    let module: String = value.get_type().getattr("__module__")?.extract()?;
+    if !module.starts_with("scipy.sparse") || !value.hasattr("format")? {
+        return Ok(None);
+    }
+    let py = value.py();
+    let format: String = value.getattr("format")?.extract()?;
+    let (format, value) = match format.as_str() {
+        "coo" => (SparseFormat::Coo, value.clone()),
+        _ => (SparseFormat::Csr, value.call_method0("tocsr")?),
+    };
+    let shape: (i64, i64) = value
+        .getattr("shape")?
+        .extract()
+        .context("sparse matrix must be two-dimensional")?;
+
+    let pyarrow = py.import_bound("pyarrow")?;
+    let to_arrow = |values: Bound<'_, PyAny>| -> Result<ArrayRef> {
+        let array = pyarrow.call_method1("array", (values,))?;
+        let data = ArrayData::from_pyarrow_bound(&array)
+            .context("failed to convert sparse matrix to arrow")?;
+        Ok(make_array(data))
+    };
+    let index = |name: &str| -> Result<ArrayRef> {
+        to_arrow(value.getattr(name)?.call_method1("astype", ("int64",))?)
+    };
+    let [first, second] = format.index_names();
+    Ok(Some(SparseMatrix {
+        format,
+        shape: [shape.0, shape.1],
+        indices: [index(first)?, index(second)?],
+        data: to_arrow(value.getattr("data")?)?,
+    }))
+}
+
+/// Creates the `scipy.sparse` matrix of the given arrow representation.
+pub fn sparse_to_python(py: Python<'_>, array: &ArrayRef) -> Result<PyObject> {
+    let matrix = array_to_sparse(array)?;
+    let sparse = py
+        .import_bound("scipy.sparse")
+        .context("failed to import `scipy.sparse`")?;
+    let kwargs = [("zero_copy_only", false)].into_py_dict_bound(py);
+    let to_numpy = |array: &ArrayRef| -> Result<PyObject> {
+        let array = array.to_data().to_pyarrow(py)?;
+        Ok(array.call_method_bound(py, "to_numpy", (), Some(&kwargs))?)
+    };
+    let data = to_numpy(&matrix.data)?;
+    let [first, second] = [to_numpy(&matrix.indices[0])?, to_numpy(&matrix.indices[1])?];
+    let shape = (matrix.shape[0], matrix.shape[1]);
+    let kwargs = [("shape", shape)].into_py_dict_bound(py);
+    let matrix = match matrix.format {
+        SparseFormat::Csr => {
+            sparse.call_method("csr_matrix", ((data, second, first),), Some(&kwargs))?
+        }
+        SparseFormat::Coo => {
+            sparse.call_method("coo_matrix", ((data, (first, second)),), Some(&kwargs))?
+        }
+    };
+    Ok(matrix.unbind())
+}
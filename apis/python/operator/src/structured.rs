@@ -0,0 +1,168 @@
+//! Transfer of numpy structured arrays, e.g. arrays of packed C structs.
+//!
+//! A structured array is sent as a struct array with one child per field.
+//! Fields of a nested structured dtype become nested struct arrays and subarray
+//! fields, e.g. `("pos", "f4", (3,))`, become (nested) fixed-size lists.
+//!
+//! Only the fields are transferred, not the padding of aligned dtypes, so the
+//! received array has a packed dtype with the same field names and types.
+
+use std::sync::Arc;
+
+use arrow::{
+    array::{make_array, Array, ArrayData, ArrayRef, AsArray, FixedSizeListArray, StructArray},
+    pyarrow::{FromPyArrow, ToPyArrow},
+};
+use arrow_schema::{DataType, Field, Fields};
+use eyre::{Context, ContextCompat, Result};
+use pyo3::{
+    prelude::*,
+    types::{IntoPyDict, PyList, PyTuple},
+};
+
+/// Converts a numpy structured array, returns `None` for other values.
+pub fn structured_from_python(value: &Bound<'_, PyAny>) -> Result<Option<ArrayRef>> {
+    let Ok(numpy) = value.py().import_bound("numpy") else {
+        return Ok(None);
+    };
+    if !value.is_instance(&numpy.getattr("ndarray")?)?
+        || value.getattr("dtype")?.getattr("names")?.is_none()
+    {
+        return Ok(None);
+    }
+    let ndim: usize = value.getattr("ndim")?.extract()?;
+    if ndim != 1 {
+        eyre::bail!("structured arrays must be one-dimensional, got {ndim} dimensions");
+    }
+    struct_from_numpy(value).map(Some)
+}
+
+fn struct_from_numpy(array: &Bound<'_, PyAny>) -> Result<ArrayRef> {
+    let names: Vec<String> = array.getattr("dtype")?.getattr("names")?.extract()?;
+    let len: usize = array.len()?;
+    if names.is_empty() {
+        return Ok(Arc::new(StructArray::new_empty_fields(len, None)));
+    }
+    let mut fields = Vec::new();
+    let mut columns = Vec::new();
+    for name in names {
+        let column = field_from_numpy(&array.get_item(&name)?)
+            .wrap_err_with(|| format!("failed to convert field `{name}`"))?;
+        fields.push(Field::new(name, column.data_type().clone(), true));
+        columns.push(column);
+    }
+    let array = StructArray::try_new(Fields::from(fields), columns, None)
+        .context("failed to create struct array")?;
+    Ok(Arc::new(array))
+}
+
+/// Converts the values of a single field, which have the shape of the subarray
+/// after the first dimension.
+fn field_from_numpy(column: &Bound<'_, PyAny>) -> Result<ArrayRef> {
+    let shape: Vec<usize> = column.getattr("shape")?.extract()?;
+    if shape.len() > 1 {
+        let flat = column.call_method1("reshape", (-1,))?;
+        return nest_fixed_size(field_from_numpy(&flat)?, &shape[1..]);
+    }
+    if !column.getattr("dtype")?.getattr("names")?.is_none() {
+        return struct_from_numpy(column);
+    }
+    let py = column.py();
+    // fields of a structured array are strided views into the records
+    let contiguous = py
+        .import_bound("numpy")?
+        .call_method1("ascontiguousarray", (column,))?;
+    let array = py
+        .import_bound("pyarrow")?
+        .call_method1("array", (contiguous,))?;
+    let data = ArrayData::from_pyarrow_bound(&array).context("unsupported field type")?;
+    Ok(make_array(data))
+}
+
+/// Wraps the flattened `values` of a subarray of the given shape into nested
+/// fixed-size lists, the innermost list corresponding to the last dimension.
+pub fn nest_fixed_size(mut values: ArrayRef, shape: &[usize]) -> Result<ArrayRef> {
+    for &size in shape.iter().rev() {
+        let size = i32::try_from(size).context("subarray dimension is too large")?;
+        let field = Arc::new(Field::new("item", values.data_type().clone(), true));
+        let list = FixedSizeListArray::try_new(field, size, values, None)
+            .context("failed to create fixed-size list")?;
+        values = Arc::new(list);
+    }
+    Ok(values)
+}
+
+/// Inverse of [`nest_fixed_size`], returns the flattened values and the shape
+/// of the subarray.
+pub fn unnest_fixed_size(array: &ArrayRef) -> (ArrayRef, Vec<usize>) {
+    let mut values = array.clone();
+    let mut shape = Vec::new();
+    while let Some(list) = values.as_fixed_size_list_opt() {
+        shape.push(list.value_length() as usize);
+        values = list.values().clone();
+    }
+    (values, shape)
+}
+
+/// Creates the numpy structured array of the given struct array.
+pub fn structured_to_python(py: Python<'_>, array: &ArrayRef) -> Result<PyObject> {
+    let array = array
+        .as_struct_opt()
+        .context("structured array must be a struct array")?;
+    let numpy = py
+        .import_bound("numpy")
+        .context("failed to import `numpy`")?;
+    Ok(struct_to_numpy(&numpy, array)?.unbind())
+}
+
+fn struct_to_numpy<'py>(
+    numpy: &Bound<'py, PyModule>,
+    array: &StructArray,
+) -> Result<Bound<'py, PyAny>> {
+    let py = numpy.py();
+    let mut columns = Vec::new();
+    let dtype = PyList::empty_bound(py);
+    for (field, column) in array.fields().iter().zip(array.columns()) {
+        let column = field_to_numpy(numpy, column)
+            .wrap_err_with(|| format!("failed to convert field `{}`", field.name()))?;
+        let shape: Vec<usize> = column.getattr("shape")?.extract()?;
+        let subshape = PyTuple::new_bound(py, &shape[1..]);
+        dtype.append((field.name().as_str(), column.getattr("dtype")?, subshape))?;
+        columns.push((field.name(), column));
+    }
+    let dtype = numpy.call_method1("dtype", (dtype,))?;
+    let records = numpy.call_method1("empty", (array.len(), dtype))?;
+    for (name, column) in columns {
+        records.set_item(name, column)?;
+    }
+    Ok(records)
+}
+
+fn field_to_numpy<'py>(
+    numpy: &Bound<'py, PyModule>,
+    array: &ArrayRef,
+) -> Result<Bound<'py, PyAny>> {
+    let py = numpy.py();
+    let (values, subshape) = unnest_fixed_size(array);
+    let values = match values.data_type() {
+        DataType::Struct(_) => struct_to_numpy(numpy, values.as_struct())?,
+        data_type => {
+            let kwargs = [("zero_copy_only", false)].into_py_dict_bound(py);
+            let values = values
+                .to_data()
+                .to_pyarrow(py)?
+                .call_method_bound(py, "to_numpy", (), Some(&kwargs))?
+                .into_bound(py);
+            // strings are returned as object arrays, which have no fixed size
+            match data_type {
+                DataType::Utf8 | DataType::LargeUtf8 => values.call_method1("astype", ("U",))?,
+                DataType::Binary | DataType::LargeBinary | DataType::FixedSizeBinary(_) => {
+                    values.call_method1("astype", ("S",))?
+                }
+                _ => values,
+            }
+        }
+    };
+    let shape: Vec<usize> = std::iter::once(array.len()).chain(subshape).collect();
+    Ok(values.call_method1("reshape", (PyTuple::new_bound(py, shape),))?)
+}
@@ -0,0 +1,105 @@
+//! Transfer of Arrow tables, i.e. sequences of record batches with one schema.
+//!
+//! A `pyarrow.Table` or a list of `pyarrow.RecordBatch` is sent as a `uint8`
+//! array that contains the batches in the Arrow IPC streaming format. The
+//! receiver reconstructs the table through `Event.to_table()`. In contrast to a
+//! single struct array, the batches are kept separate, so tables that were
+//! assembled from several chunks are not concatenated on send.
+
+use std::{io::Cursor, sync::Arc};
+
+use arrow::{
+    array::{ArrayRef, AsArray, UInt8Array},
+    datatypes::UInt8Type,
+    ipc::{reader::StreamReader, writer::StreamWriter},
+    pyarrow::{FromPyArrow, ToPyArrow},
+    record_batch::RecordBatch,
+};
+use arrow_schema::{Schema, SchemaRef};
+use eyre::{Context, ContextCompat, Result};
+use pyo3::{prelude::*, types::PyList};
+
+/// Converts a `pyarrow.Table` or a non-empty list of `pyarrow.RecordBatch`,
+/// returns `None` for other values.
+pub fn table_from_python(value: &Bound<'_, PyAny>) -> Result<Option<ArrayRef>> {
+    let Ok(pyarrow) = value.py().import_bound("pyarrow") else {
+        return Ok(None);
+    };
+    let record_batch = pyarrow.getattr("RecordBatch")?;
+    let (schema, batches) = if value.is_instance(&pyarrow.getattr("Table")?)? {
+        let schema = Schema::from_pyarrow_bound(&value.getattr("schema")?)
+            .context("unsupported table schema")?;
+        (Arc::new(schema), value.call_method0("to_batches")?)
+    } else if let Ok(list) = value.downcast::<PyList>() {
+        if list.is_empty()
+            || !list
+                .iter()
+                .all(|item| item.is_instance(&record_batch).unwrap_or(false))
+        {
+            return Ok(None);
+        }
+        let schema = Schema::from_pyarrow_bound(&list.get_item(0)?.getattr("schema")?)
+            .context("unsupported record batch schema")?;
+        (Arc::new(schema), list.clone().into_any())
+    } else {
+        return Ok(None);
+    };
+    let batches = batches
+        .iter()?
+        .map(|batch| RecordBatch::from_pyarrow_bound(&batch?).context("invalid record batch"))
+        .collect::<Result<Vec<_>>>()?;
+    batches_to_ipc(&schema, &batches).map(Some)
+}
+
+/// Serializes the batches in the Arrow IPC streaming format.
+///
+/// Fails if the fields of a batch differ from the fields of the `schema`.
+pub fn batches_to_ipc(schema: &SchemaRef, batches: &[RecordBatch]) -> Result<ArrayRef> {
+    for (i, batch) in batches.iter().enumerate() {
+        if batch.schema().fields() != schema.fields() {
+            eyre::bail!(
+                "schema of record batch {i} does not match the schema of the first batch: \
+                expected {:?}, got {:?}",
+                schema.fields(),
+                batch.schema().fields()
+            );
+        }
+    }
+    let mut writer =
+        StreamWriter::try_new(Vec::new(), schema).context("failed to create IPC writer")?;
+    for batch in batches {
+        writer
+            .write(batch)
+            .context("failed to write record batch")?;
+    }
+    let bytes = writer.into_inner().context("failed to finish IPC stream")?;
+    Ok(Arc::new(UInt8Array::from(bytes)))
+}
+
+/// Inverse of [`batches_to_ipc`].
+pub fn ipc_to_batches(array: &ArrayRef) -> Result<(SchemaRef, Vec<RecordBatch>)> {
+    let bytes = array
+        .as_primitive_opt::<UInt8Type>()
+        .context("expected a `uint8` array")?;
+    let reader = StreamReader::try_new(Cursor::new(bytes.values().as_ref()), None)
+        .context("value is not an Arrow IPC stream")?;
+    let schema = reader.schema();
+    let batches = reader
+        .collect::<Result<Vec<_>, _>>()
+        .context("failed to read record batch")?;
+    Ok((schema, batches))
+}
+
+/// Converts an input that was sent as a table back to a `pyarrow.Table`.
+pub fn table_to_python(py: Python<'_>, array: &ArrayRef) -> Result<PyObject> {
+    let (schema, batches) = ipc_to_batches(array)?;
+    let batches = batches
+        .iter()
+        .map(|batch| batch.to_pyarrow(py))
+        .collect::<PyResult<Vec<_>>>()?;
+    let table = py
+        .import_bound("pyarrow")?
+        .getattr("Table")?
+        .call_method1("from_batches", (batches, schema.to_pyarrow(py)?))?;
+    Ok(table.unbind())
+}
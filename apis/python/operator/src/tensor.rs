@@ -0,0 +1,183 @@
+//! Transfer of multi-dimensional numpy arrays as Arrow tensors.
+//!
+//! An n-dimensional array is sent as a single element of the canonical
+//! `arrow.fixed_shape_tensor` extension type, i.e. as a fixed-size list that
+//! contains the values of the array in memory order. Arrays are sent without
+//! their arrow field, so the name and the metadata of the extension type are
+//! stored in the [`EXTENSION_NAME`] and [`EXTENSION_METADATA`] metadata
+//! parameters instead, with the same keys and values as in the field metadata.
+//!
+//! As defined by the extension type, the `shape` in the extension metadata is
+//! the physical shape of the values in memory. If the memory layout of the
+//! array is not row-major, e.g. for Fortran-ordered or transposed arrays, the
+//! `permutation` maps it to the logical shape, i.e. the logical shape is
+//! `[shape[i] for i in permutation]`. This keeps the strides of such arrays
+//! without copying them. Arrays with other strides, e.g. slices with a step,
+//! are copied into row-major order.
+
+use std::cmp::Reverse;
+
+use arrow::{
+    array::{Array, ArrayRef, AsArray},
+    pyarrow::ToPyArrow,
+};
+use dora_node_api::{Metadata, MetadataParameters, Parameter};
+use eyre::{Context, ContextCompat, Result};
+use pyo3::{prelude::*, types::IntoPyDict};
+
+/// Metadata parameter key of the name of the extension type of a value.
+pub const EXTENSION_NAME: &str = "ARROW:extension:name";
+/// Metadata parameter key of the serialized parameters of the extension type.
+pub const EXTENSION_METADATA: &str = "ARROW:extension:metadata";
+/// Name of the canonical extension type for tensors of a fixed shape.
+pub const FIXED_SHAPE_TENSOR: &str = "arrow.fixed_shape_tensor";
+
+/// Converts a numpy array with at least two dimensions, returns `None` for
+/// other values.
+///
+/// Returns the tensor as `pyarrow` array and the metadata parameters that
+/// describe its extension type.
+pub fn tensor_from_python(
+    value: &Bound<'_, PyAny>,
+) -> Result<Option<(PyObject, MetadataParameters)>> {
+    let py = value.py();
+    let Ok(numpy) = py.import_bound("numpy") else {
+        return Ok(None);
+    };
+    // structured arrays are sent as struct arrays, see `structured`
+    if !value.is_instance(&numpy.getattr("ndarray")?)?
+        || !value.getattr("dtype")?.getattr("names")?.is_none()
+    {
+        return Ok(None);
+    }
+    let shape: Vec<usize> = value.getattr("shape")?.extract()?;
+    if shape.len() < 2 {
+        return Ok(None);
+    }
+    if shape.contains(&0) {
+        eyre::bail!("cannot send empty array of shape {shape:?} as tensor");
+    }
+
+    let strides: Vec<isize> = value.getattr("strides")?.extract()?;
+    let (physical_shape, permutation) = memory_layout(&shape, &strides);
+    let physical = match &permutation {
+        Some(permutation) => {
+            value.call_method1("transpose", (inverse_permutation(permutation),))?
+        }
+        None => value.clone(),
+    };
+    let is_row_major: bool = physical
+        .getattr("flags")?
+        .get_item("C_CONTIGUOUS")?
+        .extract()?;
+    let (physical, physical_shape, permutation) = if is_row_major {
+        (physical, physical_shape, permutation)
+    } else {
+        let copy = numpy.call_method1("ascontiguousarray", (value,))?;
+        (copy, shape.clone(), None)
+    };
+
+    let pyarrow = py.import_bound("pyarrow")?;
+    let values = pyarrow.call_method1("array", (physical.call_method1("reshape", (-1,))?,))?;
+    let kwargs = [("list_size", shape.iter().product::<usize>())].into_py_dict_bound(py);
+    let tensor = pyarrow
+        .getattr("FixedSizeListArray")?
+        .call_method("from_arrays", (values,), Some(&kwargs))
+        .context("failed to create tensor array")?;
+
+    let mut parameters = MetadataParameters::new();
+    parameters.insert(
+        EXTENSION_NAME.to_owned(),
+        Parameter::String(FIXED_SHAPE_TENSOR.to_owned()),
+    );
+    parameters.insert(
+        EXTENSION_METADATA.to_owned(),
+        Parameter::String(tensor_metadata(&physical_shape, permutation.as_deref())),
+    );
+    Ok(Some((tensor.unbind(), parameters)))
+}
+
+/// Creates the numpy array of a value that was sent through [`tensor_from_python`].
+///
+/// The array is a view into the received value if possible.
+pub fn tensor_to_python(py: Python<'_>, array: &ArrayRef, metadata: &Metadata) -> Result<PyObject> {
+    match metadata.parameters.get(EXTENSION_NAME) {
+        Some(Parameter::String(name)) if name == FIXED_SHAPE_TENSOR => {}
+        _ => eyre::bail!("value is no `{FIXED_SHAPE_TENSOR}`"),
+    }
+    let Some(Parameter::String(extension_metadata)) = metadata.parameters.get(EXTENSION_METADATA)
+    else {
+        eyre::bail!("missing `{EXTENSION_METADATA}` of tensor");
+    };
+    let (shape, permutation) = parse_tensor_metadata(extension_metadata)?;
+    let list = array
+        .as_fixed_size_list_opt()
+        .context("tensor must be a fixed-size list array")?;
+    if list.len() != 1 {
+        eyre::bail!("expected a single tensor, got {}", list.len());
+    }
+
+    let kwargs = [("zero_copy_only", false)].into_py_dict_bound(py);
+    let flat = list
+        .value(0)
+        .to_data()
+        .to_pyarrow(py)?
+        .call_method_bound(py, "to_numpy", (), Some(&kwargs))?
+        .into_bound(py);
+    let physical = flat.call_method1("reshape", (shape,))?;
+    let ndarray = match permutation {
+        Some(permutation) => physical.call_method1("transpose", (permutation,))?,
+        None => physical,
+    };
+    Ok(ndarray.unbind())
+}
+
+/// Finds the memory layout of an array with the given logical shape and strides.
+///
+/// Returns the physical shape, with the dimensions ordered by decreasing stride,
+/// and the `permutation` that maps it back to the logical shape, or `None` if
+/// the dimensions are already in memory order.
+pub fn memory_layout(shape: &[usize], strides: &[isize]) -> (Vec<usize>, Option<Vec<usize>>) {
+    let mut order: Vec<usize> = (0..shape.len()).collect();
+    order.sort_by_key(|&dim| Reverse(strides[dim].unsigned_abs()));
+    let physical_shape = order.iter().map(|&dim| shape[dim]).collect();
+    let is_identity = order.iter().enumerate().all(|(i, &dim)| i == dim);
+    let permutation = Some(inverse_permutation(&order)).filter(|_| !is_identity);
+    (physical_shape, permutation)
+}
+
+/// Serializes the parameters of the `arrow.fixed_shape_tensor` extension type.
+pub fn tensor_metadata(shape: &[usize], permutation: Option<&[usize]>) -> String {
+    let mut metadata = serde_json::json!({ "shape": shape });
+    if let Some(permutation) = permutation {
+        metadata["permutation"] = serde_json::json!(permutation);
+    }
+    metadata.to_string()
+}
+
+/// Inverse of [`tensor_metadata`], returns the shape and the permutation.
+pub fn parse_tensor_metadata(metadata: &str) -> Result<(Vec<usize>, Option<Vec<usize>>)> {
+    let metadata: serde_json::Value =
+        serde_json::from_str(metadata).context("invalid tensor metadata")?;
+    let shape: Vec<usize> = serde_json::from_value(metadata["shape"].clone())
+        .context("invalid `shape` in tensor metadata")?;
+    let permutation: Option<Vec<usize>> = serde_json::from_value(metadata["permutation"].clone())
+        .context("invalid `permutation` in tensor metadata")?;
+    if let Some(permutation) = &permutation {
+        let mut sorted = permutation.clone();
+        sorted.sort_unstable();
+        if !sorted.iter().copied().eq(0..shape.len()) {
+            eyre::bail!("`permutation` {permutation:?} does not match shape {shape:?}");
+        }
+    }
+    Ok((shape, permutation))
+}
+
+/// Returns the permutation that undoes the given one.
+pub fn inverse_permutation(permutation: &[usize]) -> Vec<usize> {
+    let mut inverse = vec![0; permutation.len()];
+    for (i, &dim) in permutation.iter().enumerate() {
+        inverse[dim] = i;
+    }
+    inverse
+}
@@ -0,0 +1,92 @@
+//! Conversion of received values into `torch` tensors without copying them.
+//!
+//! The arrow buffer of a value is exported through DLPack, so the tensor shares
+//! its memory with the received value, which is kept alive for the lifetime of
+//! the tensor. Values that were sent as `arrow.fixed_shape_tensor`, see
+//! [`tensor`](crate::tensor), keep their shape and strides. Values that were sent
+//! from a GPU buffer, see [`cuda`](crate::cuda), get their shape back and are
+//! moved to the CUDA device again, which requires a copy.
+
+use arrow::{
+    array::{Array, ArrayRef, AsArray},
+    pyarrow::ToPyArrow,
+};
+use dora_node_api::{Metadata, Parameter};
+use eyre::{Context, ContextCompat, Result};
+use pyo3::prelude::*;
+
+use crate::{
+    cuda::{CUDA_DEVICE, CUDA_SHAPE},
+    tensor::{parse_tensor_metadata, EXTENSION_METADATA, EXTENSION_NAME, FIXED_SHAPE_TENSOR},
+};
+
+/// Creates a `torch` tensor that shares the memory of the received value.
+///
+/// The tensor is moved to the given `device` if any, e.g. `"cuda"`, which
+/// copies the data. Values that were sent from a GPU buffer are moved back to
+/// their CUDA device if no `device` is given.
+pub fn to_torch(
+    py: Python<'_>,
+    array: &ArrayRef,
+    metadata: &Metadata,
+    device: Option<&str>,
+) -> Result<PyObject> {
+    let torch = py
+        .import_bound("torch")
+        .context("`to_torch` requires `torch` to be installed")?;
+    let parameters = &metadata.parameters;
+    let is_tensor = matches!(
+        parameters.get(EXTENSION_NAME),
+        Some(Parameter::String(name)) if name == FIXED_SHAPE_TENSOR
+    );
+    let (tensor, default_device) = if is_tensor {
+        let Some(Parameter::String(extension_metadata)) = parameters.get(EXTENSION_METADATA) else {
+            eyre::bail!("missing `{EXTENSION_METADATA}` of tensor");
+        };
+        let (shape, permutation) = parse_tensor_metadata(extension_metadata)?;
+        let list = array
+            .as_fixed_size_list_opt()
+            .context("tensor must be a fixed-size list array")?;
+        if list.len() != 1 {
+            eyre::bail!("expected a single tensor, got {}", list.len());
+        }
+        let flat = from_dlpack(&torch, &list.value(0))?;
+        let physical = flat.call_method1("reshape", (shape,))?;
+        let tensor = match permutation {
+            Some(permutation) => physical.call_method1("permute", (permutation,))?,
+            None => physical,
+        };
+        (tensor, None)
+    } else if let Some(Parameter::ListInt(shape)) = parameters.get(CUDA_SHAPE) {
+        let tensor = from_dlpack(&torch, array)?.call_method1("reshape", (shape.clone(),))?;
+        let cuda_device = match parameters.get(CUDA_DEVICE) {
+            Some(Parameter::Integer(ordinal)) => format!("cuda:{ordinal}"),
+            _ => "cuda".to_owned(),
+        };
+        (tensor, Some(cuda_device))
+    } else {
+        (from_dlpack(&torch, array)?, None)
+    };
+
+    let tensor = match device.or(default_device.as_deref()) {
+        Some(device) => tensor
+            .call_method1("to", (device,))
+            .with_context(|| format!("failed to move tensor to device `{device}`"))?,
+        None => tensor,
+    };
+    Ok(tensor.unbind())
+}
+
+/// Wraps the buffer of a primitive array as one-dimensional tensor.
+fn from_dlpack<'py>(torch: &Bound<'py, PyModule>, array: &ArrayRef) -> Result<Bound<'py, PyAny>> {
+    let values = array.to_data().to_pyarrow(torch.py())?;
+    torch
+        .call_method1("from_dlpack", (values,))
+        .with_context(|| {
+            format!(
+                "failed to export array of type {} through DLPack, which requires a \
+                primitive array without nulls and `pyarrow` 15 or later",
+                array.data_type()
+            )
+        })
+}
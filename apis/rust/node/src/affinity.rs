@@ -0,0 +1,81 @@
+//! Pinning of node threads to specific CPU cores.
+//!
+//! Only supported on Linux, where the affinity of each thread can be changed
+//! through the `sched_setaffinity` syscall.
+
+use std::sync::{
+    atomic::{AtomicI64, Ordering},
+    Arc,
+};
+
+/// Kernel ID of a background thread of the node.
+///
+/// The thread records its ID on start, so that its CPU affinity can be changed
+/// from other threads later.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct NativeThreadId(Arc<AtomicI64>);
+
+impl NativeThreadId {
+    /// Records the ID of the calling thread.
+    pub fn record_current(&self) {
+        #[cfg(target_os = "linux")]
+        self.0.store(
+            unsafe { libc::syscall(libc::SYS_gettid) },
+            Ordering::Relaxed,
+        );
+    }
+
+    /// Marks the thread as exited, so that its ID is not used anymore.
+    pub fn clear(&self) {
+        self.0.store(0, Ordering::Relaxed);
+    }
+
+    /// Restricts the thread to the given CPU cores.
+    ///
+    /// Does nothing if the thread was not started yet or already exited.
+    pub fn set_affinity(&self, cores: &[usize]) -> eyre::Result<()> {
+        match self.0.load(Ordering::Relaxed) {
+            0 => Ok(()),
+            tid => set_affinity(tid, cores),
+        }
+    }
+}
+
+/// Restricts the calling thread to the given CPU cores.
+pub(crate) fn set_current_thread_affinity(cores: &[usize]) -> eyre::Result<()> {
+    // `0` refers to the calling thread
+    set_affinity(0, cores)
+}
+
+#[cfg(target_os = "linux")]
+fn set_affinity(tid: i64, cores: &[usize]) -> eyre::Result<()> {
+    use eyre::Context;
+
+    if cores.is_empty() {
+        eyre::bail!("at least one CPU core is required");
+    }
+    let mut set: libc::cpu_set_t = unsafe { std::mem::zeroed() };
+    for &core in cores {
+        if core >= libc::CPU_SETSIZE as usize {
+            eyre::bail!("invalid CPU core {core}");
+        }
+        unsafe { libc::CPU_SET(core, &mut set) };
+    }
+    let result = unsafe {
+        libc::sched_setaffinity(
+            tid as libc::pid_t,
+            std::mem::size_of::<libc::cpu_set_t>(),
+            &set,
+        )
+    };
+    if result != 0 {
+        return Err(std::io::Error::last_os_error())
+            .wrap_err_with(|| format!("failed to pin thread to CPU cores {cores:?}"));
+    }
+    Ok(())
+}
+
+#[cfg(not(target_os = "linux"))]
+fn set_affinity(_tid: i64, _cores: &[usize]) -> eyre::Result<()> {
+    eyre::bail!("setting the CPU affinity is only supported on Linux")
+}
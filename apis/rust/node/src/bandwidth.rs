@@ -0,0 +1,111 @@
+use std::{
+    collections::{BTreeMap, VecDeque},
+    time::{Duration, Instant},
+};
+
+use dora_core::config::DataId;
+
+/// Length of the rolling window over which the bandwidth is averaged.
+pub const BANDWIDTH_WINDOW: Duration = Duration::from_secs(5);
+/// Messages within this interval are summed up into a single bucket.
+const BUCKET_LENGTH: Duration = Duration::from_millis(100);
+
+/// Measures the number of payload bytes per second of each input or output.
+///
+/// The rate is averaged over the last [`BANDWIDTH_WINDOW`]. Messages are
+/// aggregated into buckets, so the memory use does not grow with the message
+/// rate.
+#[derive(Debug)]
+pub struct BandwidthMeter {
+    started: Instant,
+    /// Start time and total size of the recent buckets of each ID.
+    buckets: BTreeMap<DataId, VecDeque<(Instant, usize)>>,
+}
+
+impl BandwidthMeter {
+    pub fn new() -> Self {
+        Self {
+            started: Instant::now(),
+            buckets: BTreeMap::new(),
+        }
+    }
+
+    /// Records a message of `bytes` payload bytes on the given input or output.
+    pub fn record(&mut self, id: &DataId, bytes: usize) {
+        self.record_at(id, bytes, Instant::now());
+    }
+
+    fn record_at(&mut self, id: &DataId, bytes: usize, now: Instant) {
+        let buckets = self.buckets.entry(id.clone()).or_default();
+        match buckets.back_mut() {
+            Some((start, total)) if now.duration_since(*start) < BUCKET_LENGTH => {
+                *total += bytes;
+            }
+            _ => buckets.push_back((now, bytes)),
+        }
+        Self::prune(buckets, now);
+    }
+
+    /// Returns the bytes per second of every ID that had at least one message.
+    ///
+    /// IDs without messages in the current window are reported with a rate of `0`.
+    pub fn rates(&mut self) -> BTreeMap<DataId, f64> {
+        self.rates_at(Instant::now())
+    }
+
+    fn rates_at(&mut self, now: Instant) -> BTreeMap<DataId, f64> {
+        // don't underestimate the rate before the first window is complete
+        let window = now
+            .duration_since(self.started)
+            .clamp(BUCKET_LENGTH, BANDWIDTH_WINDOW);
+        self.buckets
+            .iter_mut()
+            .map(|(id, buckets)| {
+                Self::prune(buckets, now);
+                let total: usize = buckets.iter().map(|(_, bytes)| bytes).sum();
+                (id.clone(), total as f64 / window.as_secs_f64())
+            })
+            .collect()
+    }
+
+    fn prune(buckets: &mut VecDeque<(Instant, usize)>, now: Instant) {
+        while let Some((start, _)) = buckets.front() {
+            if now.duration_since(*start) < BANDWIDTH_WINDOW {
+                break;
+            }
+            buckets.pop_front();
+        }
+    }
+}
+
+impl Default for BandwidthMeter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rate_is_averaged_over_the_window() {
+        let mut meter = BandwidthMeter::new();
+        let start = meter.started;
+        let image = DataId::from("image".to_owned());
+        let pose = DataId::from("pose".to_owned());
+
+        for i in 0..50 {
+            let now = start + Duration::from_millis(100 * i + 50);
+            meter.record_at(&image, 1_000_000, now);
+            meter.record_at(&pose, 100, now);
+        }
+        let rates = meter.rates_at(start + BANDWIDTH_WINDOW);
+        assert_eq!(rates[&image], 10_000_000.0);
+        assert_eq!(rates[&pose], 1_000.0);
+
+        // old messages drop out of the window
+        let rates = meter.rates_at(start + 3 * BANDWIDTH_WINDOW);
+        assert_eq!(rates[&image], 0.0);
+    }
+}
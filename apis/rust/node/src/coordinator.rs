@@ -0,0 +1,38 @@
+use std::net::SocketAddr;
+
+use communication_layer_request_reply::{RequestReplyLayer, TcpLayer};
+use dora_message::{
+    cli_to_coordinator::ControlRequest,
+    coordinator_to_cli::{ControlRequestReply, DataflowListEntry, DataflowStatus},
+};
+use eyre::{bail, Context};
+
+/// Queries the dora-coordinator at the given control address for the dataflows
+/// that are currently running.
+///
+/// Unlike the other functions of this crate, this doesn't require a node, so it
+/// can be used by tools that monitor several dataflows. The coordinator listens
+/// for control requests on port
+/// [`DORA_COORDINATOR_PORT_CONTROL_DEFAULT`](dora_core::topics::DORA_COORDINATOR_PORT_CONTROL_DEFAULT)
+/// by default.
+pub fn list_dataflows(coordinator_addr: SocketAddr) -> eyre::Result<Vec<DataflowListEntry>> {
+    let mut session = TcpLayer::new()
+        .connect(coordinator_addr)
+        .wrap_err_with(|| format!("failed to connect to dora-coordinator at {coordinator_addr}"))?;
+    let request =
+        serde_json::to_vec(&ControlRequest::List).wrap_err("failed to serialize list message")?;
+    let reply_raw = session
+        .request(&request)
+        .wrap_err("failed to send list message")?;
+    let reply: ControlRequestReply =
+        serde_json::from_slice(&reply_raw).wrap_err("failed to parse reply")?;
+    match reply {
+        ControlRequestReply::DataflowList(list) => Ok(list
+            .0
+            .into_iter()
+            .filter(|entry| entry.status == DataflowStatus::Running)
+            .collect()),
+        ControlRequestReply::Error(err) => bail!("{err}"),
+        other => bail!("unexpected list dataflow reply: {other:?}"),
+    }
+}
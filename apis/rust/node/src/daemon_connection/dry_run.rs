@@ -0,0 +1,126 @@
+//! A transport that records the outputs of a node instead of sending them.
+
+use std::sync::{Arc, Mutex};
+
+use dora_arrow_convert::ArrowData;
+use dora_core::config::DataId;
+use dora_message::{
+    daemon_to_node::DaemonReply,
+    metadata::Metadata,
+    node_to_daemon::{DaemonRequest, DataMessage, Timestamped},
+};
+use eyre::bail;
+
+use super::{Connection, ConnectionKind, Transport};
+use crate::RawData;
+
+/// An output that was recorded by a [`DryRunTransport`].
+#[derive(Debug, Clone)]
+pub struct SentOutput {
+    pub output_id: DataId,
+    pub metadata: Metadata,
+    pub data: ArrowData,
+}
+
+/// A transport that does not connect to a daemon, for testing node logic in
+/// isolation.
+///
+/// Outputs are converted and validated as usual, but recorded instead of being
+/// sent. The event stream of the node ends immediately. Clones of the transport
+/// share the recorded outputs, so keep a clone to inspect them:
+///
+/// ```no_run
+/// use dora_node_api::{DoraNode, DryRunTransport};
+///
+/// # let node_config: dora_message::daemon_to_node::NodeConfig = todo!();
+/// let transport = DryRunTransport::default();
+/// let (mut node, _events) = DoraNode::init_with_transport(node_config, &transport)?;
+/// // ... send outputs through `node`
+/// for output in transport.take_sent_outputs() {
+///     println!("{}: {:?}", output.output_id, output.data);
+/// }
+/// # Ok::<(), eyre::Report>(())
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct DryRunTransport {
+    sent: Arc<Mutex<Vec<SentOutput>>>,
+}
+
+impl DryRunTransport {
+    /// Returns the outputs that were recorded since the last call, in send order.
+    pub fn take_sent_outputs(&self) -> Vec<SentOutput> {
+        std::mem::take(&mut *self.lock())
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Vec<SentOutput>> {
+        self.sent.lock().unwrap_or_else(|err| err.into_inner())
+    }
+}
+
+impl Transport for DryRunTransport {
+    fn connect(&self, _kind: ConnectionKind) -> eyre::Result<Box<dyn Connection>> {
+        Ok(Box::new(self.clone()))
+    }
+
+    fn supports_shared_memory(&self) -> bool {
+        // the data must be part of the `SendMessage` request to record it
+        false
+    }
+}
+
+impl Connection for DryRunTransport {
+    fn request(&mut self, request: &Timestamped<DaemonRequest>) -> eyre::Result<DaemonReply> {
+        let reply = match &request.inner {
+            DaemonRequest::SendMessage {
+                output_id,
+                metadata,
+                data,
+            } => {
+                let data = match data {
+                    None => RawData::Empty,
+                    Some(DataMessage::Vec(data)) => RawData::Vec(data.clone()),
+                    Some(DataMessage::SharedMemory { .. }) => {
+                        bail!("shared memory is not supported in dry-run mode")
+                    }
+                };
+                let data = data.into_arrow_array(&metadata.type_info)?;
+                self.lock().push(SentOutput {
+                    output_id: output_id.clone(),
+                    metadata: metadata.clone(),
+                    data: arrow::array::make_array(data).into(),
+                });
+                DaemonReply::Empty
+            }
+            // empty replies close the event and drop streams
+            DaemonRequest::NextEvent { .. } => DaemonReply::NextEvents(Vec::new()),
+            DaemonRequest::NextFinishedDropTokens => DaemonReply::NextDropEvents(Vec::new()),
+            DaemonRequest::CheckAcked { .. } => DaemonReply::Acked(true),
+            DaemonRequest::PipelineDepths => DaemonReply::PipelineDepths(Vec::new()),
+            DaemonRequest::ClockSkew { .. } => DaemonReply::ClockSkew(Default::default()),
+            DaemonRequest::NodeConfig { .. } => DaemonReply::NodeConfig {
+                result: Err("no node config in dry-run mode".into()),
+            },
+            DaemonRequest::ReportDropTokens { .. }
+            | DaemonRequest::ReportQueueDepths(_)
+            | DaemonRequest::AckInputs(_)
+            | DaemonRequest::ReportProgress { .. }
+            | DaemonRequest::ReportSummary(_)
+            | DaemonRequest::AckStop
+            | DaemonRequest::Log { .. } => DaemonReply::Empty,
+            DaemonRequest::Register(_)
+            | DaemonRequest::Subscribe
+            | DaemonRequest::TakeOverSubscription
+            | DaemonRequest::SubscribeDrop
+            | DaemonRequest::CloseOutputs(_)
+            | DaemonRequest::DeclareOutput(_)
+            | DaemonRequest::DisconnectInput(_)
+            | DaemonRequest::SubscribePattern(_)
+            | DaemonRequest::SetDeadLetterOutput(_)
+            | DaemonRequest::SetConflation { .. }
+            | DaemonRequest::SubscribeLifecycleEvents
+            | DaemonRequest::OutputsDone
+            | DaemonRequest::EventStreamDropped => DaemonReply::Result(Ok(())),
+        };
+        Ok(reply)
+    }
+}
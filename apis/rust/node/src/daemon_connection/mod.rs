@@ -2,7 +2,7 @@ use dora_core::{config::NodeId, uhlc::Timestamp};
 use dora_message::{
     daemon_to_node::DaemonReply,
     node_to_daemon::{DaemonRequest, NodeRegisterRequest, Timestamped},
-    DataflowId,
+    semver, DataflowId,
 };
 use eyre::{bail, eyre, Context};
 use shared_memory_server::{ShmemClient, ShmemConf};
@@ -13,18 +13,52 @@ use std::{
     time::Duration,
 };
 
+pub use dry_run::{DryRunTransport, SentOutput};
+pub use transport::{Connection, ConnectionKind, Transport};
+
+mod dry_run;
 mod tcp;
+mod transport;
 #[cfg(unix)]
 mod unix_domain;
 
+/// Returned on initialization if the node was built against a dora version that
+/// is not compatible with the version of the daemon.
+///
+/// The node and the daemon exchange messages in a format that may change
+/// between dora versions, so both need to be built from compatible versions.
+#[derive(Debug, Clone)]
+pub struct VersionMismatchError {
+    pub node_version: semver::Version,
+    pub daemon_version: semver::Version,
+}
+
+impl std::fmt::Display for VersionMismatchError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "node was built against dora v{}, which is not compatible with \
+            dora-daemon v{}",
+            self.node_version, self.daemon_version
+        )
+    }
+}
+
+impl std::error::Error for VersionMismatchError {}
+
 pub enum DaemonChannel {
     Shmem(ShmemClient<Timestamped<DaemonRequest>, DaemonReply>),
     Tcp(TcpStream),
     #[cfg(unix)]
     UnixDomain(UnixStream),
+    Custom(Box<dyn Connection>),
 }
 
 impl DaemonChannel {
+    pub fn connect(transport: &dyn Transport, kind: ConnectionKind) -> eyre::Result<Self> {
+        transport.connect(kind).map(DaemonChannel::Custom)
+    }
+
     #[tracing::instrument(level = "trace")]
     pub fn new_tcp(socket_addr: SocketAddr) -> eyre::Result<Self> {
         let stream = TcpStream::connect(socket_addr).wrap_err("failed to open TCP connection")?;
@@ -70,6 +104,16 @@ impl DaemonChannel {
             DaemonReply::Result(result) => result
                 .map_err(|e| eyre!(e))
                 .wrap_err("failed to register node with dora-daemon")?,
+            DaemonReply::VersionMismatch {
+                node_version,
+                daemon_version,
+            } => {
+                return Err(VersionMismatchError {
+                    node_version,
+                    daemon_version,
+                }
+                .into());
+            }
             other => bail!("unexpected register reply: {other:?}"),
         }
         Ok(())
@@ -81,6 +125,7 @@ impl DaemonChannel {
             DaemonChannel::Tcp(stream) => tcp::request(stream, request),
             #[cfg(unix)]
             DaemonChannel::UnixDomain(stream) => unix_domain::request(stream, request),
+            DaemonChannel::Custom(connection) => connection.request(request),
         }
     }
 }
@@ -0,0 +1,90 @@
+//! Pluggable transports for the communication between a node and the dora daemon.
+//!
+//! By default, nodes connect to the daemon as specified by the
+//! [`DaemonCommunication`] of their node config, using shared memory, TCP, or
+//! Unix domain sockets. Custom transports can be injected through
+//! [`DoraNode::init_with_transport`](crate::DoraNode::init_with_transport).
+
+use dora_message::{
+    daemon_to_node::{DaemonCommunication, DaemonReply},
+    node_to_daemon::{DaemonRequest, Timestamped},
+};
+use eyre::Context;
+
+use super::DaemonChannel;
+
+/// The purpose of a connection to the daemon.
+///
+/// Every node opens one connection of each kind, which are used concurrently
+/// from different threads.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConnectionKind {
+    /// Used for sending outputs and other requests to the daemon.
+    Control,
+    /// Used for receiving events from the daemon.
+    Events,
+    /// Used for notifying the daemon when the event stream is dropped.
+    EventsClose,
+    /// Used for receiving notifications about dropped output samples.
+    Drop,
+}
+
+/// A request/reply connection to the daemon.
+pub trait Connection: Send {
+    /// Sends the given request to the daemon and waits for its reply.
+    ///
+    /// The daemon does not reply to some requests, e.g. to
+    /// [`DaemonRequest::SendMessage`]. For these, [`DaemonReply::Empty`] should be
+    /// returned without waiting.
+    fn request(&mut self, request: &Timestamped<DaemonRequest>) -> eyre::Result<DaemonReply>;
+}
+
+/// Opens the connections of a node to the daemon.
+pub trait Transport {
+    /// Opens a new connection of the given kind.
+    fn connect(&self, kind: ConnectionKind) -> eyre::Result<Box<dyn Connection>>;
+
+    /// Whether large outputs can be passed to the daemon through shared memory.
+    ///
+    /// If `false`, all output data is sent as part of the `SendMessage` request.
+    fn supports_shared_memory(&self) -> bool {
+        true
+    }
+}
+
+impl Connection for DaemonChannel {
+    fn request(&mut self, request: &Timestamped<DaemonRequest>) -> eyre::Result<DaemonReply> {
+        DaemonChannel::request(self, request)
+    }
+}
+
+/// The default transport, as configured by the daemon.
+impl Transport for DaemonCommunication {
+    fn connect(&self, kind: ConnectionKind) -> eyre::Result<Box<dyn Connection>> {
+        let channel = match self {
+            DaemonCommunication::Shmem {
+                daemon_control_region_id,
+                daemon_drop_region_id,
+                daemon_events_region_id,
+                daemon_events_close_region_id,
+            } => {
+                let region_id = match kind {
+                    ConnectionKind::Control => daemon_control_region_id,
+                    ConnectionKind::Events => daemon_events_region_id,
+                    ConnectionKind::EventsClose => daemon_events_close_region_id,
+                    ConnectionKind::Drop => daemon_drop_region_id,
+                };
+                unsafe { DaemonChannel::new_shmem(region_id) }
+                    .wrap_err_with(|| format!("failed to create shmem {kind:?} channel"))?
+            }
+            DaemonCommunication::Tcp { socket_addr } => DaemonChannel::new_tcp(*socket_addr)
+                .wrap_err_with(|| format!("failed to connect {kind:?} channel"))?,
+            #[cfg(unix)]
+            DaemonCommunication::UnixDomain { socket_file } => {
+                DaemonChannel::new_unix_socket(socket_file)
+                    .wrap_err_with(|| format!("failed to connect {kind:?} channel"))?
+            }
+        };
+        Ok(Box::new(channel))
+    }
+}
@@ -0,0 +1,120 @@
+//! Buffering of received events on a background thread.
+
+use std::sync::{Arc, Mutex, MutexGuard, Weak};
+
+use super::{scheduler::Scheduler, thread::EventItem};
+use crate::affinity::NativeThreadId;
+
+/// Input queues of an event stream, shared with the buffer thread.
+#[derive(Debug)]
+pub(crate) struct SharedScheduler(Arc<Mutex<Scheduler>>);
+
+impl SharedScheduler {
+    pub fn new(scheduler: Scheduler) -> Self {
+        Self(Arc::new(Mutex::new(scheduler)))
+    }
+
+    pub fn lock(&self) -> MutexGuard<'_, Scheduler> {
+        lock(&self.0)
+    }
+}
+
+fn lock(scheduler: &Mutex<Scheduler>) -> MutexGuard<'_, Scheduler> {
+    // the scheduler stays consistent even if a thread panicked while holding it
+    scheduler.lock().unwrap_or_else(|err| err.into_inner())
+}
+
+/// Background thread that moves received events into the input queues.
+///
+/// Without it, received events are only moved into the queues when the node
+/// asks for the next event, so the `queue_size` limits are not applied while
+/// the node is busy.
+pub(crate) struct BufferThread {
+    /// Receives a notification after events were added to the queues.
+    ///
+    /// Disconnected once the event stream from the daemon is closed.
+    pub notifications: flume::r#async::RecvStream<'static, ()>,
+    pub thread_id: NativeThreadId,
+}
+
+impl BufferThread {
+    /// Spawns the thread, which exits once the event stream is dropped.
+    pub fn spawn(receiver: flume::Receiver<EventItem>, scheduler: &SharedScheduler) -> Self {
+        // queued events must be dropped with the event stream to release their
+        // drop tokens, so the thread must not keep the queues alive
+        let scheduler: Weak<Mutex<Scheduler>> = Arc::downgrade(&scheduler.0);
+        // a single pending notification is enough to wake up the receiver
+        let (notify_tx, notify_rx) = flume::bounded(1);
+        let thread_id = NativeThreadId::default();
+        let thread_id_cloned = thread_id.clone();
+        std::thread::spawn(move || {
+            thread_id_cloned.record_current();
+            while let Ok(event) = receiver.recv() {
+                let Some(scheduler) = scheduler.upgrade() else {
+                    break;
+                };
+                lock(&scheduler).add_event(event);
+                let _ = notify_tx.try_send(());
+            }
+            thread_id_cloned.clear();
+        });
+        Self {
+            notifications: notify_rx.into_stream(),
+            thread_id,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::VecDeque;
+
+    use dora_core::{metadata::ArrowTypeInfoExt, uhlc::HLC};
+    use dora_message::{
+        daemon_to_node::NodeEvent,
+        id::DataId,
+        metadata::{ArrowTypeInfo, Metadata},
+    };
+    use futures::StreamExt;
+
+    use super::*;
+    use crate::event_stream::scheduler::NON_INPUT_EVENT;
+
+    #[test]
+    fn queue_size_is_applied_without_receiving() {
+        let queues = [("image", 1), (NON_INPUT_EVENT, 10)]
+            .into_iter()
+            .map(|(id, size)| (DataId::from(id.to_owned()), (size, VecDeque::new())))
+            .collect();
+        let scheduler = SharedScheduler::new(Scheduler::new(queues));
+        let (tx, rx) = flume::unbounded();
+        let mut buffer = BufferThread::spawn(rx, &scheduler);
+
+        let mut acks = Vec::new();
+        for _ in 0..3 {
+            let (ack_tx, ack_rx) = flume::bounded(0);
+            acks.push(ack_rx);
+            let event = EventItem::NodeEvent {
+                event: NodeEvent::Input {
+                    id: DataId::from("image".to_owned()),
+                    metadata: Metadata::new(HLC::default().new_timestamp(), ArrowTypeInfo::empty()),
+                    data: None,
+                },
+                ack_channel: ack_tx,
+            };
+            tx.send(event).unwrap();
+        }
+        drop(tx);
+        // wait until the buffer thread exits
+        while futures::executor::block_on(buffer.notifications.next()).is_some() {}
+
+        // the outdated events were dropped, which releases their drop tokens
+        assert!(acks[0].is_disconnected());
+        assert!(acks[1].is_disconnected());
+        assert!(!acks[2].is_disconnected());
+        assert_eq!(
+            scheduler.lock().queue_depths()[&DataId::from("image".to_owned())],
+            1
+        );
+    }
+}
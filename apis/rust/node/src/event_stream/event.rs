@@ -2,12 +2,19 @@ use std::{ptr::NonNull, sync::Arc};
 
 use aligned_vec::{AVec, ConstAlign};
 use dora_arrow_convert::{ArrowData, IntoArrow};
-use dora_core::config::{DataId, OperatorId};
+use dora_core::{
+    config::{DataId, OperatorId},
+    uhlc,
+};
 use dora_message::metadata::{ArrowTypeInfo, BufferOffset, Metadata};
 use eyre::{Context, Result};
 use shared_memory_extended::{Shmem, ShmemConf};
 
-#[derive(Debug)]
+/// An event received by a node.
+///
+/// Cloning an event is cheap: the data of inputs is reference-counted, so clones
+/// share the same underlying buffer.
+#[derive(Debug, Clone)]
 #[non_exhaustive]
 pub enum Event {
     Stop,
@@ -22,6 +29,23 @@ pub enum Event {
     InputClosed {
         id: DataId,
     },
+    /// Signals that no more input events with a timestamp at or before `time`
+    /// will arrive.
+    ///
+    /// Only emitted if enabled through
+    /// [`EventStream::set_watermarks`](crate::EventStream::set_watermarks).
+    Watermark {
+        time: uhlc::Timestamp,
+    },
+    /// The payload of an input could not be decoded, e.g. because it is
+    /// corrupted or does not match its type information.
+    ///
+    /// Only the affected input event is lost, the event stream continues with
+    /// the next event.
+    DecodeError {
+        id: DataId,
+        error: String,
+    },
     Error(String),
 }
 
@@ -35,6 +59,10 @@ impl RawData {
     pub fn into_arrow_array(self, type_info: &ArrowTypeInfo) -> Result<arrow::array::ArrayData> {
         let raw_buffer = match self {
             RawData::Empty => return Ok(().into_arrow().into()),
+            // zero-length payload, e.g. a pure trigger signal
+            RawData::Vec(data) if data.is_empty() => {
+                arrow::buffer::Buffer::from_vec(Vec::<u8>::new())
+            }
             RawData::Vec(data) => {
                 let ptr = NonNull::new(data.as_ptr() as *mut _).unwrap();
                 let len = data.len();
@@ -62,7 +90,7 @@ fn buffer_into_arrow_array(
     raw_buffer: &arrow::buffer::Buffer,
     type_info: &ArrowTypeInfo,
 ) -> eyre::Result<arrow::array::ArrayData> {
-    if raw_buffer.is_empty() {
+    if raw_buffer.is_empty() && type_info.len == 0 {
         return Ok(arrow::array::ArrayData::new_empty(&type_info.data_type));
     }
 
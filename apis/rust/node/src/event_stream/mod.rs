@@ -1,99 +1,132 @@
 use std::{
     collections::{BTreeMap, HashMap, VecDeque},
     sync::Arc,
-    time::Duration,
+    time::{Duration, Instant},
 };
 
 use dora_message::{
-    daemon_to_node::{DaemonCommunication, DaemonReply, DataMessage, NodeEvent},
+    daemon_to_node::{DaemonReply, DataMessage, LifecycleEvent, NodeEvent},
     id::DataId,
+    metadata::{Metadata, Parameter, RELIABLE},
     node_to_daemon::{DaemonRequest, Timestamped},
     DataflowId,
 };
 pub use event::{Event, MappedInputData, RawData};
 use futures::{
     future::{select, Either},
-    Stream, StreamExt,
+    FutureExt, Stream, StreamExt,
 };
 use futures_timer::Delay;
-use scheduler::{Scheduler, NON_INPUT_EVENT};
+pub use scheduler::InputSchedule;
+use scheduler::{Scheduler, DEFAULT_QUEUE_SIZE, NON_INPUT_EVENT};
+use watermark::WatermarkTracker;
 
 use self::{
+    buffer::{BufferThread, SharedScheduler},
     event::SharedMemoryData,
     thread::{EventItem, EventStreamThreadHandle},
 };
-use crate::daemon_connection::DaemonChannel;
+use crate::{
+    bandwidth::BandwidthMeter,
+    daemon_connection::{ConnectionKind, DaemonChannel, Transport},
+    metrics::MessageCounters,
+};
 use dora_core::{
     config::{Input, NodeId},
     uhlc,
 };
 use eyre::{eyre, Context};
 
+mod buffer;
 mod event;
 pub mod merged;
 mod scheduler;
 mod thread;
+mod watermark;
+
+/// Returned on initialization if another process is already connected to the
+/// daemon with the same node ID.
+///
+/// This usually means that a node was started twice, e.g. manually in addition
+/// to the instance that was spawned by the daemon.
+#[derive(Debug, Clone)]
+pub struct DuplicateNodeIdError {
+    pub node_id: NodeId,
+}
+
+impl std::fmt::Display for DuplicateNodeIdError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "node ID `{}` is already used by another process of the dataflow",
+            self.node_id
+        )
+    }
+}
+
+impl std::error::Error for DuplicateNodeIdError {}
 
 pub struct EventStream {
     node_id: NodeId,
     receiver: flume::r#async::RecvStream<'static, EventItem>,
-    _thread_handle: EventStreamThreadHandle,
+    /// Handed to the buffer thread by [`Self::buffer_in_background`].
+    raw_receiver: Option<flume::Receiver<EventItem>>,
+    /// Set if received events are moved into the scheduler in the background.
+    buffer: Option<BufferThread>,
+    lifecycle_events: flume::Receiver<LifecycleEvent>,
+    tees: Vec<flume::Sender<Event>>,
+    thread_handle: EventStreamThreadHandle,
+    /// Used for signaling the closure of the event stream and for reporting
+    /// queue depths to the daemon.
     close_channel: DaemonChannel,
     clock: Arc<uhlc::HLC>,
-    scheduler: Scheduler,
+    scheduler: SharedScheduler,
+    reported_queue_depths: BTreeMap<DataId, usize>,
+    last_queue_depth_report: Instant,
+    watermarks: WatermarkTracker,
+    emit_watermarks: bool,
+    /// Watermark event that is returned before the next received event.
+    pending_watermark: Option<uhlc::Timestamp>,
+    /// Payload bytes per delivered input, see [`Self::input_bandwidth`].
+    input_bandwidth: BandwidthMeter,
+    /// Cumulative counters per delivered input, see [`Self::input_counters`].
+    input_counters: BTreeMap<DataId, MessageCounters>,
+    /// See [`Self::set_manual_acks`].
+    manual_acks: bool,
+    /// Reliably sent inputs that were delivered, but not acknowledged yet, as
+    /// pairs of input ID and message ID.
+    unacked: Vec<(DataId, u64)>,
 }
 
+/// Minimum interval between two queue depth reports to the daemon.
+const QUEUE_DEPTH_REPORT_INTERVAL: Duration = Duration::from_millis(100);
+
 impl EventStream {
-    #[tracing::instrument(level = "trace", skip(clock))]
+    #[tracing::instrument(level = "trace", skip(transport, clock))]
     pub(crate) fn init(
         dataflow_id: DataflowId,
         node_id: &NodeId,
-        daemon_communication: &DaemonCommunication,
+        transport: &dyn Transport,
         input_config: BTreeMap<DataId, Input>,
         clock: Arc<uhlc::HLC>,
+        take_over: bool,
     ) -> eyre::Result<Self> {
-        let channel = match daemon_communication {
-            DaemonCommunication::Shmem {
-                daemon_events_region_id,
-                ..
-            } => unsafe { DaemonChannel::new_shmem(daemon_events_region_id) }.wrap_err_with(
-                || format!("failed to create shmem event stream for node `{node_id}`"),
-            )?,
-            DaemonCommunication::Tcp { socket_addr } => DaemonChannel::new_tcp(*socket_addr)
-                .wrap_err_with(|| format!("failed to connect event stream for node `{node_id}`"))?,
-            #[cfg(unix)]
-            DaemonCommunication::UnixDomain { socket_file } => {
-                DaemonChannel::new_unix_socket(socket_file).wrap_err_with(|| {
-                    format!("failed to connect event stream for node `{node_id}`")
-                })?
-            }
-        };
-
-        let close_channel = match daemon_communication {
-            DaemonCommunication::Shmem {
-                daemon_events_close_region_id,
-                ..
-            } => unsafe { DaemonChannel::new_shmem(daemon_events_close_region_id) }.wrap_err_with(
-                || format!("failed to create shmem event close channel for node `{node_id}`"),
-            )?,
-            DaemonCommunication::Tcp { socket_addr } => DaemonChannel::new_tcp(*socket_addr)
-                .wrap_err_with(|| {
-                    format!("failed to connect event close channel for node `{node_id}`")
-                })?,
-            #[cfg(unix)]
-            DaemonCommunication::UnixDomain { socket_file } => {
-                DaemonChannel::new_unix_socket(socket_file).wrap_err_with(|| {
-                    format!("failed to connect event close channel for node `{node_id}`")
-                })?
-            }
-        };
+        let channel = DaemonChannel::connect(transport, ConnectionKind::Events)
+            .wrap_err_with(|| format!("failed to connect event stream for node `{node_id}`"))?;
+        let close_channel = DaemonChannel::connect(transport, ConnectionKind::EventsClose)
+            .wrap_err_with(|| {
+                format!("failed to connect event close channel for node `{node_id}`")
+            })?;
 
         let mut queue_size_limit: HashMap<DataId, (usize, VecDeque<EventItem>)> = input_config
             .iter()
             .map(|(input, config)| {
                 (
                     input.clone(),
-                    (config.queue_size.unwrap_or(1), VecDeque::new()),
+                    (
+                        config.queue_size.unwrap_or(DEFAULT_QUEUE_SIZE),
+                        VecDeque::new(),
+                    ),
                 )
             })
             .collect();
@@ -104,6 +137,7 @@ impl EventStream {
         );
 
         let scheduler = Scheduler::new(queue_size_limit);
+        let watermarks = WatermarkTracker::new(input_config.into_keys());
 
         Self::init_on_channel(
             dataflow_id,
@@ -112,9 +146,12 @@ impl EventStream {
             close_channel,
             clock,
             scheduler,
+            watermarks,
+            take_over,
         )
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub(crate) fn init_on_channel(
         dataflow_id: DataflowId,
         node_id: &NodeId,
@@ -122,11 +159,18 @@ impl EventStream {
         mut close_channel: DaemonChannel,
         clock: Arc<uhlc::HLC>,
         scheduler: Scheduler,
+        watermarks: WatermarkTracker,
+        take_over: bool,
     ) -> eyre::Result<Self> {
         channel.register(dataflow_id, node_id.clone(), clock.new_timestamp())?;
+        let subscribe = if take_over {
+            DaemonRequest::TakeOverSubscription
+        } else {
+            DaemonRequest::Subscribe
+        };
         let reply = channel
             .request(&Timestamped {
-                inner: DaemonRequest::Subscribe,
+                inner: subscribe,
                 timestamp: clock.new_timestamp(),
             })
             .map_err(|e| eyre!(e))
@@ -137,25 +181,267 @@ impl EventStream {
             DaemonReply::Result(Err(err)) => {
                 eyre::bail!("subscribe failed: {err}")
             }
+            DaemonReply::DuplicateNodeId(node_id) => {
+                return Err(DuplicateNodeIdError { node_id }.into());
+            }
             other => eyre::bail!("unexpected subscribe reply: {other:?}"),
         }
 
         close_channel.register(dataflow_id, node_id.clone(), clock.new_timestamp())?;
 
         let (tx, rx) = flume::bounded(100_000_000);
+        let (lifecycle_tx, lifecycle_rx) = flume::unbounded();
 
-        let thread_handle = thread::init(node_id.clone(), tx, channel, clock.clone())?;
+        let thread_handle =
+            thread::init(node_id.clone(), tx, lifecycle_tx, channel, clock.clone())?;
 
         Ok(EventStream {
             node_id: node_id.clone(),
-            receiver: rx.into_stream(),
-            _thread_handle: thread_handle,
+            receiver: rx.clone().into_stream(),
+            raw_receiver: Some(rx),
+            buffer: None,
+            lifecycle_events: lifecycle_rx,
+            tees: Vec::new(),
+            thread_handle,
             close_channel,
             clock,
-            scheduler,
+            scheduler: SharedScheduler::new(scheduler),
+            reported_queue_depths: BTreeMap::new(),
+            last_queue_depth_report: Instant::now(),
+            watermarks,
+            emit_watermarks: false,
+            pending_watermark: None,
+            input_bandwidth: BandwidthMeter::new(),
+            input_counters: BTreeMap::new(),
+            manual_acks: false,
+            unacked: Vec::new(),
         })
     }
 
+    /// Returns the channel on which lifecycle events of the dataflow are delivered.
+    ///
+    /// The channel stays empty until the node subscribes through
+    /// [`DoraNode::subscribe_lifecycle_events`](crate::DoraNode::subscribe_lifecycle_events).
+    pub fn lifecycle_events(&self) -> flume::Receiver<LifecycleEvent> {
+        self.lifecycle_events.clone()
+    }
+
+    /// Forks the event stream for tee-style processing.
+    ///
+    /// The returned channel receives a copy of every event that this stream
+    /// returns, e.g. to log inputs independently of their processing. Events are
+    /// copied when they are returned by this stream, so events that are dropped
+    /// because of the `queue_size` or [`set_max_buffered_bytes`](Self::set_max_buffered_bytes)
+    /// limits are not forwarded.
+    ///
+    /// The copies share the data buffer with the original event, so the payload
+    /// is not duplicated. However, the buffer is only freed (and shared memory
+    /// only returned to the sender) once all copies are dropped. The channel is
+    /// unbounded, so a tee that is not drained keeps all copied inputs alive.
+    /// Drop the receiver to stop the copying.
+    pub fn tee(&mut self) -> flume::Receiver<Event> {
+        let (tx, rx) = flume::unbounded();
+        self.tees.push(tx);
+        rx
+    }
+
+    /// Limits the total payload size of input events that are buffered by the node.
+    ///
+    /// When the limit is exceeded, the oldest events of the input that buffers the
+    /// most bytes are dropped, in the same way as for the `queue_size` limit of
+    /// inputs. The most recent event of each input is always kept. This is useful
+    /// for large inputs such as images, for which a count limit says little about
+    /// the memory usage.
+    ///
+    /// The limit only applies to the `recv` methods, not to the [`Stream`]
+    /// implementation, which does not buffer events, unless
+    /// [`buffer_in_background`](Self::buffer_in_background) is enabled.
+    pub fn set_max_buffered_bytes(&mut self, max_buffered_bytes: Option<usize>) {
+        self.scheduler
+            .lock()
+            .set_max_buffered_bytes(max_buffered_bytes);
+    }
+
+    /// Sets the order in which the queued events of different inputs are returned
+    /// by the `recv` methods.
+    ///
+    /// By default, the inputs with queued events are returned in turns, see
+    /// [`InputSchedule::RoundRobin`]. With [`InputSchedule::WeightedFair`], each
+    /// input is guaranteed a share of the returned events according to its
+    /// weight while several inputs are backed up, e.g. to keep serving a
+    /// low-rate control input under a flood of camera images.
+    ///
+    /// Like [`set_max_buffered_bytes`](Self::set_max_buffered_bytes), the
+    /// schedule does not apply to the [`Stream`] implementation, unless
+    /// [`buffer_in_background`](Self::buffer_in_background) is enabled.
+    pub fn set_input_schedule(&mut self, schedule: InputSchedule) -> eyre::Result<()> {
+        if let InputSchedule::WeightedFair { weights } = &schedule {
+            if let Some((id, weight)) = weights
+                .iter()
+                .find(|(_id, weight)| !(weight.is_finite() && **weight > 0.0))
+            {
+                eyre::bail!("weight of input `{id}` must be a positive number, got {weight}");
+            }
+        }
+        self.scheduler.lock().set_schedule(schedule);
+        Ok(())
+    }
+
+    /// Moves received events into the input queues on a background thread.
+    ///
+    /// By default, received events are only moved into the input queues when the
+    /// next event is requested. While the node is busy, e.g. in a long-running
+    /// callback of a Python node, the events pile up without applying the
+    /// `queue_size` and [`set_max_buffered_bytes`](Self::set_max_buffered_bytes)
+    /// limits, so outdated events keep their memory, including shared memory
+    /// regions of the sender. With background buffering, the limits are applied
+    /// as soon as events are received, and requesting the next event only takes
+    /// it from the queues.
+    ///
+    /// Once enabled, the [`Stream`] implementation takes events from the input
+    /// queues as well, so the limits apply to it too. Calling this method again
+    /// has no effect.
+    pub fn buffer_in_background(&mut self) {
+        if let Some(receiver) = self.raw_receiver.take() {
+            self.buffer = Some(BufferThread::spawn(receiver, &self.scheduler));
+        }
+    }
+
+    /// Enables [`Event::Watermark`] events for event-time processing, e.g. to close
+    /// the windows of time-windowed aggregations.
+    ///
+    /// The watermark is the minimum over all open inputs of the latest timestamp
+    /// that was returned by this stream for the input. Since the messages of an
+    /// input are sent by a single node with increasing timestamps, no event with a
+    /// timestamp at or before the watermark will arrive anymore. The watermark
+    /// advances as follows:
+    ///
+    /// - There is no watermark until every input received at least one event.
+    /// - Closed inputs no longer hold back the watermark.
+    /// - The watermark never moves backwards.
+    ///
+    /// Whenever an event advances the watermark, a `Watermark` event is returned
+    /// right after it. Messages that were sent with a non-default
+    /// [`Priority`](crate::metadata::Priority) can overtake older messages of the
+    /// same input, so they might arrive after a watermark that covers them.
+    pub fn set_watermarks(&mut self, enabled: bool) {
+        self.emit_watermarks = enabled;
+        if !enabled {
+            self.pending_watermark = None;
+        }
+    }
+
+    /// Disables the automatic acknowledgement of reliably sent inputs.
+    ///
+    /// By default, inputs sent through
+    /// [`send_output_reliable`](crate::DoraNode::send_output_reliable) are
+    /// acknowledged as soon as they are returned from the event stream. With
+    /// manual acknowledgements, they must be acknowledged explicitly through
+    /// [`ack`](Self::ack) or [`ack_all`](Self::ack_all) once they were
+    /// processed, so that the sender only proceeds after the processing
+    /// finished. Inputs that are not acknowledged within the timeout of the
+    /// sender are sent again every [`RELIABLE_RETRY_INTERVAL`](crate::RELIABLE_RETRY_INTERVAL)
+    /// and the sender fails once its timeout expires.
+    ///
+    /// Disabling manual acknowledgements acknowledges all pending inputs.
+    pub fn set_manual_acks(&mut self, enabled: bool) -> eyre::Result<()> {
+        self.manual_acks = enabled;
+        if !enabled {
+            self.ack_all()?;
+        }
+        Ok(())
+    }
+
+    /// Acknowledges a delivered input, see [`set_manual_acks`](Self::set_manual_acks).
+    ///
+    /// Does nothing if the input was not sent reliably or was already
+    /// acknowledged. Copies of the input that were delivered again because of
+    /// retries are acknowledged as well.
+    pub fn ack(&mut self, input: &DataId, metadata: &Metadata) -> eyre::Result<()> {
+        let Some(Parameter::Integer(message_id)) = metadata.parameters.get(RELIABLE) else {
+            return Ok(());
+        };
+        let ack = (input.clone(), *message_id as u64);
+        let pending = self.unacked.len();
+        self.unacked.retain(|unacked| unacked != &ack);
+        if self.unacked.len() == pending {
+            return Ok(());
+        }
+        self.send_acks(vec![ack])
+    }
+
+    /// Acknowledges all delivered inputs that were not acknowledged yet, in a
+    /// single request to the daemon.
+    ///
+    /// Acknowledging a batch of processed inputs at once is cheaper than
+    /// calling [`ack`](Self::ack) for each of them.
+    pub fn ack_all(&mut self) -> eyre::Result<()> {
+        if self.unacked.is_empty() {
+            return Ok(());
+        }
+        let acks = std::mem::take(&mut self.unacked);
+        self.send_acks(acks)
+    }
+
+    /// Returns the number of delivered inputs that were not acknowledged yet.
+    pub fn unacked_count(&self) -> usize {
+        self.unacked.len()
+    }
+
+    /// Forgets all delivered inputs that were not acknowledged yet, without
+    /// acknowledging them.
+    ///
+    /// The senders of the inputs send them again until their timeout expires.
+    /// Returns the number of forgotten inputs.
+    pub fn clear_unacked(&mut self) -> usize {
+        std::mem::take(&mut self.unacked).len()
+    }
+
+    /// Returns the current watermark, see [`set_watermarks`](Self::set_watermarks).
+    ///
+    /// The watermark is also tracked if watermark events are disabled.
+    pub fn watermark(&self) -> Option<uhlc::Timestamp> {
+        self.watermarks.watermark()
+    }
+
+    /// Returns the total payload size of the input events that were received,
+    /// but not yet returned by `recv`.
+    pub fn buffered_bytes(&mut self) -> usize {
+        // move already received events into the scheduler to account for them
+        self.move_received_events();
+        self.scheduler.lock().buffered_bytes()
+    }
+
+    /// Returns the number of input events that were received, but not yet
+    /// returned by `recv`, per input.
+    pub fn queue_depths(&mut self) -> BTreeMap<DataId, usize> {
+        self.move_received_events();
+        self.scheduler.lock().queue_depths()
+    }
+
+    /// Drops all input events that were received, but not yet returned by
+    /// `recv`, e.g. to recover from a backlog. Returns the number of dropped
+    /// events.
+    ///
+    /// Other events, e.g. `InputClosed` or `Stop`, are still returned.
+    pub fn clear_queues(&mut self) -> usize {
+        self.move_received_events();
+        self.scheduler.lock().clear_inputs()
+    }
+
+    /// Moves the events that were already received into the scheduler.
+    ///
+    /// Does nothing if the events are buffered in the background.
+    fn move_received_events(&mut self) {
+        if self.buffer.is_some() {
+            return;
+        }
+        let mut scheduler = self.scheduler.lock();
+        while let Some(Some(event)) = self.receiver.next().now_or_never() {
+            scheduler.add_event(event);
+        }
+    }
+
     /// wait for the next event on the events stream.
     pub fn recv(&mut self) -> Option<Event> {
         futures::executor::block_on(self.recv_async())
@@ -167,33 +453,209 @@ impl EventStream {
     }
 
     pub async fn recv_async(&mut self) -> Option<Event> {
-        loop {
-            if self.scheduler.is_empty() {
-                if let Some(event) = self.receiver.next().await {
-                    self.scheduler.add_event(event);
-                } else {
-                    break;
+        if let Some(event) = self.take_pending_watermark() {
+            return Some(event);
+        }
+        let event = match &mut self.buffer {
+            Some(buffer) => loop {
+                let next = self.scheduler.lock().next();
+                if next.is_some() {
+                    break next;
+                }
+                if buffer.notifications.next().await.is_none() {
+                    // the buffer thread exited, so no more events will be queued
+                    break self.scheduler.lock().next();
+                }
+            },
+            None => {
+                loop {
+                    let is_empty = self.scheduler.lock().is_empty();
+                    if is_empty {
+                        if let Some(event) = self.receiver.next().await {
+                            self.scheduler.lock().add_event(event);
+                        } else {
+                            break;
+                        }
+                    } else {
+                        match select(Delay::new(Duration::from_micros(300)), self.receiver.next())
+                            .await
+                        {
+                            Either::Left((_elapsed, _)) => break,
+                            Either::Right((Some(event), _)) => {
+                                self.scheduler.lock().add_event(event)
+                            }
+                            Either::Right((None, _)) => break,
+                        };
+                    }
                 }
-            } else {
-                match select(Delay::new(Duration::from_micros(300)), self.receiver.next()).await {
-                    Either::Left((_elapsed, _)) => break,
-                    Either::Right((Some(event), _)) => self.scheduler.add_event(event),
-                    Either::Right((None, _)) => break,
-                };
+                self.scheduler.lock().next()
             }
+        };
+        self.report_queue_depths();
+        event.map(|item| self.deliver(item))
+    }
+
+    /// Reports the number of buffered events per input to the daemon, which
+    /// aggregates them for [`DoraNode::pipeline_depths`](crate::DoraNode::pipeline_depths).
+    ///
+    /// Reports are rate-limited, except when all queues become empty.
+    fn report_queue_depths(&mut self) {
+        let due = self.last_queue_depth_report.elapsed() >= QUEUE_DEPTH_REPORT_INTERVAL;
+        if due {
+            // account for the events that were not moved into the scheduler yet
+            self.move_received_events();
+        }
+        let depths = self.scheduler.lock().queue_depths();
+        let drained = depths.values().all(|depth| *depth == 0);
+        if depths == self.reported_queue_depths || !(due || drained) {
+            return;
+        }
+
+        self.last_queue_depth_report = Instant::now();
+        let request = Timestamped {
+            inner: DaemonRequest::ReportQueueDepths(depths.clone()),
+            timestamp: self.clock.new_timestamp(),
+        };
+        match self.close_channel.request(&request) {
+            Ok(DaemonReply::Empty) => {}
+            Ok(other) => tracing::warn!("unexpected ReportQueueDepths reply: {other:?}"),
+            Err(err) => tracing::warn!("failed to report queue depths: {err:?}"),
         }
-        let event = self.scheduler.next();
-        event.map(Self::convert_event_item)
+        self.reported_queue_depths = depths;
     }
 
     pub async fn recv_async_timeout(&mut self, dur: Duration) -> Option<Event> {
-        let next_event = match select(Delay::new(dur), self.receiver.next()).await {
-            Either::Left((_elapsed, _)) => {
-                Some(EventItem::TimeoutError(eyre!("Receiver timed out")))
+        if let Some(event) = self.take_pending_watermark() {
+            return Some(event);
+        }
+        let next_event = match &mut self.buffer {
+            Some(buffer) => {
+                let mut timeout = Delay::new(dur);
+                loop {
+                    let next = self.scheduler.lock().next();
+                    if next.is_some() {
+                        break next;
+                    }
+                    match select(&mut timeout, buffer.notifications.next()).await {
+                        Either::Left((_elapsed, _)) => {
+                            break Some(EventItem::TimeoutError(eyre!("Receiver timed out")))
+                        }
+                        Either::Right((Some(()), _)) => {}
+                        Either::Right((None, _)) => break self.scheduler.lock().next(),
+                    }
+                }
+            }
+            None => {
+                // events that are held back by the scheduler, e.g. an `InputClosed`
+                // behind the queued inputs, must not be overtaken by the end of the stream
+                let queued = self.scheduler.lock().next();
+                match queued {
+                    Some(event) => Some(event),
+                    None => match select(Delay::new(dur), self.receiver.next()).await {
+                        Either::Left((_elapsed, _)) => {
+                            Some(EventItem::TimeoutError(eyre!("Receiver timed out")))
+                        }
+                        Either::Right((event, _)) => event,
+                    },
+                }
+            }
+        };
+        next_event.map(|item| self.deliver(item))
+    }
+
+    /// Returns the bytes per second that were received on each input, averaged
+    /// over the last [`BANDWIDTH_WINDOW`](crate::BANDWIDTH_WINDOW).
+    ///
+    /// Only the payload of the inputs that were delivered by this stream is
+    /// counted, so inputs that were dropped because of a full queue are excluded.
+    pub fn input_bandwidth(&mut self) -> BTreeMap<DataId, f64> {
+        self.input_bandwidth.rates()
+    }
+
+    /// Returns the cumulative message counters of each input that was delivered
+    /// by this stream, see [`openmetrics_text`](crate::openmetrics_text).
+    pub fn input_counters(&self) -> &BTreeMap<DataId, MessageCounters> {
+        &self.input_counters
+    }
+
+    /// Pins the background thread that receives the events from the daemon to
+    /// the given CPU cores.
+    ///
+    /// See [`DoraNode::set_affinity`](crate::DoraNode::set_affinity) for details.
+    pub fn set_affinity(&self, cores: &[usize]) -> eyre::Result<()> {
+        self.thread_handle.set_affinity(cores)?;
+        if let Some(buffer) = &self.buffer {
+            buffer.thread_id.set_affinity(cores)?;
+        }
+        Ok(())
+    }
+
+    /// Converts the given item and sends a copy of it to all tees.
+    fn deliver(&mut self, item: EventItem) -> Event {
+        if let EventItem::NodeEvent {
+            event: NodeEvent::Input { id, metadata, data },
+            ..
+        } = &item
+        {
+            let len = match data {
+                None => 0,
+                Some(DataMessage::Vec(v)) => v.len(),
+                Some(DataMessage::SharedMemory { len, .. }) => *len,
+            };
+            self.input_bandwidth.record(id, len);
+            self.input_counters
+                .entry(id.clone())
+                .or_default()
+                .record_input(len, metadata);
+            if let Some(Parameter::Integer(message_id)) = metadata.parameters.get(RELIABLE) {
+                let ack = (id.clone(), *message_id as u64);
+                if self.manual_acks {
+                    self.unacked.push(ack);
+                } else if let Err(err) = self.send_acks(vec![ack]) {
+                    tracing::warn!("{err:?}");
+                }
             }
-            Either::Right((event, _)) => event,
+        }
+        // timeouts are local to this stream
+        let forward = !matches!(item, EventItem::TimeoutError(_));
+        let event = Self::convert_event_item(item);
+        if forward {
+            self.forward_to_tees(&event);
+        }
+        if let Some(time) = self.watermarks.observe(&event) {
+            if self.emit_watermarks {
+                self.pending_watermark = Some(time);
+            }
+        }
+        event
+    }
+
+    /// Acknowledges reliably sent inputs to the daemon, see
+    /// [`DoraNode::send_output_reliable`](crate::DoraNode::send_output_reliable).
+    fn send_acks(&mut self, acks: Vec<(DataId, u64)>) -> eyre::Result<()> {
+        let request = Timestamped {
+            inner: DaemonRequest::AckInputs(acks),
+            timestamp: self.clock.new_timestamp(),
         };
-        next_event.map(Self::convert_event_item)
+        match self.close_channel.request(&request) {
+            Ok(DaemonReply::Empty) => Ok(()),
+            Ok(other) => eyre::bail!("unexpected AckInputs reply: {other:?}"),
+            Err(err) => Err(err.wrap_err("failed to acknowledge inputs")),
+        }
+    }
+
+    fn take_pending_watermark(&mut self) -> Option<Event> {
+        let event = Event::Watermark {
+            time: self.pending_watermark.take()?,
+        };
+        self.forward_to_tees(&event);
+        Some(event)
+    }
+
+    fn forward_to_tees(&mut self, event: &Event) {
+        if !self.tees.is_empty() {
+            self.tees.retain(|tee| tee.send(event.clone()).is_ok());
+        }
     }
 
     fn convert_event_item(item: EventItem) -> Event {
@@ -231,12 +693,15 @@ impl EventStream {
                             metadata,
                             data: data.into(),
                         },
-                        Err(err) => Event::Error(format!("{err:?}")),
+                        Err(err) => Event::DecodeError {
+                            id,
+                            error: format!("{err:?}"),
+                        },
                     }
                 }
-                NodeEvent::AllInputsClosed => {
+                NodeEvent::AllInputsClosed | NodeEvent::Lifecycle(_) => {
                     let err = eyre!(
-                        "received `AllInputsClosed` event, which should be handled by background task"
+                        "received `{event:?}` event, which should be handled by background task"
                     );
                     tracing::error!("{err:?}");
                     Event::Error(err.wrap_err("internal error").to_string())
@@ -260,9 +725,33 @@ impl Stream for EventStream {
         mut self: std::pin::Pin<&mut Self>,
         cx: &mut std::task::Context<'_>,
     ) -> std::task::Poll<Option<Self::Item>> {
-        self.receiver
-            .poll_next_unpin(cx)
-            .map(|item| item.map(Self::convert_event_item))
+        let this = &mut *self;
+        if let Some(event) = this.take_pending_watermark() {
+            return std::task::Poll::Ready(Some(event));
+        }
+        let item = match &mut this.buffer {
+            Some(buffer) => loop {
+                let next = this.scheduler.lock().next();
+                if next.is_some() {
+                    break std::task::Poll::Ready(next);
+                }
+                match buffer.notifications.poll_next_unpin(cx) {
+                    std::task::Poll::Ready(Some(())) => {}
+                    std::task::Poll::Ready(None) => {
+                        break std::task::Poll::Ready(this.scheduler.lock().next())
+                    }
+                    std::task::Poll::Pending => break std::task::Poll::Pending,
+                }
+            },
+            None => {
+                let queued = this.scheduler.lock().next();
+                match queued {
+                    Some(event) => std::task::Poll::Ready(Some(event)),
+                    None => this.receiver.poll_next_unpin(cx),
+                }
+            }
+        };
+        item.map(|item| item.map(|item| this.deliver(item)))
     }
 }
 
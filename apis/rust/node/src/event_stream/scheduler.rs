@@ -1,9 +1,36 @@
-use std::collections::{HashMap, VecDeque};
+use std::collections::{BTreeMap, HashMap, VecDeque};
 
-use dora_message::{daemon_to_node::NodeEvent, id::DataId};
+use dora_message::{
+    daemon_to_node::{DataMessage, NodeEvent},
+    id::DataId,
+    metadata::{Parameter, Priority, CONFLATED, DROPPED_COUNT},
+};
 
 use super::thread::EventItem;
 pub const NON_INPUT_EVENT: &str = "dora/non_input_event";
+/// Queue size of inputs that don't specify a `queue_size`.
+pub const DEFAULT_QUEUE_SIZE: usize = 1;
+
+/// Order in which the queued events of different inputs are returned.
+///
+/// Events with a higher [`Priority`] are always returned first. Non-input
+/// events, e.g. `Stop`, are returned before all inputs.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub enum InputSchedule {
+    /// Alternates between the inputs that have queued events, starting with the
+    /// input that was not returned for the longest time.
+    #[default]
+    RoundRobin,
+    /// Returns the queued events of each input at a rate proportional to its
+    /// weight, as long as the input has queued events.
+    ///
+    /// An input with weight `2.0` is returned twice as often as an input with
+    /// weight `1.0` while both are backed up, so that a flood of events on one
+    /// input cannot starve another input below its share. Inputs without an
+    /// entry have weight `1.0`. Inputs that were idle don't accumulate credit,
+    /// so they don't delay the other inputs when they become active again.
+    WeightedFair { weights: BTreeMap<DataId, f64> },
+}
 
 // This scheduler will make sure that there is fairness between
 // inputs.
@@ -20,6 +47,16 @@ pub const NON_INPUT_EVENT: &str = "dora/non_input_event";
 pub struct Scheduler {
     last_used: VecDeque<DataId>, // Tracks the last-used event ID
     event_queues: HashMap<DataId, (usize, VecDeque<EventItem>)>, // Tracks events per ID
+    dropped: HashMap<DataId, u64>, // Number of events dropped per ID since the last delivered one
+    // `InputClosed` events that wait for the queued events of their input, kept
+    // out of the queues so that they don't count towards the queue limits
+    held_back: HashMap<DataId, EventItem>,
+    buffered_bytes: usize, // Total payload size of all queued input events
+    max_buffered_bytes: Option<usize>,
+    schedule: InputSchedule,
+    // Virtual time at which each input is due next, for weighted fair scheduling
+    passes: HashMap<DataId, f64>,
+    virtual_time: f64, // Pass of the input that was returned last
 }
 
 impl Scheduler {
@@ -34,10 +71,90 @@ impl Scheduler {
         Self {
             last_used: topic,
             event_queues,
+            dropped: HashMap::new(),
+            held_back: HashMap::new(),
+            buffered_bytes: 0,
+            max_buffered_bytes: None,
+            schedule: InputSchedule::default(),
+            passes: HashMap::new(),
+            virtual_time: 0.0,
+        }
+    }
+
+    /// Sets the order in which the queued events of different inputs are returned.
+    pub fn set_schedule(&mut self, schedule: InputSchedule) {
+        self.schedule = schedule;
+        self.passes.clear();
+        self.virtual_time = 0.0;
+    }
+
+    /// Limits the total payload size of the queued input events.
+    ///
+    /// When the limit is exceeded, the oldest events of the input that buffers the
+    /// most bytes are dropped, like for the per-input `queue_size` limit. The most
+    /// recent event of each input is always kept, even if it exceeds the limit on
+    /// its own.
+    pub fn set_max_buffered_bytes(&mut self, max_buffered_bytes: Option<usize>) {
+        self.max_buffered_bytes = max_buffered_bytes;
+        self.enforce_byte_limit();
+    }
+
+    pub fn buffered_bytes(&self) -> usize {
+        self.buffered_bytes
+    }
+
+    /// Returns the number of queued events per input.
+    pub fn queue_depths(&self) -> BTreeMap<DataId, usize> {
+        self.event_queues
+            .iter()
+            .filter(|(id, _)| id.as_str() != NON_INPUT_EVENT)
+            .map(|(id, (_size, queue))| (id.clone(), queue.len()))
+            .collect()
+    }
+
+    /// Drops all queued input events and returns their number.
+    ///
+    /// Other events, e.g. `InputClosed` or `Stop`, are kept.
+    pub fn clear_inputs(&mut self) -> usize {
+        let mut cleared = 0;
+        for (_size, queue) in self.event_queues.values_mut() {
+            queue.retain(|event| {
+                let is_input = matches!(
+                    event,
+                    EventItem::NodeEvent {
+                        event: NodeEvent::Input { .. },
+                        ..
+                    }
+                );
+                cleared += usize::from(is_input);
+                !is_input
+            });
+        }
+        self.dropped.clear();
+        self.buffered_bytes = 0;
+        let held_back: Vec<_> = self.held_back.keys().cloned().collect();
+        for id in held_back {
+            self.release_held_back(&id);
         }
+        cleared
     }
 
     pub fn add_event(&mut self, event: EventItem) {
+        // Deliver `InputClosed` after the events that are still queued for the
+        // input, instead of overtaking them as a non-input event.
+        if let EventItem::NodeEvent {
+            event: NodeEvent::InputClosed { id },
+            ..
+        } = &event
+        {
+            if let Some((_size, queue)) = self.event_queues.get_mut(id) {
+                if !queue.is_empty() {
+                    self.held_back.insert(id.clone(), event);
+                    return;
+                }
+            }
+        }
+
         let event_id = match &event {
             EventItem::NodeEvent {
                 event:
@@ -51,15 +168,60 @@ impl Scheduler {
             _ => &DataId::from(NON_INPUT_EVENT.to_string()),
         };
 
-        // Enforce queue size limit
-        if let Some((size, queue)) = self.event_queues.get_mut(event_id) {
-            // Remove the oldest event if at limit
-            if &queue.len() >= size {
-                queue.pop_front();
+        if !self.event_queues.contains_key(event_id) {
+            // inputs that were subscribed at runtime, e.g. through a pattern,
+            // are not part of the node config and use the default queue size
+            self.last_used.push_front(event_id.clone());
+        }
+        let (size, queue) = self
+            .event_queues
+            .entry(event_id.clone())
+            .or_insert_with(|| (DEFAULT_QUEUE_SIZE, VecDeque::new()));
+        if queue.is_empty() {
+            // idle inputs don't accumulate credit
+            if let Some(pass) = self.passes.get_mut(event_id) {
+                *pass = pass.max(self.virtual_time);
+            }
+        }
+
+        // Conflated inputs replace all queued events of the input
+        if is_conflated(&event) {
+            for replaced in queue.drain(..) {
+                self.buffered_bytes -= payload_len(&replaced);
+                *self.dropped.entry(event_id.clone()).or_default() += 1;
+            }
+        }
+        // Enforce queue size limit by removing the oldest event
+        if queue.len() >= *size {
+            if let Some(dropped) = queue.pop_front() {
+                self.buffered_bytes -= payload_len(&dropped);
+                *self.dropped.entry(event_id.clone()).or_default() += 1;
+            }
+        }
+        self.buffered_bytes += payload_len(&event);
+        queue.push_back(event);
+
+        self.enforce_byte_limit();
+    }
+
+    fn enforce_byte_limit(&mut self) {
+        let Some(max) = self.max_buffered_bytes else {
+            return;
+        };
+        while self.buffered_bytes > max {
+            // drop from the input that buffers the most bytes, keeping its latest event
+            let largest = self
+                .event_queues
+                .iter_mut()
+                .filter(|(id, (_size, queue))| id.as_str() != NON_INPUT_EVENT && queue.len() > 1)
+                .max_by_key(|(_id, (_size, queue))| queue.iter().map(payload_len).sum::<usize>());
+            let Some((id, (_size, queue))) = largest else {
+                break;
+            };
+            if let Some(dropped) = queue.pop_front() {
+                self.buffered_bytes -= payload_len(&dropped);
+                *self.dropped.entry(id.clone()).or_default() += 1;
             }
-            queue.push_back(event);
-        } else {
-            unimplemented!("Received an event that was not in the definition event id description.")
         }
     }
 
@@ -74,19 +236,65 @@ impl Scheduler {
             }
         }
 
-        // Process the ID with the oldest timestamp using BTreeMap Ordering
-        for (index, id) in self.last_used.clone().iter().enumerate() {
-            if let Some((_size, queue)) = self.event_queues.get_mut(id) {
-                if let Some(event) = queue.pop_front() {
-                    // Put last used at last
-                    self.last_used.remove(index);
-                    self.last_used.push_back(id.clone());
-                    return Some(event);
+        // Process the ID with the oldest timestamp using BTreeMap Ordering,
+        // unless another input has an event with a higher priority or, for
+        // weighted fair scheduling, an earlier pass
+        let mut selected: Option<(usize, Priority, f64)> = None;
+        for (index, id) in self.last_used.iter().enumerate() {
+            if let Some((_size, queue)) = self.event_queues.get(id) {
+                if let Some(event) = queue.front() {
+                    let priority = event_priority(event);
+                    let pass = self.pass(id);
+                    if selected.map_or(true, |(_, selected, selected_pass)| {
+                        priority > selected || (priority == selected && pass < selected_pass)
+                    }) {
+                        selected = Some((index, priority, pass));
+                    }
                 }
             }
         }
 
-        None
+        let (index, _, pass) = selected?;
+        // Put last used at last
+        let id = self.last_used.remove(index)?;
+        self.last_used.push_back(id.clone());
+        if let InputSchedule::WeightedFair { weights } = &self.schedule {
+            let weight = weights.get(&id).copied().unwrap_or(1.0);
+            self.virtual_time = pass;
+            self.passes.insert(id.clone(), pass + 1.0 / weight);
+        }
+        let (_size, queue) = self.event_queues.get_mut(&id)?;
+        let mut event = queue.pop_front()?;
+        let drained = queue.is_empty();
+        self.buffered_bytes -= payload_len(&event);
+        if drained {
+            self.release_held_back(&id);
+        }
+        if let Some(dropped) = self.dropped.remove(&id) {
+            attach_dropped_count(&mut event, dropped);
+        }
+        Some(event)
+    }
+
+    /// Queues the held-back `InputClosed` event of the input as non-input event,
+    /// so that it is returned by the next call to [`next`](Self::next).
+    fn release_held_back(&mut self, id: &DataId) {
+        if let Some(event) = self.held_back.remove(id) {
+            let (_size, queue) = self
+                .event_queues
+                .entry(DataId::from(NON_INPUT_EVENT.to_string()))
+                .or_insert_with(|| (DEFAULT_QUEUE_SIZE, VecDeque::new()));
+            queue.push_back(event);
+        }
+    }
+
+    fn pass(&self, id: &DataId) -> f64 {
+        match self.schedule {
+            InputSchedule::RoundRobin => 0.0,
+            InputSchedule::WeightedFair { .. } => {
+                self.passes.get(id).copied().unwrap_or(self.virtual_time)
+            }
+        }
     }
 
     pub fn is_empty(&self) -> bool {
@@ -95,3 +303,388 @@ impl Scheduler {
             .all(|(_id, (_size, queue))| queue.is_empty())
     }
 }
+
+fn event_priority(event: &EventItem) -> Priority {
+    match event {
+        EventItem::NodeEvent {
+            event: NodeEvent::Input { metadata, .. },
+            ..
+        } => metadata.priority(),
+        _ => Priority::Normal,
+    }
+}
+
+fn is_conflated(event: &EventItem) -> bool {
+    match event {
+        EventItem::NodeEvent {
+            event: NodeEvent::Input { metadata, .. },
+            ..
+        } => metadata.parameters.contains_key(CONFLATED),
+        _ => false,
+    }
+}
+
+fn payload_len(event: &EventItem) -> usize {
+    match event {
+        EventItem::NodeEvent {
+            event: NodeEvent::Input {
+                data: Some(data), ..
+            },
+            ..
+        } => match data {
+            DataMessage::Vec(data) => data.len(),
+            DataMessage::SharedMemory { len, .. } => *len,
+        },
+        _ => 0,
+    }
+}
+
+fn attach_dropped_count(event: &mut EventItem, dropped: u64) {
+    if let EventItem::NodeEvent {
+        event: NodeEvent::Input { metadata, .. },
+        ..
+    } = event
+    {
+        // the daemon reports the messages that it dropped itself, e.g. for conflated inputs
+        let dropped = metadata.dropped_count().saturating_add(dropped);
+        metadata.parameters.insert(
+            DROPPED_COUNT.to_owned(),
+            Parameter::Integer(dropped.try_into().unwrap_or(i64::MAX)),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use aligned_vec::{AVec, ConstAlign};
+    use dora_core::{metadata::ArrowTypeInfoExt, uhlc::HLC};
+    use dora_message::metadata::{ArrowTypeInfo, Metadata, PRIORITY};
+
+    use super::*;
+
+    fn input(id: &str, len: usize) -> EventItem {
+        let data: AVec<u8, ConstAlign<128>> = AVec::__from_elem(128, 0, len);
+        EventItem::NodeEvent {
+            event: NodeEvent::Input {
+                id: DataId::from(id.to_owned()),
+                metadata: Metadata::new(
+                    HLC::default().new_timestamp(),
+                    ArrowTypeInfo::byte_array(len),
+                ),
+                data: Some(DataMessage::Vec(data)),
+            },
+            ack_channel: flume::bounded(0).0,
+        }
+    }
+
+    #[test]
+    fn byte_limit_drops_oldest_events_of_largest_input() {
+        let queues = ["image", "imu", NON_INPUT_EVENT]
+            .into_iter()
+            .map(|id| (DataId::from(id.to_owned()), (10, VecDeque::new())))
+            .collect();
+        let mut scheduler = Scheduler::new(queues);
+        scheduler.set_max_buffered_bytes(Some(250));
+
+        scheduler.add_event(input("image", 100));
+        scheduler.add_event(input("imu", 10));
+        scheduler.add_event(input("image", 100));
+        assert_eq!(scheduler.buffered_bytes(), 210);
+
+        // exceeds the limit -> the oldest image is dropped
+        scheduler.add_event(input("image", 100));
+        assert_eq!(scheduler.buffered_bytes(), 210);
+        assert_eq!(
+            scheduler.dropped.get(&DataId::from("image".to_owned())),
+            Some(&1)
+        );
+
+        // the latest event of each input is kept, even if it's too large on its own
+        scheduler.set_max_buffered_bytes(Some(50));
+        assert_eq!(scheduler.buffered_bytes(), 110);
+
+        while scheduler.next().is_some() {}
+        assert_eq!(scheduler.buffered_bytes(), 0);
+    }
+
+    #[test]
+    fn conflated_inputs_replace_queued_events() {
+        let queues = ["pose", NON_INPUT_EVENT]
+            .into_iter()
+            .map(|id| (DataId::from(id.to_owned()), (10, VecDeque::new())))
+            .collect();
+        let mut scheduler = Scheduler::new(queues);
+
+        scheduler.add_event(input("pose", 10));
+        scheduler.add_event(input("pose", 10));
+        let mut conflated = input("pose", 20);
+        if let EventItem::NodeEvent {
+            event: NodeEvent::Input { metadata, .. },
+            ..
+        } = &mut conflated
+        {
+            metadata
+                .parameters
+                .insert(CONFLATED.to_owned(), Parameter::Bool(true));
+        }
+        scheduler.add_event(conflated);
+        assert_eq!(scheduler.buffered_bytes(), 20);
+
+        let Some(EventItem::NodeEvent {
+            event: NodeEvent::Input { metadata, .. },
+            ..
+        }) = scheduler.next()
+        else {
+            panic!("expected input event");
+        };
+        assert_eq!(metadata.dropped_count(), 2);
+        assert!(scheduler.next().is_none());
+    }
+
+    #[test]
+    fn clear_inputs_keeps_other_events() {
+        let queues = ["image", NON_INPUT_EVENT]
+            .into_iter()
+            .map(|id| (DataId::from(id.to_owned()), (10, VecDeque::new())))
+            .collect();
+        let mut scheduler = Scheduler::new(queues);
+
+        scheduler.add_event(input("image", 100));
+        scheduler.add_event(input("image", 100));
+        // queued behind the inputs of the closed input
+        scheduler.add_event(EventItem::NodeEvent {
+            event: NodeEvent::InputClosed {
+                id: DataId::from("image".to_owned()),
+            },
+            ack_channel: flume::bounded(0).0,
+        });
+        assert_eq!(scheduler.clear_inputs(), 2);
+        assert_eq!(scheduler.buffered_bytes(), 0);
+
+        assert!(matches!(
+            scheduler.next(),
+            Some(EventItem::NodeEvent {
+                event: NodeEvent::InputClosed { .. },
+                ..
+            })
+        ));
+        assert!(scheduler.next().is_none());
+    }
+
+    #[test]
+    fn high_priority_events_are_delivered_first() {
+        let queues = ["image", "command", NON_INPUT_EVENT]
+            .into_iter()
+            .map(|id| (DataId::from(id.to_owned()), (10, VecDeque::new())))
+            .collect();
+        let mut scheduler = Scheduler::new(queues);
+
+        scheduler.add_event(input("image", 1));
+        scheduler.add_event(input("image", 1));
+        let mut command = input("command", 1);
+        if let EventItem::NodeEvent {
+            event: NodeEvent::Input { metadata, .. },
+            ..
+        } = &mut command
+        {
+            metadata
+                .parameters
+                .insert(PRIORITY.to_owned(), Priority::High.into());
+        }
+        scheduler.add_event(command);
+
+        let ids: Vec<_> = std::iter::from_fn(|| scheduler.next())
+            .map(|event| match event {
+                EventItem::NodeEvent {
+                    event: NodeEvent::Input { id, .. },
+                    ..
+                } => id.to_string(),
+                _ => unreachable!(),
+            })
+            .collect();
+        assert_eq!(ids, ["command", "image", "image"]);
+    }
+
+    #[test]
+    fn input_closed_is_delivered_after_queued_inputs() {
+        let queues = ["image", NON_INPUT_EVENT]
+            .into_iter()
+            .map(|id| (DataId::from(id.to_owned()), (10, VecDeque::new())))
+            .collect();
+        let mut scheduler = Scheduler::new(queues);
+
+        scheduler.add_event(input("image", 1));
+        scheduler.add_event(input("image", 1));
+        scheduler.add_event(EventItem::NodeEvent {
+            event: NodeEvent::InputClosed {
+                id: DataId::from("image".to_owned()),
+            },
+            ack_channel: flume::bounded(0).0,
+        });
+
+        let events: Vec<_> = std::iter::from_fn(|| scheduler.next())
+            .map(|event| match event {
+                EventItem::NodeEvent {
+                    event: NodeEvent::Input { .. },
+                    ..
+                } => "input",
+                EventItem::NodeEvent {
+                    event: NodeEvent::InputClosed { .. },
+                    ..
+                } => "closed",
+                _ => unreachable!(),
+            })
+            .collect();
+        assert_eq!(events, ["input", "input", "closed"]);
+    }
+
+    #[test]
+    fn held_back_input_closed_is_not_counted_as_queued_input() {
+        let queues = ["image", "imu", NON_INPUT_EVENT]
+            .into_iter()
+            .map(|id| (DataId::from(id.to_owned()), (10, VecDeque::new())))
+            .collect();
+        let mut scheduler = Scheduler::new(queues);
+        scheduler.set_max_buffered_bytes(Some(250));
+
+        scheduler.add_event(input("image", 100));
+        scheduler.add_event(input("image", 100));
+        scheduler.add_event(EventItem::NodeEvent {
+            event: NodeEvent::InputClosed {
+                id: DataId::from("image".to_owned()),
+            },
+            ack_channel: flume::bounded(0).0,
+        });
+        assert_eq!(
+            scheduler.queue_depths()[&DataId::from("image".to_owned())],
+            2
+        );
+
+        // exceeds the limit -> only the oldest image is dropped, the latest image
+        // is kept in front of the closed event
+        scheduler.add_event(input("imu", 100));
+        assert_eq!(
+            scheduler.queue_depths()[&DataId::from("image".to_owned())],
+            1
+        );
+
+        let events: Vec<_> = std::iter::from_fn(|| scheduler.next())
+            .map(|event| match event {
+                EventItem::NodeEvent {
+                    event: NodeEvent::Input { id, .. },
+                    ..
+                } => id.to_string(),
+                EventItem::NodeEvent {
+                    event: NodeEvent::InputClosed { id },
+                    ..
+                } => format!("{id} closed"),
+                _ => unreachable!(),
+            })
+            .collect();
+        let image_events: Vec<_> = events.iter().filter(|event| *event != "imu").collect();
+        assert_eq!(image_events, ["image", "image closed"]);
+        assert_eq!(events.len(), 3);
+    }
+
+    #[test]
+    fn queue_depths_count_input_events() {
+        let queues = ["image", "imu", NON_INPUT_EVENT]
+            .into_iter()
+            .map(|id| (DataId::from(id.to_owned()), (10, VecDeque::new())))
+            .collect();
+        let mut scheduler = Scheduler::new(queues);
+
+        scheduler.add_event(input("image", 1));
+        scheduler.add_event(input("image", 1));
+        scheduler.add_event(EventItem::NodeEvent {
+            event: NodeEvent::Stop,
+            ack_channel: flume::bounded(0).0,
+        });
+        let depths = scheduler.queue_depths();
+        assert_eq!(depths.len(), 2);
+        assert_eq!(depths[&DataId::from("image".to_owned())], 2);
+        assert_eq!(depths[&DataId::from("imu".to_owned())], 0);
+
+        scheduler.next(); // the stop event has priority
+        scheduler.next();
+        assert_eq!(
+            scheduler.queue_depths()[&DataId::from("image".to_owned())],
+            1
+        );
+    }
+
+    #[test]
+    fn weighted_fair_schedule_serves_inputs_by_weight() {
+        let queues = ["camera", "estop", NON_INPUT_EVENT]
+            .into_iter()
+            .map(|id| (DataId::from(id.to_owned()), (100, VecDeque::new())))
+            .collect();
+        let mut scheduler = Scheduler::new(queues);
+        scheduler.set_schedule(InputSchedule::WeightedFair {
+            weights: [(DataId::from("estop".to_owned()), 3.0)].into(),
+        });
+
+        for _ in 0..20 {
+            scheduler.add_event(input("camera", 1));
+        }
+        for _ in 0..6 {
+            scheduler.add_event(input("estop", 1));
+        }
+
+        let ids: Vec<_> = std::iter::from_fn(|| scheduler.next())
+            .take(8)
+            .map(|event| match event {
+                EventItem::NodeEvent {
+                    event: NodeEvent::Input { id, .. },
+                    ..
+                } => id.to_string(),
+                _ => unreachable!(),
+            })
+            .collect();
+        let estop = ids.iter().filter(|id| *id == "estop").count();
+        assert_eq!(estop, 6, "{ids:?}");
+
+        // the idle input doesn't accumulate credit while only `camera` is served
+        for _ in 0..10 {
+            scheduler.next();
+        }
+        for _ in 0..4 {
+            scheduler.add_event(input("estop", 1));
+        }
+        let ids: Vec<_> = std::iter::from_fn(|| scheduler.next())
+            .take(4)
+            .map(|event| match event {
+                EventItem::NodeEvent {
+                    event: NodeEvent::Input { id, .. },
+                    ..
+                } => id.to_string(),
+                _ => unreachable!(),
+            })
+            .collect();
+        assert_eq!(ids.iter().filter(|id| *id == "estop").count(), 3, "{ids:?}");
+    }
+
+    #[test]
+    fn unknown_inputs_use_default_queue_size() {
+        let queues = ["image", NON_INPUT_EVENT]
+            .into_iter()
+            .map(|id| (DataId::from(id.to_owned()), (10, VecDeque::new())))
+            .collect();
+        let mut scheduler = Scheduler::new(queues);
+
+        scheduler.add_event(input("camera_front", 1));
+        scheduler.add_event(input("camera_front", 1));
+        scheduler.add_event(input("image", 1));
+        let depths = scheduler.queue_depths();
+        assert_eq!(depths[&DataId::from("camera_front".to_owned())], 1);
+        assert_eq!(
+            scheduler
+                .dropped
+                .get(&DataId::from("camera_front".to_owned())),
+            Some(&1)
+        );
+
+        let count = std::iter::from_fn(|| scheduler.next()).count();
+        assert_eq!(count, 2);
+    }
+}
@@ -3,7 +3,7 @@ use dora_core::{
     uhlc::{self, Timestamp},
 };
 use dora_message::{
-    daemon_to_node::{DaemonReply, NodeEvent},
+    daemon_to_node::{DaemonReply, LifecycleEvent, NodeEvent},
     node_to_daemon::{DaemonRequest, DropToken, Timestamped},
 };
 use eyre::{eyre, Context};
@@ -13,17 +13,28 @@ use std::{
     time::{Duration, Instant},
 };
 
-use crate::daemon_connection::DaemonChannel;
+use crate::{affinity::NativeThreadId, daemon_connection::DaemonChannel};
 
 pub fn init(
     node_id: NodeId,
     tx: flume::Sender<EventItem>,
+    lifecycle_tx: flume::Sender<LifecycleEvent>,
     channel: DaemonChannel,
     clock: Arc<uhlc::HLC>,
 ) -> eyre::Result<EventStreamThreadHandle> {
     let node_id_cloned = node_id.clone();
-    let join_handle = std::thread::spawn(|| event_stream_loop(node_id_cloned, tx, channel, clock));
-    Ok(EventStreamThreadHandle::new(node_id, join_handle))
+    let thread_id = NativeThreadId::default();
+    let thread_id_cloned = thread_id.clone();
+    let join_handle = std::thread::spawn(move || {
+        thread_id_cloned.record_current();
+        event_stream_loop(node_id_cloned, tx, lifecycle_tx, channel, clock);
+        thread_id_cloned.clear();
+    });
+    Ok(EventStreamThreadHandle::new(
+        node_id,
+        join_handle,
+        thread_id,
+    ))
 }
 
 #[derive(Debug)]
@@ -39,10 +50,15 @@ pub enum EventItem {
 pub struct EventStreamThreadHandle {
     node_id: NodeId,
     handle: flume::Receiver<std::thread::Result<()>>,
+    thread_id: NativeThreadId,
 }
 
 impl EventStreamThreadHandle {
-    fn new(node_id: NodeId, join_handle: std::thread::JoinHandle<()>) -> Self {
+    fn new(
+        node_id: NodeId,
+        join_handle: std::thread::JoinHandle<()>,
+        thread_id: NativeThreadId,
+    ) -> Self {
         let (tx, rx) = flume::bounded(1);
         std::thread::spawn(move || {
             let _ = tx.send(join_handle.join());
@@ -50,8 +66,13 @@ impl EventStreamThreadHandle {
         Self {
             node_id,
             handle: rx,
+            thread_id,
         }
     }
+
+    pub fn set_affinity(&self, cores: &[usize]) -> eyre::Result<()> {
+        self.thread_id.set_affinity(cores)
+    }
 }
 
 impl Drop for EventStreamThreadHandle {
@@ -84,10 +105,11 @@ impl Drop for EventStreamThreadHandle {
     }
 }
 
-#[tracing::instrument(skip(tx, channel, clock))]
+#[tracing::instrument(skip(tx, lifecycle_tx, channel, clock))]
 fn event_stream_loop(
     node_id: NodeId,
     tx: flume::Sender<EventItem>,
+    lifecycle_tx: flume::Sender<LifecycleEvent>,
     mut channel: DaemonChannel,
     clock: Arc<uhlc::HLC>,
 ) {
@@ -140,6 +162,11 @@ fn event_stream_loop(
                     // skip this internal event
                     continue;
                 }
+                NodeEvent::Lifecycle(event) => {
+                    // lifecycle events are delivered separately from inputs
+                    let _ = lifecycle_tx.send(event.clone());
+                    continue;
+                }
                 _ => None,
             };
 
@@ -0,0 +1,120 @@
+use std::collections::BTreeMap;
+
+use dora_core::{config::DataId, uhlc};
+
+use super::Event;
+
+/// Derives the event-time watermark of a node from the timestamps of its inputs.
+///
+/// The watermark is the minimum over all open inputs of the latest timestamp
+/// that was received on the input. Since the messages of an input are sent by
+/// a single node, their timestamps increase, so no event with a timestamp at or
+/// before the watermark will arrive anymore.
+///
+/// Advancement policy:
+///
+/// - There is no watermark until every input received at least one event.
+/// - Closed inputs no longer hold back the watermark. Once all inputs are
+///   closed, the watermark does not advance anymore.
+/// - The watermark never moves backwards.
+#[derive(Debug)]
+pub struct WatermarkTracker {
+    /// Latest timestamp of each open input, `None` if nothing was received yet.
+    latest: BTreeMap<DataId, Option<uhlc::Timestamp>>,
+    watermark: Option<uhlc::Timestamp>,
+}
+
+impl WatermarkTracker {
+    pub fn new(inputs: impl IntoIterator<Item = DataId>) -> Self {
+        Self {
+            latest: inputs.into_iter().map(|id| (id, None)).collect(),
+            watermark: None,
+        }
+    }
+
+    /// The current watermark.
+    pub fn watermark(&self) -> Option<uhlc::Timestamp> {
+        self.watermark
+    }
+
+    /// Updates the watermark with the given delivered event.
+    ///
+    /// Returns the new watermark if it advanced.
+    pub fn observe(&mut self, event: &Event) -> Option<uhlc::Timestamp> {
+        match event {
+            Event::Input { id, metadata, .. } => {
+                let latest = self.latest.get_mut(id)?;
+                *latest = (*latest).max(Some(metadata.timestamp()));
+            }
+            Event::InputClosed { id } => {
+                self.latest.remove(id)?;
+            }
+            _ => return None,
+        }
+
+        let mut min: Option<uhlc::Timestamp> = None;
+        for latest in self.latest.values() {
+            // inputs that did not receive any event yet hold back the watermark
+            let latest = (*latest)?;
+            min = Some(min.map_or(latest, |min| min.min(latest)));
+        }
+        let min = min?;
+        if self.watermark.map_or(true, |watermark| min > watermark) {
+            self.watermark = Some(min);
+            Some(min)
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use dora_core::{metadata::ArrowTypeInfoExt, uhlc::HLC};
+    use dora_message::metadata::{ArrowTypeInfo, Metadata};
+
+    use super::*;
+
+    fn input(id: &str, timestamp: uhlc::Timestamp) -> Event {
+        Event::Input {
+            id: DataId::from(id.to_owned()),
+            metadata: Metadata::new(timestamp, ArrowTypeInfo::empty()),
+            data: arrow::array::new_empty_array(&arrow::datatypes::DataType::Null).into(),
+        }
+    }
+
+    fn closed(id: &str) -> Event {
+        Event::InputClosed {
+            id: DataId::from(id.to_owned()),
+        }
+    }
+
+    #[test]
+    fn watermark_is_minimum_of_latest_input_timestamps() {
+        let clock = HLC::default();
+        let [t1, t2, t3, t4] = [(); 4].map(|_| clock.new_timestamp());
+        let mut tracker = WatermarkTracker::new(["camera", "lidar"].map(|id| id.to_owned().into()));
+
+        // no watermark until every input received an event
+        assert_eq!(tracker.observe(&input("camera", t1)), None);
+        assert_eq!(tracker.observe(&input("camera", t3)), None);
+        assert_eq!(tracker.observe(&input("lidar", t2)), Some(t2));
+        // the watermark does not advance while `lidar` lags behind
+        assert_eq!(tracker.observe(&input("camera", t4)), None);
+        assert_eq!(tracker.watermark(), Some(t2));
+        assert_eq!(tracker.observe(&input("lidar", t4)), Some(t4));
+    }
+
+    #[test]
+    fn closed_inputs_do_not_hold_back_the_watermark() {
+        let clock = HLC::default();
+        let [t1, t2] = [(); 2].map(|_| clock.new_timestamp());
+        let mut tracker = WatermarkTracker::new(["camera", "lidar"].map(|id| id.to_owned().into()));
+
+        assert_eq!(tracker.observe(&input("camera", t1)), None);
+        assert_eq!(tracker.observe(&closed("lidar")), Some(t1));
+        assert_eq!(tracker.observe(&input("camera", t2)), Some(t2));
+        assert_eq!(tracker.observe(&closed("camera")), None);
+        assert_eq!(tracker.watermark(), Some(t2));
+    }
+}
@@ -14,16 +14,37 @@
 //! ```
 //!
 pub use arrow;
+pub use bandwidth::BANDWIDTH_WINDOW;
+pub use coordinator::list_dataflows;
+pub use daemon_connection::{
+    Connection, ConnectionKind, DryRunTransport, SentOutput, Transport, VersionMismatchError,
+};
 pub use dora_arrow_convert::*;
 pub use dora_core::{self, uhlc};
 pub use dora_message::{
-    metadata::{Metadata, MetadataParameters, Parameter},
+    common::{ClockOffset, LogLevel},
+    coordinator_to_cli::{DataflowListEntry, DataflowStatus},
+    daemon_to_node::{EdgeDepth, LifecycleEvent, NodeSummary},
+    metadata::{self, Metadata, MetadataParameters, Parameter},
     DataflowId,
 };
-pub use event_stream::{merged, Event, EventStream, MappedInputData, RawData};
+#[cfg(feature = "tracing")]
+pub use dora_tracing::{log_level, set_log_level};
+pub use event_stream::{
+    merged, DuplicateNodeIdError, Event, EventStream, InputSchedule, MappedInputData, RawData,
+};
 pub use flume::Receiver;
-pub use node::{arrow_utils, DataSample, DoraNode, ZERO_COPY_THRESHOLD};
+pub use metrics::{openmetrics_text, summarize, MessageCounters};
+pub use node::{
+    arrow_utils, AckTimeoutError, DataSample, DoraNode, PARAM_ENV_PREFIX, RELIABLE_RETRY_INTERVAL,
+    SCHEDULED_OUTPUTS_DROP_TIMEOUT, ZERO_COPY_THRESHOLD,
+};
 
+mod affinity;
+mod bandwidth;
+mod coordinator;
 mod daemon_connection;
 mod event_stream;
+mod metrics;
 mod node;
+mod process_title;
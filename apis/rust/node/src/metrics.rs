@@ -0,0 +1,239 @@
+use std::{
+    collections::BTreeMap,
+    fmt::Write,
+    time::{Duration, SystemTime},
+};
+
+use dora_core::config::{DataId, NodeId};
+use dora_message::{daemon_to_node::NodeSummary, metadata::Metadata, DataflowId};
+
+/// Cumulative counters of the messages of an input or output since the node
+/// was started.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MessageCounters {
+    /// Number of messages that were delivered or sent.
+    pub messages: u64,
+    /// Payload bytes of these messages, without the metadata.
+    pub bytes: u64,
+    /// Number of messages that were dropped before delivery because the input
+    /// queue was full. Always `0` for outputs.
+    ///
+    /// Drops are counted when the next message of the input is delivered.
+    pub dropped: u64,
+    /// Sum of the delays between the creation of each message by the sender and
+    /// its delivery by the event stream. Always zero for outputs.
+    pub latency_sum: Duration,
+}
+
+impl MessageCounters {
+    pub(crate) fn record(&mut self, bytes: usize) {
+        self.messages += 1;
+        self.bytes += bytes as u64;
+    }
+
+    pub(crate) fn record_input(&mut self, bytes: usize, metadata: &Metadata) {
+        self.record(bytes);
+        self.dropped += metadata.dropped_count();
+        let sent = metadata.timestamp().get_time().to_system_time();
+        self.latency_sum += SystemTime::now().duration_since(sent).unwrap_or_default();
+    }
+}
+
+/// Formats the counters of a node in the OpenMetrics text format, which can be
+/// scraped by Prometheus.
+///
+/// Every sample is labeled with the `dataflow` and `node` ID, and with the
+/// `input` or `output` ID. Input latencies are reported as summary with a
+/// `_sum` and `_count`, so that the mean latency over a time range can be
+/// computed through `rate(..._sum) / rate(..._count)`.
+pub fn openmetrics_text(
+    dataflow_id: &DataflowId,
+    node_id: &NodeId,
+    inputs: &BTreeMap<DataId, MessageCounters>,
+    outputs: &BTreeMap<DataId, MessageCounters>,
+) -> String {
+    let node_labels = format!(
+        "dataflow=\"{}\",node=\"{}\"",
+        escape_label(&dataflow_id.to_string()),
+        escape_label(node_id.as_ref())
+    );
+    let inputs: Vec<_> = inputs
+        .iter()
+        .map(|(id, c)| (format!("{node_labels},input=\"{}\"", escape_label(id)), c))
+        .collect();
+    let outputs: Vec<_> = outputs
+        .iter()
+        .map(|(id, c)| (format!("{node_labels},output=\"{}\"", escape_label(id)), c))
+        .collect();
+
+    let mut text = String::new();
+    write_counter(
+        &mut text,
+        "dora_node_input_messages",
+        "Number of messages received on the input.",
+        &inputs,
+        |c| c.messages,
+    );
+    write_counter(
+        &mut text,
+        "dora_node_input_bytes",
+        "Payload bytes received on the input.",
+        &inputs,
+        |c| c.bytes,
+    );
+    write_counter(
+        &mut text,
+        "dora_node_input_dropped",
+        "Number of messages of the input that were dropped because the queue was full.",
+        &inputs,
+        |c| c.dropped,
+    );
+    let name = "dora_node_input_latency_seconds";
+    writeln!(text, "# TYPE {name} summary").unwrap();
+    writeln!(
+        text,
+        "# HELP {name} Delay between sending and receiving the messages of the input."
+    )
+    .unwrap();
+    for (labels, c) in &inputs {
+        let sum = c.latency_sum.as_secs_f64();
+        writeln!(text, "{name}_sum{{{labels}}} {sum}").unwrap();
+        writeln!(text, "{name}_count{{{labels}}} {}", c.messages).unwrap();
+    }
+    write_counter(
+        &mut text,
+        "dora_node_output_messages",
+        "Number of messages sent on the output.",
+        &outputs,
+        |c| c.messages,
+    );
+    write_counter(
+        &mut text,
+        "dora_node_output_bytes",
+        "Payload bytes sent on the output.",
+        &outputs,
+        |c| c.bytes,
+    );
+    text.push_str("# EOF\n");
+    text
+}
+
+/// Sums up the counters of all inputs and outputs of a node, e.g. for
+/// [`DoraNode::send_summary`](crate::DoraNode::send_summary).
+pub fn summarize(
+    inputs: &BTreeMap<DataId, MessageCounters>,
+    outputs: &BTreeMap<DataId, MessageCounters>,
+) -> NodeSummary {
+    let mut summary = NodeSummary::default();
+    let mut latency_sum = Duration::ZERO;
+    for counters in inputs.values() {
+        summary.received += counters.messages;
+        summary.received_bytes += counters.bytes;
+        summary.dropped += counters.dropped;
+        latency_sum += counters.latency_sum;
+    }
+    for counters in outputs.values() {
+        summary.sent += counters.messages;
+        summary.sent_bytes += counters.bytes;
+    }
+    if summary.received > 0 {
+        summary.mean_latency = Some(latency_sum.div_f64(summary.received as f64));
+    }
+    summary
+}
+
+fn write_counter(
+    text: &mut String,
+    name: &str,
+    help: &str,
+    samples: &[(String, &MessageCounters)],
+    value: fn(&MessageCounters) -> u64,
+) {
+    writeln!(text, "# TYPE {name} counter").unwrap();
+    writeln!(text, "# HELP {name} {help}").unwrap();
+    for (labels, counters) in samples {
+        writeln!(text, "{name}_total{{{labels}}} {}", value(counters)).unwrap();
+    }
+}
+
+fn escape_label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn samples_are_labeled_per_input_and_output() {
+        let dataflow_id = DataflowId::nil();
+        let node_id = NodeId::from("camera".to_owned());
+        let inputs = BTreeMap::from([(
+            DataId::from("tick".to_owned()),
+            MessageCounters {
+                messages: 4,
+                bytes: 0,
+                dropped: 1,
+                latency_sum: Duration::from_millis(500),
+            },
+        )]);
+        let outputs = BTreeMap::from([(
+            DataId::from("image".to_owned()),
+            MessageCounters {
+                messages: 3,
+                bytes: 3_000_000,
+                ..Default::default()
+            },
+        )]);
+
+        let text = openmetrics_text(&dataflow_id, &node_id, &inputs, &outputs);
+        let labels = format!("dataflow=\"{dataflow_id}\",node=\"camera\"");
+        assert!(text.contains(&format!(
+            "dora_node_input_messages_total{{{labels},input=\"tick\"}} 4\n"
+        )));
+        assert!(text.contains(&format!(
+            "dora_node_input_dropped_total{{{labels},input=\"tick\"}} 1\n"
+        )));
+        assert!(text.contains(&format!(
+            "dora_node_input_latency_seconds_sum{{{labels},input=\"tick\"}} 0.5\n"
+        )));
+        assert!(text.contains(&format!(
+            "dora_node_output_bytes_total{{{labels},output=\"image\"}} 3000000\n"
+        )));
+        assert!(text.ends_with("# EOF\n"));
+    }
+
+    #[test]
+    fn summary_adds_up_all_inputs_and_outputs() {
+        let counters = |messages, latency_ms| MessageCounters {
+            messages,
+            bytes: messages * 10,
+            dropped: 1,
+            latency_sum: Duration::from_millis(latency_ms),
+        };
+        let inputs = BTreeMap::from([
+            (DataId::from("a".to_owned()), counters(3, 300)),
+            (DataId::from("b".to_owned()), counters(1, 500)),
+        ]);
+        let outputs = BTreeMap::from([(DataId::from("out".to_owned()), counters(2, 0))]);
+
+        let summary = summarize(&inputs, &outputs);
+        assert_eq!(summary.received, 4);
+        assert_eq!(summary.received_bytes, 40);
+        assert_eq!(summary.dropped, 2);
+        assert_eq!(summary.sent, 2);
+        assert_eq!(summary.sent_bytes, 20);
+        assert_eq!(summary.mean_latency, Some(Duration::from_millis(200)));
+
+        let empty = summarize(&BTreeMap::new(), &outputs);
+        assert_eq!(empty.mean_latency, None);
+    }
+
+    #[test]
+    fn label_values_are_escaped() {
+        assert_eq!(escape_label("a\"b\\c\nd"), "a\\\"b\\\\c\\nd");
+    }
+}
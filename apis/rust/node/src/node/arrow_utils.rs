@@ -1,5 +1,9 @@
-use arrow::array::{ArrayData, BufferSpec};
+use std::sync::Arc;
+
+use arrow::array::{new_empty_array, Array, ArrayData, ArrayRef, BufferSpec, MutableArrayData};
+use arrow::datatypes::{DataType, Field};
 use dora_message::metadata::{ArrowTypeInfo, BufferOffset};
+use eyre::Context;
 
 pub fn required_data_size(array: &ArrayData) -> usize {
     let mut next_offset = 0;
@@ -69,3 +73,81 @@ fn copy_array_into_sample_inner(
         child_data,
     }
 }
+
+/// Copies the given array into newly allocated buffers.
+///
+/// Unlike cloning, which shares the buffers, the copy does not keep the memory
+/// of the original array alive, e.g. the shared memory region of an input.
+pub fn deep_copy(array: &ArrayData) -> ArrayData {
+    let mut copy = MutableArrayData::new(vec![array], false, array.len());
+    copy.extend(0, 0, array.len());
+    copy.freeze()
+}
+
+/// Checks that the given array is consistent, in particular its offsets.
+///
+/// `List`, `Binary`, and `Utf8` arrays use 32-bit offsets, so they can address
+/// at most 2GB of values. Offsets that overflowed this limit are negative or out
+/// of bounds, which would silently corrupt the array on the receiver side.
+pub fn check_offsets(array: &ArrayData) -> eyre::Result<()> {
+    array
+        .validate()
+        .map_err(|err| match large_offsets_type(array.data_type()) {
+            Some(large) => eyre::eyre!(
+                "invalid `{}` array: {err} (arrays with more than 2GB of values \
+            need 64-bit offsets, use `{large}` instead)",
+                array.data_type()
+            ),
+            None => eyre::eyre!("invalid `{}` array: {err}", array.data_type()),
+        })
+}
+
+/// Returns the equivalent of `data_type` that uses 64-bit offsets.
+///
+/// Nested lists are converted recursively. Returns `None` if the type does not
+/// contain any 32-bit offsets.
+pub fn large_offsets_type(data_type: &DataType) -> Option<DataType> {
+    match data_type {
+        DataType::Utf8 => Some(DataType::LargeUtf8),
+        DataType::Binary => Some(DataType::LargeBinary),
+        DataType::List(field) => {
+            let item =
+                large_offsets_type(field.data_type()).unwrap_or_else(|| field.data_type().clone());
+            Some(DataType::LargeList(Arc::new(Field::new(
+                field.name(),
+                item,
+                field.is_nullable(),
+            ))))
+        }
+        DataType::LargeList(field) => large_offsets_type(field.data_type()).map(|item| {
+            DataType::LargeList(Arc::new(Field::new(
+                field.name(),
+                item,
+                field.is_nullable(),
+            )))
+        }),
+        _ => None,
+    }
+}
+
+/// Combines the chunks of a chunked array of the given `data_type` into a single
+/// array.
+///
+/// Chunked arrays are created by pyarrow for values that exceed the 2GB limit
+/// of 32-bit offsets, so the chunks are switched to the equivalent type with
+/// 64-bit offsets first, see [`large_offsets_type`].
+pub fn combine_chunks(chunks: &[ArrayRef], data_type: &DataType) -> eyre::Result<ArrayRef> {
+    let data_type = large_offsets_type(data_type).unwrap_or_else(|| data_type.clone());
+    let chunks = chunks
+        .iter()
+        .map(|chunk| {
+            arrow::compute::cast(chunk, &data_type)
+                .wrap_err_with(|| format!("failed to convert chunk to `{data_type}`"))
+        })
+        .collect::<eyre::Result<Vec<_>>>()?;
+    if chunks.is_empty() {
+        return Ok(new_empty_array(&data_type));
+    }
+    let chunks: Vec<&dyn Array> = chunks.iter().map(|chunk| chunk.as_ref()).collect();
+    arrow::compute::concat(&chunks).wrap_err("failed to combine chunks")
+}
@@ -1,14 +1,14 @@
 use std::sync::Arc;
 
-use crate::daemon_connection::DaemonChannel;
+use crate::daemon_connection::{ConnectionKind, DaemonChannel, Transport};
 use dora_core::{
     config::{DataId, NodeId},
     uhlc::HLC,
 };
 use dora_message::{
-    daemon_to_node::{DaemonCommunication, DaemonReply},
+    daemon_to_node::{ClockOffset, DaemonReply, EdgeDepth, NodeSummary},
     metadata::Metadata,
-    node_to_daemon::{DaemonRequest, DataMessage, Timestamped},
+    node_to_daemon::{DaemonRequest, DataMessage, LogLevel, Timestamped},
     DataflowId,
 };
 use eyre::{bail, eyre, Context};
@@ -19,27 +19,15 @@ pub(crate) struct ControlChannel {
 }
 
 impl ControlChannel {
-    #[tracing::instrument(level = "trace", skip(clock))]
+    #[tracing::instrument(level = "trace", skip(transport, clock))]
     pub(crate) fn init(
         dataflow_id: DataflowId,
         node_id: &NodeId,
-        daemon_communication: &DaemonCommunication,
+        transport: &dyn Transport,
         clock: Arc<HLC>,
     ) -> eyre::Result<Self> {
-        let channel = match daemon_communication {
-            DaemonCommunication::Shmem {
-                daemon_control_region_id,
-                ..
-            } => unsafe { DaemonChannel::new_shmem(daemon_control_region_id) }
-                .wrap_err("failed to create shmem control channel")?,
-            DaemonCommunication::Tcp { socket_addr } => DaemonChannel::new_tcp(*socket_addr)
-                .wrap_err("failed to connect control channel")?,
-            #[cfg(unix)]
-            DaemonCommunication::UnixDomain { socket_file } => {
-                DaemonChannel::new_unix_socket(socket_file)
-                    .wrap_err("failed to connect control channel")?
-            }
-        };
+        let channel = DaemonChannel::connect(transport, ConnectionKind::Control)
+            .wrap_err("failed to connect control channel")?;
 
         Self::init_on_channel(dataflow_id, node_id, channel, clock)
     }
@@ -90,6 +78,241 @@ impl ControlChannel {
         Ok(())
     }
 
+    pub fn declare_output(&mut self, output_id: DataId) -> eyre::Result<()> {
+        let reply = self
+            .channel
+            .request(&Timestamped {
+                inner: DaemonRequest::DeclareOutput(output_id),
+                timestamp: self.clock.new_timestamp(),
+            })
+            .wrap_err("failed to declare output to dora-daemon")?;
+        match reply {
+            DaemonReply::Result(result) => result
+                .map_err(|e| eyre!(e))
+                .wrap_err("failed to receive declare output reply from dora-daemon")?,
+            other => bail!("unexpected declare output reply: {other:?}"),
+        }
+        Ok(())
+    }
+
+    pub fn disconnect_input(&mut self, input_id: DataId) -> eyre::Result<()> {
+        let reply = self
+            .channel
+            .request(&Timestamped {
+                inner: DaemonRequest::DisconnectInput(input_id),
+                timestamp: self.clock.new_timestamp(),
+            })
+            .wrap_err("failed to disconnect input")?;
+        match reply {
+            DaemonReply::Result(result) => result
+                .map_err(|e| eyre!(e))
+                .wrap_err("failed to receive disconnect input reply from dora-daemon")?,
+            other => bail!("unexpected disconnect input reply: {other:?}"),
+        }
+        Ok(())
+    }
+
+    pub fn subscribe_pattern(&mut self, pattern: String) -> eyre::Result<()> {
+        let reply = self
+            .channel
+            .request(&Timestamped {
+                inner: DaemonRequest::SubscribePattern(pattern),
+                timestamp: self.clock.new_timestamp(),
+            })
+            .wrap_err("failed to subscribe to pattern")?;
+        match reply {
+            DaemonReply::Result(result) => result
+                .map_err(|e| eyre!(e))
+                .wrap_err("failed to receive subscribe pattern reply from dora-daemon")?,
+            other => bail!("unexpected subscribe pattern reply: {other:?}"),
+        }
+        Ok(())
+    }
+
+    pub fn set_dead_letter_output(&mut self, output: Option<DataId>) -> eyre::Result<()> {
+        let reply = self
+            .channel
+            .request(&Timestamped {
+                inner: DaemonRequest::SetDeadLetterOutput(output),
+                timestamp: self.clock.new_timestamp(),
+            })
+            .wrap_err("failed to set dead-letter output")?;
+        match reply {
+            DaemonReply::Result(result) => result
+                .map_err(|e| eyre!(e))
+                .wrap_err("failed to receive dead-letter output reply from dora-daemon")?,
+            other => bail!("unexpected dead-letter output reply: {other:?}"),
+        }
+        Ok(())
+    }
+
+    pub fn set_conflation(&mut self, output_id: DataId, enabled: bool) -> eyre::Result<()> {
+        let reply = self
+            .channel
+            .request(&Timestamped {
+                inner: DaemonRequest::SetConflation { output_id, enabled },
+                timestamp: self.clock.new_timestamp(),
+            })
+            .wrap_err("failed to set conflation")?;
+        match reply {
+            DaemonReply::Result(result) => result
+                .map_err(|e| eyre!(e))
+                .wrap_err("failed to receive conflation reply from dora-daemon")?,
+            other => bail!("unexpected conflation reply: {other:?}"),
+        }
+        Ok(())
+    }
+
+    pub fn subscribe_lifecycle_events(&mut self) -> eyre::Result<()> {
+        let reply = self
+            .channel
+            .request(&Timestamped {
+                inner: DaemonRequest::SubscribeLifecycleEvents,
+                timestamp: self.clock.new_timestamp(),
+            })
+            .wrap_err("failed to subscribe to lifecycle events")?;
+        match reply {
+            DaemonReply::Result(result) => result
+                .map_err(|e| eyre!(e))
+                .wrap_err("failed to receive lifecycle subscribe reply from dora-daemon")?,
+            other => bail!("unexpected lifecycle subscribe reply: {other:?}"),
+        }
+        Ok(())
+    }
+
+    pub fn pipeline_depths(&mut self) -> eyre::Result<Vec<EdgeDepth>> {
+        let reply = self
+            .channel
+            .request(&Timestamped {
+                inner: DaemonRequest::PipelineDepths,
+                timestamp: self.clock.new_timestamp(),
+            })
+            .wrap_err("failed to request pipeline depths")?;
+        match reply {
+            DaemonReply::PipelineDepths(depths) => Ok(depths),
+            DaemonReply::Result(Err(err)) => {
+                Err(eyre!(err)).wrap_err("failed to receive pipeline depths from dora-daemon")
+            }
+            other => bail!("unexpected pipeline depths reply: {other:?}"),
+        }
+    }
+
+    pub fn clock_skew(&mut self, node_id: NodeId) -> eyre::Result<ClockOffset> {
+        let reply = self
+            .channel
+            .request(&Timestamped {
+                inner: DaemonRequest::ClockSkew { node_id },
+                timestamp: self.clock.new_timestamp(),
+            })
+            .wrap_err("failed to request clock skew")?;
+        match reply {
+            DaemonReply::ClockSkew(skew) => Ok(skew),
+            DaemonReply::Result(Err(err)) => {
+                Err(eyre!(err)).wrap_err("failed to receive clock skew from dora-daemon")
+            }
+            other => bail!("unexpected clock skew reply: {other:?}"),
+        }
+    }
+
+    pub fn check_acked(
+        &mut self,
+        output_id: DataId,
+        message_id: u64,
+        discard: bool,
+    ) -> eyre::Result<bool> {
+        let reply = self
+            .channel
+            .request(&Timestamped {
+                inner: DaemonRequest::CheckAcked {
+                    output_id,
+                    message_id,
+                    discard,
+                },
+                timestamp: self.clock.new_timestamp(),
+            })
+            .wrap_err("failed to request acknowledgement status")?;
+        match reply {
+            DaemonReply::Acked(acked) => Ok(acked),
+            DaemonReply::Result(Err(err)) => Err(eyre!(err))
+                .wrap_err("failed to receive acknowledgement status from dora-daemon"),
+            other => bail!("unexpected CheckAcked reply: {other:?}"),
+        }
+    }
+
+    pub fn log(
+        &mut self,
+        level: LogLevel,
+        target: Option<String>,
+        message: String,
+    ) -> eyre::Result<()> {
+        let reply = self
+            .channel
+            .request(&Timestamped {
+                inner: DaemonRequest::Log {
+                    level,
+                    target,
+                    message,
+                },
+                timestamp: self.clock.new_timestamp(),
+            })
+            .wrap_err("failed to send log message to dora-daemon")?;
+        match reply {
+            DaemonReply::Empty => Ok(()),
+            other => bail!("unexpected Log reply: {other:?}"),
+        }
+    }
+
+    pub fn report_progress(
+        &mut self,
+        task: String,
+        progress: f32,
+        message: Option<String>,
+    ) -> eyre::Result<()> {
+        let reply = self
+            .channel
+            .request(&Timestamped {
+                inner: DaemonRequest::ReportProgress {
+                    task,
+                    progress,
+                    message,
+                },
+                timestamp: self.clock.new_timestamp(),
+            })
+            .wrap_err("failed to send progress report to dora-daemon")?;
+        match reply {
+            DaemonReply::Empty => Ok(()),
+            other => bail!("unexpected ReportProgress reply: {other:?}"),
+        }
+    }
+
+    pub fn report_summary(&mut self, summary: NodeSummary) -> eyre::Result<()> {
+        let reply = self
+            .channel
+            .request(&Timestamped {
+                inner: DaemonRequest::ReportSummary(summary),
+                timestamp: self.clock.new_timestamp(),
+            })
+            .wrap_err("failed to send summary to dora-daemon")?;
+        match reply {
+            DaemonReply::Empty => Ok(()),
+            other => bail!("unexpected ReportSummary reply: {other:?}"),
+        }
+    }
+
+    pub fn ack_stop(&mut self) -> eyre::Result<()> {
+        let reply = self
+            .channel
+            .request(&Timestamped {
+                inner: DaemonRequest::AckStop,
+                timestamp: self.clock.new_timestamp(),
+            })
+            .wrap_err("failed to send stop acknowledgement to dora-daemon")?;
+        match reply {
+            DaemonReply::Empty => Ok(()),
+            other => bail!("unexpected AckStop reply: {other:?}"),
+        }
+    }
+
     pub fn send_message(
         &mut self,
         output_id: DataId,
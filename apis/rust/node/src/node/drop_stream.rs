@@ -1,9 +1,12 @@
 use std::{sync::Arc, time::Duration};
 
-use crate::daemon_connection::DaemonChannel;
+use crate::{
+    affinity::NativeThreadId,
+    daemon_connection::{ConnectionKind, DaemonChannel, Transport},
+};
 use dora_core::{config::NodeId, uhlc};
 use dora_message::{
-    daemon_to_node::{DaemonCommunication, DaemonReply, NodeDropEvent},
+    daemon_to_node::{DaemonReply, NodeDropEvent},
     node_to_daemon::{DaemonRequest, DropToken, Timestamped},
     DataflowId,
 };
@@ -13,34 +16,19 @@ use flume::RecvTimeoutError;
 pub struct DropStream {
     receiver: flume::Receiver<DropToken>,
     _thread_handle: DropStreamThreadHandle,
+    thread_id: NativeThreadId,
 }
 
 impl DropStream {
-    #[tracing::instrument(level = "trace", skip(hlc))]
+    #[tracing::instrument(level = "trace", skip(transport, hlc))]
     pub(crate) fn init(
         dataflow_id: DataflowId,
         node_id: &NodeId,
-        daemon_communication: &DaemonCommunication,
+        transport: &dyn Transport,
         hlc: Arc<uhlc::HLC>,
     ) -> eyre::Result<Self> {
-        let channel = match daemon_communication {
-            DaemonCommunication::Shmem {
-                daemon_drop_region_id,
-                ..
-            } => {
-                unsafe { DaemonChannel::new_shmem(daemon_drop_region_id) }.wrap_err_with(|| {
-                    format!("failed to create shmem drop stream for node `{node_id}`")
-                })?
-            }
-            DaemonCommunication::Tcp { socket_addr } => DaemonChannel::new_tcp(*socket_addr)
-                .wrap_err_with(|| format!("failed to connect drop stream for node `{node_id}`"))?,
-            #[cfg(unix)]
-            DaemonCommunication::UnixDomain { socket_file } => {
-                DaemonChannel::new_unix_socket(socket_file).wrap_err_with(|| {
-                    format!("failed to connect drop stream for node `{node_id}`")
-                })?
-            }
-        };
+        let channel = DaemonChannel::connect(transport, ConnectionKind::Drop)
+            .wrap_err_with(|| format!("failed to connect drop stream for node `{node_id}`"))?;
 
         Self::init_on_channel(dataflow_id, node_id, channel, hlc)
     }
@@ -72,13 +60,24 @@ impl DropStream {
         let (tx, rx) = flume::bounded(0);
         let node_id_cloned = node_id.clone();
 
-        let handle = std::thread::spawn(|| drop_stream_loop(node_id_cloned, tx, channel, clock));
+        let thread_id = NativeThreadId::default();
+        let thread_id_cloned = thread_id.clone();
+        let handle = std::thread::spawn(move || {
+            thread_id_cloned.record_current();
+            drop_stream_loop(node_id_cloned, tx, channel, clock);
+            thread_id_cloned.clear();
+        });
 
         Ok(Self {
             receiver: rx,
             _thread_handle: DropStreamThreadHandle::new(node_id.clone(), handle),
+            thread_id,
         })
     }
+
+    pub(crate) fn set_affinity(&self, cores: &[usize]) -> eyre::Result<()> {
+        self.thread_id.set_affinity(cores)
+    }
 }
 
 impl std::ops::Deref for DropStream {
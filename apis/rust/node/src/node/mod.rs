@@ -1,33 +1,45 @@
-use crate::{daemon_connection::DaemonChannel, EventStream};
+use crate::{
+    affinity,
+    bandwidth::BandwidthMeter,
+    daemon_connection::{DaemonChannel, DryRunTransport, Transport},
+    metrics::MessageCounters,
+    process_title, EventStream,
+};
 
 use self::{
-    arrow_utils::{copy_array_into_sample, required_data_size},
+    arrow_utils::{check_offsets, copy_array_into_sample, required_data_size},
     control_channel::ControlChannel,
     drop_stream::DropStream,
 };
 use aligned_vec::{AVec, ConstAlign};
-use arrow::array::Array;
+use arrow::array::{make_array, Array};
 use dora_core::{
     config::{DataId, NodeId, NodeRunConfig},
-    descriptor::Descriptor,
+    descriptor::{Descriptor, ParamValue},
     metadata::ArrowTypeInfoExt,
     topics::{DORA_DAEMON_LOCAL_LISTEN_PORT_DEFAULT, LOCALHOST},
     uhlc,
 };
 
 use dora_message::{
-    daemon_to_node::{DaemonReply, NodeConfig},
-    metadata::{ArrowTypeInfo, Metadata, MetadataParameters},
-    node_to_daemon::{DaemonRequest, DataMessage, DropToken, Timestamped},
+    daemon_to_node::{
+        ClockOffset, DaemonCommunication, DaemonReply, EdgeDepth, NodeConfig, NodeSummary,
+    },
+    metadata::{
+        attachments_size, ArrowTypeInfo, DeadLetterReason, Metadata, MetadataParameters, Parameter,
+        DROPPED_COUNT, MAX_ATTACHMENTS_SIZE, RELIABLE, SEQUENCE_NUMBER,
+    },
+    node_to_daemon::{DaemonRequest, DataMessage, DropToken, LogLevel, Timestamped},
     DataflowId,
 };
 use eyre::{bail, WrapErr};
 use shared_memory_extended::{Shmem, ShmemConf};
 use std::{
-    collections::{BTreeSet, HashMap, VecDeque},
+    collections::{BTreeMap, BTreeSet, HashMap, VecDeque},
+    net::SocketAddr,
     ops::{Deref, DerefMut},
     sync::Arc,
-    time::Duration,
+    time::{Duration, Instant, SystemTime},
 };
 use tracing::{info, warn};
 
@@ -37,9 +49,42 @@ use dora_tracing::set_up_tracing;
 pub mod arrow_utils;
 mod control_channel;
 mod drop_stream;
+mod state;
 
 pub const ZERO_COPY_THRESHOLD: usize = 4096;
 
+/// Interval after which unacknowledged reliable outputs are sent again, see
+/// [`DoraNode::send_output_reliable`].
+pub const RELIABLE_RETRY_INTERVAL: Duration = Duration::from_secs(1);
+const ACK_POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// Prefix of the environment variables that override dataflow parameters, see
+/// [`DoraNode::param`].
+pub const PARAM_ENV_PREFIX: &str = "DORA_PARAM_";
+
+/// Maximum time to wait for pending scheduled outputs when the node is dropped.
+pub const SCHEDULED_OUTPUTS_DROP_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Returned by [`DoraNode::send_output_reliable`] if not all receivers
+/// acknowledged the output within the timeout.
+#[derive(Debug, Clone)]
+pub struct AckTimeoutError {
+    pub output_id: DataId,
+    pub timeout: Duration,
+}
+
+impl std::fmt::Display for AckTimeoutError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "output `{}` was not acknowledged by all receivers within {:?}",
+            self.output_id, self.timeout
+        )
+    }
+}
+
+impl std::error::Error for AckTimeoutError {}
+
 pub struct DoraNode {
     id: NodeId,
     dataflow_id: DataflowId,
@@ -53,6 +98,26 @@ pub struct DoraNode {
 
     dataflow_descriptor: Descriptor,
     warned_unknown_output: BTreeSet<DataId>,
+    sequence_numbers: HashMap<DataId, u64>,
+    /// Outputs that were declared at runtime through `declare_dynamic_output`.
+    dynamic_outputs: BTreeSet<DataId>,
+    /// Outputs to send at a later time, ordered by target time and insertion order.
+    scheduled_outputs: BTreeMap<(SystemTime, u64), ScheduledOutput>,
+    next_schedule_id: u64,
+    /// ID of the next output sent through `send_output_reliable`.
+    next_message_id: u64,
+    /// Whether large outputs are sent through shared memory, see [`Transport`].
+    shared_memory: bool,
+    /// Payload bytes per output, see [`Self::output_bandwidth`].
+    output_bandwidth: BandwidthMeter,
+    /// Cumulative counters per output, see [`Self::output_counters`].
+    output_counters: BTreeMap<DataId, MessageCounters>,
+    /// Whether the node was created through [`Self::init_dry_run`].
+    dry_run: bool,
+    /// See [`Self::set_dead_letter_output`].
+    dead_letter_output: Option<DataId>,
+    /// Connection info of the node config, used by [`Self::init_after_fork`].
+    daemon_communication: DaemonCommunication,
 }
 
 impl DoraNode {
@@ -64,6 +129,12 @@ impl DoraNode {
     /// let (mut node, mut events) = DoraNode::init_from_env().expect("Could not init node.");
     /// ```
     ///
+    /// Fails with a [`DuplicateNodeIdError`](crate::DuplicateNodeIdError) if another
+    /// process is already connected to the daemon with the same node ID, and with a
+    /// [`VersionMismatchError`](crate::VersionMismatchError) if the node was built
+    /// against a dora version that is not compatible with the daemon.
+    ///
+    /// The process is named after the node, see [`set_process_title`](Self::set_process_title).
     pub fn init_from_env() -> eyre::Result<(Self, EventStream)> {
         let node_config: NodeConfig = {
             let raw = std::env::var("DORA_NODE_CONFIG").wrap_err(
@@ -74,7 +145,11 @@ impl DoraNode {
         #[cfg(feature = "tracing")]
         set_up_tracing(node_config.node_id.as_ref())
             .context("failed to set up tracing subscriber")?;
-        Self::init(node_config)
+        let (node, events) = Self::init(node_config)?;
+        if let Err(err) = node.set_process_title() {
+            warn!("{err:?}");
+        }
+        Ok((node, events))
     }
 
     /// Initiate a node from a dataflow id and a node id.
@@ -89,14 +164,41 @@ impl DoraNode {
     pub fn init_from_node_id(node_id: NodeId) -> eyre::Result<(Self, EventStream)> {
         // Make sure that the node is initialized outside of dora start.
         let daemon_address = (LOCALHOST, DORA_DAEMON_LOCAL_LISTEN_PORT_DEFAULT).into();
+        Self::init_from_daemon(daemon_address, None, node_id)
+    }
 
+    /// Initiate a dynamic node of the given dataflow through an explicit daemon
+    /// address, independent of the environment of the process.
+    ///
+    /// The dataflow ID selects between several running dataflows that contain a
+    /// dynamic node with the same ID. This allows a process to be a member of
+    /// multiple dataflows at once, e.g. to forward messages between them:
+    ///
+    /// ```no_run
+    /// use dora_node_api::{dora_core::config::NodeId, DataflowId, DoraNode};
+    /// # let (dataflow_a, dataflow_b) = (DataflowId::nil(), DataflowId::nil());
+    ///
+    /// let daemon = ([127, 0, 0, 1], 53291).into();
+    /// let bridge = NodeId::from("bridge".to_string());
+    /// let (node_a, events_a) = DoraNode::init_from_daemon(daemon, Some(dataflow_a), bridge.clone())?;
+    /// let (node_b, events_b) = DoraNode::init_from_daemon(daemon, Some(dataflow_b), bridge)?;
+    /// # Ok::<(), eyre::Report>(())
+    /// ```
+    pub fn init_from_daemon(
+        daemon_address: SocketAddr,
+        dataflow_id: Option<DataflowId>,
+        node_id: NodeId,
+    ) -> eyre::Result<(Self, EventStream)> {
         let mut channel =
             DaemonChannel::new_tcp(daemon_address).context("Could not connect to the daemon")?;
         let clock = Arc::new(uhlc::HLC::default());
 
         let reply = channel
             .request(&Timestamped {
-                inner: DaemonRequest::NodeConfig { node_id },
+                inner: DaemonRequest::NodeConfig {
+                    node_id,
+                    dataflow_id,
+                },
                 timestamp: clock.new_timestamp(),
             })
             .wrap_err("failed to request node config from daemon")?;
@@ -120,8 +222,130 @@ impl DoraNode {
         }
     }
 
+    /// Initiate a node that is not connected to a daemon, for testing node logic.
+    ///
+    /// Outputs are converted and validated as usual, but recorded by the returned
+    /// [`DryRunTransport`] instead of being sent. Since there is no dataflow
+    /// descriptor, all output IDs are accepted. The event stream ends immediately.
+    ///
+    /// ```
+    /// use dora_node_api::{arrow::array::UInt8Array, dora_core::config::NodeId, DoraNode};
+    ///
+    /// let (mut node, _events, transport) = DoraNode::init_dry_run(NodeId::from("test".to_owned()))?;
+    /// node.send_output("out".to_owned().into(), Default::default(), UInt8Array::from(vec![1, 2]))?;
+    /// assert_eq!(transport.take_sent_outputs()[0].data.0.len(), 2);
+    /// # Ok::<(), eyre::Report>(())
+    /// ```
+    pub fn init_dry_run(node_id: NodeId) -> eyre::Result<(Self, EventStream, DryRunTransport)> {
+        let node_config = NodeConfig {
+            dataflow_id: DataflowId::nil(),
+            node_id,
+            run_config: NodeRunConfig {
+                inputs: BTreeMap::new(),
+                outputs: BTreeSet::new(),
+                dynamic_outputs: false,
+            },
+            // not used, the transport is passed explicitly
+            daemon_communication: DaemonCommunication::Tcp {
+                socket_addr: (LOCALHOST, DORA_DAEMON_LOCAL_LISTEN_PORT_DEFAULT).into(),
+            },
+            dataflow_descriptor: Descriptor {
+                communication: Default::default(),
+                deploy: Default::default(),
+                nodes: Vec::new(),
+                params: BTreeMap::new(),
+                schemas: BTreeMap::new(),
+                shutdown_order: Vec::new(),
+            },
+            dynamic: false,
+        };
+        let transport = DryRunTransport::default();
+        let (mut node, events) = Self::init_with_transport(node_config, &transport)?;
+        node.dry_run = true;
+        Ok((node, events, transport))
+    }
+
     #[tracing::instrument]
     pub fn init(node_config: NodeConfig) -> eyre::Result<(Self, EventStream)> {
+        let transport = node_config.daemon_communication.clone();
+        Self::init_with_transport(node_config, &transport)
+    }
+
+    /// Initiate a node that communicates with the daemon over a custom transport.
+    ///
+    /// The `daemon_communication` field of the node config is ignored. Use this to
+    /// experiment with alternative transports without changing the node logic:
+    ///
+    /// ```no_run
+    /// use dora_node_api::{Connection, ConnectionKind, DoraNode, Transport};
+    /// use dora_message::{
+    ///     daemon_to_node::DaemonReply,
+    ///     node_to_daemon::{DaemonRequest, Timestamped},
+    /// };
+    ///
+    /// struct RdmaConnection;
+    ///
+    /// impl Connection for RdmaConnection {
+    ///     fn request(&mut self, request: &Timestamped<DaemonRequest>) -> eyre::Result<DaemonReply> {
+    ///         todo!("send the request and wait for the reply")
+    ///     }
+    /// }
+    ///
+    /// struct RdmaTransport;
+    ///
+    /// impl Transport for RdmaTransport {
+    ///     fn connect(&self, kind: ConnectionKind) -> eyre::Result<Box<dyn Connection>> {
+    ///         Ok(Box::new(RdmaConnection))
+    ///     }
+    ///
+    ///     fn supports_shared_memory(&self) -> bool {
+    ///         false
+    ///     }
+    /// }
+    ///
+    /// # let node_config: dora_message::daemon_to_node::NodeConfig = todo!();
+    /// let (mut node, mut events) =
+    ///     DoraNode::init_with_transport(node_config, &RdmaTransport).expect("Could not init node.");
+    /// ```
+    #[tracing::instrument(skip(transport))]
+    pub fn init_with_transport(
+        node_config: NodeConfig,
+        transport: &dyn Transport,
+    ) -> eyre::Result<(Self, EventStream)> {
+        Self::init_with_transport_inner(node_config, transport, false)
+    }
+
+    /// Connects to the daemon again as the same node, in a child process that
+    /// was created through `fork`.
+    ///
+    /// The child takes over the subscription of the parent, so the parent must
+    /// not use its node anymore afterwards. The connection of the parent is
+    /// neither used nor closed, so `self` must not be dropped in the child, e.g.
+    /// by passing it to [`std::mem::forget`]. Nodes that were created with a
+    /// custom transport reconnect through the `daemon_communication` of their
+    /// node config.
+    pub fn init_after_fork(&self) -> eyre::Result<(Self, EventStream)> {
+        if self.dry_run {
+            bail!("dry-run nodes cannot be reinitialized after fork");
+        }
+        let node_config = NodeConfig {
+            dataflow_id: self.dataflow_id,
+            node_id: self.id.clone(),
+            run_config: self.node_config.clone(),
+            daemon_communication: self.daemon_communication.clone(),
+            dataflow_descriptor: self.dataflow_descriptor.clone(),
+            // not used by `init_with_transport_inner`
+            dynamic: false,
+        };
+        let transport = node_config.daemon_communication.clone();
+        Self::init_with_transport_inner(node_config, &transport, true)
+    }
+
+    fn init_with_transport_inner(
+        node_config: NodeConfig,
+        transport: &dyn Transport,
+        take_over_subscription: bool,
+    ) -> eyre::Result<(Self, EventStream)> {
         let NodeConfig {
             dataflow_id,
             node_id,
@@ -136,17 +360,16 @@ impl DoraNode {
         let event_stream = EventStream::init(
             dataflow_id,
             &node_id,
-            &daemon_communication,
+            transport,
             input_config,
             clock.clone(),
+            take_over_subscription,
         )
         .wrap_err("failed to init event stream")?;
-        let drop_stream =
-            DropStream::init(dataflow_id, &node_id, &daemon_communication, clock.clone())
-                .wrap_err("failed to init drop stream")?;
-        let control_channel =
-            ControlChannel::init(dataflow_id, &node_id, &daemon_communication, clock.clone())
-                .wrap_err("failed to init control channel")?;
+        let drop_stream = DropStream::init(dataflow_id, &node_id, transport, clock.clone())
+            .wrap_err("failed to init drop stream")?;
+        let control_channel = ControlChannel::init(dataflow_id, &node_id, transport, clock.clone())
+            .wrap_err("failed to init control channel")?;
 
         let node = Self {
             id: node_id,
@@ -159,17 +382,37 @@ impl DoraNode {
             cache: VecDeque::new(),
             dataflow_descriptor,
             warned_unknown_output: BTreeSet::new(),
+            sequence_numbers: HashMap::new(),
+            dynamic_outputs: BTreeSet::new(),
+            scheduled_outputs: BTreeMap::new(),
+            next_schedule_id: 0,
+            next_message_id: 0,
+            shared_memory: transport.supports_shared_memory(),
+            output_bandwidth: BandwidthMeter::new(),
+            output_counters: BTreeMap::new(),
+            dry_run: false,
+            dead_letter_output: None,
+            daemon_communication,
         };
         Ok((node, event_stream))
     }
 
     fn validate_output(&mut self, output_id: &DataId) -> bool {
+        if self.dry_run {
+            self.node_config.outputs.insert(output_id.clone());
+        }
         if !self.node_config.outputs.contains(output_id) {
             if !self.warned_unknown_output.contains(output_id) {
-                warn!("Ignoring output `{output_id}` not in node's output list.");
+                match &self.dead_letter_output {
+                    Some(dead_letter) => warn!(
+                        "Redirecting output `{output_id}` not in node's output list to `{dead_letter}`."
+                    ),
+                    None => warn!("Ignoring output `{output_id}` not in node's output list."),
+                }
                 self.warned_unknown_output.insert(output_id.clone());
             }
-            false
+            // undeclared outputs are redirected in `send_output_sample`
+            self.dead_letter_output.is_some()
         } else {
             true
         }
@@ -219,6 +462,26 @@ impl DoraNode {
         self.send_output_sample(output_id, type_info, parameters, Some(sample))
     }
 
+    /// Sends the given arrow array as output.
+    ///
+    /// Like for all send methods, the delivery priority can be set through the
+    /// `parameters`, e.g. to ensure that control messages are not delayed by
+    /// queued bulk data. Receivers reorder queued inputs by priority, but never
+    /// across other events such as `InputClosed`.
+    ///
+    /// ```no_run
+    /// use dora_node_api::{
+    ///     arrow::array::UInt8Array,
+    ///     metadata::{Priority, PRIORITY},
+    ///     DoraNode, MetadataParameters,
+    /// };
+    /// # let (mut node, _events) = DoraNode::init_from_env().unwrap();
+    ///
+    /// let mut parameters = MetadataParameters::default();
+    /// parameters.insert(PRIORITY.to_owned(), Priority::High.into());
+    /// node.send_output("command".to_owned().into(), parameters, UInt8Array::from(vec![1]))
+    ///     .unwrap();
+    /// ```
     pub fn send_output(
         &mut self,
         output_id: DataId,
@@ -230,6 +493,8 @@ impl DoraNode {
         };
 
         let arrow_array = data.to_data();
+        check_offsets(&arrow_array)
+            .wrap_err_with(|| format!("cannot send output `{output_id}`"))?;
 
         let total_len = required_data_size(&arrow_array);
 
@@ -242,6 +507,122 @@ impl DoraNode {
         Ok(())
     }
 
+    /// Sends the given output with at-least-once delivery to the local receivers.
+    ///
+    /// Blocks until every receiving node acknowledged the output, which happens
+    /// when the event is returned from its event stream. Unacknowledged outputs
+    /// are sent again every [`RELIABLE_RETRY_INTERVAL`]. If not all receivers
+    /// acknowledged the output within `timeout`, an [`AckTimeoutError`] is
+    /// returned. Receivers whose input is closed in the meantime are not waited
+    /// for.
+    ///
+    /// Retries can deliver the same output more than once, possibly after
+    /// newer outputs, so receivers must handle duplicates idempotently. All
+    /// copies carry the same [`RELIABLE`] metadata parameter, which can be used
+    /// to detect them, as well as the same sequence number. Receivers on other
+    /// machines get the output on a best-effort basis, like for
+    /// [`send_output`](Self::send_output), and are not waited for. The daemon
+    /// logs a warning for reliable outputs with such receivers.
+    pub fn send_output_reliable(
+        &mut self,
+        output_id: DataId,
+        mut parameters: MetadataParameters,
+        data: impl Array,
+        timeout: Duration,
+    ) -> eyre::Result<()> {
+        if !self.validate_output(&output_id) {
+            return Ok(());
+        };
+
+        let arrow_array = data.to_data();
+        check_offsets(&arrow_array)
+            .wrap_err_with(|| format!("cannot send output `{output_id}`"))?;
+
+        let message_id = self.next_message_id;
+        self.next_message_id += 1;
+        parameters.insert(RELIABLE.to_owned(), Parameter::Integer(message_id as i64));
+        // retries are copies of the same message, so they keep its sequence number
+        let sequence_number = self.next_sequence_number(&output_id);
+
+        let deadline = Instant::now() + timeout;
+        loop {
+            let total_len = required_data_size(&arrow_array);
+            let mut sample = self.allocate_data_sample(total_len)?;
+            let type_info = copy_array_into_sample(&mut sample, &arrow_array);
+            self.send_numbered_output_sample(
+                output_id.clone(),
+                type_info,
+                parameters.clone(),
+                Some(sample),
+                Some(sequence_number),
+            )
+            .wrap_err("failed to send output")?;
+
+            let retry_at = (Instant::now() + RELIABLE_RETRY_INTERVAL).min(deadline);
+            loop {
+                if self
+                    .control_channel
+                    .check_acked(output_id.clone(), message_id, false)?
+                {
+                    return Ok(());
+                }
+                let now = Instant::now();
+                if now >= retry_at {
+                    break;
+                }
+                std::thread::sleep(ACK_POLL_INTERVAL.min(retry_at - now));
+            }
+            if Instant::now() >= deadline {
+                // stop tracking the output in the daemon
+                if self
+                    .control_channel
+                    .check_acked(output_id.clone(), message_id, true)?
+                {
+                    return Ok(());
+                }
+                if let Some(dead_letter) = self.dead_letter_output.clone() {
+                    parameters.remove(RELIABLE);
+                    DeadLetterReason::NotAcknowledged.insert_into(&mut parameters, &output_id);
+                    self.send_output(dead_letter, parameters, make_array(arrow_array))
+                        .wrap_err("failed to send output to dead-letter output")?;
+                }
+                return Err(AckTimeoutError { output_id, timeout }.into());
+            }
+        }
+    }
+
+    /// Sends the given output at time `at` instead of immediately.
+    ///
+    /// The output is kept in a buffer ordered by target time until it is sent by
+    /// [`send_scheduled_outputs`](Self::send_scheduled_outputs), which is called on
+    /// every send. If `at` is in the past, the output is sent immediately. Outputs
+    /// that are still pending when the node is dropped are sent at their target
+    /// time before the node exits, unless they are due more than
+    /// [`SCHEDULED_OUTPUTS_DROP_TIMEOUT`] later. Such outputs are discarded.
+    pub fn send_output_at(
+        &mut self,
+        output_id: DataId,
+        parameters: MetadataParameters,
+        data: impl Array,
+        at: SystemTime,
+    ) -> eyre::Result<()> {
+        if !self.validate_output(&output_id) {
+            return Ok(());
+        };
+
+        let arrow_array = data.to_data();
+        check_offsets(&arrow_array)
+            .wrap_err_with(|| format!("cannot send output `{output_id}`"))?;
+
+        let total_len = required_data_size(&arrow_array);
+
+        let mut sample = self.allocate_data_sample(total_len)?;
+        let type_info = copy_array_into_sample(&mut sample, &arrow_array);
+
+        self.schedule_output_sample(at, output_id, type_info, parameters, Some(sample))
+            .wrap_err("failed to schedule output")
+    }
+
     pub fn send_output_bytes(
         &mut self,
         output_id: DataId,
@@ -257,6 +638,8 @@ impl DoraNode {
         })
     }
 
+    /// Sends an output with the given arrow type info, filling its data through
+    /// the `data` closure.
     pub fn send_typed_output<F>(
         &mut self,
         output_id: DataId,
@@ -284,11 +667,59 @@ impl DoraNode {
         type_info: ArrowTypeInfo,
         parameters: MetadataParameters,
         sample: Option<DataSample>,
+    ) -> eyre::Result<()> {
+        self.send_numbered_output_sample(output_id, type_info, parameters, sample, None)
+    }
+
+    /// Returns the sequence number of the next message of the given output.
+    fn next_sequence_number(&mut self, output_id: &DataId) -> u64 {
+        let sequence_number = self.sequence_numbers.entry(output_id.clone()).or_default();
+        *sequence_number += 1;
+        *sequence_number - 1
+    }
+
+    /// Sends the sample with the given sequence number, or with the next sequence
+    /// number of the output if it's `None`.
+    fn send_numbered_output_sample(
+        &mut self,
+        mut output_id: DataId,
+        type_info: ArrowTypeInfo,
+        mut parameters: MetadataParameters,
+        sample: Option<DataSample>,
+        mut sequence_number: Option<u64>,
     ) -> eyre::Result<()> {
         self.handle_finished_drop_tokens()?;
 
+        if let Some(dead_letter) = &self.dead_letter_output {
+            if !self.node_config.outputs.contains(&output_id) {
+                DeadLetterReason::UndeclaredOutput.insert_into(&mut parameters, &output_id);
+                output_id = dead_letter.clone();
+                // sequence numbers are counted per output
+                sequence_number = None;
+            }
+        }
+
+        check_attachments_size(&output_id, &parameters)?;
+
+        // the drop count describes the delivery of a received input, so it must
+        // not be forwarded to the receivers of this output
+        parameters.remove(DROPPED_COUNT);
+        let sequence_number =
+            sequence_number.unwrap_or_else(|| self.next_sequence_number(&output_id));
+        parameters.insert(
+            SEQUENCE_NUMBER.to_owned(),
+            Parameter::Integer(sequence_number as i64),
+        );
+
         let metadata = Metadata::from_parameters(self.clock.new_timestamp(), type_info, parameters);
 
+        let data_len = sample.as_ref().map_or(0, |sample| sample.len());
+        self.output_bandwidth.record(&output_id, data_len);
+        self.output_counters
+            .entry(output_id.clone())
+            .or_default()
+            .record(data_len);
+
         let (data, shmem) = match sample {
             Some(sample) => sample.finalize(),
             None => (None, None),
@@ -306,12 +737,69 @@ impl DoraNode {
         Ok(())
     }
 
+    /// Schedules the given sample to be sent at time `at`.
+    ///
+    /// See [`send_output_at`](Self::send_output_at) for details.
+    pub fn schedule_output_sample(
+        &mut self,
+        at: SystemTime,
+        output_id: DataId,
+        type_info: ArrowTypeInfo,
+        parameters: MetadataParameters,
+        sample: Option<DataSample>,
+    ) -> eyre::Result<()> {
+        self.send_scheduled_outputs()?;
+        check_attachments_size(&output_id, &parameters)?;
+
+        if at <= SystemTime::now() {
+            return self.send_output_sample(output_id, type_info, parameters, sample);
+        }
+        let output = ScheduledOutput {
+            output_id,
+            type_info,
+            parameters,
+            sample,
+        };
+        self.scheduled_outputs
+            .insert((at, self.next_schedule_id), output);
+        self.next_schedule_id += 1;
+        Ok(())
+    }
+
+    /// Sends all scheduled outputs whose target time has been reached.
+    ///
+    /// Returns the target time of the next pending scheduled output, if any.
+    pub fn send_scheduled_outputs(&mut self) -> eyre::Result<Option<SystemTime>> {
+        let now = SystemTime::now();
+        while let Some(entry) = self.scheduled_outputs.first_entry() {
+            let (at, _) = *entry.key();
+            if at > now {
+                return Ok(Some(at));
+            }
+            let ScheduledOutput {
+                output_id,
+                type_info,
+                parameters,
+                sample,
+            } = entry.remove();
+            self.send_output_sample(output_id, type_info, parameters, sample)?;
+        }
+        Ok(None)
+    }
+
     pub fn close_outputs(&mut self, outputs: Vec<DataId>) -> eyre::Result<()> {
         for output_id in &outputs {
             if !self.node_config.outputs.remove(output_id) {
                 eyre::bail!("unknown output {output_id}");
             }
         }
+        if self
+            .dead_letter_output
+            .as_ref()
+            .is_some_and(|dead_letter| outputs.contains(dead_letter))
+        {
+            self.dead_letter_output = None;
+        }
 
         self.control_channel
             .report_closed_outputs(outputs)
@@ -320,6 +808,308 @@ impl DoraNode {
         Ok(())
     }
 
+    /// Sets an output on which messages that cannot be delivered are sent
+    /// instead of being dropped, or disables this if `None`.
+    ///
+    /// The output must be declared. Redirected messages carry the
+    /// [`DEAD_LETTER_REASON`](dora_message::metadata::DEAD_LETTER_REASON) and
+    /// [`DEAD_LETTER_OUTPUT`](dora_message::metadata::DEAD_LETTER_OUTPUT) metadata parameters
+    /// with the [`DeadLetterReason`] and the original output ID. Messages are
+    /// redirected if
+    ///
+    /// - the output is not declared, instead of being ignored,
+    /// - no node is subscribed to the output when the message is sent,
+    /// - a [reliably sent](Self::send_output_reliable) message is not
+    ///   acknowledged in time. The error is returned nevertheless.
+    ///
+    /// Messages that are dropped by a receiver because its input queue is full,
+    /// i.e. through its `queue_size` or `max_buffered_bytes` limit, or because a
+    /// newer message of a conflated output replaced them, are not redirected.
+    /// They are reported to the receiver through the
+    /// [`DROPPED_COUNT`](dora_message::metadata::DROPPED_COUNT) parameter instead.
+    pub fn set_dead_letter_output(&mut self, output_id: Option<DataId>) -> eyre::Result<()> {
+        if let Some(output_id) = &output_id {
+            if self.dry_run {
+                self.node_config.outputs.insert(output_id.clone());
+            }
+            if !self.node_config.outputs.contains(output_id) {
+                bail!("dead-letter output `{output_id}` is not declared");
+            }
+        }
+        self.control_channel
+            .set_dead_letter_output(output_id.clone())
+            .wrap_err("failed to set dead-letter output")?;
+        self.dead_letter_output = output_id;
+        Ok(())
+    }
+
+    /// The output set through [`set_dead_letter_output`](Self::set_dead_letter_output).
+    pub fn dead_letter_output(&self) -> Option<&DataId> {
+        self.dead_letter_output.as_ref()
+    }
+
+    /// Enables or disables conflation for the given output.
+    ///
+    /// Messages of a conflated output replace the messages of the output that
+    /// are still buffered for a receiver, i.e. receivers that fall behind only
+    /// get the most recent message instead of a backlog. Unlike a rate limit,
+    /// this doesn't delay or drop messages for receivers that keep up. The
+    /// replaced messages are counted in the
+    /// [`DROPPED_COUNT`](dora_message::metadata::DROPPED_COUNT) of the next
+    /// delivered message.
+    ///
+    /// Conflation is enforced by the daemon, which marks the messages with the
+    /// [`CONFLATED`](dora_message::metadata::CONFLATED) parameter.
+    pub fn set_conflation(&mut self, output_id: DataId, enabled: bool) -> eyre::Result<()> {
+        if self.dry_run {
+            self.node_config.outputs.insert(output_id.clone());
+        }
+        if !self.node_config.outputs.contains(&output_id) {
+            bail!("cannot conflate undeclared output `{output_id}`");
+        }
+        self.control_channel
+            .set_conflation(output_id, enabled)
+            .wrap_err("failed to set conflation")
+    }
+
+    /// Declares an output that is not listed in the dataflow descriptor.
+    ///
+    /// The daemon routes the output to all inputs that are mapped to it in the
+    /// dataflow descriptor. Such mappings are only valid if the node sets
+    /// `dynamic_outputs: true` in the descriptor. Dynamic outputs can be
+    /// removed again through [`remove_output`](Self::remove_output), but a
+    /// removed output cannot be declared again.
+    pub fn declare_dynamic_output(&mut self, output_id: DataId) -> eyre::Result<()> {
+        if self.node_config.outputs.contains(&output_id) {
+            bail!("output {output_id} is already declared");
+        }
+        self.control_channel
+            .declare_output(output_id.clone())
+            .wrap_err("failed to declare output to daemon")?;
+        self.node_config.outputs.insert(output_id.clone());
+        self.warned_unknown_output.remove(&output_id);
+        self.dynamic_outputs.insert(output_id);
+
+        Ok(())
+    }
+
+    /// Removes an output that was declared through
+    /// [`declare_dynamic_output`](Self::declare_dynamic_output).
+    ///
+    /// Inputs mapped to the output are closed.
+    pub fn remove_output(&mut self, output_id: DataId) -> eyre::Result<()> {
+        if !self.dynamic_outputs.remove(&output_id) {
+            bail!("output {output_id} is not a dynamic output");
+        }
+        self.close_outputs(vec![output_id])
+    }
+
+    /// Unsubscribes the given input from its upstream output.
+    ///
+    /// The daemon stops routing new messages to the input and closes it, so the
+    /// event stream reports an [`Event::InputClosed`](crate::Event::InputClosed)
+    /// for it. Messages that were already sent to this node are still delivered
+    /// before the `InputClosed` event. The other inputs are not affected.
+    pub fn disconnect_input(&mut self, input_id: DataId) -> eyre::Result<()> {
+        self.control_channel
+            .disconnect_input(input_id)
+            .wrap_err("failed to disconnect input")
+    }
+
+    /// Subscribes to all outputs of the dataflow that match the given glob pattern.
+    ///
+    /// The pattern supports `*` and `?` wildcards, e.g. `camera_*`. Patterns
+    /// without a `/` are matched against the output IDs and the matching
+    /// outputs are delivered as inputs with the same ID. Patterns that contain
+    /// a `/`, e.g. `camera_*/image`, are matched against `<node>/<output>`, which
+    /// is also used as input ID. This allows subscribing to all outputs of the
+    /// nodes of a namespace, e.g. `perception/*` for the nodes
+    /// `perception/camera` and `perception/lidar`, see
+    /// [`NodeId::namespace`]. Outputs that are declared later through
+    /// [`declare_dynamic_output`](Self::declare_dynamic_output) are delivered
+    /// as well.
+    ///
+    /// The outputs of this node and outputs that are already mapped to an input
+    /// of the same ID are skipped. Only outputs of nodes on the same machine are
+    /// matched. Since the event stream is closed once all inputs are closed,
+    /// subscribing fails if the node has no open inputs anymore.
+    ///
+    /// Inputs subscribed this way use the default queue size of 1.
+    pub fn subscribe(&mut self, pattern: &str) -> eyre::Result<()> {
+        self.control_channel
+            .subscribe_pattern(pattern.to_owned())
+            .wrap_err_with(|| format!("failed to subscribe to `{pattern}`"))
+    }
+
+    /// Subscribes to notifications about nodes joining or leaving the dataflow,
+    /// and to their progress reports, see [`send_progress`](Self::send_progress).
+    ///
+    /// The notifications are delivered separately from the inputs, on the
+    /// [`EventStream::lifecycle_events`] channel. The daemon first reports a
+    /// [`LifecycleEvent::NodeJoined`](crate::LifecycleEvent::NodeJoined) event for
+    /// every node that already joined, including this node.
+    pub fn subscribe_lifecycle_events(&mut self) -> eyre::Result<()> {
+        self.control_channel
+            .subscribe_lifecycle_events()
+            .wrap_err("failed to subscribe to lifecycle events")
+    }
+
+    /// Returns the number of queued events for every edge of the dataflow.
+    ///
+    /// The depth of an edge is the number of events that the receiving node
+    /// buffered for the input, but did not process yet. Nodes report their
+    /// queue depths to the daemon periodically while receiving events through
+    /// [`EventStream::recv`] and related methods, so the returned values can be
+    /// slightly outdated. Only edges to nodes that run on the same machine as this
+    /// node are included.
+    ///
+    /// This allows finding the bottleneck of a dataflow: it's usually the node
+    /// behind the edges with the largest depths.
+    pub fn pipeline_depths(&mut self) -> eyre::Result<Vec<EdgeDepth>> {
+        self.control_channel.pipeline_depths()
+    }
+
+    /// Returns the estimated offset of the wall clock of the given node relative
+    /// to the wall clock of this node.
+    ///
+    /// The offset is positive if the clock of the other node is ahead. Subtract
+    /// it from the timestamps of the other node to compare them with timestamps
+    /// of this node. The estimate is zero for nodes on the same machine. For
+    /// other machines, it is derived from the clock synchronization of the
+    /// daemons with the dora-coordinator, which runs with every heartbeat. Fails
+    /// if the clocks were not synchronized yet, e.g. right after the start.
+    pub fn clock_skew(&mut self, node_id: NodeId) -> eyre::Result<ClockOffset> {
+        self.control_channel.clock_skew(node_id)
+    }
+
+    /// Pins the calling thread and the background threads of the node to the
+    /// given CPU cores.
+    ///
+    /// This keeps latency-critical nodes off the cores that are used by other
+    /// heavy processes, e.g. perception. The thread that receives the inputs
+    /// belongs to the [`EventStream`], pin it through
+    /// [`EventStream::set_affinity`]. Only supported on Linux, an error is
+    /// returned on other platforms.
+    pub fn set_affinity(&self, cores: &[usize]) -> eyre::Result<()> {
+        affinity::set_current_thread_affinity(cores)?;
+        self.drop_stream.set_affinity(cores)
+    }
+
+    /// Names the calling thread after the node, so that the node process can be
+    /// identified in `ps` and `top`.
+    ///
+    /// The title consists of the node ID and the beginning of the dataflow ID,
+    /// e.g. `camera@0a1b2c3d`, cut to the 15 bytes supported by Linux. When called
+    /// from the main thread, this renames the process. This is done automatically
+    /// by [`init_from_env`](Self::init_from_env). Does nothing on other platforms.
+    pub fn set_process_title(&self) -> eyre::Result<()> {
+        let title = process_title::node_title(&self.id, &self.dataflow_id);
+        process_title::set_current_thread_title(&title)
+    }
+
+    /// Returns the bytes per second that were sent on each output, averaged over
+    /// the last [`BANDWIDTH_WINDOW`](crate::BANDWIDTH_WINDOW).
+    ///
+    /// Only the payload is counted, not the metadata. Outputs that were never sent
+    /// are not included. See [`EventStream::input_bandwidth`] for the inputs.
+    pub fn output_bandwidth(&mut self) -> BTreeMap<DataId, f64> {
+        self.output_bandwidth.rates()
+    }
+
+    /// Returns the cumulative message counters of each output that was sent, see
+    /// [`openmetrics_text`](crate::openmetrics_text).
+    pub fn output_counters(&self) -> &BTreeMap<DataId, MessageCounters> {
+        &self.output_counters
+    }
+
+    /// Returns the number of outputs that were scheduled through
+    /// [`send_output_at`](Self::send_output_at), but not sent yet.
+    pub fn scheduled_output_count(&self) -> usize {
+        self.scheduled_outputs.len()
+    }
+
+    /// Discards all outputs that were scheduled, but not sent yet, and returns
+    /// their number.
+    pub fn clear_scheduled_outputs(&mut self) -> usize {
+        std::mem::take(&mut self.scheduled_outputs).len()
+    }
+
+    /// Returns the number of shared memory outputs that are still in use by
+    /// receivers, i.e. whose drop token was not reported as finished yet.
+    pub fn pending_drop_token_count(&self) -> usize {
+        self.sent_out_shared_memory.len()
+    }
+
+    /// Returns the number of shared memory regions that are kept for reuse by
+    /// later outputs.
+    pub fn shared_memory_cache_len(&self) -> usize {
+        self.cache.len()
+    }
+
+    /// Frees the shared memory regions that are kept for reuse by later outputs.
+    pub fn clear_shared_memory_cache(&mut self) {
+        self.cache.clear();
+    }
+
+    /// Sends a log message to the dora-coordinator, tagged with the ID of this node.
+    ///
+    /// The message is shown when attaching to the dataflow, e.g. through
+    /// `dora start --attach`, like the log messages of the daemon. The `target`
+    /// describes the source of the message, e.g. a module name or `stdout`.
+    pub fn log(
+        &mut self,
+        level: LogLevel,
+        target: Option<String>,
+        message: impl Into<String>,
+    ) -> eyre::Result<()> {
+        self.control_channel.log(level, target, message.into())
+    }
+
+    /// Reports the progress of a long-running task, e.g. a batch job, as a
+    /// fraction between `0.0` and `1.0`.
+    ///
+    /// The report is not sent as output, but delivered as
+    /// [`LifecycleEvent::Progress`](crate::LifecycleEvent::Progress) to the nodes
+    /// that [subscribed to lifecycle events](Self::subscribe_lifecycle_events),
+    /// e.g. a monitoring node that shows progress bars. The `task` distinguishes
+    /// several tasks of the same node.
+    pub fn send_progress(
+        &mut self,
+        task: impl Into<String>,
+        progress: f32,
+        message: Option<String>,
+    ) -> eyre::Result<()> {
+        if !(0.0..=1.0).contains(&progress) {
+            bail!("progress must be between 0.0 and 1.0, got {progress}");
+        }
+        self.control_channel
+            .report_progress(task.into(), progress, message)
+    }
+
+    /// Reports the lifetime statistics of the node, e.g. right before it closes.
+    ///
+    /// Like progress reports, the summary is not sent as output, but delivered as
+    /// [`LifecycleEvent::Summary`](crate::LifecycleEvent::Summary) to the nodes
+    /// that [subscribed to lifecycle events](Self::subscribe_lifecycle_events),
+    /// e.g. a collector of statistics. Use [`summarize`](crate::summarize) to
+    /// compute the summary from the counters of the node and its event stream.
+    pub fn send_summary(&mut self, summary: NodeSummary) -> eyre::Result<()> {
+        self.control_channel.report_summary(summary)
+    }
+
+    /// Acknowledges that the node finished its shutdown after receiving
+    /// [`Event::Stop`](crate::Event::Stop), e.g. after flushing its buffered
+    /// data.
+    ///
+    /// If the dataflow declares a
+    /// [`shutdown_order`](dora_core::descriptor::Descriptor::shutdown_order), the
+    /// daemon sends the stop event to the next node of the order only after this
+    /// acknowledgement, or after the node exited. Without a `shutdown_order`, the
+    /// acknowledgement has no effect.
+    pub fn ack_stop(&mut self) -> eyre::Result<()> {
+        self.control_channel.ack_stop()
+    }
+
     pub fn id(&self) -> &NodeId {
         &self.id
     }
@@ -328,12 +1118,57 @@ impl DoraNode {
         &self.dataflow_id
     }
 
+    /// Returns the time at which the dataflow was started.
+    ///
+    /// The start time is assigned by the dora-coordinator when it spawns the
+    /// dataflow, so it is the same for all nodes, regardless of when they joined.
+    /// It has millisecond precision. Returns `None` if the dataflow ID does not
+    /// contain a timestamp, e.g. for nodes created through
+    /// [`init_dry_run`](Self::init_dry_run).
+    pub fn dataflow_start_time(&self) -> Option<SystemTime> {
+        // dataflow IDs are time-ordered v7 UUIDs
+        let (secs, nanos) = self.dataflow_id.get_timestamp()?.to_unix();
+        Some(SystemTime::UNIX_EPOCH + Duration::new(secs, nanos))
+    }
+
+    /// Returns how long the dataflow has been running, see
+    /// [`dataflow_start_time`](Self::dataflow_start_time).
+    ///
+    /// The uptime is measured with the system clock of this machine, so it is
+    /// affected by clock differences to the machine of the dora-coordinator.
+    pub fn dataflow_uptime(&self) -> Option<Duration> {
+        let start = self.dataflow_start_time()?;
+        Some(SystemTime::now().duration_since(start).unwrap_or_default())
+    }
+
     pub fn node_config(&self) -> &NodeRunConfig {
         &self.node_config
     }
 
+    /// Reads the value stored for `key` in the persistent state of this node.
+    ///
+    /// The state is stored under the working directory, keyed by dataflow ID and
+    /// node ID, so it survives restarts of the node. Returns `None` if no value
+    /// was stored for `key` yet.
+    ///
+    /// The state is not locked across processes. If multiple instances of this
+    /// node run at the same time, the last write wins.
+    pub fn get_state(&self, key: &str) -> eyre::Result<Option<serde_json::Value>> {
+        let path = state::state_path(&self.dataflow_id, &self.id)?;
+        state::get(&path, key).wrap_err_with(|| format!("failed to get state `{key}`"))
+    }
+
+    /// Stores a scalar `value` for `key` in the persistent state of this node.
+    ///
+    /// Passing `None` removes `key` from the state. Arrays and objects are
+    /// rejected. See [`get_state`](Self::get_state) for details.
+    pub fn set_state(&mut self, key: &str, value: Option<serde_json::Value>) -> eyre::Result<()> {
+        let path = state::state_path(&self.dataflow_id, &self.id)?;
+        state::set(&path, key, value).wrap_err_with(|| format!("failed to set state `{key}`"))
+    }
+
     pub fn allocate_data_sample(&mut self, data_len: usize) -> eyre::Result<DataSample> {
-        let data = if data_len >= ZERO_COPY_THRESHOLD {
+        let data = if data_len >= ZERO_COPY_THRESHOLD && self.shared_memory {
             // create shared memory region
             let shared_memory = self.allocate_shared_memory(data_len)?;
 
@@ -408,11 +1243,57 @@ impl DoraNode {
     pub fn dataflow_descriptor(&self) -> &Descriptor {
         &self.dataflow_descriptor
     }
+
+    /// Returns the value of the dataflow-level parameter `name`.
+    ///
+    /// Parameters are declared in the top-level `params` section of the dataflow
+    /// descriptor. An environment variable named like the parameter in upper case
+    /// with the [`PARAM_ENV_PREFIX`], e.g. `DORA_PARAM_MAP_PATH` for `map_path`,
+    /// overrides the declared value. Overrides are returned as
+    /// [`ParamValue::String`], since their type is not known. This allows
+    /// changing a parameter for a single node through its `env` entries.
+    ///
+    /// Returns `None` if the parameter is neither declared nor overridden.
+    pub fn param(&self, name: &str) -> Option<ParamValue> {
+        if let Ok(value) = std::env::var(param_env_var(name)) {
+            return Some(ParamValue::String(value));
+        }
+        self.dataflow_descriptor.params.get(name).cloned()
+    }
+}
+
+/// Name of the environment variable that overrides the parameter `name`.
+fn param_env_var(name: &str) -> String {
+    format!("{PARAM_ENV_PREFIX}{}", name.to_uppercase())
 }
 
 impl Drop for DoraNode {
     #[tracing::instrument(skip(self), fields(self.id = %self.id), level = "trace")]
     fn drop(&mut self) {
+        // send pending scheduled outputs at their target time, if it is near
+        let deadline = SystemTime::now() + SCHEDULED_OUTPUTS_DROP_TIMEOUT;
+        while let Some(&(at, _)) = self.scheduled_outputs.keys().next() {
+            if at > deadline {
+                tracing::warn!(
+                    "discarding {} scheduled outputs that are not due within {:?}",
+                    self.scheduled_outputs.len(),
+                    SCHEDULED_OUTPUTS_DROP_TIMEOUT
+                );
+                self.scheduled_outputs.clear();
+                break;
+            }
+            if let Ok(remaining) = at.duration_since(SystemTime::now()) {
+                std::thread::sleep(remaining);
+            }
+            if let Err(err) = self
+                .send_scheduled_outputs()
+                .context("failed to send scheduled outputs on drop")
+            {
+                tracing::warn!("{err:?}");
+                break;
+            }
+        }
+
         // close all outputs first to notify subscribers as early as possible
         if let Err(err) = self
             .control_channel
@@ -463,6 +1344,13 @@ impl Drop for DoraNode {
     }
 }
 
+struct ScheduledOutput {
+    output_id: DataId,
+    type_info: ArrowTypeInfo,
+    parameters: MetadataParameters,
+    sample: Option<DataSample>,
+}
+
 pub struct DataSample {
     inner: DataSampleInner,
     len: usize,
@@ -552,3 +1440,92 @@ impl DerefMut for ShmemHandle {
 
 unsafe impl Send for ShmemHandle {}
 unsafe impl Sync for ShmemHandle {}
+
+fn check_attachments_size(output_id: &DataId, parameters: &MetadataParameters) -> eyre::Result<()> {
+    let size = attachments_size(parameters);
+    if size > MAX_ATTACHMENTS_SIZE {
+        bail!(
+            "attachments of output `{output_id}` are too large ({size} bytes, \
+            maximum is {MAX_ATTACHMENTS_SIZE} bytes)"
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn node_with_params(params: &[(&str, ParamValue)]) -> DoraNode {
+        let (mut node, _events, _transport) =
+            DoraNode::init_dry_run(NodeId::from("test".to_owned())).unwrap();
+        for (name, value) in params {
+            node.dataflow_descriptor
+                .params
+                .insert(name.to_string(), value.clone());
+        }
+        node
+    }
+
+    #[test]
+    fn params_are_overridden_by_prefixed_env_vars() {
+        let node = node_with_params(&[("override_test", ParamValue::Integer(1))]);
+        std::env::set_var("DORA_PARAM_OVERRIDE_TEST", "2");
+        assert_eq!(
+            node.param("override_test"),
+            Some(ParamValue::String("2".to_owned()))
+        );
+        // unrelated env vars without the prefix are ignored
+        std::env::set_var("UNPREFIXED_TEST", "3");
+        assert_eq!(node.param("unprefixed_test"), None);
+    }
+
+    #[test]
+    fn dropped_count_is_not_forwarded() {
+        let (mut node, _events, transport) =
+            DoraNode::init_dry_run(NodeId::from("test".to_owned())).unwrap();
+        let mut parameters = MetadataParameters::default();
+        parameters.insert(DROPPED_COUNT.to_owned(), Parameter::Integer(3));
+        node.send_output(
+            "out".to_owned().into(),
+            parameters,
+            arrow::array::UInt8Array::from(vec![1]),
+        )
+        .unwrap();
+        let sent = transport.take_sent_outputs();
+        assert_eq!(sent[0].metadata.dropped_count(), 0);
+    }
+
+    #[test]
+    fn reliable_outputs_take_one_sequence_number() {
+        let (mut node, _events, transport) =
+            DoraNode::init_dry_run(NodeId::from("test".to_owned())).unwrap();
+        let output_id: DataId = "out".to_owned().into();
+        node.send_output_reliable(
+            output_id.clone(),
+            Default::default(),
+            arrow::array::UInt8Array::from(vec![1]),
+            Duration::from_secs(1),
+        )
+        .unwrap();
+        node.send_output(
+            output_id,
+            Default::default(),
+            arrow::array::UInt8Array::from(vec![2]),
+        )
+        .unwrap();
+        let sent = transport.take_sent_outputs();
+        let sequence_numbers: Vec<_> = sent
+            .iter()
+            .map(|output| output.metadata.sequence_number())
+            .collect();
+        assert_eq!(sequence_numbers, [Some(0), Some(1)]);
+    }
+
+    #[test]
+    fn params_fall_back_to_descriptor() {
+        let node = node_with_params(&[("fallback_test", ParamValue::Float(1.5))]);
+        assert_eq!(node.param("fallback_test"), Some(ParamValue::Float(1.5)));
+        assert_eq!(node.param("undeclared_test"), None);
+    }
+}
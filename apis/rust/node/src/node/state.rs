@@ -0,0 +1,95 @@
+//! Node-local key-value state that is persisted on disk.
+//!
+//! The state of a node is stored as a JSON object in
+//! `out/<dataflow_id>/state/<node_id>.json`, relative to the working directory
+//! of the node. Every update rewrites the whole file through a temporary file
+//! and an atomic rename, so readers never observe a partially written state.
+//!
+//! There is no locking between processes: if multiple instances of the same
+//! node run in parallel (e.g. a dynamic node started twice), the last write
+//! wins and concurrent updates of different keys may be lost.
+
+use std::{
+    collections::BTreeMap,
+    fs,
+    io::ErrorKind,
+    path::{Path, PathBuf},
+};
+
+use dora_core::config::NodeId;
+use dora_message::DataflowId;
+use eyre::{bail, Context};
+use serde_json::Value;
+
+pub(crate) fn state_path(dataflow_id: &DataflowId, node_id: &NodeId) -> eyre::Result<PathBuf> {
+    let working_dir = std::env::current_dir().context("failed to get current working dir")?;
+    Ok(working_dir
+        .join("out")
+        .join(dataflow_id.to_string())
+        .join("state")
+        .join(format!("{}.json", node_id.to_file_name())))
+}
+
+pub(crate) fn get(path: &Path, key: &str) -> eyre::Result<Option<Value>> {
+    Ok(read(path)?.remove(key))
+}
+
+pub(crate) fn set(path: &Path, key: &str, value: Option<Value>) -> eyre::Result<()> {
+    if let Some(value) = &value {
+        if value.is_array() || value.is_object() {
+            bail!("state values must be scalars, got `{value}` for key `{key}`");
+        }
+    }
+
+    let mut state = read(path)?;
+    match value {
+        Some(value) => state.insert(key.to_owned(), value),
+        None => state.remove(key),
+    };
+
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("failed to create state dir `{}`", parent.display()))?;
+    }
+    let serialized = serde_json::to_vec_pretty(&state).context("failed to serialize state")?;
+    let tmp_path = path.with_extension("json.tmp");
+    fs::write(&tmp_path, serialized)
+        .with_context(|| format!("failed to write state to `{}`", tmp_path.display()))?;
+    fs::rename(&tmp_path, path)
+        .with_context(|| format!("failed to move state file to `{}`", path.display()))
+}
+
+fn read(path: &Path) -> eyre::Result<BTreeMap<String, Value>> {
+    match fs::read(path) {
+        Ok(raw) => serde_json::from_slice(&raw)
+            .with_context(|| format!("failed to parse state file `{}`", path.display())),
+        Err(err) if err.kind() == ErrorKind::NotFound => Ok(BTreeMap::new()),
+        Err(err) => {
+            Err(err).with_context(|| format!("failed to read state file `{}`", path.display()))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn set_and_get_state() {
+        let dir = std::env::temp_dir().join(format!("dora-state-test-{}", std::process::id()));
+        let path = dir.join("state").join("node.json");
+
+        assert_eq!(get(&path, "calibration").unwrap(), None);
+        set(&path, "calibration", Some(Value::from(0.5))).unwrap();
+        set(&path, "name", Some(Value::from("camera"))).unwrap();
+        assert_eq!(get(&path, "calibration").unwrap(), Some(Value::from(0.5)));
+        assert_eq!(get(&path, "name").unwrap(), Some(Value::from("camera")));
+
+        set(&path, "name", None).unwrap();
+        assert_eq!(get(&path, "name").unwrap(), None);
+
+        assert!(set(&path, "list", Some(Value::from(vec![1, 2]))).is_err());
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
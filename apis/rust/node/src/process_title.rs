@@ -0,0 +1,78 @@
+//! Naming of node processes, so that they can be told apart in `ps` and `top`.
+//!
+//! Only supported on Linux, where the name of the main thread is shown as the
+//! process name. The kernel limits names to 15 bytes.
+
+use dora_core::config::NodeId;
+use dora_message::DataflowId;
+
+/// Maximum length of a thread name on Linux, without the terminating NUL byte.
+const MAX_TITLE_LEN: usize = 15;
+
+/// The process title of the given node.
+///
+/// Starts with the node ID, which is the most useful part if the title is cut
+/// off, followed by the beginning of the dataflow ID.
+pub(crate) fn node_title(node_id: &NodeId, dataflow_id: &DataflowId) -> String {
+    let dataflow = dataflow_id.simple().to_string();
+    truncate(&format!("{node_id}@{}", &dataflow[..8])).to_owned()
+}
+
+/// Cuts the title to the maximum length, at a character boundary.
+fn truncate(title: &str) -> &str {
+    let mut end = title.len().min(MAX_TITLE_LEN);
+    while !title.is_char_boundary(end) {
+        end -= 1;
+    }
+    &title[..end]
+}
+
+/// Sets the name of the calling thread, which is the process name when called
+/// from the main thread.
+#[cfg(target_os = "linux")]
+pub(crate) fn set_current_thread_title(title: &str) -> eyre::Result<()> {
+    use eyre::Context;
+
+    let name = std::ffi::CString::new(truncate(title))
+        .wrap_err("process title must not contain NUL bytes")?;
+    let result = unsafe { libc::prctl(libc::PR_SET_NAME, name.as_ptr(), 0, 0, 0) };
+    if result != 0 {
+        return Err(std::io::Error::last_os_error()).wrap_err("failed to set process title");
+    }
+    Ok(())
+}
+
+#[cfg(not(target_os = "linux"))]
+pub(crate) fn set_current_thread_title(_title: &str) -> eyre::Result<()> {
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn title_starts_with_node_id() {
+        let dataflow_id = DataflowId::from_u128(0x0123_4567_89ab_cdef_0123_4567_89ab_cdef);
+        let title = node_title(&NodeId::from("camera".to_owned()), &dataflow_id);
+        assert_eq!(title, "camera@01234567");
+
+        let title = node_title(&NodeId::from("object-detection".to_owned()), &dataflow_id);
+        assert_eq!(title, "object-detectio");
+
+        assert_eq!(truncate("kamera-überwachung"), "kamera-überwac");
+        assert_eq!(truncate("kamera-überwachung").len(), 15);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn thread_title_is_set() {
+        std::thread::spawn(|| {
+            set_current_thread_title("dora-test-title-long").unwrap();
+            let comm = std::fs::read_to_string("/proc/thread-self/comm").unwrap();
+            assert_eq!(comm.trim_end(), "dora-test-title");
+        })
+        .join()
+        .unwrap();
+    }
+}
@@ -529,7 +529,9 @@ fn run(args: Args) -> eyre::Result<()> {
             })
             .context("failed to run dora-daemon")?
         }
-        Command::Runtime => dora_runtime::main().context("Failed to run dora-runtime")?,
+        Command::Runtime => {
+            dora_runtime::main().context("Failed to run dora-runtime")?;
+        }
     };
 
     Ok(())
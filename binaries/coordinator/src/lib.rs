@@ -9,6 +9,7 @@ use dora_core::{
 };
 use dora_message::{
     cli_to_coordinator::ControlRequest,
+    common::{wall_clock_ns, ClockOffset},
     coordinator_to_cli::{
         ControlRequestReply, DataflowIdAndName, DataflowList, DataflowListEntry, DataflowResult,
         DataflowStatus, LogMessage,
@@ -200,6 +201,7 @@ async fn start_inner(
                                     stream: connection,
                                     listen_socket: (ip, listen_port).into(),
                                     last_heartbeat: Instant::now(),
+                                    clock_offset: None,
                                 },
                             );
                             if let Some(_previous) = previous {
@@ -513,18 +515,24 @@ async fn start_inner(
                                     name: d.name.clone(),
                                 },
                                 status: DataflowStatus::Running,
+                                node_count: d.nodes.len(),
                             });
                             let finished_failed =
                                 dataflow_results.iter().map(|(&uuid, results)| {
-                                    let name =
-                                        archived_dataflows.get(&uuid).and_then(|d| d.name.clone());
+                                    let archived = archived_dataflows.get(&uuid);
+                                    let name = archived.and_then(|d| d.name.clone());
+                                    let node_count = archived.map_or(0, |d| d.nodes.len());
                                     let id = DataflowIdAndName { uuid, name };
                                     let status = if results.values().all(|r| r.is_ok()) {
                                         DataflowStatus::Finished
                                     } else {
                                         DataflowStatus::Failed
                                     };
-                                    DataflowListEntry { id, status }
+                                    DataflowListEntry {
+                                        id,
+                                        status,
+                                        node_count,
+                                    }
                                 });
 
                             let reply = Ok(ControlRequestReply::DataflowList(DataflowList(
@@ -564,6 +572,12 @@ async fn start_inner(
                 }
             },
             Event::DaemonHeartbeatInterval => {
+                let clock_offsets: BTreeMap<_, _> = daemon_connections
+                    .iter()
+                    .filter_map(|(machine_id, connection)| {
+                        Some((machine_id.clone(), connection.clock_offset?))
+                    })
+                    .collect();
                 let mut disconnected = BTreeSet::new();
                 for (machine_id, connection) in &mut daemon_connections {
                     if connection.last_heartbeat.elapsed() > Duration::from_secs(15) {
@@ -578,7 +592,11 @@ async fn start_inner(
                     }
                     let result: eyre::Result<()> = tokio::time::timeout(
                         Duration::from_millis(500),
-                        send_heartbeat_message(&mut connection.stream, clock.new_timestamp()),
+                        send_heartbeat_message(
+                            &mut connection.stream,
+                            clock_offsets.clone(),
+                            clock.new_timestamp(),
+                        ),
                     )
                     .await
                     .wrap_err("timeout")
@@ -614,6 +632,11 @@ async fn start_inner(
                     connection.last_heartbeat = Instant::now();
                 }
             }
+            Event::DaemonClockSync { machine_id, offset } => {
+                if let Some(connection) = daemon_connections.get_mut(&machine_id) {
+                    connection.clock_offset = Some(offset);
+                }
+            }
             Event::Log(message) => {
                 if let Some(dataflow) = running_dataflows.get_mut(&message.dataflow_id) {
                     for subscriber in &mut dataflow.log_subscribers {
@@ -661,6 +684,9 @@ struct DaemonConnection {
     stream: TcpStream,
     listen_socket: SocketAddr,
     last_heartbeat: Instant,
+    /// Latest estimate of the offset of the machine's wall clock relative to
+    /// the coordinator.
+    clock_offset: Option<ClockOffset>,
 }
 
 async fn handle_destroy(
@@ -687,8 +713,11 @@ async fn handle_destroy(
     result
 }
 
+/// Sends a heartbeat to the daemon, followed by a clock synchronization request
+/// that carries the latest clock offsets of all machines.
 async fn send_heartbeat_message(
     connection: &mut TcpStream,
+    clock_offsets: BTreeMap<String, ClockOffset>,
     timestamp: uhlc::Timestamp,
 ) -> eyre::Result<()> {
     let message = serde_json::to_vec(&Timestamped {
@@ -699,7 +728,20 @@ async fn send_heartbeat_message(
 
     tcp_send(connection, &message)
         .await
-        .wrap_err("failed to send heartbeat message to daemon")
+        .wrap_err("failed to send heartbeat message to daemon")?;
+
+    let message = serde_json::to_vec(&Timestamped {
+        inner: DaemonCoordinatorEvent::ClockSync {
+            coordinator_time_ns: wall_clock_ns(),
+            offsets: clock_offsets,
+        },
+        timestamp,
+    })
+    .context("Could not serialize clock sync message")?;
+
+    tcp_send(connection, &message)
+        .await
+        .wrap_err("failed to send clock sync message to daemon")
 }
 
 struct RunningDataflow {
@@ -979,8 +1021,17 @@ async fn destroy_daemons(
 pub enum Event {
     NewDaemonConnection(TcpStream),
     DaemonConnectError(eyre::Report),
-    DaemonHeartbeat { machine_id: String },
-    Dataflow { uuid: Uuid, event: DataflowEvent },
+    DaemonHeartbeat {
+        machine_id: String,
+    },
+    DaemonClockSync {
+        machine_id: String,
+        offset: ClockOffset,
+    },
+    Dataflow {
+        uuid: Uuid,
+        event: DataflowEvent,
+    },
     Control(ControlEvent),
     Daemon(DaemonRequest),
     DaemonHeartbeatInterval,
@@ -994,6 +1045,7 @@ impl Event {
     pub fn log(&self) -> bool {
         match self {
             Event::DaemonHeartbeatInterval => false,
+            Event::DaemonClockSync { .. } => false,
             _ => true,
         }
     }
@@ -1,6 +1,9 @@
 use crate::{tcp_utils::tcp_receive, DaemonRequest, DataflowEvent, Event};
 use dora_core::uhlc::HLC;
-use dora_message::daemon_to_coordinator::{CoordinatorRequest, DaemonEvent, Timestamped};
+use dora_message::{
+    common::{wall_clock_ns, ClockOffset},
+    daemon_to_coordinator::{CoordinatorRequest, DaemonEvent, Timestamped},
+};
 use eyre::Context;
 use std::{io::ErrorKind, net::SocketAddr, sync::Arc};
 use tokio::{
@@ -100,6 +103,22 @@ pub async fn handle_connection(
                         break;
                     }
                 }
+                DaemonEvent::ClockSync {
+                    request_sent_ns,
+                    received_ns,
+                    sent_ns,
+                } => {
+                    let offset = ClockOffset::from_exchange(
+                        request_sent_ns,
+                        received_ns,
+                        sent_ns,
+                        wall_clock_ns(),
+                    );
+                    let event = Event::DaemonClockSync { machine_id, offset };
+                    if events_tx.send(event).await.is_err() {
+                        break;
+                    }
+                }
             },
         };
     }
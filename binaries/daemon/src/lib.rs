@@ -11,15 +11,20 @@ use dora_core::{
     uhlc::{self, HLC},
 };
 use dora_message::{
-    common::{DataMessage, DropToken, LogLevel, NodeError, NodeErrorCause, NodeExitStatus},
+    common::{
+        wall_clock_ns, ClockOffset, DataMessage, DropToken, LogLevel, NodeError, NodeErrorCause,
+        NodeExitStatus,
+    },
     coordinator_to_cli::DataflowResult,
     coordinator_to_daemon::{DaemonCoordinatorEvent, SpawnDataflowNodes},
     daemon_to_coordinator::{
         CoordinatorRequest, DaemonCoordinatorReply, DaemonEvent, DataflowDaemonResult, LogMessage,
     },
     daemon_to_daemon::InterDaemonEvent,
-    daemon_to_node::{DaemonReply, NodeConfig, NodeDropEvent, NodeEvent},
-    metadata::{self, ArrowTypeInfo},
+    daemon_to_node::{
+        DaemonReply, EdgeDepth, LifecycleEvent, NodeConfig, NodeDropEvent, NodeEvent, NodeSummary,
+    },
+    metadata::{self, ArrowTypeInfo, DeadLetterReason},
     node_to_daemon::{DynamicNodeEvent, Timestamped},
     DataflowId,
 };
@@ -33,7 +38,7 @@ use pending::PendingNodes;
 use shared_memory_server::ShmemConf;
 use socket_stream_utils::socket_stream_send;
 use std::{
-    collections::{BTreeMap, BTreeSet, HashMap},
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque},
     net::SocketAddr,
     path::{Path, PathBuf},
     pin::pin,
@@ -71,6 +76,8 @@ use tracing_opentelemetry::OpenTelemetrySpanExt;
 use crate::pending::DataflowStatus;
 
 const STDERR_LOG_LINES: usize = 10;
+/// Time after which nodes are killed if they don't stop after a stop request.
+const DEFAULT_GRACE_DURATION: Duration = Duration::from_millis(15000);
 
 pub struct Daemon {
     running: HashMap<DataflowId, RunningDataflow>,
@@ -82,6 +89,9 @@ pub struct Daemon {
     last_coordinator_heartbeat: Instant,
     inter_daemon_connections: BTreeMap<String, InterDaemonConnection>,
     machine_id: String,
+    /// Offsets of the wall clocks of all machines relative to the coordinator,
+    /// as last estimated by the coordinator.
+    clock_offsets: BTreeMap<String, ClockOffset>,
 
     /// used for testing and examples
     exit_when_done: Option<BTreeSet<(Uuid, NodeId)>>,
@@ -234,6 +244,7 @@ impl Daemon {
             last_coordinator_heartbeat: Instant::now(),
             inter_daemon_connections: BTreeMap::new(),
             machine_id,
+            clock_offsets: BTreeMap::new(),
             exit_when_done,
             dataflow_node_results: BTreeMap::new(),
             clock,
@@ -309,7 +320,12 @@ impl Daemon {
                     tracing::info!("received ctrlc signal -> stopping all dataflows");
                     for dataflow in self.running.values_mut() {
                         dataflow
-                            .stop_all(&mut self.coordinator_connection, &self.clock, None)
+                            .stop_all(
+                                &mut self.coordinator_connection,
+                                &self.clock,
+                                None,
+                                &self.events_tx,
+                            )
                             .await?;
                     }
                 }
@@ -521,6 +537,7 @@ impl Daemon {
                             &mut self.coordinator_connection,
                             &self.clock,
                             grace_duration,
+                            &self.events_tx,
                         );
                         (Ok(()), Some(future))
                     }
@@ -558,6 +575,31 @@ impl Daemon {
                 let _ = reply_tx.send(None);
                 RunStatus::Continue
             }
+            DaemonCoordinatorEvent::ClockSync {
+                coordinator_time_ns,
+                offsets,
+            } => {
+                let received_ns = wall_clock_ns();
+                let _ = reply_tx.send(None);
+                self.clock_offsets = offsets;
+                if let Some(connection) = &mut self.coordinator_connection {
+                    let msg = serde_json::to_vec(&Timestamped {
+                        inner: CoordinatorRequest::Event {
+                            machine_id: self.machine_id.clone(),
+                            event: DaemonEvent::ClockSync {
+                                request_sent_ns: coordinator_time_ns,
+                                received_ns,
+                                sent_ns: wall_clock_ns(),
+                            },
+                        },
+                        timestamp: self.clock.new_timestamp(),
+                    })?;
+                    socket_stream_send(connection, &msg)
+                        .await
+                        .wrap_err("failed to send clock sync message to dora-coordinator")?;
+                }
+                RunStatus::Continue
+            }
         };
         Ok(status)
     }
@@ -636,10 +678,14 @@ impl Daemon {
                 bail!("there is already a running dataflow with ID `{dataflow_id}`")
             }
         };
+        dataflow.shutdown_order = dataflow_descriptor.shutdown_order.clone();
 
         let mut log_messages = Vec::new();
         for node in nodes {
             let local = node.deploy.machine == self.machine_id;
+            dataflow
+                .node_machines
+                .insert(node.id.clone(), node.deploy.machine.clone());
 
             let inputs = node_inputs(&node);
             for (input_id, input) in inputs {
@@ -676,6 +722,12 @@ impl Daemon {
                 }
             }
             if local {
+                let outputs = node.kind.run_config().outputs;
+                dataflow.open_outputs.extend(
+                    outputs
+                        .into_iter()
+                        .map(|output| OutputId(node.id.clone(), output)),
+                );
                 if node.kind.dynamic() {
                     dataflow.dynamic_nodes.insert(node.id.clone());
                 } else {
@@ -744,25 +796,33 @@ impl Daemon {
     ) -> eyre::Result<()> {
         match event {
             DynamicNodeEventWrapper {
-                event: DynamicNodeEvent::NodeConfig { node_id },
+                event:
+                    DynamicNodeEvent::NodeConfig {
+                        node_id,
+                        dataflow_id,
+                    },
                 reply_tx,
             } => {
+                let matches = |id: &DataflowId, dataflow: &RunningDataflow| {
+                    dataflow_id.map_or(true, |dataflow_id| *id == dataflow_id)
+                        && dataflow.running_nodes.contains_key(&node_id)
+                };
                 let number_node_id = self
                     .running
                     .iter()
-                    .filter(|(_id, dataflow)| dataflow.running_nodes.contains_key(&node_id))
+                    .filter(|(id, dataflow)| matches(id, dataflow))
                     .count();
 
                 let node_config = match number_node_id {
                     2.. => Err(format!(
                         "multiple dataflows contains dynamic node id {node_id}. \
                         Please only have one running dataflow with the specified \
-                        node id if you want to use dynamic node",
+                        node id or pass the dataflow ID if you want to use dynamic node",
                     )),
                     1 => self
                         .running
                         .iter()
-                        .filter(|(_id, dataflow)| dataflow.running_nodes.contains_key(&node_id))
+                        .filter(|(id, dataflow)| matches(id, dataflow))
                         .map(|(id, dataflow)| -> Result<NodeConfig> {
                             let node_config = dataflow
                                 .running_nodes
@@ -806,6 +866,7 @@ impl Daemon {
         match event {
             DaemonNodeEvent::Subscribe {
                 event_sender,
+                take_over,
                 reply_sender,
             } => {
                 let dataflow = self.running.get_mut(&dataflow_id).ok_or_else(|| {
@@ -816,6 +877,26 @@ impl Daemon {
                     Err(err) => {
                         let _ = reply_sender.send(DaemonReply::Result(Err(err)));
                     }
+                    Ok(dataflow)
+                        if take_over && dataflow.subscribe_channels.contains_key(&node_id) =>
+                    {
+                        // the dataflow is running already, so the node is not pending
+                        tracing::info!("node `{node_id}` took over its existing subscription");
+                        Self::subscribe(dataflow, node_id, event_sender, &self.clock).await;
+                        let _ = reply_sender.send(DaemonReply::Result(Ok(())));
+                    }
+                    Ok(dataflow)
+                        if dataflow
+                            .subscribe_channels
+                            .get(&node_id)
+                            .is_some_and(|channel| !channel.is_closed()) =>
+                    {
+                        tracing::warn!(
+                            "rejected second subscription of node `{node_id}`, \
+                            is the node ID used by multiple processes?"
+                        );
+                        let _ = reply_sender.send(DaemonReply::DuplicateNodeId(node_id));
+                    }
                     Ok(dataflow) => {
                         tracing::info!("node `{node_id}` is ready");
                         Self::subscribe(dataflow, node_id.clone(), event_sender, &self.clock).await;
@@ -880,8 +961,206 @@ impl Daemon {
                 };
 
                 let reply = inner.await.map_err(|err| format!("{err:?}"));
+                if let Some(dataflow) = self.running.get_mut(&dataflow_id) {
+                    dataflow
+                        .dynamic_outputs
+                        .retain(|OutputId(source_id, output_id)| {
+                            !(source_id == &node_id && outputs.contains(output_id))
+                        });
+                }
                 let _ = reply_sender.send(DaemonReply::Result(reply));
             }
+            DaemonNodeEvent::DeclareOutput {
+                output,
+                reply_sender,
+            } => {
+                let result = match self.running.get_mut(&dataflow_id) {
+                    Some(dataflow) => dataflow.declare_output(OutputId(node_id, output)),
+                    None => Err(eyre!(
+                        "failed to declare output: no running dataflow with ID `{dataflow_id}`"
+                    )),
+                };
+                let _ = reply_sender.send(DaemonReply::Result(
+                    result.map_err(|err| format!("{err:?}")),
+                ));
+            }
+            DaemonNodeEvent::DisconnectInput {
+                input,
+                reply_sender,
+            } => {
+                let result = match self.running.get_mut(&dataflow_id) {
+                    Some(dataflow) => dataflow.disconnect_input(node_id, input, &self.clock),
+                    None => Err(eyre!(
+                        "failed to disconnect input: no running dataflow with ID `{dataflow_id}`"
+                    )),
+                };
+                let _ = reply_sender.send(DaemonReply::Result(
+                    result.map_err(|err| format!("{err:?}")),
+                ));
+            }
+            DaemonNodeEvent::SubscribePattern {
+                pattern,
+                reply_sender,
+            } => {
+                let result = match self.running.get_mut(&dataflow_id) {
+                    Some(dataflow) => dataflow.subscribe_pattern(node_id, pattern),
+                    None => Err(eyre!(
+                        "failed to subscribe to pattern: no running dataflow with ID `{dataflow_id}`"
+                    )),
+                };
+                let _ = reply_sender.send(DaemonReply::Result(
+                    result.map_err(|err| format!("{err:?}")),
+                ));
+            }
+            DaemonNodeEvent::SetDeadLetterOutput {
+                output,
+                reply_sender,
+            } => {
+                let result = match self.running.get_mut(&dataflow_id) {
+                    Some(dataflow) => dataflow.set_dead_letter_output(node_id, output),
+                    None => Err(eyre!(
+                        "failed to set dead-letter output: no running dataflow with ID `{dataflow_id}`"
+                    )),
+                };
+                let _ = reply_sender.send(DaemonReply::Result(
+                    result.map_err(|err| format!("{err:?}")),
+                ));
+            }
+            DaemonNodeEvent::SetConflation {
+                output_id,
+                enabled,
+                reply_sender,
+            } => {
+                let result = match self.running.get_mut(&dataflow_id) {
+                    Some(dataflow) => dataflow.set_conflation(node_id, output_id, enabled),
+                    None => Err(eyre!(
+                        "failed to set conflation: no running dataflow with ID `{dataflow_id}`"
+                    )),
+                };
+                let _ = reply_sender.send(DaemonReply::Result(
+                    result.map_err(|err| format!("{err:?}")),
+                ));
+            }
+            DaemonNodeEvent::SubscribeLifecycleEvents { reply_sender } => {
+                let result = match self.running.get_mut(&dataflow_id) {
+                    Some(dataflow) => {
+                        dataflow.subscribe_lifecycle_events(node_id, &self.clock)
+                    }
+                    None => Err(eyre!(
+                        "failed to subscribe to lifecycle events: no running dataflow with ID `{dataflow_id}`"
+                    )),
+                };
+                let _ = reply_sender.send(DaemonReply::Result(
+                    result.map_err(|err| format!("{err:?}")),
+                ));
+            }
+            DaemonNodeEvent::ReportQueueDepths { depths } => {
+                match self.running.get_mut(&dataflow_id) {
+                    Some(dataflow) => {
+                        dataflow.queue_depths.insert(node_id, depths);
+                    }
+                    None => tracing::warn!(
+                        "failed to record queue depths: no running dataflow with ID `{dataflow_id}`"
+                    ),
+                }
+            }
+            DaemonNodeEvent::ReportProgress {
+                task,
+                progress,
+                message,
+            } => match self.running.get(&dataflow_id) {
+                Some(dataflow) => dataflow.send_lifecycle_event(
+                    LifecycleEvent::Progress {
+                        node_id,
+                        task,
+                        progress,
+                        message,
+                    },
+                    &self.clock,
+                ),
+                None => tracing::warn!(
+                    "failed to report progress: no running dataflow with ID `{dataflow_id}`"
+                ),
+            },
+            DaemonNodeEvent::AckStop => match self.running.get_mut(&dataflow_id) {
+                Some(dataflow) => dataflow.node_stopped(&node_id, &self.clock, &self.events_tx),
+                None => tracing::warn!(
+                    "failed to acknowledge stop: no running dataflow with ID `{dataflow_id}`"
+                ),
+            },
+            DaemonNodeEvent::ReportSummary { summary } => match self.running.get(&dataflow_id) {
+                Some(dataflow) => dataflow.send_lifecycle_event(
+                    LifecycleEvent::Summary { node_id, summary },
+                    &self.clock,
+                ),
+                None => tracing::warn!(
+                    "failed to report summary: no running dataflow with ID `{dataflow_id}`"
+                ),
+            },
+            DaemonNodeEvent::Log {
+                level,
+                target,
+                message,
+            } => {
+                self.send_log_message(LogMessage {
+                    dataflow_id,
+                    node_id: Some(node_id),
+                    level,
+                    target,
+                    module_path: None,
+                    file: None,
+                    line: None,
+                    message,
+                })
+                .await?;
+            }
+            DaemonNodeEvent::ClockSkew {
+                node_id: other,
+                reply_sender,
+            } => {
+                let reply = match self.clock_skew(dataflow_id, &other) {
+                    Ok(skew) => DaemonReply::ClockSkew(skew),
+                    Err(err) => DaemonReply::Result(Err(format!("{err:?}"))),
+                };
+                let _ = reply_sender.send(reply);
+            }
+            DaemonNodeEvent::PipelineDepths { reply_sender } => {
+                let reply = match self.running.get(&dataflow_id) {
+                    Some(dataflow) => DaemonReply::PipelineDepths(dataflow.pipeline_depths()),
+                    None => DaemonReply::Result(Err(format!(
+                        "failed to get pipeline depths: no running dataflow with ID `{dataflow_id}`"
+                    ))),
+                };
+                let _ = reply_sender.send(reply);
+            }
+            DaemonNodeEvent::AckInputs { acks } => match self.running.get_mut(&dataflow_id) {
+                Some(dataflow) => {
+                    for (input, message_id) in acks {
+                        dataflow.ack_input(node_id.clone(), input, message_id);
+                    }
+                }
+                None => tracing::warn!(
+                    "failed to acknowledge input: no running dataflow with ID `{dataflow_id}`"
+                ),
+            },
+            DaemonNodeEvent::CheckAcked {
+                output_id,
+                message_id,
+                discard,
+                reply_sender,
+            } => {
+                let reply = match self.running.get_mut(&dataflow_id) {
+                    Some(dataflow) => DaemonReply::Acked(dataflow.check_acked(
+                        OutputId(node_id, output_id),
+                        message_id,
+                        discard,
+                    )),
+                    None => DaemonReply::Result(Err(format!(
+                        "failed to check acknowledgements: no running dataflow with ID `{dataflow_id}`"
+                    ))),
+                };
+                let _ = reply_sender.send(reply);
+            }
             DaemonNodeEvent::OutputsDone { reply_sender } => {
                 let result = match self.running.get_mut(&dataflow_id) {
                     Some(dataflow) => {
@@ -948,6 +1227,29 @@ impl Daemon {
         Ok(())
     }
 
+    /// Estimates the offset of the wall clock of the machine that runs `node_id`
+    /// relative to the wall clock of this machine.
+    fn clock_skew(&self, dataflow_id: Uuid, node_id: &NodeId) -> eyre::Result<ClockOffset> {
+        let dataflow = self
+            .running
+            .get(&dataflow_id)
+            .wrap_err_with(|| format!("no running dataflow with ID `{dataflow_id}`"))?;
+        let machine = dataflow
+            .node_machines
+            .get(node_id)
+            .wrap_err_with(|| format!("no node `{node_id}` in dataflow `{dataflow_id}`"))?;
+        if *machine == self.machine_id {
+            return Ok(ClockOffset::default());
+        }
+        let offset = |machine: &str| {
+            self.clock_offsets
+                .get(machine)
+                .copied()
+                .wrap_err_with(|| format!("clock of machine `{machine}` was not synchronized yet"))
+        };
+        Ok(offset(machine)?.relative_to(offset(&self.machine_id)?))
+    }
+
     async fn send_reload(
         &mut self,
         dataflow_id: Uuid,
@@ -979,6 +1281,22 @@ impl Daemon {
         let dataflow = self.running.get_mut(&dataflow_id).wrap_err_with(|| {
             format!("send out failed: no running dataflow with ID `{dataflow_id}`")
         })?;
+        let (output_id, mut metadata) =
+            dataflow.redirect_dead_letter(&node_id, output_id, metadata);
+        if dataflow
+            .conflated_outputs
+            .contains(&OutputId(node_id.clone(), output_id.clone()))
+        {
+            metadata
+                .parameters
+                .insert(metadata::CONFLATED.to_owned(), Parameter::Bool(true));
+        }
+        if let Some(Parameter::Integer(message_id)) = metadata.parameters.get(metadata::RELIABLE) {
+            dataflow.track_acks(
+                OutputId(node_id.clone(), output_id.clone()),
+                *message_id as u64,
+            );
+        }
         let data_bytes = send_output_to_local_receivers(
             node_id.clone(),
             output_id.clone(),
@@ -1052,12 +1370,18 @@ impl Daemon {
         }
 
         // if a stop event was already sent for the dataflow, send it to
-        // the newly connected node too
-        if dataflow.stop_sent {
+        // the newly connected node too, unless it's not its turn yet
+        if dataflow.stop_sent && !dataflow.pending_stops.contains(&node_id) {
             let _ = send_with_timestamp(&event_sender, NodeEvent::Stop, clock);
         }
 
-        dataflow.subscribe_channels.insert(node_id, event_sender);
+        dataflow
+            .subscribe_channels
+            .insert(node_id.clone(), event_sender);
+
+        if dataflow.joined_nodes.insert(node_id.clone()) {
+            dataflow.send_lifecycle_event(LifecycleEvent::NodeJoined { node_id }, clock);
+        }
     }
 
     #[tracing::instrument(skip(dataflow, inter_daemon_connections, clock), fields(uuid = %dataflow.id), level = "trace")]
@@ -1104,6 +1428,17 @@ impl Daemon {
         if let Some(mut pid) = dataflow.running_nodes.remove(node_id).and_then(|n| n.pid) {
             pid.mark_as_stopped()
         }
+        dataflow.node_stopped(node_id, &self.clock, &self.events_tx);
+        dataflow.lifecycle_subscribers.remove(node_id);
+        dataflow.queue_depths.remove(node_id);
+        if dataflow.joined_nodes.remove(node_id) {
+            dataflow.send_lifecycle_event(
+                LifecycleEvent::NodeLeft {
+                    node_id: node_id.clone(),
+                },
+                &self.clock,
+            );
+        }
         if dataflow
             .running_nodes
             .iter()
@@ -1323,6 +1658,18 @@ impl Daemon {
                     }
                 }
             }
+            DoraEvent::StopTimeout {
+                dataflow_id,
+                node_id,
+            } => {
+                let Some(dataflow) = self.running.get_mut(&dataflow_id) else {
+                    return Ok(RunStatus::Continue);
+                };
+                if dataflow.awaiting_stop.as_ref() == Some(&node_id) {
+                    warn!("{node_id} did not acknowledge the stop within its share of the grace period, stopping the next nodes");
+                }
+                dataflow.node_stopped(&node_id, &self.clock, &self.events_tx);
+            }
         }
         Ok(RunStatus::Continue)
     }
@@ -1455,6 +1802,50 @@ fn node_inputs(node: &ResolvedNode) -> BTreeMap<DataId, Input> {
     }
 }
 
+/// Returns the input name for the given output if it matches the glob pattern.
+///
+/// Patterns that contain a `/` are matched against `<node>/<output>`, other
+/// patterns against the output ID only.
+fn pattern_input(pattern: &str, OutputId(node_id, output): &OutputId) -> Option<DataId> {
+    let name = if pattern.contains('/') {
+        format!("{node_id}/{output}")
+    } else {
+        output.to_string()
+    };
+    glob_matches(pattern, &name).then(|| DataId::from(name))
+}
+
+/// Checks whether the text matches a glob pattern with `*` and `?` wildcards.
+fn glob_matches(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+    let (mut p, mut t) = (0, 0);
+    // pattern position after the last `*` and the text position it matched up to
+    let mut backtrack = None;
+    while t < text.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                p += 1;
+                backtrack = Some((p, t));
+            }
+            Some(&c) if c == '?' || c == text[t] => {
+                p += 1;
+                t += 1;
+            }
+            _ => match backtrack {
+                Some((star_p, star_t)) => {
+                    // let the last `*` match one more character
+                    p = star_p;
+                    t = star_t + 1;
+                    backtrack = Some((star_p, t));
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
 async fn send_input_closed_events<F>(
     dataflow: &mut RunningDataflow,
     inter_daemon_connections: &mut BTreeMap<String, InterDaemonConnection>,
@@ -1464,6 +1855,13 @@ async fn send_input_closed_events<F>(
 where
     F: FnMut(&OutputId) -> bool,
 {
+    dataflow.open_outputs.retain(|output_id| !filter(output_id));
+    dataflow
+        .dead_letter_outputs
+        .retain(|node_id, output| !filter(&OutputId(node_id.clone(), output.clone())));
+    dataflow
+        .conflated_outputs
+        .retain(|output_id| !filter(output_id));
     let local_node_inputs: BTreeSet<_> = dataflow
         .mappings
         .iter()
@@ -1590,6 +1988,34 @@ pub struct RunningDataflow {
     /// We want to treat dynamic nodes differently in some cases, so we need
     /// to know which nodes are dynamic.
     dynamic_nodes: BTreeSet<NodeId>,
+    /// Machines that run the nodes of the dataflow, including remote nodes.
+    node_machines: BTreeMap<NodeId, String>,
+    /// Outputs that were declared by nodes at runtime, in addition to the
+    /// outputs listed in the dataflow descriptor.
+    dynamic_outputs: HashSet<OutputId>,
+    /// Outputs of local nodes that were not closed yet.
+    open_outputs: BTreeSet<OutputId>,
+    /// Glob patterns that local nodes subscribed to through
+    /// `DaemonRequest::SubscribePattern`.
+    pattern_subscriptions: Vec<(NodeId, String)>,
+    /// Outputs on which local nodes want their messages without receivers to be
+    /// re-sent, see `DaemonRequest::SetDeadLetterOutput`.
+    dead_letter_outputs: BTreeMap<NodeId, DataId>,
+    /// Outputs of local nodes whose messages replace the buffered messages of
+    /// the receivers, see `DaemonRequest::SetConflation`.
+    conflated_outputs: BTreeSet<OutputId>,
+    /// Local nodes that subscribed to their event stream and did not stop yet.
+    joined_nodes: BTreeSet<NodeId>,
+    /// Nodes that are notified when other nodes join or leave the dataflow.
+    lifecycle_subscribers: BTreeSet<NodeId>,
+    /// Number of buffered events per input, as last reported by the local nodes.
+    queue_depths: BTreeMap<NodeId, BTreeMap<DataId, usize>>,
+    /// Local receivers that did not acknowledge a reliably sent message yet,
+    /// keyed by output and message ID.
+    pending_acks: HashMap<(OutputId, u64), BTreeSet<InputId>>,
+    /// Reliably sent outputs with receivers on other machines, which are only
+    /// warned about once.
+    remote_reliable_outputs: HashSet<OutputId>,
 
     open_external_mappings: HashMap<OutputId, BTreeMap<String, BTreeSet<InputId>>>,
 
@@ -1598,6 +2024,24 @@ pub struct RunningDataflow {
     /// Keep handles to all timer tasks of this dataflow to cancel them on drop.
     _timer_handles: Vec<futures::future::RemoteHandle<()>>,
     stop_sent: bool,
+    /// Local nodes that are stopped one after another, see
+    /// `Descriptor::shutdown_order`.
+    shutdown_order: Vec<NodeId>,
+    /// Nodes of the `shutdown_order` that were not sent a stop event yet.
+    pending_stops: VecDeque<NodeId>,
+    /// Node of the `shutdown_order` that was sent a stop event, but did not
+    /// acknowledge it or exit yet.
+    awaiting_stop: Option<NodeId>,
+    /// Grace duration of the last stop request.
+    stop_grace_duration: Duration,
+    /// Time that each node of the `shutdown_order` has to acknowledge its stop
+    /// before the next nodes are stopped. The grace duration is split evenly
+    /// between the ordered nodes and the remaining nodes.
+    ordered_stop_timeout: Duration,
+    /// Time after which all remaining nodes are stopped and the ordered nodes
+    /// are killed, regardless of the `shutdown_order`. Set by the first stop
+    /// request.
+    stop_deadline: Option<tokio::time::Instant>,
 
     /// Used in `open_inputs`.
     ///
@@ -1623,10 +2067,27 @@ impl RunningDataflow {
             open_inputs: BTreeMap::new(),
             running_nodes: BTreeMap::new(),
             dynamic_nodes: BTreeSet::new(),
+            node_machines: BTreeMap::new(),
+            dynamic_outputs: HashSet::new(),
+            open_outputs: BTreeSet::new(),
+            pattern_subscriptions: Vec::new(),
+            dead_letter_outputs: BTreeMap::new(),
+            conflated_outputs: BTreeSet::new(),
+            joined_nodes: BTreeSet::new(),
+            lifecycle_subscribers: BTreeSet::new(),
+            queue_depths: BTreeMap::new(),
+            pending_acks: HashMap::new(),
+            remote_reliable_outputs: HashSet::new(),
             open_external_mappings: HashMap::new(),
             pending_drop_tokens: HashMap::new(),
             _timer_handles: Vec::new(),
             stop_sent: false,
+            shutdown_order: Vec::new(),
+            pending_stops: VecDeque::new(),
+            awaiting_stop: None,
+            stop_grace_duration: DEFAULT_GRACE_DURATION,
+            ordered_stop_timeout: DEFAULT_GRACE_DURATION,
+            stop_deadline: None,
             empty_set: BTreeSet::new(),
             cascading_error_causes: Default::default(),
             grace_duration_kills: Default::default(),
@@ -1692,8 +2153,9 @@ impl RunningDataflow {
     async fn stop_all(
         &mut self,
         coordinator_connection: &mut Option<TcpStream>,
-        clock: &HLC,
+        clock: &Arc<HLC>,
         grace_duration: Option<Duration>,
+        events_tx: &mpsc::Sender<Timestamped<Event>>,
     ) -> eyre::Result<()> {
         self.pending_nodes
             .handle_dataflow_stop(
@@ -1704,6 +2166,88 @@ impl RunningDataflow {
             )
             .await?;
 
+        if self.stop_sent {
+            // a repeated stop request skips the rest of the `shutdown_order`
+            self.pending_stops.clear();
+            self.awaiting_stop = None;
+        } else {
+            self.pending_stops = self
+                .shutdown_order
+                .iter()
+                .filter(|node_id| self.running_nodes.contains_key(*node_id))
+                .cloned()
+                .collect();
+        }
+        self.stop_grace_duration = grace_duration.unwrap_or(DEFAULT_GRACE_DURATION);
+        // one share per ordered node and one for the remaining nodes
+        let shares = u32::try_from(self.pending_stops.len() + 1).unwrap_or(u32::MAX);
+        self.ordered_stop_timeout = self.stop_grace_duration / shares;
+        // all nodes must stop within one grace duration, however many are ordered
+        let deadline = tokio::time::Instant::now() + self.stop_grace_duration;
+        self.stop_deadline = Some(match self.stop_deadline {
+            Some(previous) => previous.min(deadline),
+            None => deadline,
+        });
+        self.stop_sent = true;
+        self.stop_next_nodes(clock, events_tx);
+        Ok(())
+    }
+
+    /// Continues an ordered shutdown once the given node acknowledged its stop
+    /// event or exited.
+    fn node_stopped(
+        &mut self,
+        node_id: &NodeId,
+        clock: &Arc<HLC>,
+        events_tx: &mpsc::Sender<Timestamped<Event>>,
+    ) {
+        self.pending_stops.retain(|id| id != node_id);
+        if self.awaiting_stop.as_ref() == Some(node_id) {
+            self.awaiting_stop = None;
+            self.stop_next_nodes(clock, events_tx);
+        }
+    }
+
+    /// Sends the stop event to the next node of the `shutdown_order`, or to all
+    /// remaining nodes once the nodes of the `shutdown_order` stopped or the
+    /// stop deadline passed.
+    ///
+    /// Ordered nodes that don't acknowledge their stop within the
+    /// `ordered_stop_timeout` are skipped, but only killed at the stop deadline.
+    /// The remaining nodes get at least the `ordered_stop_timeout` to stop, even
+    /// if the stop deadline passed already.
+    fn stop_next_nodes(&mut self, clock: &Arc<HLC>, events_tx: &mpsc::Sender<Timestamped<Event>>) {
+        if self.awaiting_stop.is_some() {
+            return;
+        }
+        if self
+            .stop_deadline
+            .is_some_and(|deadline| deadline <= tokio::time::Instant::now())
+        {
+            self.pending_stops.clear();
+        }
+        if let Some(node_id) = self.pending_stops.pop_front() {
+            // nodes that did not subscribe yet receive the stop event on subscribe
+            if let Some(channel) = self.subscribe_channels.remove(&node_id) {
+                let _ = send_with_timestamp(&channel, NodeEvent::Stop, clock);
+            }
+            let pid = self
+                .running_nodes
+                .get_mut(&node_id)
+                .and_then(|n| n.pid.take());
+            let now = tokio::time::Instant::now();
+            let deadline = self.stop_deadline.unwrap_or(now + self.stop_grace_duration);
+            self.kill_after_grace_duration(vec![(node_id.clone(), pid)], deadline);
+            self.notify_stop_timeout(
+                node_id.clone(),
+                now + self.ordered_stop_timeout,
+                clock,
+                events_tx,
+            );
+            self.awaiting_stop = Some(node_id);
+            return;
+        }
+
         for (_node_id, channel) in self.subscribe_channels.drain() {
             let _ = send_with_timestamp(&channel, NodeEvent::Stop, clock);
         }
@@ -1713,10 +2257,50 @@ impl RunningDataflow {
             .iter_mut()
             .map(|(id, n)| (id.clone(), n.pid.take()))
             .collect();
+        let min_deadline = tokio::time::Instant::now() + self.ordered_stop_timeout;
+        let deadline = self
+            .stop_deadline
+            .map_or(min_deadline, |deadline| deadline.max(min_deadline));
+        self.kill_after_grace_duration(running_processes, deadline);
+    }
+
+    /// Sends a `DoraEvent::StopTimeout` for the node at the given time, so that
+    /// an ordered shutdown continues with the next node if the node doesn't
+    /// acknowledge its stop in time.
+    fn notify_stop_timeout(
+        &self,
+        node_id: NodeId,
+        at: tokio::time::Instant,
+        clock: &Arc<HLC>,
+        events_tx: &mpsc::Sender<Timestamped<Event>>,
+    ) {
+        let dataflow_id = self.id;
+        let clock = clock.clone();
+        let events_tx = events_tx.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep_until(at).await;
+            let event = Timestamped {
+                inner: DoraEvent::StopTimeout {
+                    dataflow_id,
+                    node_id,
+                }
+                .into(),
+                timestamp: clock.new_timestamp(),
+            };
+            let _ = events_tx.send(event).await;
+        });
+    }
+
+    /// Kills the given processes if they are still running at the deadline.
+    fn kill_after_grace_duration(
+        &self,
+        running_processes: Vec<(NodeId, Option<ProcessId>)>,
+        deadline: tokio::time::Instant,
+    ) {
+        let duration = self.stop_grace_duration;
         let grace_duration_kills = self.grace_duration_kills.clone();
         tokio::spawn(async move {
-            let duration = grace_duration.unwrap_or(Duration::from_millis(15000));
-            tokio::time::sleep(duration).await;
+            tokio::time::sleep_until(deadline).await;
 
             for (node, pid) in running_processes {
                 if let Some(mut pid) = pid {
@@ -1730,10 +2314,300 @@ impl RunningDataflow {
                 }
             }
         });
-        self.stop_sent = true;
+    }
+
+    fn declare_output(&mut self, output_id: OutputId) -> eyre::Result<()> {
+        let OutputId(node_id, output) = &output_id;
+        let empty_set = BTreeSet::new();
+        let local_receivers = self.mappings.get(&output_id).unwrap_or(&empty_set);
+        // inputs are only closed once, so a closed output cannot be declared again
+        if local_receivers
+            .iter()
+            .any(|(receiver_id, input_id)| !self.open_inputs(receiver_id).contains(input_id))
+        {
+            bail!("output `{node_id}/{output}` was already closed");
+        }
+        if self.dynamic_outputs.contains(&output_id) {
+            bail!("output `{node_id}/{output}` was already declared");
+        }
+        tracing::debug!(
+            "declared dynamic output `{node_id}/{output}` with {} local receivers",
+            local_receivers.len()
+        );
+        self.dynamic_outputs.insert(output_id.clone());
+        self.open_outputs.insert(output_id.clone());
+        for (receiver, pattern) in self.pattern_subscriptions.clone() {
+            self.route_pattern_input(&receiver, &pattern, &output_id);
+        }
+        Ok(())
+    }
+
+    /// Stops routing messages to the given input and closes it.
+    ///
+    /// Messages that were already sent to the node are still delivered before
+    /// the `InputClosed` event.
+    fn disconnect_input(
+        &mut self,
+        node_id: NodeId,
+        input: DataId,
+        clock: &HLC,
+    ) -> eyre::Result<()> {
+        if !self.open_inputs(&node_id).contains(&input) {
+            bail!("input `{node_id}/{input}` is not open");
+        }
+        let input_id = (node_id, input);
+        for receivers in self.mappings.values_mut() {
+            receivers.remove(&input_id);
+        }
+        for receivers in self.timers.values_mut() {
+            receivers.remove(&input_id);
+        }
+        let (node_id, input) = input_id;
+        tracing::debug!("disconnected input `{node_id}/{input}`");
+        close_input(self, &node_id, &input, clock);
+        Ok(())
+    }
+
+    /// Routes all open outputs that match the given glob pattern to the node.
+    ///
+    /// Outputs that are declared later are routed too, see
+    /// [`declare_output`](Self::declare_output).
+    fn subscribe_pattern(&mut self, node_id: NodeId, pattern: String) -> eyre::Result<()> {
+        // the event stream of the node is closed once all of its inputs are closed
+        if self.open_inputs(&node_id).is_empty() {
+            bail!("cannot subscribe to `{pattern}`: all inputs of node `{node_id}` are closed");
+        }
+        let outputs: Vec<_> = self.open_outputs.iter().cloned().collect();
+        for output_id in &outputs {
+            self.route_pattern_input(&node_id, &pattern, output_id);
+        }
+        tracing::debug!("node `{node_id}` subscribed to outputs matching `{pattern}`");
+        self.pattern_subscriptions.push((node_id, pattern));
+        Ok(())
+    }
+
+    fn set_dead_letter_output(
+        &mut self,
+        node_id: NodeId,
+        output: Option<DataId>,
+    ) -> eyre::Result<()> {
+        match output {
+            Some(output) => {
+                let output_id = OutputId(node_id.clone(), output.clone());
+                if !self.open_outputs.contains(&output_id) {
+                    bail!("cannot use `{output}` as dead-letter output: no open output of node `{node_id}`");
+                }
+                self.dead_letter_outputs.insert(node_id, output);
+            }
+            None => {
+                self.dead_letter_outputs.remove(&node_id);
+            }
+        }
+        Ok(())
+    }
+
+    fn set_conflation(
+        &mut self,
+        node_id: NodeId,
+        output: DataId,
+        enabled: bool,
+    ) -> eyre::Result<()> {
+        let output_id = OutputId(node_id.clone(), output.clone());
+        if !enabled {
+            self.conflated_outputs.remove(&output_id);
+            return Ok(());
+        }
+        if !self.open_outputs.contains(&output_id) {
+            bail!("cannot conflate `{output}`: no open output of node `{node_id}`");
+        }
+        self.conflated_outputs.insert(output_id);
         Ok(())
     }
 
+    /// Redirects messages without receivers to the dead-letter output of the
+    /// sending node, if it set one.
+    fn redirect_dead_letter(
+        &self,
+        node_id: &NodeId,
+        output_id: DataId,
+        mut metadata: metadata::Metadata,
+    ) -> (DataId, metadata::Metadata) {
+        let Some(dead_letter) = self.dead_letter_outputs.get(node_id) else {
+            return (output_id, metadata);
+        };
+        if dead_letter == &output_id
+            || self.has_receivers(&OutputId(node_id.clone(), output_id.clone()))
+        {
+            return (output_id, metadata);
+        }
+        DeadLetterReason::NoSubscribers.insert_into(&mut metadata.parameters, &output_id);
+        (dead_letter.clone(), metadata)
+    }
+
+    /// Whether a message on the given output would reach any node.
+    fn has_receivers(&self, output_id: &OutputId) -> bool {
+        let local = self.mappings.get(output_id).is_some_and(|receivers| {
+            receivers.iter().any(|(node, input)| {
+                self.subscribe_channels.contains_key(node) && self.open_inputs(node).contains(input)
+            })
+        });
+        let remote = self
+            .open_external_mappings
+            .get(output_id)
+            .is_some_and(|receivers| !receivers.is_empty());
+        local || remote
+    }
+
+    /// Routes the given output to the node if it matches the pattern.
+    fn route_pattern_input(&mut self, node_id: &NodeId, pattern: &str, output_id: &OutputId) {
+        let Some(input) = pattern_input(pattern, output_id) else {
+            return;
+        };
+        let open_inputs = self.open_inputs(node_id);
+        // skip the node's own outputs and inputs that are already mapped, and
+        // don't open new inputs once the event stream of the node is closed
+        if &output_id.0 == node_id || open_inputs.contains(&input) || open_inputs.is_empty() {
+            return;
+        }
+        tracing::debug!(
+            "routing `{}/{}` to `{node_id}/{input}`",
+            output_id.0,
+            output_id.1
+        );
+        self.open_inputs
+            .entry(node_id.clone())
+            .or_default()
+            .insert(input.clone());
+        self.mappings
+            .entry(output_id.clone())
+            .or_default()
+            .insert((node_id.clone(), input));
+    }
+
+    fn subscribe_lifecycle_events(&mut self, node_id: NodeId, clock: &HLC) -> eyre::Result<()> {
+        let channel = self
+            .subscribe_channels
+            .get(&node_id)
+            .wrap_err_with(|| format!("node `{node_id}` has no open event stream"))?;
+        // report the current roster first
+        for joined in &self.joined_nodes {
+            let event = LifecycleEvent::NodeJoined {
+                node_id: joined.clone(),
+            };
+            let _ = send_with_timestamp(channel, NodeEvent::Lifecycle(event), clock);
+        }
+        self.lifecycle_subscribers.insert(node_id);
+        Ok(())
+    }
+
+    fn send_lifecycle_event(&self, event: LifecycleEvent, clock: &HLC) {
+        for subscriber in &self.lifecycle_subscribers {
+            if let Some(channel) = self.subscribe_channels.get(subscriber) {
+                let _ = send_with_timestamp(channel, NodeEvent::Lifecycle(event.clone()), clock);
+            }
+        }
+    }
+
+    /// Returns the queue depths of all edges that end at a running local node.
+    ///
+    /// Nodes that did not report their queues yet are assumed to have empty queues.
+    fn pipeline_depths(&self) -> Vec<EdgeDepth> {
+        let mut edges = Vec::new();
+        for (OutputId(source_node, output), targets) in &self.mappings {
+            for (target_node, input) in targets {
+                if !self.running_nodes.contains_key(target_node) {
+                    continue;
+                }
+                let depth = self
+                    .queue_depths
+                    .get(target_node)
+                    .and_then(|depths| depths.get(input))
+                    .copied()
+                    .unwrap_or(0);
+                edges.push(EdgeDepth {
+                    source: (source_node.clone(), output.clone()),
+                    target: (target_node.clone(), input.clone()),
+                    depth,
+                });
+            }
+        }
+        edges.sort_by(|a, b| (&a.source, &a.target).cmp(&(&b.source, &b.target)));
+        edges
+    }
+
+    /// Starts tracking the acknowledgements of a reliably sent message.
+    ///
+    /// Retries of a message that is already tracked keep the receivers that
+    /// did not acknowledge it yet.
+    ///
+    /// Only local receivers are tracked. Receivers on other machines get the
+    /// message on a best-effort basis, which is logged as a warning once per
+    /// output.
+    fn track_acks(&mut self, output_id: OutputId, message_id: u64) {
+        let has_remote_receivers = self
+            .open_external_mappings
+            .get(&output_id)
+            .is_some_and(|receivers| !receivers.is_empty());
+        if has_remote_receivers && self.remote_reliable_outputs.insert(output_id.clone()) {
+            let OutputId(node_id, output) = &output_id;
+            warn!(
+                "output `{node_id}/{output}` is sent reliably, but receivers on other \
+                machines don't acknowledge it, so it is delivered to them on a \
+                best-effort basis"
+            );
+        }
+        let receivers: BTreeSet<InputId> = self
+            .mappings
+            .get(&output_id)
+            .into_iter()
+            .flatten()
+            .filter(|(node, input)| self.open_inputs(node).contains(input))
+            .cloned()
+            .collect();
+        self.pending_acks
+            .entry((output_id, message_id))
+            .or_insert(receivers);
+    }
+
+    fn ack_input(&mut self, node_id: NodeId, input: DataId, message_id: u64) {
+        let input_id = (node_id, input);
+        let Some(output_id) = self
+            .mappings
+            .iter()
+            .find(|(_, receivers)| receivers.contains(&input_id))
+            .map(|(output_id, _)| output_id.clone())
+        else {
+            return;
+        };
+        if let Some(pending) = self.pending_acks.get_mut(&(output_id, message_id)) {
+            pending.remove(&input_id);
+        }
+    }
+
+    /// Returns whether all receivers acknowledged the given message.
+    ///
+    /// Receivers whose input was closed in the meantime are not waited for.
+    /// The message is no longer tracked once it was acknowledged, or if
+    /// `discard` is set.
+    fn check_acked(&mut self, output_id: OutputId, message_id: u64, discard: bool) -> bool {
+        let key = (output_id, message_id);
+        let acked = match self.pending_acks.get_mut(&key) {
+            Some(pending) => {
+                pending.retain(|(node, input)| {
+                    self.open_inputs
+                        .get(node)
+                        .is_some_and(|open| open.contains(input))
+                });
+                pending.is_empty()
+            }
+            None => true,
+        };
+        if acked || discard {
+            self.pending_acks.remove(&key);
+        }
+        acked
+    }
+
     fn open_inputs(&self, node_id: &NodeId) -> &BTreeSet<DataId> {
         self.open_inputs.get(node_id).unwrap_or(&self.empty_set)
     }
@@ -1783,7 +2657,7 @@ fn empty_type_info() -> ArrowTypeInfo {
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct OutputId(NodeId, DataId);
 type InputId = (NodeId, DataId);
 
@@ -1824,6 +2698,9 @@ pub enum DaemonNodeEvent {
     },
     Subscribe {
         event_sender: UnboundedSender<Timestamped<NodeEvent>>,
+        /// Whether an open subscription of the node is replaced, see
+        /// `DaemonRequest::TakeOverSubscription`.
+        take_over: bool,
         reply_sender: oneshot::Sender<DaemonReply>,
     },
     SubscribeDrop {
@@ -1834,6 +2711,63 @@ pub enum DaemonNodeEvent {
         outputs: Vec<dora_core::config::DataId>,
         reply_sender: oneshot::Sender<DaemonReply>,
     },
+    DeclareOutput {
+        output: DataId,
+        reply_sender: oneshot::Sender<DaemonReply>,
+    },
+    DisconnectInput {
+        input: DataId,
+        reply_sender: oneshot::Sender<DaemonReply>,
+    },
+    SubscribePattern {
+        pattern: String,
+        reply_sender: oneshot::Sender<DaemonReply>,
+    },
+    SetDeadLetterOutput {
+        output: Option<DataId>,
+        reply_sender: oneshot::Sender<DaemonReply>,
+    },
+    SetConflation {
+        output_id: DataId,
+        enabled: bool,
+        reply_sender: oneshot::Sender<DaemonReply>,
+    },
+    SubscribeLifecycleEvents {
+        reply_sender: oneshot::Sender<DaemonReply>,
+    },
+    ReportQueueDepths {
+        depths: BTreeMap<DataId, usize>,
+    },
+    PipelineDepths {
+        reply_sender: oneshot::Sender<DaemonReply>,
+    },
+    ClockSkew {
+        node_id: NodeId,
+        reply_sender: oneshot::Sender<DaemonReply>,
+    },
+    AckInputs {
+        acks: Vec<(DataId, u64)>,
+    },
+    CheckAcked {
+        output_id: DataId,
+        message_id: u64,
+        discard: bool,
+        reply_sender: oneshot::Sender<DaemonReply>,
+    },
+    ReportProgress {
+        task: String,
+        progress: f32,
+        message: Option<String>,
+    },
+    ReportSummary {
+        summary: NodeSummary,
+    },
+    AckStop,
+    Log {
+        level: LogLevel,
+        target: Option<String>,
+        message: String,
+    },
     SendOut {
         output_id: DataId,
         metadata: metadata::Metadata,
@@ -1865,6 +2799,11 @@ pub enum DoraEvent {
         node_id: NodeId,
         exit_status: NodeExitStatus,
     },
+    /// The grace duration of a node of the `shutdown_order` elapsed.
+    StopTimeout {
+        dataflow_id: DataflowId,
+        node_id: NodeId,
+    },
 }
 
 #[must_use]
@@ -1974,6 +2913,7 @@ impl CoreNodeKindExt for CoreNodeKind {
             CoreNodeKind::Runtime(n) => NodeRunConfig {
                 inputs: runtime_node_inputs(n),
                 outputs: runtime_node_outputs(n),
+                dynamic_outputs: false,
             },
             CoreNodeKind::Custom(n) => n.run_config.clone(),
         }
@@ -1986,3 +2926,142 @@ impl CoreNodeKindExt for CoreNodeKind {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use dora_message::daemon_to_node::DaemonCommunication;
+
+    const GRACE_DURATION: Duration = Duration::from_millis(200);
+
+    fn dataflow_with_nodes(
+        nodes: &[&str],
+        shutdown_order: &[&str],
+    ) -> (
+        RunningDataflow,
+        BTreeMap<NodeId, mpsc::UnboundedReceiver<Timestamped<NodeEvent>>>,
+    ) {
+        let dataflow_id = Uuid::new_v4();
+        let mut dataflow = RunningDataflow::new(dataflow_id, String::new());
+        let descriptor: Descriptor = serde_yaml::from_str("nodes: []").unwrap();
+        let mut receivers = BTreeMap::new();
+        for node in nodes {
+            let node_id: NodeId = node.to_string().into();
+            let (tx, rx) = mpsc::unbounded_channel();
+            dataflow.subscribe_channels.insert(node_id.clone(), tx);
+            receivers.insert(node_id.clone(), rx);
+            let node_config = NodeConfig {
+                dataflow_id,
+                node_id: node_id.clone(),
+                run_config: NodeRunConfig {
+                    inputs: Default::default(),
+                    outputs: Default::default(),
+                    dynamic_outputs: false,
+                },
+                daemon_communication: DaemonCommunication::Tcp {
+                    socket_addr: ([127, 0, 0, 1], 0).into(),
+                },
+                dataflow_descriptor: descriptor.clone(),
+                dynamic: false,
+            };
+            dataflow.running_nodes.insert(
+                node_id,
+                RunningNode {
+                    pid: None,
+                    node_config,
+                },
+            );
+        }
+        dataflow.shutdown_order = shutdown_order
+            .iter()
+            .map(|node| node.to_string().into())
+            .collect();
+        (dataflow, receivers)
+    }
+
+    fn stopped(
+        receivers: &mut BTreeMap<NodeId, mpsc::UnboundedReceiver<Timestamped<NodeEvent>>>,
+    ) -> Vec<String> {
+        receivers
+            .iter_mut()
+            .filter_map(|(node_id, rx)| match rx.try_recv() {
+                Ok(event) if matches!(event.inner, NodeEvent::Stop) => Some(node_id.to_string()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn stop_follows_shutdown_order() {
+        let clock = Arc::new(HLC::default());
+        let (events_tx, _events_rx) = mpsc::channel(10);
+        let (mut dataflow, mut receivers) = dataflow_with_nodes(&["a", "b", "c"], &["b", "a"]);
+
+        dataflow
+            .stop_all(&mut None, &clock, Some(GRACE_DURATION), &events_tx)
+            .await
+            .unwrap();
+        assert_eq!(stopped(&mut receivers), ["b"]);
+
+        dataflow.node_stopped(&"b".to_string().into(), &clock, &events_tx);
+        assert_eq!(stopped(&mut receivers), ["a"]);
+
+        dataflow.node_stopped(&"a".to_string().into(), &clock, &events_tx);
+        assert_eq!(stopped(&mut receivers), ["c"]);
+    }
+
+    #[tokio::test]
+    async fn ordered_stop_splits_the_grace_duration() {
+        let clock = Arc::new(HLC::default());
+        let (events_tx, mut events_rx) = mpsc::channel(10);
+        let (mut dataflow, mut receivers) = dataflow_with_nodes(&["a", "b", "c"], &["a", "b"]);
+        // one share for `a`, one for `b`, and one for the remaining nodes
+        let share = GRACE_DURATION / 3;
+
+        let start = tokio::time::Instant::now();
+        dataflow
+            .stop_all(&mut None, &clock, Some(GRACE_DURATION), &events_tx)
+            .await
+            .unwrap();
+        assert_eq!(stopped(&mut receivers), ["a"]);
+
+        // neither `a` nor `b` acknowledge the stop, so each times out after its share
+        for (expected, shares, next) in [("a", 1, "b"), ("b", 2, "c")] {
+            let event = tokio::time::timeout(GRACE_DURATION, events_rx.recv())
+                .await
+                .unwrap()
+                .unwrap();
+            let Event::Dora(DoraEvent::StopTimeout { node_id, .. }) = event.inner else {
+                panic!("unexpected event");
+            };
+            assert_eq!(node_id.to_string(), expected);
+            assert!(start.elapsed() >= share * shares);
+            assert!(start.elapsed() < GRACE_DURATION);
+            dataflow.node_stopped(&node_id, &clock, &events_tx);
+            assert_eq!(stopped(&mut receivers), [next]);
+        }
+    }
+
+    #[tokio::test]
+    async fn repeated_stop_skips_shutdown_order() {
+        let clock = Arc::new(HLC::default());
+        let (events_tx, _events_rx) = mpsc::channel(10);
+        let (mut dataflow, mut receivers) = dataflow_with_nodes(&["a", "b", "c"], &["a", "b"]);
+
+        dataflow
+            .stop_all(&mut None, &clock, Some(GRACE_DURATION), &events_tx)
+            .await
+            .unwrap();
+        assert_eq!(stopped(&mut receivers), ["a"]);
+
+        dataflow
+            .stop_all(&mut None, &clock, Some(GRACE_DURATION), &events_tx)
+            .await
+            .unwrap();
+        assert_eq!(stopped(&mut receivers), ["b", "c"]);
+
+        // a late acknowledgement of `a` doesn't send further stop events
+        dataflow.node_stopped(&"a".to_string().into(), &clock, &events_tx);
+        assert!(stopped(&mut receivers).is_empty());
+    }
+}
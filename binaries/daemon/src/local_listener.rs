@@ -71,14 +71,21 @@ async fn handle_connection_loop(
     loop {
         match receive_message(&mut connection).await {
             Ok(Some(Timestamped {
-                inner: DaemonRequest::NodeConfig { node_id },
+                inner:
+                    DaemonRequest::NodeConfig {
+                        node_id,
+                        dataflow_id,
+                    },
                 timestamp,
             })) => {
                 let (reply_tx, reply_rx) = oneshot::channel();
                 if events_tx
                     .send_async(Timestamped {
                         inner: DynamicNodeEventWrapper {
-                            event: DynamicNodeEvent::NodeConfig { node_id },
+                            event: DynamicNodeEvent::NodeConfig {
+                                node_id,
+                                dataflow_id,
+                            },
                             reply_tx,
                         },
                         timestamp,
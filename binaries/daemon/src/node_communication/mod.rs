@@ -6,7 +6,9 @@ use dora_core::{
 };
 use dora_message::{
     common::{DropToken, Timestamped},
-    daemon_to_node::{DaemonCommunication, DaemonReply, NodeDropEvent, NodeEvent},
+    current_crate_version,
+    daemon_to_node::{DaemonCommunication, DaemonReply, DataMessage, NodeDropEvent, NodeEvent},
+    metadata::{Parameter, Priority, CONFLATED, DROPPED_COUNT},
     node_to_daemon::DaemonRequest,
     DataflowId,
 };
@@ -14,6 +16,7 @@ use eyre::{eyre, Context};
 use futures::{future, task, Future};
 use shared_memory_server::{ShmemConf, ShmemServer};
 use std::{
+    cmp::Reverse,
     collections::{BTreeMap, VecDeque},
     mem,
     sync::Arc,
@@ -152,7 +155,7 @@ pub async fn spawn_listener_loop(
             if !tmpfile_dir.exists() {
                 std::fs::create_dir_all(&tmpfile_dir).context("could not create tmp dir")?;
             }
-            let socket_file = tmpfile_dir.join(format!("{}.sock", node_id));
+            let socket_file = tmpfile_dir.join(format!("{}.sock", node_id.to_file_name()));
             let socket = match UnixListener::bind(&socket_file) {
                 Ok(socket) => socket,
                 Err(err) => {
@@ -217,8 +220,15 @@ impl Listener {
         match message.inner {
             DaemonRequest::Register(register_request) => {
                 let result = register_request.check_version();
+                let reply = match &result {
+                    Ok(()) => DaemonReply::Result(Ok(())),
+                    Err(_) => DaemonReply::VersionMismatch {
+                        node_version: register_request.dora_version().clone(),
+                        daemon_version: current_crate_version(),
+                    },
+                };
                 let send_result = connection
-                    .send_reply(DaemonReply::Result(result.clone()))
+                    .send_reply(reply)
                     .await
                     .wrap_err("failed to send register reply");
                 let dataflow_id = register_request.dataflow_id;
@@ -280,7 +290,7 @@ impl Listener {
                     future::Either::Right((message, _)) => break message,
                 };
 
-                self.queue.push_back(Box::new(Some(event)));
+                self.queue_event(event).await?;
                 self.handle_events().await?;
             };
 
@@ -302,11 +312,59 @@ impl Listener {
     }
 
     async fn handle_events(&mut self) -> eyre::Result<()> {
-        if let Some(events) = &mut self.subscribed_events {
-            while let Ok(event) = events.try_recv() {
-                self.queue.push_back(Box::new(Some(event)));
+        while let Some(event) = self
+            .subscribed_events
+            .as_mut()
+            .and_then(|events| events.try_recv().ok())
+        {
+            self.queue_event(event).await?;
+        }
+        Ok(())
+    }
+
+    /// Queues the event until the node requests the next events.
+    ///
+    /// Inputs of conflated outputs replace the queued events of the same input.
+    /// The drop tokens of replaced messages are reported right away and their
+    /// number is added to the `DROPPED_COUNT` of the new event.
+    async fn queue_event(&mut self, mut event: Timestamped<NodeEvent>) -> eyre::Result<()> {
+        if let NodeEvent::Input { id, metadata, .. } = &mut event.inner {
+            if metadata.parameters.contains_key(CONFLATED) {
+                let mut replaced = 0;
+                let mut drop_tokens = Vec::new();
+                for queued in self.queue.iter_mut() {
+                    let is_same_input = matches!(
+                        &**queued,
+                        Some(Timestamped {
+                            inner: NodeEvent::Input { id: queued_id, .. },
+                            ..
+                        }) if *queued_id == *id
+                    );
+                    if !is_same_input {
+                        continue;
+                    }
+                    if let Some(Timestamped {
+                        inner: NodeEvent::Input { metadata, data, .. },
+                        ..
+                    }) = queued.take()
+                    {
+                        replaced += 1 + metadata.dropped_count();
+                        if let Some(DataMessage::SharedMemory { drop_token, .. }) = data {
+                            drop_tokens.push(drop_token);
+                        }
+                    }
+                }
+                if replaced > 0 {
+                    let dropped = metadata.dropped_count() + replaced;
+                    metadata.parameters.insert(
+                        DROPPED_COUNT.to_owned(),
+                        Parameter::Integer(dropped.try_into().unwrap_or(i64::MAX)),
+                    );
+                }
+                self.report_drop_tokens(drop_tokens).await?;
             }
         }
+        self.queue.push_back(Box::new(Some(event)));
         Ok(())
     }
 
@@ -354,6 +412,171 @@ impl Listener {
                 )
                 .await?
             }
+            DaemonRequest::DeclareOutput(output) => {
+                let (reply_sender, reply) = oneshot::channel();
+                self.process_daemon_event(
+                    DaemonNodeEvent::DeclareOutput {
+                        output,
+                        reply_sender,
+                    },
+                    Some(reply),
+                    connection,
+                )
+                .await?
+            }
+            DaemonRequest::DisconnectInput(input) => {
+                let (reply_sender, reply) = oneshot::channel();
+                self.process_daemon_event(
+                    DaemonNodeEvent::DisconnectInput {
+                        input,
+                        reply_sender,
+                    },
+                    Some(reply),
+                    connection,
+                )
+                .await?
+            }
+            DaemonRequest::SubscribePattern(pattern) => {
+                let (reply_sender, reply) = oneshot::channel();
+                self.process_daemon_event(
+                    DaemonNodeEvent::SubscribePattern {
+                        pattern,
+                        reply_sender,
+                    },
+                    Some(reply),
+                    connection,
+                )
+                .await?
+            }
+            DaemonRequest::SetDeadLetterOutput(output) => {
+                let (reply_sender, reply) = oneshot::channel();
+                self.process_daemon_event(
+                    DaemonNodeEvent::SetDeadLetterOutput {
+                        output,
+                        reply_sender,
+                    },
+                    Some(reply),
+                    connection,
+                )
+                .await?
+            }
+            DaemonRequest::SetConflation { output_id, enabled } => {
+                let (reply_sender, reply) = oneshot::channel();
+                self.process_daemon_event(
+                    DaemonNodeEvent::SetConflation {
+                        output_id,
+                        enabled,
+                        reply_sender,
+                    },
+                    Some(reply),
+                    connection,
+                )
+                .await?
+            }
+            DaemonRequest::SubscribeLifecycleEvents => {
+                let (reply_sender, reply) = oneshot::channel();
+                self.process_daemon_event(
+                    DaemonNodeEvent::SubscribeLifecycleEvents { reply_sender },
+                    Some(reply),
+                    connection,
+                )
+                .await?
+            }
+            DaemonRequest::ReportQueueDepths(depths) => {
+                self.process_daemon_event(
+                    DaemonNodeEvent::ReportQueueDepths { depths },
+                    None,
+                    connection,
+                )
+                .await?
+            }
+            DaemonRequest::ReportProgress {
+                task,
+                progress,
+                message,
+            } => {
+                self.process_daemon_event(
+                    DaemonNodeEvent::ReportProgress {
+                        task,
+                        progress,
+                        message,
+                    },
+                    None,
+                    connection,
+                )
+                .await?
+            }
+            DaemonRequest::ReportSummary(summary) => {
+                self.process_daemon_event(
+                    DaemonNodeEvent::ReportSummary { summary },
+                    None,
+                    connection,
+                )
+                .await?
+            }
+            DaemonRequest::AckStop => {
+                self.process_daemon_event(DaemonNodeEvent::AckStop, None, connection)
+                    .await?
+            }
+            DaemonRequest::Log {
+                level,
+                target,
+                message,
+            } => {
+                self.process_daemon_event(
+                    DaemonNodeEvent::Log {
+                        level,
+                        target,
+                        message,
+                    },
+                    None,
+                    connection,
+                )
+                .await?
+            }
+            DaemonRequest::ClockSkew { node_id } => {
+                let (reply_sender, reply) = oneshot::channel();
+                self.process_daemon_event(
+                    DaemonNodeEvent::ClockSkew {
+                        node_id,
+                        reply_sender,
+                    },
+                    Some(reply),
+                    connection,
+                )
+                .await?
+            }
+            DaemonRequest::PipelineDepths => {
+                let (reply_sender, reply) = oneshot::channel();
+                self.process_daemon_event(
+                    DaemonNodeEvent::PipelineDepths { reply_sender },
+                    Some(reply),
+                    connection,
+                )
+                .await?
+            }
+            DaemonRequest::AckInputs(acks) => {
+                self.process_daemon_event(DaemonNodeEvent::AckInputs { acks }, None, connection)
+                    .await?
+            }
+            DaemonRequest::CheckAcked {
+                output_id,
+                message_id,
+                discard,
+            } => {
+                let (reply_sender, reply) = oneshot::channel();
+                self.process_daemon_event(
+                    DaemonNodeEvent::CheckAcked {
+                        output_id,
+                        message_id,
+                        discard,
+                        reply_sender,
+                    },
+                    Some(reply),
+                    connection,
+                )
+                .await?
+            }
             DaemonRequest::SendMessage {
                 output_id,
                 metadata,
@@ -366,12 +589,13 @@ impl Listener {
                 };
                 self.process_daemon_event(event, None, connection).await?;
             }
-            DaemonRequest::Subscribe => {
+            request @ (DaemonRequest::Subscribe | DaemonRequest::TakeOverSubscription) => {
                 let (tx, rx) = mpsc::unbounded_channel();
                 let (reply_sender, reply) = oneshot::channel();
                 self.process_daemon_event(
                     DaemonNodeEvent::Subscribe {
                         event_sender: tx,
+                        take_over: matches!(request, DaemonRequest::TakeOverSubscription),
                         reply_sender,
                     },
                     Some(reply),
@@ -398,10 +622,11 @@ impl Listener {
                 self.report_drop_tokens(drop_tokens).await?;
 
                 // try to take the queued events first
-                let queued_events: Vec<_> = mem::take(&mut self.queue)
+                let mut queued_events: Vec<_> = mem::take(&mut self.queue)
                     .into_iter()
                     .filter_map(|e| *e)
                     .collect();
+                sort_by_priority(&mut queued_events);
                 let reply = if queued_events.is_empty() {
                     match self.subscribed_events.as_mut() {
                         // wait for next event
@@ -544,8 +769,90 @@ impl Listener {
     }
 }
 
+/// Moves high-priority inputs before queued inputs of lower priority.
+///
+/// Other events, e.g. `Stop` or `InputClosed`, act as barriers: inputs are
+/// never moved across them. The sort is stable, so inputs of the same priority
+/// keep their order.
+fn sort_by_priority(events: &mut [Timestamped<NodeEvent>]) {
+    for inputs in events.split_mut(|event| !matches!(event.inner, NodeEvent::Input { .. })) {
+        inputs.sort_by_key(|event| Reverse(input_priority(&event.inner)));
+    }
+}
+
+fn input_priority(event: &NodeEvent) -> Priority {
+    match event {
+        NodeEvent::Input { metadata, .. } => metadata.priority(),
+        _ => Priority::Normal,
+    }
+}
+
 #[async_trait::async_trait]
 trait Connection {
     async fn receive_message(&mut self) -> eyre::Result<Option<Timestamped<DaemonRequest>>>;
     async fn send_reply(&mut self, message: DaemonReply) -> eyre::Result<()>;
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use dora_core::metadata::ArrowTypeInfoExt;
+    use dora_message::metadata::{ArrowTypeInfo, Metadata, MetadataParameters, PRIORITY};
+
+    fn input(id: &str, priority: Priority, clock: &uhlc::HLC) -> Timestamped<NodeEvent> {
+        let mut parameters = MetadataParameters::new();
+        parameters.insert(PRIORITY.to_owned(), priority.into());
+        Timestamped {
+            inner: NodeEvent::Input {
+                id: id.to_owned().into(),
+                metadata: Metadata::from_parameters(
+                    clock.new_timestamp(),
+                    ArrowTypeInfo::empty(),
+                    parameters,
+                ),
+                data: None,
+            },
+            timestamp: clock.new_timestamp(),
+        }
+    }
+
+    fn ids(events: &[Timestamped<NodeEvent>]) -> Vec<String> {
+        events
+            .iter()
+            .map(|event| match &event.inner {
+                NodeEvent::Input { id, .. } => id.to_string(),
+                NodeEvent::InputClosed { id } => format!("closed {id}"),
+                NodeEvent::Stop => "stop".to_owned(),
+                other => format!("{other:?}"),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn priority_sort_does_not_cross_control_events() {
+        let clock = uhlc::HLC::default();
+        let mut events = vec![
+            input("a", Priority::Low, &clock),
+            input("b", Priority::High, &clock),
+            Timestamped {
+                inner: NodeEvent::InputClosed {
+                    id: "a".to_owned().into(),
+                },
+                timestamp: clock.new_timestamp(),
+            },
+            input("c", Priority::Normal, &clock),
+            input("d", Priority::Normal, &clock),
+            input("e", Priority::High, &clock),
+            Timestamped {
+                inner: NodeEvent::Stop,
+                timestamp: clock.new_timestamp(),
+            },
+            input("f", Priority::High, &clock),
+        ];
+        sort_by_priority(&mut events);
+        assert_eq!(
+            ids(&events),
+            ["b", "a", "closed a", "e", "c", "d", "stop", "f"]
+        );
+    }
+}
@@ -11,21 +11,40 @@ use eyre::{bail, Context, Result};
 use futures::{Stream, StreamExt};
 use futures_concurrency::stream::Merge;
 use operator::{run_operator, OperatorEvent, StopReason};
+use registry::OperatorStatus;
 
 #[cfg(feature = "tracing")]
 use dora_tracing::set_up_tracing;
 use std::{
     collections::{BTreeMap, BTreeSet, HashMap},
     mem,
+    time::Duration,
 };
 use tokio::{
     runtime::Builder,
     sync::{mpsc, oneshot},
+    time::Instant,
 };
 use tokio_stream::wrappers::ReceiverStream;
 mod operator;
+pub mod registry;
 
-pub fn main() -> eyre::Result<()> {
+/// Environment variable to configure how long operators may keep sending outputs
+/// after a stop was requested, in seconds.
+pub const DRAIN_TIMEOUT_ENV: &str = "DORA_RUNTIME_DRAIN_TIMEOUT";
+const DEFAULT_DRAIN_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// How the runtime shut down.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Shutdown {
+    /// All operators finished and their outputs were sent.
+    Drained,
+    /// The drain timeout expired before the listed operators finished, so
+    /// the outputs that they sent afterwards were dropped.
+    Forced { pending: Vec<OperatorId> },
+}
+
+pub fn main() -> eyre::Result<Shutdown> {
     let config: RuntimeConfig = {
         let raw = std::env::var("DORA_RUNTIME_CONFIG")
             .wrap_err("env variable DORA_RUNTIME_CONFIG must be set")?;
@@ -40,6 +59,7 @@ pub fn main() -> eyre::Result<()> {
     set_up_tracing(node_id.as_ref()).context("failed to set up tracing subscriber")?;
 
     let dataflow_descriptor = config.dataflow_descriptor.clone();
+    let drain_timeout = drain_timeout()?;
 
     let operator_definition = if operators.is_empty() {
         bail!("no operators");
@@ -50,6 +70,8 @@ pub fn main() -> eyre::Result<()> {
         ops.remove(0)
     };
 
+    registry::register(&operator_definition.id, &operator_definition.config);
+
     let (operator_events_tx, events) = mpsc::channel(1);
     let operator_id = operator_definition.id.clone();
     let operator_events = ReceiverStream::new(events).map(move |event| RuntimeEvent::Operator {
@@ -76,13 +98,14 @@ pub fn main() -> eyre::Result<()> {
     .into_iter()
     .collect();
     let (init_done_tx, init_done) = oneshot::channel();
-    let main_task = std::thread::spawn(move || -> Result<()> {
+    let main_task = std::thread::spawn(move || -> Result<Shutdown> {
         tokio_runtime.block_on(run(
             operator_config,
             config,
             operator_events,
             operator_channels,
             init_done,
+            drain_timeout,
         ))
     });
 
@@ -98,11 +121,22 @@ pub fn main() -> eyre::Result<()> {
     .wrap_err_with(|| format!("failed to run operator {operator_id}"))?;
 
     match main_task.join() {
-        Ok(result) => result.wrap_err("main task failed")?,
+        Ok(result) => result.wrap_err("main task failed"),
         Err(panic) => std::panic::resume_unwind(panic),
     }
+}
 
-    Ok(())
+fn drain_timeout() -> eyre::Result<Duration> {
+    match std::env::var(DRAIN_TIMEOUT_ENV) {
+        Ok(raw) => {
+            let secs: f64 = raw
+                .parse()
+                .wrap_err_with(|| format!("invalid value `{raw}` for {DRAIN_TIMEOUT_ENV}"))?;
+            Duration::try_from_secs_f64(secs)
+                .wrap_err_with(|| format!("invalid value `{raw}` for {DRAIN_TIMEOUT_ENV}"))
+        }
+        Err(_) => Ok(DEFAULT_DRAIN_TIMEOUT),
+    }
 }
 
 fn queue_sizes(config: &OperatorConfig) -> std::collections::BTreeMap<DataId, usize> {
@@ -121,7 +155,8 @@ async fn run(
     operator_events: impl Stream<Item = RuntimeEvent> + Unpin,
     mut operator_channels: HashMap<OperatorId, flume::Sender<Event>>,
     init_done: oneshot::Receiver<Result<()>>,
-) -> eyre::Result<()> {
+    drain_timeout: Duration,
+) -> eyre::Result<Shutdown> {
     #[cfg(feature = "metrics")]
     let _meter_provider = init_meter_provider(config.node_id.to_string());
     init_done
@@ -129,6 +164,9 @@ async fn run(
         .wrap_err("the `init_done` channel was closed unexpectedly")?
         .wrap_err("failed to init an operator")?;
     tracing::info!("All operators are ready, starting runtime");
+    for operator_id in operators.keys() {
+        registry::set_status(operator_id, OperatorStatus::Running);
+    }
 
     let (mut node, mut daemon_events) = DoraNode::init(config)?;
     let (daemon_events_tx, daemon_event_stream) = flume::bounded(1);
@@ -146,7 +184,41 @@ async fn run(
         .map(|(id, config)| (id, config.inputs.keys().collect()))
         .collect();
 
-    while let Some(event) = events.next().await {
+    // set once a stop was requested; operators may flush their outputs until then
+    let mut drain_deadline = None;
+    // operators that were still running when the drain timeout expired
+    let mut forced: Option<Vec<OperatorId>> = None;
+
+    let shutdown = loop {
+        let event = match drain_deadline {
+            Some(deadline) if forced.is_none() => {
+                match tokio::time::timeout_at(deadline, events.next()).await {
+                    Ok(event) => event,
+                    Err(_) => {
+                        let pending: Vec<_> = operators
+                            .keys()
+                            .filter(|id| {
+                                registry::operator(id)
+                                    .is_some_and(|op| op.status == OperatorStatus::Running)
+                            })
+                            .cloned()
+                            .collect();
+                        tracing::warn!(
+                            "operators {pending:?} did not finish within drain timeout of \
+                            {drain_timeout:?}, dropping their remaining outputs"
+                        );
+                        // keep receiving operator events until the operators exit, so
+                        // that they don't fail on a closed channel
+                        forced = Some(pending);
+                        continue;
+                    }
+                }
+            }
+            _ => events.next().await,
+        };
+        let Some(event) = event else {
+            break shutdown_status(forced);
+        };
         match event {
             RuntimeEvent::Operator {
                 id: operator_id,
@@ -154,15 +226,28 @@ async fn run(
             } => {
                 match event {
                     OperatorEvent::Error(err) => {
+                        registry::set_status(
+                            &operator_id,
+                            OperatorStatus::Failed {
+                                error: format!("{err:?}"),
+                            },
+                        );
                         bail!(err.wrap_err(format!(
                             "operator {}/{operator_id} raised an error",
                             node.id()
                         )))
                     }
                     OperatorEvent::Panic(payload) => {
+                        registry::set_status(
+                            &operator_id,
+                            OperatorStatus::Failed {
+                                error: format!("panicked: {payload:?}"),
+                            },
+                        );
                         bail!("operator {operator_id} panicked: {payload:?}");
                     }
                     OperatorEvent::Finished { reason } => {
+                        registry::set_status(&operator_id, OperatorStatus::Finished);
                         if let StopReason::ExplicitStopAll = reason {
                             // let hlc = dora_core::message::uhlc::HLC::default();
                             // let metadata = dora_core::message::Metadata::new(hlc.new_timestamp());
@@ -200,7 +285,7 @@ async fn run(
                         operator_channels.remove(&operator_id);
 
                         if operator_channels.is_empty() {
-                            break;
+                            break shutdown_status(forced);
                         }
                     }
                     OperatorEvent::AllocateOutputSample { len, sample: tx } => {
@@ -215,6 +300,10 @@ async fn run(
                         parameters,
                         data,
                     } => {
+                        if forced.is_some() {
+                            continue;
+                        }
+                        registry::record_output(&operator_id);
                         let output_id = operator_output_id(&operator_id, &output_id);
                         let result;
                         (node, result) = tokio::task::spawn_blocking(move || {
@@ -233,6 +322,7 @@ async fn run(
                 for (_, channel) in operator_channels.drain() {
                     let _ = channel.send_async(Event::Stop).await;
                 }
+                drain_deadline.get_or_insert_with(|| Instant::now() + drain_timeout);
             }
             RuntimeEvent::Event(Event::Reload {
                 operator_id: Some(operator_id),
@@ -272,6 +362,8 @@ async fn run(
                     })
                 {
                     tracing::warn!("{err}");
+                } else {
+                    registry::record_input(&operator_id, &input_id);
                 }
             }
             RuntimeEvent::Event(Event::InputClosed { id }) => {
@@ -311,15 +403,25 @@ async fn run(
                 }
             }
             RuntimeEvent::Event(Event::Error(err)) => eyre::bail!("received error event: {err}"),
+            RuntimeEvent::Event(Event::DecodeError { id, error }) => {
+                eyre::bail!("failed to decode input `{id}`: {error}")
+            }
             RuntimeEvent::Event(other) => {
                 tracing::warn!("received unknown event `{other:?}`");
             }
         }
-    }
+    };
 
     mem::drop(events);
 
-    Ok(())
+    Ok(shutdown)
+}
+
+fn shutdown_status(forced: Option<Vec<OperatorId>>) -> Shutdown {
+    match forced {
+        Some(pending) => Shutdown::Forced { pending },
+        None => Shutdown::Drained,
+    }
 }
 
 fn operator_output_id(operator_id: &OperatorId, output_id: &DataId) -> DataId {
@@ -1,5 +1,5 @@
 use dora_core::config::DataId;
-use dora_node_api::Event;
+use dora_node_api::{metadata::DROPPED_COUNT, Event, Parameter};
 use futures::{
     future::{self, FusedFuture},
     FutureExt,
@@ -24,6 +24,8 @@ pub fn channel(
 struct InputBuffer {
     queue: VecDeque<Option<Event>>,
     queue_sizes: BTreeMap<DataId, usize>,
+    /// Number of dropped events per input since the last sent event of that input.
+    dropped: BTreeMap<DataId, u64>,
 }
 
 impl InputBuffer {
@@ -31,6 +33,7 @@ impl InputBuffer {
         Self {
             queue: VecDeque::new(),
             queue_sizes,
+            dropped: BTreeMap::new(),
         }
     }
 
@@ -82,7 +85,17 @@ impl InputBuffer {
     ) -> future::Fuse<flume::r#async::SendFut<'a, Event>> {
         loop {
             match self.queue.pop_front() {
-                Some(Some(next)) => break outgoing.send_async(next).fuse(),
+                Some(Some(mut next)) => {
+                    if let Event::Input { id, metadata, .. } = &mut next {
+                        if let Some(dropped) = self.dropped.remove(id) {
+                            metadata.parameters.insert(
+                                DROPPED_COUNT.to_owned(),
+                                Parameter::Integer(dropped.try_into().unwrap_or(i64::MAX)),
+                            );
+                        }
+                    }
+                    break outgoing.send_async(next).fuse();
+                }
                 Some(None) => {
                     // dropped event, try again with next one
                 }
@@ -110,6 +123,7 @@ impl InputBuffer {
             match queue_size_remaining.get_mut(input_id) {
                 Some(0) => {
                     dropped += 1;
+                    *self.dropped.entry(input_id.clone()).or_default() += 1;
                     *event = None;
                 }
                 Some(size_remaining) => {
@@ -212,12 +212,9 @@ pub fn run(
                     );
                 }
 
-                let py_event = PyEvent {
-                    event: MergedEvent::Dora(event),
-                    _cleanup: None,
-                }
-                .to_py_dict(py)
-                .context("Could not convert event to pydict bound")?;
+                let py_event = Py::new(py, PyEvent::new(MergedEvent::Dora(event), None))
+                    .and_then(|event| PyEvent::to_python(event, py, false))
+                    .context("Could not convert event to pydict bound")?;
 
                 let status_enum = operator
                     .call_method1(py, "on_event", (py_event, send_output.clone()))
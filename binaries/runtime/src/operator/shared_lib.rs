@@ -226,6 +226,12 @@ impl<'lib> SharedLibraryOperator<'lib> {
                     input: None,
                     stop: false,
                 },
+                Event::DecodeError { id, error } => dora_operator_api_types::RawEvent {
+                    error: Some(format!("failed to decode input `{id}`: {error}").into()),
+                    input_closed: None,
+                    input: None,
+                    stop: false,
+                },
                 other => {
                     tracing::warn!("unexpected event: {other:?}");
                     continue;
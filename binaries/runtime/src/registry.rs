@@ -0,0 +1,112 @@
+//! Process-wide registry of the operators loaded by this runtime.
+//!
+//! The registry is filled when the runtime starts and is updated as events flow
+//! through the runtime. It can be queried at any time, e.g. from another thread
+//! or from within an operator, to inspect which operators are loaded and
+//! whether they are receiving data.
+
+use std::{
+    collections::BTreeMap,
+    sync::Mutex,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use dora_core::{
+    config::{DataId, OperatorId},
+    descriptor::OperatorConfig,
+};
+use serde::Serialize;
+
+static OPERATORS: Mutex<BTreeMap<OperatorId, OperatorInfo>> = Mutex::new(BTreeMap::new());
+
+/// Snapshot of the state of a loaded operator.
+#[derive(Debug, Clone, Serialize)]
+pub struct OperatorInfo {
+    pub id: OperatorId,
+    pub inputs: Vec<DataId>,
+    pub outputs: Vec<DataId>,
+    pub status: OperatorStatus,
+    /// Number of inputs that were forwarded to the operator.
+    pub received_inputs: u64,
+    /// Number of outputs that the operator sent.
+    pub sent_outputs: u64,
+    /// The ID of the last input that was forwarded to the operator.
+    pub last_input: Option<DataId>,
+    /// Time of the last forwarded input or sent output, in seconds since the UNIX epoch.
+    pub last_tick: Option<f64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum OperatorStatus {
+    Initializing,
+    Running,
+    Finished,
+    Failed { error: String },
+}
+
+/// Returns a snapshot of all operators known to the runtime.
+pub fn operators() -> Vec<OperatorInfo> {
+    OPERATORS
+        .lock()
+        .map(|operators| operators.values().cloned().collect())
+        .unwrap_or_default()
+}
+
+/// Returns a snapshot of the operator with the given ID, if it is loaded.
+pub fn operator(operator_id: &OperatorId) -> Option<OperatorInfo> {
+    OPERATORS
+        .lock()
+        .ok()
+        .and_then(|operators| operators.get(operator_id).cloned())
+}
+
+pub(crate) fn register(operator_id: &OperatorId, config: &OperatorConfig) {
+    let info = OperatorInfo {
+        id: operator_id.clone(),
+        inputs: config.inputs.keys().cloned().collect(),
+        outputs: config.outputs.iter().cloned().collect(),
+        status: OperatorStatus::Initializing,
+        received_inputs: 0,
+        sent_outputs: 0,
+        last_input: None,
+        last_tick: None,
+    };
+    if let Ok(mut operators) = OPERATORS.lock() {
+        operators.insert(operator_id.clone(), info);
+    }
+}
+
+pub(crate) fn set_status(operator_id: &OperatorId, status: OperatorStatus) {
+    update(operator_id, |info| info.status = status);
+}
+
+pub(crate) fn record_input(operator_id: &OperatorId, input_id: &DataId) {
+    update(operator_id, |info| {
+        info.received_inputs += 1;
+        info.last_input = Some(input_id.clone());
+        info.last_tick = now();
+    });
+}
+
+pub(crate) fn record_output(operator_id: &OperatorId) {
+    update(operator_id, |info| {
+        info.sent_outputs += 1;
+        info.last_tick = now();
+    });
+}
+
+fn update(operator_id: &OperatorId, f: impl FnOnce(&mut OperatorInfo)) {
+    if let Ok(mut operators) = OPERATORS.lock() {
+        if let Some(info) = operators.get_mut(operator_id) {
+            f(info);
+        }
+    }
+}
+
+fn now() -> Option<f64> {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .ok()
+        .map(|d| d.as_secs_f64())
+}
@@ -11,7 +11,7 @@ pub trait IntoArrow {
     fn into_arrow(self) -> Self::A;
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct ArrowData(pub arrow::array::ArrayRef);
 
 impl Deref for ArrowData {
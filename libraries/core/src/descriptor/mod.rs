@@ -1,6 +1,6 @@
 use dora_message::{
-    config::{Input, InputMapping, NodeRunConfig},
-    id::{DataId, OperatorId},
+    config::{Input, InputMapping, NodeRunConfig, UserInputMapping},
+    id::{DataId, NodeId, OperatorId, NAMESPACE_SEPARATOR},
 };
 use eyre::{bail, Context, OptionExt, Result};
 use std::{
@@ -12,10 +12,10 @@ use std::{
 // reexport for compatibility
 pub use dora_message::descriptor::{
     CoreNodeKind, CustomNode, Descriptor, Node, OperatorConfig, OperatorDefinition, OperatorSource,
-    PythonSource, ResolvedDeploy, ResolvedNode, RuntimeNode, SingleOperatorDefinition,
-    DYNAMIC_SOURCE, SHELL_SOURCE,
+    ParamValue, PythonSource, ResolvedDeploy, ResolvedNode, RuntimeNode, SchemaField,
+    SingleOperatorDefinition, DYNAMIC_SOURCE, SHELL_SOURCE,
 };
-pub use validate::ResolvedNodeExt;
+pub use validate::{ResolvedNodeExt, StructureError, StructureErrorKind};
 pub use visualize::collect_dora_timers;
 
 mod validate;
@@ -27,6 +27,7 @@ pub trait DescriptorExt {
     fn blocking_read(path: &Path) -> eyre::Result<Descriptor>;
     fn parse(buf: Vec<u8>) -> eyre::Result<Descriptor>;
     fn check(&self, working_dir: &Path) -> eyre::Result<()>;
+    fn check_structure(&self, allow_cycles: bool) -> Vec<StructureError>;
     fn check_in_daemon(
         &self,
         working_dir: &Path,
@@ -51,8 +52,12 @@ impl DescriptorExt for Descriptor {
             })
             .collect();
 
+        let node_ids: Vec<_> = self.nodes.iter().map(|n| &n.id).collect();
+
         let mut resolved = vec![];
         for mut node in self.nodes.clone() {
+            check_node_id(&node.id)?;
+
             // adjust input mappings
             let mut node_kind = node_kind_mut(&mut node)?;
             let input_mappings: Vec<_> = match &mut node_kind {
@@ -72,6 +77,9 @@ impl DescriptorExt for Descriptor {
                     InputMapping::User(m) => Some(m),
                 })
             {
+                if let Some(resolved) = resolve_namespace(mapping, &node_ids) {
+                    *mapping = resolved;
+                }
                 if let Some(op_name) = single_operator_nodes.get(&mapping.source).copied() {
                     mapping.output = DataId::from(format!("{op_name}/{}", mapping.output));
                 }
@@ -87,6 +95,7 @@ impl DescriptorExt for Descriptor {
                     run_config: NodeRunConfig {
                         inputs: node.inputs,
                         outputs: node.outputs,
+                        dynamic_outputs: node.dynamic_outputs,
                     },
                     envs: None,
                 }),
@@ -141,6 +150,10 @@ impl DescriptorExt for Descriptor {
             .wrap_err("Dataflow could not be validated.")
     }
 
+    fn check_structure(&self, allow_cycles: bool) -> Vec<StructureError> {
+        validate::check_structure(self, allow_cycles)
+    }
+
     fn check_in_daemon(
         &self,
         working_dir: &Path,
@@ -164,6 +177,46 @@ pub async fn read_as_descriptor(path: &Path) -> eyre::Result<Descriptor> {
     Descriptor::parse(buf)
 }
 
+/// Checks that a node ID has no empty namespaces and is not in the `dora`
+/// namespace, which is reserved for built-in inputs such as timers.
+pub(crate) fn check_node_id(id: &NodeId) -> eyre::Result<()> {
+    let id_str: &str = id.as_ref();
+    if id_str.split(NAMESPACE_SEPARATOR).any(str::is_empty) {
+        bail!("node ID `{id}` must not be empty or contain an empty namespace");
+    }
+    if id.is_in_namespace("dora") {
+        bail!("node ID `{id}` uses the reserved `dora` namespace");
+    }
+    Ok(())
+}
+
+/// Resolves the source of an input mapping that refers to a namespaced node.
+///
+/// Input mappings are split at the first `/` when they are parsed, so the
+/// mapping `perception/camera/image` has the source `perception` and the output
+/// `camera/image`. If a node `perception/camera` exists, this returns the
+/// mapping with that source and the output `image`. The longest matching node
+/// ID takes precedence, e.g. over a runtime node `perception` with an operator
+/// `camera`. Returns `None` if the parsed source is already the right one.
+fn resolve_namespace(mapping: &UserInputMapping, node_ids: &[&NodeId]) -> Option<UserInputMapping> {
+    let full = format!("{}{NAMESPACE_SEPARATOR}{}", mapping.source, mapping.output);
+    let full_id = NodeId::from(full.clone());
+    let source = node_ids
+        .iter()
+        .copied()
+        .filter(|id| full_id.is_in_namespace(id.as_ref()))
+        // all candidates are prefixes of the mapping, so the longest is the greatest
+        .max()?;
+    if *source == mapping.source {
+        return None;
+    }
+    let source_len = AsRef::<str>::as_ref(source).len();
+    Some(UserInputMapping {
+        source: source.clone(),
+        output: DataId::from(full[source_len + 1..].to_owned()),
+    })
+}
+
 fn node_kind_mut(node: &mut Node) -> eyre::Result<NodeKindMut> {
     match node.kind()? {
         NodeKind::Standard(_) => node
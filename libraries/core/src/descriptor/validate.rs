@@ -6,14 +6,19 @@ use crate::{
 
 use dora_message::{
     config::{Input, InputMapping, UserInputMapping},
-    descriptor::{CoreNodeKind, OperatorSource, ResolvedNode, DYNAMIC_SOURCE, SHELL_SOURCE},
-    id::{DataId, OperatorId},
+    descriptor::{CoreNodeKind, Node, OperatorSource, ResolvedNode, DYNAMIC_SOURCE, SHELL_SOURCE},
+    id::{DataId, NodeId, OperatorId},
 };
 use eyre::{bail, eyre, Context};
-use std::{path::Path, process::Command};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt,
+    path::Path,
+    process::Command,
+};
 use tracing::info;
 
-use super::{resolve_path, Descriptor, DescriptorExt};
+use super::{check_node_id, resolve_path, Descriptor, DescriptorExt, NodeExt};
 const VERSION: &str = env!("CARGO_PKG_VERSION");
 
 pub fn check_dataflow(
@@ -103,6 +108,10 @@ pub fn check_dataflow(
         };
     }
 
+    if let Some(error) = check_shutdown_order(dataflow).into_iter().next() {
+        bail!("{error}");
+    }
+
     // Check that nodes can resolve `send_stdout_as`
     for node in &nodes {
         node.send_stdout_as()
@@ -160,10 +169,20 @@ fn check_input(
             })?;
             match &source_node.kind {
                 CoreNodeKind::Custom(custom_node) => {
-                    if !custom_node.run_config.outputs.contains(output) {
-                        bail!(
-                            "output `{source}/{output}` mapped to \
-                            input `{input_id_str}` does not exist",
+                    let run_config = &custom_node.run_config;
+                    if !run_config.outputs.contains(output) {
+                        if !run_config.dynamic_outputs {
+                            bail!(
+                                "output `{source}/{output}` mapped to \
+                                input `{input_id_str}` does not exist",
+                            );
+                        }
+                        // the output might be declared at runtime through
+                        // `declare_dynamic_output`
+                        tracing::info!(
+                            "output `{source}/{output}` mapped to input `{input_id_str}` \
+                            is not declared in the dataflow, it needs to be declared \
+                            dynamically by node `{source}`",
                         );
                     }
                 }
@@ -196,6 +215,238 @@ fn check_input(
     Ok(())
 }
 
+/// Kind of a structural problem of a dataflow, see [`check_structure`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StructureErrorKind {
+    /// The node definition is invalid, e.g. because it has no `path`.
+    InvalidNode,
+    /// Multiple nodes have the same ID.
+    DuplicateNodeId,
+    /// An input is mapped to a node that does not exist.
+    UnknownSource,
+    /// An input is mapped to an output that is not declared by its source.
+    UndeclaredOutput,
+    /// The inputs of the nodes form a cycle.
+    Cycle,
+    /// The `shutdown_order` lists a node that does not exist or lists a node
+    /// multiple times.
+    InvalidShutdownOrder,
+}
+
+impl StructureErrorKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            StructureErrorKind::InvalidNode => "invalid_node",
+            StructureErrorKind::DuplicateNodeId => "duplicate_node_id",
+            StructureErrorKind::UnknownSource => "unknown_source",
+            StructureErrorKind::UndeclaredOutput => "undeclared_output",
+            StructureErrorKind::Cycle => "cycle",
+            StructureErrorKind::InvalidShutdownOrder => "invalid_shutdown_order",
+        }
+    }
+}
+
+/// A structural problem of a dataflow.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StructureError {
+    pub kind: StructureErrorKind,
+    /// The node that the problem was found at, if any.
+    pub node: Option<NodeId>,
+    pub message: String,
+}
+
+impl fmt::Display for StructureError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+/// Checks the graph of the dataflow without accessing the file system.
+///
+/// In contrast to [`check_dataflow`], all problems are collected instead of
+/// returning the first one. Outputs of custom nodes that are not declared in the
+/// descriptor are reported too, even though they might be declared dynamically.
+/// Cycles are only reported if `allow_cycles` is false.
+pub fn check_structure(dataflow: &Descriptor, allow_cycles: bool) -> Vec<StructureError> {
+    let mut errors = Vec::new();
+
+    let mut seen = BTreeSet::new();
+    for node in &dataflow.nodes {
+        if !seen.insert(&node.id) {
+            errors.push(StructureError {
+                kind: StructureErrorKind::DuplicateNodeId,
+                node: Some(node.id.clone()),
+                message: format!("node ID `{}` is used by multiple nodes", node.id),
+            });
+        }
+    }
+
+    errors.extend(check_shutdown_order(dataflow));
+
+    // resolve the valid nodes only, so that the remaining checks still apply to them
+    let mut valid = dataflow.clone();
+    let check_node = |node: &Node| -> eyre::Result<()> {
+        node.kind()?;
+        check_node_id(&node.id)
+    };
+    valid.nodes.retain(|node| match check_node(node) {
+        Ok(()) => true,
+        Err(err) => {
+            errors.push(StructureError {
+                kind: StructureErrorKind::InvalidNode,
+                node: Some(node.id.clone()),
+                message: err.to_string(),
+            });
+            false
+        }
+    });
+    let nodes = match valid.resolve_aliases_and_set_defaults() {
+        Ok(nodes) => nodes,
+        Err(err) => {
+            errors.push(StructureError {
+                kind: StructureErrorKind::InvalidNode,
+                node: None,
+                message: format!("{err:?}"),
+            });
+            return errors;
+        }
+    };
+
+    // edges from source nodes to the nodes that receive their outputs
+    let mut edges: BTreeMap<&NodeId, BTreeSet<&NodeId>> = BTreeMap::new();
+    for node in &nodes {
+        let inputs: Vec<_> = match &node.kind {
+            CoreNodeKind::Custom(custom) => custom
+                .run_config
+                .inputs
+                .iter()
+                .map(|(input_id, input)| (format!("{}/{input_id}", node.id), input))
+                .collect(),
+            CoreNodeKind::Runtime(runtime) => runtime
+                .operators
+                .iter()
+                .flat_map(|operator| {
+                    operator.config.inputs.iter().map(|(input_id, input)| {
+                        (format!("{}/{}/{input_id}", node.id, operator.id), input)
+                    })
+                })
+                .collect(),
+        };
+        for (input_id, input) in inputs {
+            let InputMapping::User(mapping) = &input.mapping else {
+                continue;
+            };
+            match check_mapping(mapping, &nodes, &input_id) {
+                Ok(()) => {}
+                Err((kind, message)) => errors.push(StructureError {
+                    kind,
+                    node: Some(node.id.clone()),
+                    message,
+                }),
+            }
+            if nodes.iter().any(|n| n.id == mapping.source) {
+                edges.entry(&mapping.source).or_default().insert(&node.id);
+            }
+        }
+    }
+
+    if !allow_cycles {
+        let mut visited = BTreeSet::new();
+        for node in &nodes {
+            find_cycles(&node.id, &edges, &mut Vec::new(), &mut visited, &mut errors);
+        }
+    }
+
+    errors
+}
+
+fn check_mapping(
+    mapping: &UserInputMapping,
+    nodes: &[ResolvedNode],
+    input_id: &str,
+) -> Result<(), (StructureErrorKind, String)> {
+    let UserInputMapping { source, output } = mapping;
+    let Some(source_node) = nodes.iter().find(|n| &n.id == source) else {
+        return Err((
+            StructureErrorKind::UnknownSource,
+            format!("source node `{source}` mapped to input `{input_id}` does not exist"),
+        ));
+    };
+    let declared = match &source_node.kind {
+        CoreNodeKind::Custom(custom_node) => {
+            let run_config = &custom_node.run_config;
+            run_config.dynamic_outputs || run_config.outputs.contains(output)
+        }
+        CoreNodeKind::Runtime(runtime) => {
+            let (operator_id, output) = output.split_once('/').unwrap_or_default();
+            let output = DataId::from(output.to_owned());
+            runtime.operators.iter().any(|operator| {
+                AsRef::<str>::as_ref(&operator.id) == operator_id
+                    && operator.config.outputs.contains(&output)
+            })
+        }
+    };
+    if declared {
+        Ok(())
+    } else {
+        Err((
+            StructureErrorKind::UndeclaredOutput,
+            format!("output `{source}/{output}` mapped to input `{input_id}` is not declared"),
+        ))
+    }
+}
+
+fn check_shutdown_order(dataflow: &Descriptor) -> Vec<StructureError> {
+    let mut errors = Vec::new();
+    let mut seen = BTreeSet::new();
+    for node_id in &dataflow.shutdown_order {
+        let message = if !dataflow.nodes.iter().any(|node| &node.id == node_id) {
+            format!("node `{node_id}` of the `shutdown_order` does not exist")
+        } else if !seen.insert(node_id) {
+            format!("node `{node_id}` is listed multiple times in the `shutdown_order`")
+        } else {
+            continue;
+        };
+        errors.push(StructureError {
+            kind: StructureErrorKind::InvalidShutdownOrder,
+            node: Some(node_id.clone()),
+            message,
+        });
+    }
+    errors
+}
+
+/// Depth-first search that reports every cycle reachable from `node` once.
+fn find_cycles<'a>(
+    node: &'a NodeId,
+    edges: &BTreeMap<&'a NodeId, BTreeSet<&'a NodeId>>,
+    path: &mut Vec<&'a NodeId>,
+    visited: &mut BTreeSet<&'a NodeId>,
+    errors: &mut Vec<StructureError>,
+) {
+    if let Some(start) = path.iter().position(|n| *n == node) {
+        let cycle: Vec<_> = path[start..]
+            .iter()
+            .chain([&node])
+            .map(|n| format!("`{n}`"))
+            .collect();
+        errors.push(StructureError {
+            kind: StructureErrorKind::Cycle,
+            node: Some(node.clone()),
+            message: format!("nodes form a cycle: {}", cycle.join(" -> ")),
+        });
+        return;
+    }
+    if !visited.insert(node) {
+        return;
+    }
+    path.push(node);
+    for target in edges.get(node).into_iter().flatten() {
+        find_cycles(target, edges, path, visited, errors);
+    }
+    path.pop();
+}
+
 fn check_python_runtime() -> eyre::Result<()> {
     // Check if python dora-rs is installed and match cli version
     let reinstall_command =
@@ -223,3 +474,154 @@ assert dora.__version__=='{VERSION}',  'Python dora-rs should be {VERSION}, but
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn kinds(yaml: &str, allow_cycles: bool) -> Vec<StructureErrorKind> {
+        let dataflow: Descriptor = serde_yaml::from_str(yaml).unwrap();
+        check_structure(&dataflow, allow_cycles)
+            .into_iter()
+            .map(|error| error.kind)
+            .collect()
+    }
+
+    #[test]
+    fn structure_errors_are_collected() {
+        let dataflow = r#"
+nodes:
+  - id: camera
+    path: camera.py
+    inputs:
+      tick: dora/timer/millis/50
+      feedback: plot/ack
+    outputs:
+      - image
+  - id: plot
+    path: plot.py
+    inputs:
+      image: camera/image
+      depth: camera/depth
+      lidar: lidar/points
+    outputs:
+      - ack
+  - id: plot
+    path: other.py
+"#;
+        assert_eq!(
+            kinds(dataflow, false),
+            [
+                StructureErrorKind::DuplicateNodeId,
+                StructureErrorKind::UndeclaredOutput,
+                StructureErrorKind::UnknownSource,
+                StructureErrorKind::Cycle,
+            ]
+        );
+        assert_eq!(
+            kinds(dataflow, true),
+            [
+                StructureErrorKind::DuplicateNodeId,
+                StructureErrorKind::UndeclaredOutput,
+                StructureErrorKind::UnknownSource,
+            ]
+        );
+    }
+
+    #[test]
+    fn dynamic_outputs_must_be_enabled() {
+        let dataflow = |dynamic_outputs| {
+            format!(
+                r#"
+nodes:
+  - id: plugins
+    path: plugins.py
+    dynamic_outputs: {dynamic_outputs}
+  - id: plot
+    path: plot.py
+    inputs:
+      image: plugins/camera_0
+"#
+            )
+        };
+        assert_eq!(
+            kinds(&dataflow(false), false),
+            [StructureErrorKind::UndeclaredOutput]
+        );
+        assert_eq!(kinds(&dataflow(true), false), []);
+    }
+
+    #[test]
+    fn namespaced_sources_are_resolved() {
+        let dataflow = r#"
+nodes:
+  - id: perception/camera
+    path: camera.py
+    outputs:
+      - image
+  - id: perception
+    operators:
+      - id: camera
+        python: op.py
+        outputs:
+          - depth
+  - id: planning/planner
+    path: planner.py
+    inputs:
+      image: perception/camera/image
+      depth: perception/camera/depth
+  - id: dora/timer
+    path: timer.py
+  - id: perception//lidar
+    path: lidar.py
+"#;
+        let dataflow: Descriptor = serde_yaml::from_str(dataflow).unwrap();
+        let errors = check_structure(&dataflow, false);
+        let kinds: Vec<_> = errors.iter().map(|error| error.kind).collect();
+        // the longest node ID is the source, so `depth` is not an output of it
+        assert_eq!(
+            kinds,
+            [
+                StructureErrorKind::InvalidNode,
+                StructureErrorKind::InvalidNode,
+                StructureErrorKind::UndeclaredOutput,
+            ]
+        );
+        assert!(errors[2]
+            .message
+            .contains("output `perception/camera/depth`"));
+    }
+
+    #[test]
+    fn shutdown_order_must_list_existing_nodes_once() {
+        let dataflow = r#"
+nodes:
+  - id: camera
+    path: camera.py
+    outputs:
+      - image
+  - id: recorder
+    path: recorder.py
+    inputs:
+      image: camera/image
+shutdown_order:
+  - recorder
+  - lidar
+  - recorder
+  - camera
+"#;
+        let dataflow: Descriptor = serde_yaml::from_str(dataflow).unwrap();
+        let errors = check_structure(&dataflow, false);
+        let nodes: Vec<(StructureErrorKind, Option<&str>)> = errors
+            .iter()
+            .map(|error| (error.kind, error.node.as_ref().map(AsRef::as_ref)))
+            .collect();
+        assert_eq!(
+            nodes,
+            [
+                (StructureErrorKind::InvalidShutdownOrder, Some("lidar")),
+                (StructureErrorKind::InvalidShutdownOrder, Some("recorder")),
+            ]
+        );
+    }
+}
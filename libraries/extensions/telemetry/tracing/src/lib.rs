@@ -3,18 +3,65 @@
 //! This module init a tracing propagator for Rust code that requires tracing, and is
 //! able to serialize and deserialize context that has been sent via the middleware.
 
-use std::path::Path;
+use std::{
+    collections::BTreeMap,
+    path::Path,
+    sync::{Mutex, OnceLock},
+};
 
 use eyre::Context as EyreContext;
 use tracing::metadata::LevelFilter;
 use tracing_subscriber::{
-    filter::FilterExt, prelude::__tracing_subscriber_SubscriberExt, EnvFilter, Layer,
+    filter::{FilterExt, Targets},
+    prelude::__tracing_subscriber_SubscriberExt,
+    reload, EnvFilter, Layer,
 };
 
 use eyre::ContextCompat;
 use tracing_subscriber::Registry;
 pub mod telemetry;
 
+/// Log levels that were set through [`set_log_level`], per target.
+static LOG_LEVELS: Mutex<BTreeMap<String, LevelFilter>> = Mutex::new(BTreeMap::new());
+/// Updates the stdout filter of the subscriber that was set up by this module.
+static LOG_LEVEL_HANDLE: OnceLock<reload::Handle<Targets, Registry>> = OnceLock::new();
+
+/// Sets the log level for the given target at runtime, e.g. for `dora_node_api`.
+///
+/// The target is a module path prefix, like in `RUST_LOG`. The level applies to
+/// the stdout output and can only increase the verbosity, i.e. messages that
+/// are enabled through `RUST_LOG` or the default level are still printed.
+/// Passing `None` removes the level of the target again.
+///
+/// Levels set before the tracing subscriber is set up are applied once it is.
+pub fn set_log_level(target: &str, level: Option<LevelFilter>) -> eyre::Result<()> {
+    let mut levels = LOG_LEVELS.lock().unwrap_or_else(|err| err.into_inner());
+    match level {
+        Some(level) => levels.insert(target.to_owned(), level),
+        None => levels.remove(target),
+    };
+    if let Some(handle) = LOG_LEVEL_HANDLE.get() {
+        handle
+            .reload(log_level_targets(&levels))
+            .context("failed to update log level")?;
+    }
+    Ok(())
+}
+
+/// Returns the log level that was set for the target through [`set_log_level`].
+pub fn log_level(target: &str) -> Option<LevelFilter> {
+    let levels = LOG_LEVELS.lock().unwrap_or_else(|err| err.into_inner());
+    levels.get(target).copied()
+}
+
+fn log_level_targets(levels: &BTreeMap<String, LevelFilter>) -> Targets {
+    Targets::new().with_targets(
+        levels
+            .iter()
+            .map(|(target, level)| (target.clone(), *level)),
+    )
+}
+
 pub fn set_up_tracing(name: &str) -> eyre::Result<()> {
     set_up_tracing_opts(name, Some(LevelFilter::WARN), None)
 }
@@ -30,10 +77,14 @@ pub fn set_up_tracing_opts(
     file: Option<FileLogging>,
 ) -> eyre::Result<()> {
     let mut layers = Vec::new();
+    let mut log_level_handle = None;
 
     if let Some(level) = stdout {
         // Filter log using `RUST_LOG`. More useful for CLI.
-        let env_filter = EnvFilter::from_default_env().or(level);
+        let levels = log_level_targets(&LOG_LEVELS.lock().unwrap_or_else(|err| err.into_inner()));
+        let (levels, handle) = reload::Layer::new(levels);
+        log_level_handle = Some(handle);
+        let env_filter = EnvFilter::from_default_env().or(level).or(levels);
         let layer = tracing_subscriber::fmt::layer()
             .compact()
             .with_filter(env_filter);
@@ -71,5 +122,9 @@ pub fn set_up_tracing_opts(
     let registry = Registry::default().with(layers);
     tracing::subscriber::set_global_default(registry).context(format!(
         "failed to set tracing global subscriber for {name}"
-    ))
+    ))?;
+    if let Some(handle) = log_level_handle {
+        let _ = LOG_LEVEL_HANDLE.set(handle);
+    }
+    Ok(())
 }
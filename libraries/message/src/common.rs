@@ -1,5 +1,8 @@
 use core::fmt;
-use std::borrow::Cow;
+use std::{
+    borrow::Cow,
+    time::{SystemTime, UNIX_EPOCH},
+};
 
 use aligned_vec::{AVec, ConstAlign};
 use uuid::Uuid;
@@ -129,6 +132,59 @@ pub struct Timestamped<T> {
     pub timestamp: uhlc::Timestamp,
 }
 
+/// Estimated offset of the wall clock of a machine relative to a reference clock.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct ClockOffset {
+    /// Nanoseconds by which the clock is ahead of the reference clock, negative
+    /// if it is behind.
+    pub offset_ns: i64,
+    /// Maximum error of `offset_ns` in nanoseconds, i.e. half of the round-trip
+    /// delay of the exchange that the offset was estimated from.
+    pub uncertainty_ns: u64,
+}
+
+impl ClockOffset {
+    /// Estimates the offset of a remote clock from a request-reply exchange, as in NTP.
+    ///
+    /// The request is sent and the reply received at the given times of the
+    /// reference clock, while the remote clock received the request and sent the
+    /// reply at the given remote times. All times are in nanoseconds since the
+    /// UNIX epoch.
+    pub fn from_exchange(
+        request_sent: u64,
+        request_received: u64,
+        reply_sent: u64,
+        reply_received: u64,
+    ) -> Self {
+        let [t1, t2, t3, t4] =
+            [request_sent, request_received, reply_sent, reply_received].map(i128::from);
+        let offset = ((t2 - t1) + (t3 - t4)) / 2;
+        let delay = ((t4 - t1) - (t3 - t2)).max(0);
+        Self {
+            offset_ns: offset.clamp(i64::MIN.into(), i64::MAX.into()) as i64,
+            uncertainty_ns: (delay / 2).try_into().unwrap_or(u64::MAX),
+        }
+    }
+
+    /// Offset of this clock relative to the `base` clock, given the offsets of
+    /// both clocks to the same reference clock.
+    pub fn relative_to(self, base: ClockOffset) -> ClockOffset {
+        ClockOffset {
+            offset_ns: self.offset_ns.saturating_sub(base.offset_ns),
+            uncertainty_ns: self.uncertainty_ns.saturating_add(base.uncertainty_ns),
+        }
+    }
+}
+
+/// Current time of the wall clock in nanoseconds since the UNIX epoch, as used
+/// for the estimation of [`ClockOffset`]s.
+pub fn wall_clock_ns() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|time| time.as_nanos().try_into().unwrap_or(u64::MAX))
+        .unwrap_or_default()
+}
+
 pub type SharedMemoryId = String;
 
 #[derive(serde::Serialize, serde::Deserialize, Clone)]
@@ -33,6 +33,13 @@ pub struct NodeRunConfig {
     ///  - output_2
     #[serde(default)]
     pub outputs: BTreeSet<DataId>,
+    /// Whether the node declares additional outputs at runtime through
+    /// `declare_dynamic_output`.
+    ///
+    /// Inputs of other nodes can only be mapped to outputs of the node that are
+    /// not listed in `outputs` if this is set.
+    #[serde(default)]
+    pub dynamic_outputs: bool,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
@@ -137,7 +144,14 @@ impl Serialize for InputMapping {
     where
         S: serde::Serializer,
     {
-        serializer.collect_str(self)
+        match self {
+            // the string form is ambiguous for namespaced sources, e.g.
+            // `perception/camera/image`, so they are serialized as struct
+            InputMapping::User(mapping) if mapping.source.namespace().is_some() => {
+                mapping.serialize(serializer)
+            }
+            _ => serializer.collect_str(self),
+        }
     }
 }
 
@@ -146,62 +160,84 @@ impl<'de> Deserialize<'de> for InputMapping {
     where
         D: serde::Deserializer<'de>,
     {
-        let string = String::deserialize(deserializer)?;
-        let (source, output) = string
-            .split_once('/')
-            .ok_or_else(|| serde::de::Error::custom("input must start with `<source>/`"))?;
-
-        let deserialized = match source {
-            "dora" => match output.split_once('/') {
-                Some(("timer", output)) => {
-                    let (unit, value) = output.split_once('/').ok_or_else(|| {
-                        serde::de::Error::custom(
-                            "timer input must specify unit and value (e.g. `secs/5` or `millis/100`)",
-                        )
-                    })?;
-                    let interval = match unit {
-                        "secs" => {
-                            let value = value.parse().map_err(|_| {
-                                serde::de::Error::custom(format!(
-                                    "secs must be an integer (got `{value}`)"
-                                ))
-                            })?;
-                            Duration::from_secs(value)
-                        }
-                        "millis" => {
-                            let value = value.parse().map_err(|_| {
-                                serde::de::Error::custom(format!(
-                                    "millis must be an integer (got `{value}`)"
-                                ))
-                            })?;
-                            Duration::from_millis(value)
-                        }
-                        other => {
-                            return Err(serde::de::Error::custom(format!(
-                                "timer unit must be either secs or millis (got `{other}`"
-                            )))
-                        }
-                    };
-                    Self::Timer { interval }
-                }
-                Some((other, _)) => {
-                    return Err(serde::de::Error::custom(format!(
-                        "unknown dora input `{other}`"
-                    )))
-                }
-                None => return Err(serde::de::Error::custom("dora input has invalid format")),
-            },
-            _ => Self::User(UserInputMapping {
-                source: source.to_owned().into(),
-                output: output.to_owned().into(),
-            }),
-        };
+        deserializer.deserialize_any(InputMappingVisitor)
+    }
+}
 
-        Ok(deserialized)
+struct InputMappingVisitor;
+
+impl<'de> serde::de::Visitor<'de> for InputMappingVisitor {
+    type Value = InputMapping;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("an input mapping like `<source>/<output>`")
+    }
+
+    fn visit_str<E>(self, string: &str) -> Result<Self::Value, E>
+    where
+        E: serde::de::Error,
+    {
+        parse_input_mapping(string)
+    }
+
+    fn visit_map<A>(self, map: A) -> Result<Self::Value, A::Error>
+    where
+        A: serde::de::MapAccess<'de>,
+    {
+        let mapping =
+            UserInputMapping::deserialize(serde::de::value::MapAccessDeserializer::new(map))?;
+        Ok(InputMapping::User(mapping))
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, JsonSchema)]
+fn parse_input_mapping<E: serde::de::Error>(string: &str) -> Result<InputMapping, E> {
+    let (source, output) = string
+        .split_once('/')
+        .ok_or_else(|| E::custom("input must start with `<source>/`"))?;
+
+    let deserialized = match source {
+        "dora" => match output.split_once('/') {
+            Some(("timer", output)) => {
+                let (unit, value) = output.split_once('/').ok_or_else(|| {
+                    E::custom(
+                        "timer input must specify unit and value (e.g. `secs/5` or `millis/100`)",
+                    )
+                })?;
+                let interval = match unit {
+                    "secs" => {
+                        let value = value.parse().map_err(|_| {
+                            E::custom(format!("secs must be an integer (got `{value}`)"))
+                        })?;
+                        Duration::from_secs(value)
+                    }
+                    "millis" => {
+                        let value = value.parse().map_err(|_| {
+                            E::custom(format!("millis must be an integer (got `{value}`)"))
+                        })?;
+                        Duration::from_millis(value)
+                    }
+                    other => {
+                        return Err(E::custom(format!(
+                            "timer unit must be either secs or millis (got `{other}`"
+                        )))
+                    }
+                };
+                InputMapping::Timer { interval }
+            }
+            Some((other, _)) => return Err(E::custom(format!("unknown dora input `{other}`"))),
+            None => return Err(E::custom("dora input has invalid format")),
+        },
+        _ => InputMapping::User(UserInputMapping {
+            source: source.to_owned().into(),
+            output: output.to_owned().into(),
+        }),
+    };
+
+    Ok(deserialized)
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
 pub struct UserInputMapping {
     pub source: NodeId,
     pub output: DataId,
@@ -256,3 +292,45 @@ impl Default for RemoteCommunicationConfig {
         Self::Tcp
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn input_mapping_roundtrip() {
+        // resolved sources of namespaced nodes, see `resolve_namespace` in dora-core
+        for source in ["perception/camera", "robot/perception/camera"] {
+            let mapping = InputMapping::User(UserInputMapping {
+                source: source.to_owned().into(),
+                output: "image".to_owned().into(),
+            });
+            let input = Input {
+                mapping: mapping.clone(),
+                queue_size: None,
+            };
+            let json = serde_json::to_string(&input).unwrap();
+            assert_eq!(serde_json::from_str::<Input>(&json).unwrap(), input);
+
+            let input = Input {
+                mapping,
+                queue_size: Some(5),
+            };
+            let json = serde_json::to_string(&input).unwrap();
+            assert_eq!(serde_json::from_str::<Input>(&json).unwrap(), input);
+        }
+        // unresolved mappings keep their string form
+        let mappings = [
+            "camera/image",
+            "perception/camera/image",
+            "dora/timer/millis/100",
+        ];
+        for mapping in mappings {
+            let input: Input = serde_yaml::from_str(mapping).unwrap();
+            let json = serde_json::to_string(&input).unwrap();
+            let deserialized: Input = serde_json::from_str(&json).unwrap();
+            assert_eq!(deserialized, input);
+            assert_eq!(deserialized.mapping.to_string(), mapping);
+        }
+    }
+}
@@ -57,6 +57,9 @@ impl DataflowList {
 pub struct DataflowListEntry {
     pub id: DataflowIdAndName,
     pub status: DataflowStatus,
+    /// Number of nodes of the dataflow.
+    #[serde(default)]
+    pub node_count: usize,
 }
 
 #[derive(Debug, Clone, Copy, serde::Deserialize, serde::Serialize, PartialEq, Eq)]
@@ -6,7 +6,7 @@ use crate::{
     DataflowId,
 };
 
-pub use crate::common::Timestamped;
+pub use crate::common::{ClockOffset, Timestamped};
 
 #[derive(Debug, serde::Serialize, serde::Deserialize)]
 pub enum RegisterResult {
@@ -45,6 +45,18 @@ pub enum DaemonCoordinatorEvent {
     },
     Destroy,
     Heartbeat,
+    /// Request of the clock synchronization between the coordinator and the
+    /// daemons, sent at `coordinator_time_ns` of the coordinator's wall clock.
+    ///
+    /// The daemon answers with [`DaemonEvent::ClockSync`](crate::daemon_to_coordinator::DaemonEvent::ClockSync)
+    /// instead of a reply, so that the answer is timed independently of other
+    /// replies. The request also contains the latest offsets of the wall clocks of all
+    /// connected machines relative to the coordinator, which the daemons use to
+    /// answer [`DaemonRequest::ClockSkew`](crate::node_to_daemon::DaemonRequest::ClockSkew).
+    ClockSync {
+        coordinator_time_ns: u64,
+        offsets: BTreeMap<String, ClockOffset>,
+    },
 }
 
 #[derive(Debug, serde::Deserialize, serde::Serialize)]
@@ -58,6 +58,16 @@ pub enum DaemonEvent {
     },
     Heartbeat,
     Log(LogMessage),
+    /// Answer to a [`ClockSync`](crate::coordinator_to_daemon::DaemonCoordinatorEvent::ClockSync)
+    /// request that was sent at `request_sent_ns` of the coordinator's wall clock.
+    ///
+    /// Contains the times of the daemon's wall clock at which it received the
+    /// request and sent the answer, in nanoseconds since the UNIX epoch.
+    ClockSync {
+        request_sent_ns: u64,
+        received_ns: u64,
+        sent_ns: u64,
+    },
 }
 
 #[derive(Debug, Clone, serde::Deserialize, serde::Serialize)]
@@ -1,4 +1,4 @@
-use std::{net::SocketAddr, path::PathBuf};
+use std::{net::SocketAddr, path::PathBuf, time::Duration};
 
 use crate::{
     config::NodeRunConfig,
@@ -8,7 +8,7 @@ use crate::{
     DataflowId,
 };
 
-pub use crate::common::{DataMessage, DropToken, SharedMemoryId, Timestamped};
+pub use crate::common::{ClockOffset, DataMessage, DropToken, SharedMemoryId, Timestamped};
 
 // Passed via env variable
 #[derive(Debug, serde::Serialize, serde::Deserialize)]
@@ -48,11 +48,44 @@ pub enum DaemonCommunication {
 #[must_use]
 pub enum DaemonReply {
     Result(Result<(), String>),
-    PreparedMessage { shared_memory_id: SharedMemoryId },
+    PreparedMessage {
+        shared_memory_id: SharedMemoryId,
+    },
     NextEvents(Vec<Timestamped<NodeEvent>>),
     NextDropEvents(Vec<Timestamped<NodeDropEvent>>),
-    NodeConfig { result: Result<NodeConfig, String> },
+    NodeConfig {
+        result: Result<NodeConfig, String>,
+    },
+    PipelineDepths(Vec<EdgeDepth>),
+    /// Offset of the wall clock of another node relative to the requesting node.
+    ClockSkew(ClockOffset),
+    /// Whether all receivers acknowledged a reliably sent message.
+    Acked(bool),
+    /// The node cannot subscribe because another process is already connected
+    /// with the same node ID.
+    DuplicateNodeId(NodeId),
     Empty,
+    /// The node cannot register because it was built against a dora version
+    /// whose message format is not compatible with the daemon.
+    ///
+    /// This reply is decoded by nodes of other dora versions, so it must keep its
+    /// position in this enum. New variants are added after it.
+    VersionMismatch {
+        node_version: semver::Version,
+        daemon_version: semver::Version,
+    },
+}
+
+/// The number of events that are queued on an edge of the dataflow.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct EdgeDepth {
+    /// The sending node and its output.
+    pub source: (NodeId, DataId),
+    /// The receiving node and its input.
+    pub target: (NodeId, DataId),
+    /// The number of events that the receiving node buffered for the input, but
+    /// did not process yet.
+    pub depth: usize,
 }
 
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
@@ -70,6 +103,52 @@ pub enum NodeEvent {
         id: DataId,
     },
     AllInputsClosed,
+    Lifecycle(LifecycleEvent),
+}
+
+/// Notifies a node that another node joined or left the dataflow, reported the
+/// progress of a task, or reported its statistics before closing.
+///
+/// Only sent to nodes that subscribed through
+/// [`DaemonRequest::SubscribeLifecycleEvents`][crate::node_to_daemon::DaemonRequest::SubscribeLifecycleEvents].
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub enum LifecycleEvent {
+    /// The node subscribed to its event stream.
+    NodeJoined { node_id: NodeId },
+    /// The node exited.
+    NodeLeft { node_id: NodeId },
+    /// The node reported the progress of a long-running task, see
+    /// [`DaemonRequest::ReportProgress`][crate::node_to_daemon::DaemonRequest::ReportProgress].
+    Progress {
+        node_id: NodeId,
+        task: String,
+        progress: f32,
+        message: Option<String>,
+    },
+    /// The node reported its lifetime statistics before closing, see
+    /// [`DaemonRequest::ReportSummary`][crate::node_to_daemon::DaemonRequest::ReportSummary].
+    Summary {
+        node_id: NodeId,
+        summary: NodeSummary,
+    },
+}
+
+/// Statistics of a node over its lifetime, reported when the node closes.
+#[derive(Debug, Clone, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct NodeSummary {
+    /// Number of input events that were delivered to the node.
+    pub received: u64,
+    /// Payload bytes of the delivered input events.
+    pub received_bytes: u64,
+    /// Number of input events that were dropped because an input queue was full.
+    pub dropped: u64,
+    /// Number of outputs that the node sent.
+    pub sent: u64,
+    /// Payload bytes of the sent outputs.
+    pub sent_bytes: u64,
+    /// Mean delay between sending and receiving the input events, or `None` if
+    /// no input event was delivered.
+    pub mean_latency: Option<Duration>,
 }
 
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
@@ -26,6 +26,30 @@ pub struct Descriptor {
     #[serde(default, rename = "_unstable_deploy")]
     pub deploy: Deploy,
     pub nodes: Vec<Node>,
+    /// Dataflow-level parameters that are shared by all nodes, e.g. the path of
+    /// a map.
+    ///
+    /// Nodes read them at runtime, see `DoraNode::param`.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub params: BTreeMap<String, ParamValue>,
+    /// Named message types that are shared by all nodes, as list of fields.
+    ///
+    /// Nodes register them in their schema registry on start, so that outputs
+    /// and inputs can be validated against them by name.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub schemas: BTreeMap<String, Vec<SchemaField>>,
+    /// Order in which the nodes are stopped when the dataflow is stopped, e.g.
+    /// sinks before sources.
+    ///
+    /// The listed nodes receive their `STOP` event one after another. The next
+    /// node is only stopped after the previous node acknowledged the stop, see
+    /// `DoraNode::ack_stop`, or exited. The grace duration of the stop is split
+    /// evenly between the listed nodes and the remaining nodes, so a node that
+    /// does not acknowledge the stop within its share is skipped.
+    /// Nodes that are not listed are stopped together after the listed nodes.
+    /// The order applies to the nodes of each machine separately.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub shutdown_order: Vec<NodeId>,
 }
 
 #[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
@@ -39,6 +63,8 @@ pub struct Deploy {
 #[serde(deny_unknown_fields)]
 pub struct Node {
     /// Node identifier
+    ///
+    /// Can be organized into namespaces through `/`, e.g. `perception/camera`.
     pub id: NodeId,
     /// Node name
     pub name: Option<String>,
@@ -71,6 +97,8 @@ pub struct Node {
     pub inputs: BTreeMap<DataId, Input>,
     #[serde(default)]
     pub outputs: BTreeSet<DataId>,
+    #[serde(default)]
+    pub dynamic_outputs: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -241,6 +269,40 @@ pub enum EnvValue {
     String(String),
 }
 
+/// Field of a named message type, see [`Descriptor::schemas`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct SchemaField {
+    pub name: String,
+    /// Arrow data type of the field, e.g. `Utf8`, `Float32`, or `List(Int64)`.
+    #[serde(rename = "type")]
+    pub data_type: String,
+    /// Whether the field may contain nulls, defaults to `true`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub nullable: Option<bool>,
+}
+
+/// Value of a dataflow-level parameter, see [`Descriptor::params`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(untagged)]
+pub enum ParamValue {
+    Bool(bool),
+    Integer(i64),
+    Float(f64),
+    String(String),
+}
+
+impl fmt::Display for ParamValue {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParamValue::Bool(bool) => fmt.write_str(&bool.to_string()),
+            ParamValue::Integer(int) => fmt.write_str(&int.to_string()),
+            ParamValue::Float(float) => fmt.write_str(&float.to_string()),
+            ParamValue::String(str) => fmt.write_str(str),
+        }
+    }
+}
+
 impl fmt::Display for EnvValue {
     fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
         match self {
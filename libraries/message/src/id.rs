@@ -8,6 +8,43 @@ use serde::{Deserialize, Serialize};
 )]
 pub struct NodeId(pub(crate) String);
 
+/// Separates the namespaces of a [`NodeId`], e.g. `perception/camera`.
+pub const NAMESPACE_SEPARATOR: char = '/';
+
+impl NodeId {
+    /// Returns the namespace of the node, e.g. `perception` for
+    /// `perception/camera` or `robot/perception` for `robot/perception/camera`.
+    ///
+    /// Returns `None` if the ID has no namespace.
+    pub fn namespace(&self) -> Option<&str> {
+        self.0
+            .rsplit_once(NAMESPACE_SEPARATOR)
+            .map(|(namespace, _)| namespace)
+    }
+
+    /// Returns the ID without namespace, e.g. `camera` for `perception/camera`.
+    pub fn name(&self) -> &str {
+        self.0
+            .rsplit_once(NAMESPACE_SEPARATOR)
+            .map_or(&self.0, |(_, name)| name)
+    }
+
+    /// Returns whether the node is part of the given namespace, including
+    /// nested namespaces, e.g. `perception/camera` and
+    /// `perception/lidar/front` are both in `perception`.
+    pub fn is_in_namespace(&self, namespace: &str) -> bool {
+        self.0
+            .strip_prefix(namespace.trim_end_matches(NAMESPACE_SEPARATOR))
+            .is_some_and(|rest| rest.starts_with(NAMESPACE_SEPARATOR))
+    }
+
+    /// Returns the ID with the namespace separators escaped, so that it can be
+    /// used as a file name.
+    pub fn to_file_name(&self) -> String {
+        self.0.replace(NAMESPACE_SEPARATOR, "%2F")
+    }
+}
+
 impl FromStr for NodeId {
     type Err = Infallible;
 
@@ -24,10 +24,12 @@ pub mod coordinator_to_cli;
 
 pub use arrow_data;
 pub use arrow_schema;
+pub use semver;
 
 pub type DataflowId = uuid::Uuid;
 
-fn current_crate_version() -> semver::Version {
+/// The version of the message format, which is the version of this crate.
+pub fn current_crate_version() -> semver::Version {
     let crate_version_raw = env!("CARGO_PKG_VERSION");
     let crate_version = semver::Version::parse(crate_version_raw).unwrap();
     crate_version
@@ -33,6 +33,19 @@ impl Metadata {
         self.timestamp
     }
 
+    /// The timestamp of this message in nanoseconds since the UNIX epoch.
+    ///
+    /// Returns the [`TIMESTAMP_NS`] parameter if the sender set it, otherwise the
+    /// hybrid logical clock [`timestamp`](Self::timestamp) converted through
+    /// [`timestamp_to_nanos`]. No floating point conversion is involved, so the
+    /// value is exact.
+    pub fn timestamp_ns(&self) -> u64 {
+        match self.parameters.get(TIMESTAMP_NS) {
+            Some(Parameter::Integer(nanos)) if *nanos >= 0 => *nanos as u64,
+            _ => timestamp_to_nanos(self.timestamp),
+        }
+    }
+
     pub fn open_telemetry_context(&self) -> String {
         if let Some(Parameter::String(otel)) = self.parameters.get("open_telemetry_context") {
             otel.to_string()
@@ -40,6 +53,420 @@ impl Metadata {
             "".to_string()
         }
     }
+
+    /// The per-output sequence number assigned by the sending node.
+    ///
+    /// Sequence numbers start at `0` and are incremented for every message sent
+    /// on an output. Together with the input ID, they identify a message uniquely.
+    pub fn sequence_number(&self) -> Option<u64> {
+        if let Some(Parameter::Integer(seq)) = self.parameters.get(SEQUENCE_NUMBER) {
+            u64::try_from(*seq).ok()
+        } else {
+            None
+        }
+    }
+
+    /// The number of messages of the same input that were dropped before this one
+    /// because the input queue was full or because they were replaced by a
+    /// [conflated](CONFLATED) message.
+    pub fn dropped_count(&self) -> u64 {
+        if let Some(Parameter::Integer(count)) = self.parameters.get(DROPPED_COUNT) {
+            u64::try_from(*count).unwrap_or_default()
+        } else {
+            0
+        }
+    }
+
+    /// The events that caused this message, as `(input_id, sequence_number)` pairs.
+    ///
+    /// The input IDs are relative to the node that sent the message.
+    pub fn caused_by(&self) -> Vec<(String, u64)> {
+        if let Some(Parameter::ListString(parents)) = self.parameters.get(CAUSED_BY) {
+            parents
+                .iter()
+                .filter_map(|parent| {
+                    let (id, seq) = parent.rsplit_once(':')?;
+                    Some((id.to_owned(), seq.parse().ok()?))
+                })
+                .collect()
+        } else {
+            Vec::new()
+        }
+    }
+
+    /// The OpenTelemetry baggage entries propagated with this message.
+    pub fn open_telemetry_baggage(&self) -> BTreeMap<String, String> {
+        if let Some(Parameter::String(baggage)) = self.parameters.get(OPEN_TELEMETRY_BAGGAGE) {
+            parse_baggage(baggage)
+        } else {
+            BTreeMap::new()
+        }
+    }
+
+    /// The coordinate frame that the data of this message is expressed in,
+    /// e.g. `base_link`.
+    pub fn frame_id(&self) -> Option<&str> {
+        if let Some(Parameter::String(frame_id)) = self.parameters.get(FRAME_ID) {
+            Some(frame_id)
+        } else {
+            None
+        }
+    }
+
+    /// The physical units of the data of this message, e.g. `m` or `rad`.
+    ///
+    /// See [`KNOWN_UNITS`] for the standard unit names.
+    pub fn units(&self) -> Option<&str> {
+        if let Some(Parameter::String(units)) = self.parameters.get(UNITS) {
+            Some(units)
+        } else {
+            None
+        }
+    }
+
+    /// The MIME type of the encoded bytes of this message, e.g. `image/jpeg`.
+    ///
+    /// In contrast to the Arrow data type, which is usually a `uint8` array for
+    /// encoded data, the content type describes the format of the bytes.
+    pub fn content_type(&self) -> Option<&str> {
+        if let Some(Parameter::String(content_type)) = self.parameters.get(CONTENT_TYPE) {
+            Some(content_type)
+        } else {
+            None
+        }
+    }
+
+    /// The full name of the protobuf message type of this message, if it was
+    /// sent as serialized protobuf message.
+    pub fn proto_type(&self) -> Option<&str> {
+        if let Some(Parameter::String(proto_type)) = self.parameters.get(PROTO_TYPE) {
+            Some(proto_type)
+        } else {
+            None
+        }
+    }
+
+    /// The delivery priority of this message.
+    ///
+    /// Returns [`Priority::Normal`] if no or an invalid priority is set.
+    pub fn priority(&self) -> Priority {
+        if let Some(Parameter::String(priority)) = self.parameters.get(PRIORITY) {
+            priority.parse().unwrap_or_default()
+        } else {
+            Priority::Normal
+        }
+    }
+
+    /// The binary attachment with the given name, if any.
+    ///
+    /// See [`ATTACHMENT_PREFIX`] for details.
+    pub fn attachment(&self, name: &str) -> Option<&[u8]> {
+        if let Some(Parameter::Bytes(bytes)) = self.parameters.get(&attachment_key(name)) {
+            Some(bytes)
+        } else {
+            None
+        }
+    }
+
+    /// All binary attachments of this message, as `(name, bytes)` pairs.
+    pub fn attachments(&self) -> impl Iterator<Item = (&str, &[u8])> {
+        self.parameters.iter().filter_map(|(key, value)| {
+            let name = key.strip_prefix(ATTACHMENT_PREFIX)?;
+            match value {
+                Parameter::Bytes(bytes) => Some((name, bytes.as_slice())),
+                _ => None,
+            }
+        })
+    }
+}
+
+/// Metadata parameter key of a sender-provided timestamp, as an integer number of
+/// nanoseconds since the UNIX epoch.
+///
+/// Unlike the hybrid logical clock timestamp of the metadata, which is assigned
+/// when the message is sent, this parameter can carry the exact acquisition time
+/// of sensor data. See [`Metadata::timestamp_ns`].
+pub const TIMESTAMP_NS: &str = "timestamp_ns";
+
+/// Converts the given hybrid logical clock timestamp to nanoseconds since the
+/// UNIX epoch, using integer arithmetic only.
+pub fn timestamp_to_nanos(timestamp: uhlc::Timestamp) -> u64 {
+    timestamp
+        .get_time()
+        .to_duration()
+        .as_nanos()
+        .try_into()
+        .unwrap_or(u64::MAX)
+}
+
+/// Metadata parameter key of the per-output sequence number.
+pub const SEQUENCE_NUMBER: &str = "dora/sequence_number";
+/// Metadata parameter key of the number of messages dropped before a received input.
+///
+/// This parameter is set on the receiver side when the input queue overflows.
+/// It is removed from the parameters of sent outputs, so a node that forwards
+/// the metadata of an input does not pass its drop count on.
+pub const DROPPED_COUNT: &str = "dropped_count";
+/// Metadata parameter key of the causal parent references of a message.
+pub const CAUSED_BY: &str = "caused_by";
+
+/// Encodes the given causal parent references as a metadata parameter.
+pub fn caused_by_parameter<'a>(parents: impl IntoIterator<Item = (&'a str, u64)>) -> Parameter {
+    Parameter::ListString(
+        parents
+            .into_iter()
+            .map(|(id, seq)| format!("{id}:{seq}"))
+            .collect(),
+    )
+}
+
+/// Metadata parameter key of the OpenTelemetry baggage, encoded as a W3C `baggage` header value.
+pub const OPEN_TELEMETRY_BAGGAGE: &str = "open_telemetry_baggage";
+
+/// Metadata parameter key of the coordinate frame of the message data.
+pub const FRAME_ID: &str = "frame_id";
+/// Metadata parameter key of the physical units of the message data.
+pub const UNITS: &str = "units";
+/// Metadata parameter key of the MIME type of the message data, e.g. `image/jpeg`.
+pub const CONTENT_TYPE: &str = "content_type";
+/// Metadata parameter key of the full name of the protobuf message type of the
+/// message data, e.g. `robot.Command`.
+pub const PROTO_TYPE: &str = "proto_type";
+
+/// Metadata parameter key of the delivery [`Priority`] of a message.
+pub const PRIORITY: &str = "priority";
+
+/// Delivery priority of a message.
+///
+/// Messages with a higher priority are delivered to the receiving node before
+/// queued messages with a lower priority, e.g. so that a stop command is not
+/// stuck behind large images. Messages of the same priority are delivered in
+/// the usual order. Messages are never moved across other events of the
+/// receiver, such as the closing of an input.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Priority {
+    Low,
+    #[default]
+    Normal,
+    High,
+}
+
+impl Priority {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Priority::Low => "low",
+            Priority::Normal => "normal",
+            Priority::High => "high",
+        }
+    }
+}
+
+impl From<Priority> for Parameter {
+    fn from(priority: Priority) -> Self {
+        Parameter::String(priority.as_str().to_owned())
+    }
+}
+
+impl std::str::FromStr for Priority {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "low" => Ok(Priority::Low),
+            "normal" => Ok(Priority::Normal),
+            "high" => Ok(Priority::High),
+            other => Err(format!(
+                "invalid priority `{other}`, expected `low`, `normal`, or `high`"
+            )),
+        }
+    }
+}
+
+/// Metadata parameter key of the message ID of a reliably sent message.
+///
+/// Receiving nodes acknowledge messages with this parameter to the daemon once
+/// they are handed to the node. The ID stays the same when the sender retries
+/// the message, so receivers can use it to detect duplicates.
+pub const RELIABLE: &str = "reliable";
+
+/// Metadata parameter key of the name of the registered schema that the value
+/// of a message was validated against by its sender.
+pub const SCHEMA_NAME: &str = "schema";
+
+/// Metadata parameter that marks messages of a conflated output.
+///
+/// The daemon sets this parameter on all messages of outputs for which the
+/// sender enabled conflation. A conflated message replaces the messages of the
+/// same input that are still buffered for the receiver, so that only the most
+/// recent one is delivered if the receiver falls behind. Replaced messages are
+/// counted in the [`DROPPED_COUNT`] of the next delivered message.
+pub const CONFLATED: &str = "conflated";
+
+/// Metadata parameter key of the reason why a message was sent to the
+/// dead-letter output of its sender, see [`DeadLetterReason`].
+pub const DEAD_LETTER_REASON: &str = "dead_letter_reason";
+/// Metadata parameter key of the output that a dead-lettered message was
+/// originally sent on.
+pub const DEAD_LETTER_OUTPUT: &str = "dead_letter_output";
+
+/// Reasons why a message could not be delivered, see [`DEAD_LETTER_REASON`].
+///
+/// Only failures that are detected on the sending side are dead-lettered.
+/// Messages that a receiver drops later, because its input queue is full
+/// (`queue_size`, `max_buffered_bytes`) or because they were replaced by a newer
+/// message of a conflated output, are reported to the receiver through the
+/// [`DROPPED_COUNT`] parameter instead. Messages don't expire, so there is no
+/// reason for outdated messages.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DeadLetterReason {
+    /// The output is not listed in the dataflow descriptor.
+    UndeclaredOutput,
+    /// No node was subscribed to the output when the message was sent.
+    NoSubscribers,
+    /// A reliably sent message was not acknowledged by all receivers in time.
+    NotAcknowledged,
+}
+
+impl DeadLetterReason {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DeadLetterReason::UndeclaredOutput => "undeclared_output",
+            DeadLetterReason::NoSubscribers => "no_subscribers",
+            DeadLetterReason::NotAcknowledged => "not_acknowledged",
+        }
+    }
+
+    /// Marks the given parameters of a message as dead-lettered.
+    pub fn insert_into(&self, parameters: &mut MetadataParameters, output_id: &str) {
+        parameters.insert(
+            DEAD_LETTER_REASON.to_owned(),
+            Parameter::String(self.as_str().to_owned()),
+        );
+        parameters.insert(
+            DEAD_LETTER_OUTPUT.to_owned(),
+            Parameter::String(output_id.to_owned()),
+        );
+    }
+}
+
+/// Prefix of the metadata parameter keys of binary attachments.
+///
+/// Attachments are small opaque blobs that are sent along with the data of a
+/// message, e.g. calibration data or debug artifacts. Unlike the fields of a
+/// struct array, they are not part of the typed data. Attachments are stored
+/// as [`Parameter::Bytes`] under the key `attachment:<name>` and are
+/// transferred inline with the metadata, so their total size is limited to
+/// [`MAX_ATTACHMENTS_SIZE`].
+pub const ATTACHMENT_PREFIX: &str = "attachment:";
+/// Maximum total size in bytes of the attachments of a single message.
+pub const MAX_ATTACHMENTS_SIZE: usize = 64 * 1024;
+
+/// Returns the metadata parameter key of the attachment with the given name.
+pub fn attachment_key(name: &str) -> String {
+    format!("{ATTACHMENT_PREFIX}{name}")
+}
+
+/// Returns the total size in bytes of the attachments in the given parameters.
+pub fn attachments_size(parameters: &MetadataParameters) -> usize {
+    parameters
+        .iter()
+        .filter(|(key, _)| key.starts_with(ATTACHMENT_PREFIX))
+        .map(|(_, value)| match value {
+            Parameter::Bytes(bytes) => bytes.len(),
+            _ => 0,
+        })
+        .sum()
+}
+
+/// Standard names of physical units for the [`UNITS`] parameter.
+///
+/// Using these names allows nodes of different authors to interpret each
+/// other's data. Other units are allowed, but should be avoided.
+pub const KNOWN_UNITS: &[&str] = &[
+    "1", "%", "px", "m", "cm", "mm", "km", "rad", "deg", "s", "ms", "us", "ns", "Hz", "kg", "g",
+    "N", "Nm", "m/s", "m/s^2", "rad/s", "rad/s^2", "A", "V", "W", "Ah", "K", "degC", "Pa", "T",
+    "lx",
+];
+
+/// Checks that the value is a MIME type of the form `type/subtype`, optionally
+/// followed by parameters, e.g. `text/plain; charset=utf-8`.
+///
+/// Only the syntax is checked, so that unregistered types like
+/// `application/x-protobuf` can be used as well.
+pub fn is_valid_content_type(content_type: &str) -> bool {
+    let is_token = |s: &str| {
+        !s.is_empty()
+            && s.chars()
+                .all(|c| c.is_ascii_alphanumeric() || "!#$&-^_.+".contains(c))
+    };
+    let essence = content_type.split(';').next().unwrap_or_default().trim();
+    match essence.split_once('/') {
+        Some((type_, subtype)) => is_token(type_) && is_token(subtype),
+        None => false,
+    }
+}
+
+/// Parses a W3C `baggage` header value, e.g. `robot.id=r2,site=lab%201`.
+///
+/// Entry properties (`;`-separated suffixes) are ignored and malformed entries are skipped.
+pub fn parse_baggage(header: &str) -> BTreeMap<String, String> {
+    header
+        .split(',')
+        .filter_map(|entry| {
+            let entry = entry.split(';').next()?;
+            let (key, value) = entry.split_once('=')?;
+            let key = key.trim();
+            if key.is_empty() {
+                return None;
+            }
+            Some((percent_decode(key), percent_decode(value.trim())))
+        })
+        .collect()
+}
+
+/// Encodes the given baggage entries as a W3C `baggage` header value.
+pub fn serialize_baggage<'a>(entries: impl IntoIterator<Item = (&'a str, &'a str)>) -> String {
+    entries
+        .into_iter()
+        .map(|(key, value)| format!("{}={}", percent_encode(key), percent_encode(value)))
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+fn percent_encode(s: &str) -> String {
+    let mut encoded = String::with_capacity(s.len());
+    for byte in s.bytes() {
+        match byte {
+            b'!' | b'#'..=b'$' | b'&'..=b'+' | b'-'..=b':' | b'<' | b'>'..=b'[' | b']'..=b'~' => {
+                encoded.push(byte as char)
+            }
+            _ => encoded.push_str(&format!("%{byte:02X}")),
+        }
+    }
+    encoded
+}
+
+fn percent_decode(s: &str) -> String {
+    let bytes = s.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let escaped = (bytes[i] == b'%')
+            .then(|| s.get(i + 1..i + 3))
+            .flatten()
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match escaped {
+            Some(byte) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            None => {
+                decoded.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
 }
 
 pub type MetadataParameters = BTreeMap<String, Parameter>;
@@ -61,6 +488,8 @@ pub enum Parameter {
     Integer(i64),
     String(String),
     ListInt(Vec<i64>),
+    ListString(Vec<String>),
+    Bytes(Vec<u8>),
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
@@ -1,8 +1,11 @@
+use std::collections::BTreeMap;
+
 pub use crate::common::{
     DataMessage, DropToken, LogLevel, LogMessage, SharedMemoryId, Timestamped,
 };
 use crate::{
     current_crate_version,
+    daemon_to_node::NodeSummary,
     id::{DataId, NodeId},
     metadata::Metadata,
     versions_compatible, DataflowId,
@@ -18,6 +21,113 @@ pub enum DaemonRequest {
         data: Option<DataMessage>,
     },
     CloseOutputs(Vec<DataId>),
+    /// Registers an output that is not listed in the dataflow descriptor.
+    ///
+    /// Inputs of other nodes that are mapped to this output are routed to it
+    /// once the daemon confirms the declaration.
+    DeclareOutput(DataId),
+    /// Unsubscribes the node from the upstream output of the given input.
+    ///
+    /// The daemon stops routing messages to the input and closes it, i.e. it
+    /// sends a `NodeEvent::InputClosed` event after the last routed message.
+    DisconnectInput(DataId),
+    /// Subscribes the node to all outputs that match the given glob pattern.
+    ///
+    /// The pattern supports `*` and `?` wildcards. Patterns that contain a `/`
+    /// are matched against `<node>/<output>` and the matched inputs are named
+    /// like that, other patterns are matched against the output ID only and the
+    /// inputs are named like the output. Outputs that are declared later are
+    /// matched too.
+    ///
+    /// Node IDs can contain `/` to organize nodes into namespaces, so e.g. the
+    /// pattern `perception/*` matches all outputs of the nodes in the
+    /// `perception` namespace.
+    SubscribePattern(String),
+    /// Sets the output on which the daemon re-sends messages of the node that
+    /// have no receivers, or disables this if `None`.
+    ///
+    /// Re-sent messages carry the
+    /// [`DEAD_LETTER_REASON`](crate::metadata::DEAD_LETTER_REASON) and
+    /// [`DEAD_LETTER_OUTPUT`](crate::metadata::DEAD_LETTER_OUTPUT) parameters.
+    SetDeadLetterOutput(Option<DataId>),
+    /// Enables or disables conflation for an output of the node.
+    ///
+    /// Messages of conflated outputs carry the
+    /// [`CONFLATED`](crate::metadata::CONFLATED) parameter and replace the
+    /// undelivered messages of the output that are buffered for a receiver.
+    SetConflation {
+        output_id: DataId,
+        enabled: bool,
+    },
+    /// Requests `NodeEvent::Lifecycle` events whenever a node of the dataflow
+    /// joins or leaves, or reports progress.
+    ///
+    /// The daemon replies by first reporting all nodes that already joined.
+    SubscribeLifecycleEvents,
+    /// Reports the progress of a long-running task of the node, as a fraction
+    /// between `0.0` and `1.0`.
+    ///
+    /// The daemon forwards the report to the nodes that subscribed to lifecycle
+    /// events as [`LifecycleEvent::Progress`](crate::daemon_to_node::LifecycleEvent::Progress).
+    ReportProgress {
+        task: String,
+        progress: f32,
+        message: Option<String>,
+    },
+    /// Reports the lifetime statistics of the node before it closes.
+    ///
+    /// The daemon forwards the report to the nodes that subscribed to lifecycle
+    /// events as [`LifecycleEvent::Summary`](crate::daemon_to_node::LifecycleEvent::Summary).
+    ReportSummary(NodeSummary),
+    /// Reports the number of events that are buffered by the node per input,
+    /// i.e. received from the daemon but not processed yet.
+    ReportQueueDepths(BTreeMap<DataId, usize>),
+    /// Requests the queue depths of all edges of the dataflow, as reported by
+    /// the receiving nodes.
+    ///
+    /// The daemon replies with [`DaemonReply::PipelineDepths`](crate::daemon_to_node::DaemonReply::PipelineDepths).
+    PipelineDepths,
+    /// Requests the estimated offset of the wall clock of the machine that runs
+    /// the given node of the dataflow, relative to the clock of this node.
+    ///
+    /// The daemon replies with [`DaemonReply::ClockSkew`](crate::daemon_to_node::DaemonReply::ClockSkew).
+    ClockSkew {
+        node_id: NodeId,
+    },
+    /// Acknowledges that reliably sent messages were received, as pairs of
+    /// input ID and message ID.
+    ///
+    /// The message ID is the value of the
+    /// [`RELIABLE`](crate::metadata::RELIABLE) metadata parameter.
+    AckInputs(Vec<(DataId, u64)>),
+    /// Asks whether all receivers acknowledged the reliably sent message with the
+    /// given ID.
+    ///
+    /// The daemon replies with [`DaemonReply::Acked`](crate::daemon_to_node::DaemonReply::Acked).
+    /// If `discard` is set, the daemon stops tracking the message regardless of
+    /// the result.
+    CheckAcked {
+        output_id: DataId,
+        message_id: u64,
+        discard: bool,
+    },
+    /// Forwards a log message of the node to the dora-coordinator, tagged with
+    /// the node ID.
+    ///
+    /// The `target` describes the source of the message, e.g. `stdout`.
+    Log {
+        level: LogLevel,
+        target: Option<String>,
+        message: String,
+    },
+    /// Acknowledges that the node finished its shutdown after receiving a
+    /// `NodeEvent::Stop`.
+    ///
+    /// If the dataflow has a
+    /// [`shutdown_order`](crate::descriptor::Descriptor::shutdown_order), the
+    /// daemon waits for this acknowledgement or for the exit of the node before
+    /// it sends the stop event to the next node of the order.
+    AckStop,
     /// Signals that the node is finished sending outputs and that it received all
     /// required drop tokens.
     OutputsDone,
@@ -30,9 +140,22 @@ pub enum DaemonRequest {
     SubscribeDrop,
     NextFinishedDropTokens,
     EventStreamDropped,
+    /// Requests the config of a dynamic node.
+    ///
+    /// If `dataflow_id` is `None`, the node ID must be unique among the running
+    /// dataflows of the daemon.
     NodeConfig {
         node_id: NodeId,
+        #[serde(default)]
+        dataflow_id: Option<DataflowId>,
     },
+    /// Like [`Subscribe`](Self::Subscribe), but replaces an open subscription of
+    /// the node instead of failing with
+    /// [`DaemonReply::DuplicateNodeId`](crate::daemon_to_node::DaemonReply::DuplicateNodeId).
+    ///
+    /// Sent by a child process created through `fork` to take over the
+    /// subscription of its parent.
+    TakeOverSubscription,
 }
 
 impl DaemonRequest {
@@ -41,10 +164,26 @@ impl DaemonRequest {
         match self {
             DaemonRequest::SendMessage { .. }
             | DaemonRequest::NodeConfig { .. }
-            | DaemonRequest::ReportDropTokens { .. } => false,
+            | DaemonRequest::ReportDropTokens { .. }
+            | DaemonRequest::ReportQueueDepths(_)
+            | DaemonRequest::AckInputs(_)
+            | DaemonRequest::ReportProgress { .. }
+            | DaemonRequest::ReportSummary(_)
+            | DaemonRequest::AckStop
+            | DaemonRequest::Log { .. } => false,
             DaemonRequest::Register(NodeRegisterRequest { .. })
             | DaemonRequest::Subscribe
+            | DaemonRequest::TakeOverSubscription
             | DaemonRequest::CloseOutputs(_)
+            | DaemonRequest::DeclareOutput(_)
+            | DaemonRequest::DisconnectInput(_)
+            | DaemonRequest::SubscribePattern(_)
+            | DaemonRequest::SetDeadLetterOutput(_)
+            | DaemonRequest::SetConflation { .. }
+            | DaemonRequest::SubscribeLifecycleEvents
+            | DaemonRequest::PipelineDepths
+            | DaemonRequest::ClockSkew { .. }
+            | DaemonRequest::CheckAcked { .. }
             | DaemonRequest::OutputsDone
             | DaemonRequest::NextEvent { .. }
             | DaemonRequest::SubscribeDrop
@@ -59,7 +198,23 @@ impl DaemonRequest {
             DaemonRequest::NodeConfig { .. } => true,
             DaemonRequest::Register(NodeRegisterRequest { .. })
             | DaemonRequest::Subscribe
+            | DaemonRequest::TakeOverSubscription
             | DaemonRequest::CloseOutputs(_)
+            | DaemonRequest::DeclareOutput(_)
+            | DaemonRequest::DisconnectInput(_)
+            | DaemonRequest::SubscribePattern(_)
+            | DaemonRequest::SetDeadLetterOutput(_)
+            | DaemonRequest::SetConflation { .. }
+            | DaemonRequest::SubscribeLifecycleEvents
+            | DaemonRequest::ReportQueueDepths(_)
+            | DaemonRequest::PipelineDepths
+            | DaemonRequest::ClockSkew { .. }
+            | DaemonRequest::AckInputs(_)
+            | DaemonRequest::CheckAcked { .. }
+            | DaemonRequest::ReportProgress { .. }
+            | DaemonRequest::ReportSummary(_)
+            | DaemonRequest::AckStop
+            | DaemonRequest::Log { .. }
             | DaemonRequest::OutputsDone
             | DaemonRequest::NextEvent { .. }
             | DaemonRequest::SubscribeDrop
@@ -87,6 +242,11 @@ impl NodeRegisterRequest {
         }
     }
 
+    /// The version of the message format that the node was built with.
+    pub fn dora_version(&self) -> &semver::Version {
+        &self.dora_version
+    }
+
     pub fn check_version(&self) -> Result<(), String> {
         let crate_version = current_crate_version();
         let specified_version = &self.dora_version;
@@ -132,5 +292,23 @@ pub struct SharedMemoryInput {
 
 #[derive(Debug, serde::Deserialize, serde::Serialize)]
 pub enum DynamicNodeEvent {
-    NodeConfig { node_id: NodeId },
+    NodeConfig {
+        node_id: NodeId,
+        dataflow_id: Option<DataflowId>,
+    },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn incompatible_versions_are_rejected() {
+        let mut request = NodeRegisterRequest::new(DataflowId::nil(), "node".to_owned().into());
+        assert!(request.check_version().is_ok());
+
+        request.dora_version = semver::Version::new(0, 1, 0);
+        assert!(request.check_version().is_err());
+        assert_eq!(request.dora_version(), &semver::Version::new(0, 1, 0));
+    }
 }